@@ -1,19 +1,132 @@
 use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+/// The parameters of activation functions that are not fixed by the network domain.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ActivationParameters {
+    /// The threshold of the binary activation: units with a local field above the threshold are mapped to 1.
+    pub binary_threshold: f64,
+    /// The dead zone of the ternary activation: values within ±threshold are mapped to 0.
+    pub ternary_threshold: f64,
+    /// The lower bound of the clipping activation.
+    pub continuous_lower_bound: f64,
+    /// The upper bound of the clipping activation.
+    pub continuous_upper_bound: f64,
+    /// The gain (inverse activation temperature) of the continuous activations, which scales fields before they are mapped.
+    pub gain: f64,
+}
+
+impl Default for ActivationParameters {
+    fn default() -> Self {
+        Self {
+            binary_threshold: 0.0,
+            ternary_threshold: 0.5,
+            continuous_lower_bound: -1.0,
+            continuous_upper_bound: 1.0,
+            gain: 1.0,
+        }
+    }
+}
 
 /// Define an activation function to takes ownership of a vector.
 /// An activation function will map the vector in place to the correct domain values.
 /// Note ownership is taken here to ensure the old, unmapped vector is not used again.
 /// If the unmapped vector is needed in future, consider changing the function signature to take &mut DVector
-pub type ActivationFunction = fn(DVector<f64>) -> DVector<f64>;
+pub type ActivationFunction = fn(DVector<f64>, &ActivationParameters) -> DVector<f64>;
+
+/// Define an activation function of a single value, for updating one unit at a time without allocating a vector.
+/// Each activation function of a network domain maps every element of a vector with the matching scalar function.
+pub type ScalarActivationFunction = fn(f64, &ActivationParameters) -> f64;
+
+pub fn binary_activation(value: f64, parameters: &ActivationParameters) -> f64 {
+    if value <= parameters.binary_threshold {
+        0.0
+    } else {
+        1.0
+    }
+}
+
+pub fn bipolar_activation(value: f64, _parameters: &ActivationParameters) -> f64 {
+    if value <= 0.0 {
+        -1.0
+    } else {
+        1.0
+    }
+}
+
+pub fn ternary_activation(value: f64, parameters: &ActivationParameters) -> f64 {
+    let threshold = parameters.ternary_threshold;
+    if value > threshold {
+        1.0
+    } else if value < -threshold {
+        -1.0
+    } else {
+        0.0
+    }
+}
+
+pub fn clipping_activation(value: f64, parameters: &ActivationParameters) -> f64 {
+    (parameters.gain * value).clamp(
+        parameters.continuous_lower_bound,
+        parameters.continuous_upper_bound,
+    )
+}
 
-pub fn binary_activation_function(vector: DVector<f64>) -> DVector<f64> {
-    vector.map(|i| if i <= 0.0 { 0.0 } else { 1.0 })
+pub fn tanh_activation(value: f64, parameters: &ActivationParameters) -> f64 {
+    (parameters.gain * value).tanh()
 }
 
-pub fn bipolar_activation_function(vector: DVector<f64>) -> DVector<f64> {
-    vector.map(|i| if i <= 0.0 { -1.0 } else { 1.0 })
+pub fn identity_activation(value: f64, _parameters: &ActivationParameters) -> f64 {
+    value
 }
 
-pub fn identity_activation_function(vector: DVector<f64>) -> DVector<f64> {
+pub fn binary_activation_function(
+    vector: DVector<f64>,
+    parameters: &ActivationParameters,
+) -> DVector<f64> {
+    vector.map(|i| binary_activation(i, parameters))
+}
+
+pub fn bipolar_activation_function(
+    vector: DVector<f64>,
+    parameters: &ActivationParameters,
+) -> DVector<f64> {
+    vector.map(|i| bipolar_activation(i, parameters))
+}
+
+pub fn ternary_activation_function(
+    vector: DVector<f64>,
+    parameters: &ActivationParameters,
+) -> DVector<f64> {
+    vector.map(|i| ternary_activation(i, parameters))
+}
+
+pub fn clipping_activation_function(
+    vector: DVector<f64>,
+    parameters: &ActivationParameters,
+) -> DVector<f64> {
+    vector.map(|i| clipping_activation(i, parameters))
+}
+
+pub fn tanh_activation_function(
+    vector: DVector<f64>,
+    parameters: &ActivationParameters,
+) -> DVector<f64> {
+    vector.map(|i| tanh_activation(i, parameters))
+}
+
+pub fn identity_activation_function(
+    vector: DVector<f64>,
+    _parameters: &ActivationParameters,
+) -> DVector<f64> {
     vector
 }
+
+/// The logistic sigmoid 1 / (1 + exp(-gain * x)), mapping fields to probabilities in (0, 1).
+/// Not the activation of any network domain, but used to sample stochastic units.
+pub fn sigmoid_activation_function(
+    vector: DVector<f64>,
+    parameters: &ActivationParameters,
+) -> DVector<f64> {
+    vector.map(|i| 1.0 / (1.0 + (-parameters.gain * i).exp()))
+}
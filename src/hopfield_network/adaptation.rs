@@ -0,0 +1,33 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+/// The parameters of unit fatigue: an adaptation variable per unit that follows the state of that unit
+/// and is subtracted from its local field, so a unit that stays active is gradually weakened.
+///
+/// After every update sweep the adaptation is updated as a ← (1 - decay) a + strength s.
+/// Adaptation starts from zero for every relaxation, serial or concurrent. Units count as unstable relative
+/// to the adapted local field, so a strongly fatigued network may never stabilize and instead run for the
+/// maximum relaxation iterations.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FatigueParameters {
+    pub strength: f64,
+    pub decay: f64,
+}
+
+impl FatigueParameters {
+    /// Check if these parameters leave the dynamics unchanged.
+    pub fn is_disabled(self: &Self) -> bool {
+        self.strength == 0.0
+    }
+
+    /// Update an adaptation vector after a sweep over a state.
+    ///
+    /// # Arguments
+    ///
+    /// * `adaptation`: The adaptation of every unit, updated in place.
+    /// * `state`: The state after the sweep.
+    pub fn update_adaptation(self: &Self, adaptation: &mut DVector<f64>, state: &DVector<f64>) {
+        *adaptation *= 1.0 - self.decay;
+        adaptation.axpy(self.strength, state, 1.0);
+    }
+}
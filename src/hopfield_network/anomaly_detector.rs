@@ -0,0 +1,324 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+use super::{HopfieldNetwork, HopfieldNetworkBuilder, NetworkDomain};
+
+/// How a window of a time series is binarized into a network state. Each encoded unit is either high (1) or low
+/// (0 in the Binary domain, -1 in the Bipolar domain).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WindowEncoding {
+    /// One unit per sample, high if the sample is above the mean of its window. Invariant to the level of the
+    /// series, so only the shape of each window matters.
+    MeanThreshold,
+    /// One unit per sample, high if the sample is above a fixed threshold.
+    FixedThreshold { threshold: f64 },
+    /// One unit per consecutive pair of samples, high if the series rises between them.
+    Differential,
+    /// A thermometer code of `levels` units per sample over the range [minimum, maximum]: unit l of a sample is high
+    /// if the sample is above minimum + (l + 0.5)(maximum - minimum) / levels, so nearby values share most units.
+    Thermometer {
+        minimum: f64,
+        maximum: f64,
+        levels: usize,
+    },
+}
+
+impl WindowEncoding {
+    /// Get the number of units needed to encode a window of the given length.
+    pub fn encoded_dimension(self: &Self, window_length: usize) -> usize {
+        match self {
+            WindowEncoding::MeanThreshold | WindowEncoding::FixedThreshold { .. } => window_length,
+            WindowEncoding::Differential => window_length.saturating_sub(1),
+            WindowEncoding::Thermometer { levels, .. } => window_length * levels,
+        }
+    }
+
+    /// Encode a window as a pattern of high and low units.
+    ///
+    /// # Arguments
+    ///
+    /// * `window`: The samples of the window.
+    /// * `low_value`: The value of a low unit, 0 for the Binary domain or -1 for the Bipolar domain.
+    pub fn encode(self: &Self, window: &[f64], low_value: f64) -> DVector<f64> {
+        let level = |high: bool| if high { 1.0 } else { low_value };
+        match *self {
+            WindowEncoding::MeanThreshold => {
+                let mean = window.iter().sum::<f64>() / window.len() as f64;
+                DVector::from_iterator(window.len(), window.iter().map(|x| level(*x > mean)))
+            }
+            WindowEncoding::FixedThreshold { threshold } => {
+                DVector::from_iterator(window.len(), window.iter().map(|x| level(*x > threshold)))
+            }
+            WindowEncoding::Differential => DVector::from_iterator(
+                window.len().saturating_sub(1),
+                window.windows(2).map(|pair| level(pair[1] > pair[0])),
+            ),
+            WindowEncoding::Thermometer {
+                minimum,
+                maximum,
+                levels,
+            } => {
+                let step = (maximum - minimum) / levels as f64;
+                DVector::from_iterator(
+                    window.len() * levels,
+                    window.iter().flat_map(|x| {
+                        (0..levels).map(move |l| level(*x > minimum + (l as f64 + 0.5) * step))
+                    }),
+                )
+            }
+        }
+    }
+}
+
+/// The score of one window of a time series: the energy of the state the encoded window relaxes to, and the overlap
+/// between the encoded window and that state.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WindowScore {
+    /// The index of the first sample of the window in the series.
+    pub start: usize,
+    /// The energy of the relaxed state.
+    pub energy: f64,
+    /// The overlap of the encoded window with the relaxed state, from -1 to 1, measured on bipolar values.
+    pub overlap: f64,
+}
+
+/// The bounds of normal window scores, found by AnomalyDetector::calibrate. A window is anomalous if its relaxed
+/// energy is above the maximum energy, or its overlap is below the minimum overlap: it either relaxes to a spurious
+/// state rather than a learned window, or starts far from any attractor.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnomalyBounds {
+    pub maximum_energy: f64,
+    pub minimum_overlap: f64,
+}
+
+impl AnomalyBounds {
+    /// Check whether a window score falls outside these bounds.
+    pub fn is_anomalous(self: &Self, score: &WindowScore) -> bool {
+        score.energy > self.maximum_energy || score.overlap < self.minimum_overlap
+    }
+}
+
+/// An anomaly detector for time series, packaging a HopfieldNetwork trained on windows of normal data.
+///
+/// The series is cut into windows of a fixed length at a fixed stride, and each window is binarized by a
+/// WindowEncoding. Normal windows are learned as patterns. Each window of a series is then scored by relaxing its
+/// encoding, and flagged if the score falls outside bounds calibrated on held-out normal data.
+///
+/// As with any Hopfield network, only a limited number of distinct windows can be stored (around 0.14 times the
+/// encoded dimension for Hebbian learning), so training data should be short or periodic, or the windows long.
+#[derive(Debug)]
+pub struct AnomalyDetector {
+    network: HopfieldNetwork,
+    encoding: WindowEncoding,
+    window_length: usize,
+    stride: usize,
+    bounds: Option<AnomalyBounds>,
+}
+
+impl AnomalyDetector {
+    /// Create a new anomaly detector with no learned windows.
+    ///
+    /// # Arguments
+    ///
+    /// * `network_builder`: The builder of the network. The dimension is set from the encoding and window length,
+    ///   and the domain must be set to Binary or Bipolar.
+    /// * `encoding`: How each window is binarized.
+    /// * `window_length`: The number of samples in each window.
+    /// * `stride`: The number of samples between the starts of consecutive windows. Must be strictly positive.
+    pub fn new_anomaly_detector(
+        network_builder: HopfieldNetworkBuilder,
+        encoding: WindowEncoding,
+        window_length: usize,
+        stride: usize,
+    ) -> Self {
+        assert!(
+            stride > 0,
+            "Anomaly detector stride must be strictly positive!"
+        );
+        let dimension = encoding.encoded_dimension(window_length);
+        assert!(
+            dimension > 0,
+            "Anomaly detector windows must encode to at least one unit!"
+        );
+
+        let network = network_builder.set_network_dimension(dimension).build();
+        assert!(
+            matches!(
+                network.get_domain(),
+                NetworkDomain::Binary | NetworkDomain::Bipolar
+            ),
+            "Anomaly detector network must have the Binary or Bipolar domain!"
+        );
+
+        Self {
+            network,
+            encoding,
+            window_length,
+            stride,
+            bounds: None,
+        }
+    }
+
+    /// Get the network of this detector.
+    pub fn get_network(self: &Self) -> &HopfieldNetwork {
+        &self.network
+    }
+
+    /// Get the calibrated bounds, if calibrate has been called.
+    pub fn get_bounds(self: &Self) -> Option<AnomalyBounds> {
+        self.bounds
+    }
+
+    /// Set the bounds directly, e.g. to reuse bounds from an earlier calibration.
+    pub fn set_bounds(self: &mut Self, bounds: AnomalyBounds) {
+        self.bounds = Some(bounds);
+    }
+
+    /// Get the value of a low unit in the domain of the network.
+    fn low_value(self: &Self) -> f64 {
+        match self.network.get_domain() {
+            NetworkDomain::Binary => 0.0,
+            _ => -1.0,
+        }
+    }
+
+    /// Get the start index of every complete window of a series.
+    pub fn window_starts(self: &Self, series: &[f64]) -> Vec<usize> {
+        if series.len() < self.window_length {
+            return Vec::new();
+        }
+        (0..=series.len() - self.window_length)
+            .step_by(self.stride)
+            .collect()
+    }
+
+    /// Encode every complete window of a series.
+    fn encode_series(self: &Self, series: &[f64]) -> (Vec<usize>, Vec<DVector<f64>>) {
+        let starts = self.window_starts(series);
+        let low_value = self.low_value();
+        let states = starts
+            .iter()
+            .map(|start| {
+                self.encoding
+                    .encode(&series[*start..*start + self.window_length], low_value)
+            })
+            .collect();
+        (starts, states)
+    }
+
+    /// Learn every window of a series of normal data. Windows that repeat are handled by the duplicate policy of
+    /// the network.
+    ///
+    /// # Returns
+    ///
+    /// The number of windows learned.
+    pub fn train(self: &mut Self, series: &[f64]) -> usize {
+        let (_, patterns) = self.encode_series(series);
+        self.network.learn_states(&patterns);
+        patterns.len()
+    }
+
+    /// Score every window of a series, relaxing the windows concurrently.
+    ///
+    /// # Arguments
+    ///
+    /// * `series`: The series to score.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically.
+    ///
+    /// # Returns
+    ///
+    /// The score of each window, in order of the start of the window.
+    pub fn score_series(
+        self: &mut Self,
+        series: &[f64],
+        threads: Option<usize>,
+    ) -> Vec<WindowScore> {
+        let (starts, cues) = self.encode_series(series);
+        let relaxed_states = self
+            .network
+            .concurrent_relax_state_collection(cues.clone(), threads);
+
+        let low_value = self.low_value();
+        let to_bipolar = |value: f64| 2.0 * (value - low_value) / (1.0 - low_value) - 1.0;
+        starts
+            .into_iter()
+            .zip(cues.iter().zip(relaxed_states.iter()))
+            .map(|(start, (cue, relaxed_state))| WindowScore {
+                start,
+                energy: self.network.state_energy(relaxed_state),
+                overlap: cue
+                    .iter()
+                    .zip(relaxed_state.iter())
+                    .map(|(a, b)| to_bipolar(*a) * to_bipolar(*b))
+                    .sum::<f64>()
+                    / cue.len() as f64,
+            })
+            .collect()
+    }
+
+    /// Calibrate the bounds of normal scores on a series of normal data, ideally held out from training. The
+    /// maximum energy is the (1 - tolerance) quantile of the window energies, and the minimum overlap the tolerance
+    /// quantile of the window overlaps, so around a fraction tolerance of normal windows exceed each bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `series`: The series of normal data. Must contain at least one complete window.
+    /// * `tolerance`: The fraction of normal windows allowed outside each bound, from 0 (the most extreme normal
+    ///   scores) to 1.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically.
+    ///
+    /// # Returns
+    ///
+    /// The calibrated bounds, which are also stored in the detector.
+    pub fn calibrate(
+        self: &mut Self,
+        series: &[f64],
+        tolerance: f64,
+        threads: Option<usize>,
+    ) -> AnomalyBounds {
+        assert!(
+            (0.0..=1.0).contains(&tolerance),
+            "Anomaly detector tolerance must be between 0 and 1!"
+        );
+        let scores = self.score_series(series, threads);
+        assert!(
+            !scores.is_empty(),
+            "Calibration series must contain at least one complete window!"
+        );
+
+        let mut energies: Vec<f64> = scores.iter().map(|score| score.energy).collect();
+        let mut overlaps: Vec<f64> = scores.iter().map(|score| score.overlap).collect();
+        energies.sort_by(f64::total_cmp);
+        overlaps.sort_by(f64::total_cmp);
+        let quantile_index = |quantile: f64| {
+            ((quantile * (scores.len() - 1) as f64).round() as usize).min(scores.len() - 1)
+        };
+
+        let bounds = AnomalyBounds {
+            maximum_energy: energies[quantile_index(1.0 - tolerance)],
+            minimum_overlap: overlaps[quantile_index(tolerance)],
+        };
+        self.bounds = Some(bounds);
+        bounds
+    }
+
+    /// Score every window of a series and return the windows outside the calibrated bounds.
+    ///
+    /// # Arguments
+    ///
+    /// * `series`: The series to check.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically.
+    ///
+    /// # Returns
+    ///
+    /// The scores of the anomalous windows, in order of the start of the window.
+    pub fn detect(self: &mut Self, series: &[f64], threads: Option<usize>) -> Vec<WindowScore> {
+        let bounds = self
+            .bounds
+            .expect("Anomaly detector must be calibrated before detecting anomalies!");
+        self.score_series(series, threads)
+            .into_iter()
+            .filter(|score| bounds.is_anomalous(score))
+            .collect()
+    }
+}
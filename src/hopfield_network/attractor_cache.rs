@@ -0,0 +1,146 @@
+use super::{relaxation_result::RelaxationResult, HopfieldNetwork, NetworkDomain};
+use nalgebra::DVector;
+use std::collections::{HashMap, HashSet};
+
+/// Create a compact fingerprint of a state, suitable for use as a hash key.
+///
+/// Binary and Bipolar states are packed into bits (one bit per unit, set if the unit is positive), so a
+/// fingerprint is 64 times smaller than the state itself. Other domains use the exact bits of each value.
+///
+/// # Arguments
+///
+/// * `state`: The state to fingerprint.
+/// * `domain`: The domain of the state.
+///
+/// # Returns
+///
+/// The fingerprint as a `Vec<u64>`. Two states in the same domain are equal exactly when their fingerprints are.
+pub fn state_fingerprint(state: &DVector<f64>, domain: NetworkDomain) -> Vec<u64> {
+    match domain {
+        NetworkDomain::Binary | NetworkDomain::Bipolar => {
+            let mut packed_state = vec![0u64; state.len().div_ceil(64)];
+            for (unit_index, value) in state.iter().enumerate() {
+                if *value > 0.0 {
+                    packed_state[unit_index / 64] |= 1 << (unit_index % 64);
+                }
+            }
+            packed_state
+        }
+        _ => state.iter().map(|value| value.to_bits()).collect(),
+    }
+}
+
+/// Statistics describing how useful an AttractorCache has been.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AttractorCacheStatistics {
+    pub hits: usize,
+    pub misses: usize,
+    pub entries: usize,
+}
+
+impl AttractorCacheStatistics {
+    /// Get the fraction of lookups that were found in the cache, or 0 if there have been no lookups.
+    pub fn hit_rate(self: &Self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A memo of relaxation results, mapping the fingerprint of a cue to the attractor it relaxed to.
+///
+/// Note that relaxation uses a random update order, so a cached result is one sample of the attractors
+/// reachable from the cue rather than the only possibility.
+#[derive(Debug)]
+pub struct AttractorCache {
+    domain: NetworkDomain,
+    attractors: HashMap<Vec<u64>, DVector<f64>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl AttractorCache {
+    /// Create a new, empty cache for states of the given domain.
+    pub fn new_attractor_cache(domain: NetworkDomain) -> Self {
+        Self {
+            domain,
+            attractors: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Get the statistics of this cache.
+    pub fn statistics(self: &Self) -> AttractorCacheStatistics {
+        AttractorCacheStatistics {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.attractors.len(),
+        }
+    }
+
+    /// Remove every cached attractor. This must be done whenever the network weights change.
+    /// The hit and miss counts are kept.
+    pub fn clear(self: &mut Self) {
+        self.attractors.clear();
+    }
+
+    /// Relax a single state, using the cached attractor if this cue has been seen before. A cached attractor is
+    /// reported with no iterations, and its stability is checked again.
+    pub(super) fn relax_state(
+        self: &mut Self,
+        network: &mut HopfieldNetwork,
+        state: DVector<f64>,
+    ) -> RelaxationResult {
+        let fingerprint = state_fingerprint(&state, self.domain);
+        if let Some(attractor) = self.attractors.get(&fingerprint) {
+            self.hits += 1;
+            return network.cached_relaxation_result(attractor.clone());
+        }
+
+        self.misses += 1;
+        let result = network.relax_state(state);
+        self.attractors.insert(fingerprint, result.state.clone());
+        result
+    }
+
+    /// Relax a collection of states concurrently, only relaxing the distinct cues that have not been seen before.
+    pub(super) fn concurrent_relax_state_collection(
+        self: &mut Self,
+        network: &mut HopfieldNetwork,
+        state_collection: Vec<DVector<f64>>,
+        threads: Option<usize>,
+    ) -> Vec<DVector<f64>> {
+        let fingerprints: Vec<Vec<u64>> = state_collection
+            .iter()
+            .map(|state| state_fingerprint(state, self.domain))
+            .collect();
+
+        // Gather the first occurrence of every cue not already cached
+        let mut uncached_fingerprints = Vec::new();
+        let mut uncached_states = Vec::new();
+        let mut pending = HashSet::new();
+        for (fingerprint, state) in fingerprints.iter().zip(state_collection) {
+            if self.attractors.contains_key(fingerprint) || pending.contains(fingerprint) {
+                self.hits += 1;
+            } else {
+                self.misses += 1;
+                pending.insert(fingerprint.clone());
+                uncached_fingerprints.push(fingerprint.clone());
+                uncached_states.push(state);
+            }
+        }
+
+        let relaxed_states = network.concurrent_relax_state_collection(uncached_states, threads);
+        self.attractors
+            .extend(uncached_fingerprints.into_iter().zip(relaxed_states));
+
+        fingerprints
+            .iter()
+            .map(|fingerprint| self.attractors[fingerprint].clone())
+            .collect()
+    }
+}
@@ -0,0 +1,83 @@
+use super::{attractor_cache::state_fingerprint, NetworkDomain};
+use nalgebra::DVector;
+use std::collections::HashMap;
+
+/// An online count of the distinct attractors reached over a stream of relaxed states.
+///
+/// Only one representative state is kept for each distinct attractor, along with a hit count,
+/// so millions of relaxed states can be counted without storing every one.
+#[derive(Debug)]
+pub struct AttractorCounter {
+    domain: NetworkDomain,
+    sign_invariant: bool,
+    attractors: HashMap<Vec<u64>, (DVector<f64>, usize)>,
+    total_states: usize,
+}
+
+impl AttractorCounter {
+    /// Create a new, empty counter.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain`: The domain of the states to be counted.
+    /// * `sign_invariant`: If true, a state and its inverse (every unit flipped, see NetworkDomain::invert_value)
+    ///     are counted as the same attractor. For symmetric networks with no bias the inverse of an attractor is also an attractor.
+    #[allow(clippy::doc_overindented_list_items)]
+    pub fn new_attractor_counter(domain: NetworkDomain, sign_invariant: bool) -> Self {
+        Self {
+            domain,
+            sign_invariant,
+            attractors: HashMap::new(),
+            total_states: 0,
+        }
+    }
+
+    /// Count a relaxed state. The state is only kept if it is the first of its attractor.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The relaxed state to count.
+    pub fn add(self: &mut Self, state: DVector<f64>) {
+        let mut fingerprint = state_fingerprint(&state, self.domain);
+        if self.sign_invariant {
+            let inverse_state = state.map(|value| self.domain.invert_value(value));
+            fingerprint = fingerprint.min(state_fingerprint(&inverse_state, self.domain));
+        }
+
+        self.attractors.entry(fingerprint).or_insert((state, 0)).1 += 1;
+        self.total_states += 1;
+    }
+
+    /// Get the number of distinct attractors counted.
+    pub fn unique_attractors(self: &Self) -> usize {
+        self.attractors.len()
+    }
+
+    /// Get the total number of states counted.
+    pub fn total_states(self: &Self) -> usize {
+        self.total_states
+    }
+
+    /// Get each distinct attractor with its hit count.
+    ///
+    /// # Returns
+    ///
+    /// The first state seen for each attractor, with the number of states that reached that attractor,
+    /// ordered from most to least common.
+    pub fn attractors(self: &Self) -> Vec<(&DVector<f64>, usize)> {
+        let mut attractors: Vec<(&DVector<f64>, usize)> = self
+            .attractors
+            .values()
+            .map(|(state, count)| (state, *count))
+            .collect();
+        attractors.sort_by_key(|attractor| std::cmp::Reverse(attractor.1));
+        attractors
+    }
+
+    /// Consume the counter, returning each distinct attractor with its hit count, ordered from most to least common.
+    pub fn into_attractors(self: Self) -> Vec<(DVector<f64>, usize)> {
+        let mut attractors: Vec<(DVector<f64>, usize)> = self.attractors.into_values().collect();
+        attractors.sort_by_key(|attractor| std::cmp::Reverse(attractor.1));
+        attractors
+    }
+}
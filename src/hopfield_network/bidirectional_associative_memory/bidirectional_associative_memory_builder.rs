@@ -0,0 +1,163 @@
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use super::super::domain_preset::DomainPreset;
+use super::{ActivationParameters, BidirectionalAssociativeMemory, NetworkDomain};
+
+/// Define a builder for a new bidirectional associative memory.
+///
+/// The builder takes parameters to define the behavior of the memory once built.
+///
+/// See the associated methods for more details on what each parameter affects.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BidirectionalAssociativeMemoryBuilder {
+    x_dimension: usize,
+    y_dimension: usize,
+    domain: NetworkDomain,
+    maximum_relaxation_iterations: Option<i32>,
+    activation_parameters: ActivationParameters,
+}
+
+#[allow(dead_code)]
+impl BidirectionalAssociativeMemoryBuilder {
+    /// Create a new builder with every parameter at its default.
+    ///
+    /// Both dimensions and the domain must be set before building.
+    pub fn new_bidirectional_associative_memory_builder() -> Self {
+        Self {
+            x_dimension: 0,
+            y_dimension: 0,
+            domain: NetworkDomain::Unspecified,
+            maximum_relaxation_iterations: None,
+            activation_parameters: ActivationParameters::default(),
+        }
+    }
+
+    /// Set the dimension of the X layer.
+    ///
+    /// This must be set to a positive integer before building.
+    ///
+    /// # Arguments
+    ///
+    /// * `x_dimension` - the number of units in the X layer.
+    pub fn set_x_dimension(mut self: Self, x_dimension: usize) -> Self {
+        self.x_dimension = x_dimension;
+        self
+    }
+
+    /// Set the dimension of the Y layer. This may differ from the X dimension.
+    ///
+    /// This must be set to a positive integer before building.
+    ///
+    /// # Arguments
+    ///
+    /// * `y_dimension` - the number of units in the Y layer.
+    pub fn set_y_dimension(mut self: Self, y_dimension: usize) -> Self {
+        self.y_dimension = y_dimension;
+        self
+    }
+
+    /// Set the domain of both layers. This in turn sets the activation function.
+    ///
+    /// Note that Unspecified is the default and throws and error if building is attempted.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain` - the domain of both layers.
+    pub fn set_domain(mut self: Self, domain: NetworkDomain) -> Self {
+        self.domain = domain;
+        self
+    }
+
+    /// Set the maximum number of relaxation iterations, each updating the Y layer then the X layer.
+    ///
+    /// Defaults to the preset of the domain (see DomainPreset), which also sets the activation gain.
+    ///
+    /// # Arguments
+    ///
+    /// * `maximum_relaxation_iterations` - the maximum number of iterations of a relaxation.
+    pub fn set_maximum_relaxation_iterations(
+        mut self: Self,
+        maximum_relaxation_iterations: i32,
+    ) -> Self {
+        self.maximum_relaxation_iterations = Some(maximum_relaxation_iterations);
+        self
+    }
+
+    /// Set the threshold of the binary activation function in the builder.
+    ///
+    /// Defaults to 0.0.
+    ///
+    /// # Arguments
+    ///
+    /// * `binary_threshold` - the threshold above which fields map to 1 in the Binary domain.
+    pub fn set_binary_threshold(mut self: Self, binary_threshold: f64) -> Self {
+        self.activation_parameters.binary_threshold = binary_threshold;
+        self
+    }
+
+    /// Set the dead zone of the ternary activation function in the builder.
+    ///
+    /// Defaults to 0.5. Must be non-negative.
+    ///
+    /// # Arguments
+    ///
+    /// * `ternary_threshold` - fields within ±threshold map to 0 in the Ternary domain.
+    pub fn set_ternary_threshold(mut self: Self, ternary_threshold: f64) -> Self {
+        self.activation_parameters.ternary_threshold = ternary_threshold;
+        self
+    }
+
+    /// Serialize the parameters of this builder to a JSON config, so the exact construction of a memory
+    /// can be stored alongside results and repeated later with from_config.
+    pub fn to_config(self: &Self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Create a builder from a JSON config created by to_config.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - the JSON config to parse.
+    ///
+    /// # Returns
+    ///
+    /// The builder, or an error if the config is not valid.
+    pub fn from_config(config: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(config)
+    }
+
+    /// Build and return a new BidirectionalAssociativeMemory with no stored pairs, using the parameters specified
+    /// with builder methods. Note this consumes the builder.
+    pub fn build(self: Self) -> BidirectionalAssociativeMemory {
+        assert!(self.x_dimension > 0 && self.y_dimension > 0,
+            "BidirectionalAssociativeMemoryBuilder encountered an error during build! Both dimensions must be explicitly set to positive integers!");
+
+        assert!(self.domain != NetworkDomain::Unspecified,
+            "BidirectionalAssociativeMemoryBuilder encountered an error during build! Domain must be explicitly set to a valid network domain!");
+
+        assert!(self.activation_parameters.ternary_threshold >= 0.0,
+            "BidirectionalAssociativeMemoryBuilder encountered an error during build! Ternary threshold must be non-negative!");
+
+        let preset = DomainPreset::for_domain(self.domain);
+        let maximum_relaxation_iterations = self
+            .maximum_relaxation_iterations
+            .unwrap_or(preset.maximum_relaxation_iterations);
+        let activation_parameters = ActivationParameters {
+            gain: preset.activation_gain,
+            ..self.activation_parameters
+        };
+
+        assert!(maximum_relaxation_iterations > 0,
+            "BidirectionalAssociativeMemoryBuilder encountered an error during build! Maximum relaxation iterations must be strictly positive!");
+
+        BidirectionalAssociativeMemory {
+            matrix: DMatrix::<f64>::zeros(self.x_dimension, self.y_dimension),
+            domain: self.domain,
+            activation_fn: self.domain.activation_fn(),
+            activation_parameters,
+            maximum_relaxation_iterations,
+            stored_pairs: 0,
+        }
+    }
+}
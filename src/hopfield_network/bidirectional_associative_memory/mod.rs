@@ -0,0 +1,162 @@
+pub mod bidirectional_associative_memory_builder;
+
+use super::{
+    activation_function::{ActivationFunction, ActivationParameters},
+    NetworkDomain,
+};
+use nalgebra::{DMatrix, DVector};
+
+/// A bidirectional associative memory (Kosko): a heteroassociative memory of pairs of patterns (x, y), held in a
+/// rectangular weight matrix W between an X layer and a Y layer of possibly different dimensions.
+///
+/// A cue on either layer is relaxed by updating the layers in turn, y = f(Wᵀx) then x = f(Wy), until neither changes.
+/// In the discrete domains each layer update never increases the energy E(x, y) = -xᵀWy, so relaxation always
+/// reaches a stable pair.
+///
+/// Create one with a BidirectionalAssociativeMemoryBuilder, which mirrors the HopfieldNetworkBuilder.
+#[derive(Debug, Clone)]
+pub struct BidirectionalAssociativeMemory {
+    /// The weights, one row per X unit and one column per Y unit.
+    matrix: DMatrix<f64>,
+    domain: NetworkDomain,
+    activation_fn: ActivationFunction,
+    activation_parameters: ActivationParameters,
+    maximum_relaxation_iterations: i32,
+    stored_pairs: usize,
+}
+
+impl BidirectionalAssociativeMemory {
+    /// Returns the dimension of the X layer.
+    pub fn get_x_dimension(self: &Self) -> usize {
+        self.matrix.nrows()
+    }
+
+    /// Returns the dimension of the Y layer.
+    pub fn get_y_dimension(self: &Self) -> usize {
+        self.matrix.ncols()
+    }
+
+    /// Returns the domain of both layers.
+    pub fn get_domain(self: &Self) -> NetworkDomain {
+        self.domain
+    }
+
+    /// Returns the weight matrix, one row per X unit and one column per Y unit.
+    pub fn get_matrix(self: &Self) -> &DMatrix<f64> {
+        &self.matrix
+    }
+
+    /// Returns the number of pairs learned so far.
+    pub fn get_stored_pair_count(self: &Self) -> usize {
+        self.stored_pairs
+    }
+
+    /// Map a pattern to the values used in learning: Binary patterns are mapped to bipolar values (2ξ - 1), as in
+    /// learn_states of HopfieldNetwork, so that inactive units contribute to the weights.
+    fn learning_vector(self: &Self, pattern: &DVector<f64>) -> DVector<f64> {
+        match self.domain {
+            NetworkDomain::Binary => pattern.map(|value| 2.0 * value - 1.0),
+            _ => pattern.clone(),
+        }
+    }
+
+    /// Store pairs of patterns using the Hebbian rule, W += xyᵀ for each pair (x, y).
+    ///
+    /// # Arguments
+    ///
+    /// * `pairs`: The pairs to store. Every x must match the X dimension and every y the Y dimension.
+    pub fn learn_pairs(self: &mut Self, pairs: &[(DVector<f64>, DVector<f64>)]) {
+        assert!(
+            pairs.iter().all(|(x, y)| {
+                x.len() == self.get_x_dimension() && y.len() == self.get_y_dimension()
+            }),
+            "Every pair must match the dimensions of the X and Y layers!"
+        );
+
+        for (x, y) in pairs {
+            let x = self.learning_vector(x);
+            let y = self.learning_vector(y);
+            self.matrix.ger(1.0, &x, &y, 1.0);
+        }
+        self.stored_pairs += pairs.len();
+    }
+
+    /// Get the energy of a pair of layer states, E(x, y) = -xᵀWy.
+    pub fn pair_energy(self: &Self, x: &DVector<f64>, y: &DVector<f64>) -> f64 {
+        -x.dot(&(&self.matrix * y))
+    }
+
+    /// Update the Y layer from the X layer, y = f(Wᵀx).
+    pub fn update_y(self: &Self, x: &DVector<f64>) -> DVector<f64> {
+        (self.activation_fn)(self.matrix.tr_mul(x), &self.activation_parameters)
+    }
+
+    /// Update the X layer from the Y layer, x = f(Wy).
+    pub fn update_x(self: &Self, y: &DVector<f64>) -> DVector<f64> {
+        (self.activation_fn)(&self.matrix * y, &self.activation_parameters)
+    }
+
+    /// Relax a pair of layer states by updating the Y layer then the X layer in turn, until an iteration changes
+    /// neither layer or the maximum number of relaxation iterations is reached.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: The state of the X layer. Consumes the state.
+    /// * `y`: The state of the Y layer. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed X state, the relaxed Y state, and the number of iterations performed.
+    pub fn relax_pair(
+        self: &Self,
+        mut x: DVector<f64>,
+        mut y: DVector<f64>,
+    ) -> (DVector<f64>, DVector<f64>, usize) {
+        assert!(
+            x.len() == self.get_x_dimension() && y.len() == self.get_y_dimension(),
+            "States must match the dimensions of the X and Y layers!"
+        );
+
+        let mut iterations = 0;
+        while iterations < self.maximum_relaxation_iterations as usize {
+            iterations += 1;
+            let next_y = self.update_y(&x);
+            let next_x = self.update_x(&next_y);
+            let stable = next_x == x && next_y == y;
+            x = next_x;
+            y = next_y;
+            if stable {
+                break;
+            }
+        }
+        (x, y, iterations)
+    }
+
+    /// Recall the pair of a cue on the X layer: the Y layer is first updated from the cue, then the pair is relaxed.
+    ///
+    /// # Arguments
+    ///
+    /// * `x`: The cue on the X layer. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed X state, the relaxed Y state, and the number of iterations performed.
+    pub fn relax_from_x(self: &Self, x: DVector<f64>) -> (DVector<f64>, DVector<f64>, usize) {
+        let y = self.update_y(&x);
+        self.relax_pair(x, y)
+    }
+
+    /// Recall the pair of a cue on the Y layer: the X layer is first updated from the cue, then the pair is relaxed.
+    ///
+    /// # Arguments
+    ///
+    /// * `y`: The cue on the Y layer. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed X state, the relaxed Y state, and the number of iterations performed.
+    pub fn relax_from_y(self: &Self, y: DVector<f64>) -> (DVector<f64>, DVector<f64>, usize) {
+        let x = self.update_x(&y);
+        self.relax_pair(x, y)
+    }
+}
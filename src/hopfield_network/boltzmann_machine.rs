@@ -0,0 +1,294 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::{energy_function, local_field::LocalFieldOperator, HopfieldNetwork, NetworkDomain};
+
+/// A geometric cooling schedule for simulated annealing, see BoltzmannMachine::anneal.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct AnnealingSchedule {
+    /// The temperature of the first sweep.
+    pub initial_temperature: f64,
+    /// The temperature of the last sweep.
+    pub final_temperature: f64,
+    /// The number of Gibbs sweeps, each at a temperature a constant factor below the last.
+    pub sweeps: usize,
+}
+
+impl AnnealingSchedule {
+    /// Get the temperature of a sweep of this schedule.
+    ///
+    /// # Arguments
+    ///
+    /// * `sweep`: The index of the sweep, from 0 to sweeps - 1.
+    pub fn temperature_at(self: &Self, sweep: usize) -> f64 {
+        if self.sweeps <= 1 {
+            return self.final_temperature;
+        }
+        let progress = sweep as f64 / (self.sweeps - 1) as f64;
+        self.initial_temperature
+            * (self.final_temperature / self.initial_temperature).powf(progress)
+    }
+}
+
+/// A stochastic sibling of HopfieldNetwork: a Boltzmann machine over the same weight matrix and discrete domains,
+/// which samples each unit instead of setting it to the activation of its field.
+///
+/// Each Gibbs update samples a unit value v from the domain with probability proportional to exp(-E_v / T), where
+/// E_v is the state energy (see HopfieldNetwork::state_energy) with the unit set to v. For a Bipolar unit i with zero
+/// self-coupling this is P(s_i = +1) = sigmoid(4 h_i / T), as the energy -sᵀWs counts every coupling twice. The
+/// stationary distribution of the states is then the Boltzmann distribution P(s) ∝ exp(-E(s) / T). As T goes to 0
+/// the updates become the deterministic updates of the Hopfield network.
+#[derive(Debug, Clone)]
+pub struct BoltzmannMachine {
+    matrix: DMatrix<f64>,
+    domain: NetworkDomain,
+    temperature: f64,
+    rng: StdRng,
+}
+
+impl BoltzmannMachine {
+    /// Create a new Boltzmann machine from a weight matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `matrix`: The symmetric weight matrix.
+    /// * `domain`: The domain of the units. Must be Binary, Bipolar, or Ternary.
+    /// * `temperature`: The temperature T. Must be strictly positive.
+    /// * `seed`: The seed of the unit update order and samples.
+    pub fn new_boltzmann_machine(
+        matrix: DMatrix<f64>,
+        domain: NetworkDomain,
+        temperature: f64,
+        seed: u64,
+    ) -> Self {
+        assert!(
+            matrix.is_square(),
+            "Boltzmann machine weight matrix must be square!"
+        );
+        assert!(
+            matches!(
+                domain,
+                NetworkDomain::Binary | NetworkDomain::Bipolar | NetworkDomain::Ternary
+            ),
+            "Boltzmann machine domain must be Binary, Bipolar, or Ternary!"
+        );
+        assert!(
+            temperature > 0.0,
+            "Boltzmann machine temperature must be strictly positive!"
+        );
+
+        Self {
+            matrix,
+            domain,
+            temperature,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Get the dimension of this machine.
+    pub fn get_dimension(self: &Self) -> usize {
+        self.matrix.nrows()
+    }
+
+    /// Get the domain of this machine.
+    pub fn get_domain(self: &Self) -> NetworkDomain {
+        self.domain
+    }
+
+    /// Get the weight matrix of this machine.
+    pub fn get_matrix(self: &Self) -> &DMatrix<f64> {
+        &self.matrix
+    }
+
+    /// Get the temperature of this machine.
+    pub fn get_temperature(self: &Self) -> f64 {
+        self.temperature
+    }
+
+    /// Set the temperature of this machine. Must be strictly positive.
+    pub fn set_temperature(self: &mut Self, temperature: f64) {
+        assert!(
+            temperature > 0.0,
+            "Boltzmann machine temperature must be strictly positive!"
+        );
+        self.temperature = temperature;
+    }
+
+    /// Get the energy of a state, the same energy as HopfieldNetwork::state_energy.
+    pub fn state_energy(self: &Self, state: &DVector<f64>) -> f64 {
+        energy_function::state_energy_function(&self.matrix, state)
+    }
+
+    /// Get the possible values of a unit in the domain of this machine.
+    fn unit_values(self: &Self) -> &'static [f64] {
+        match self.domain {
+            NetworkDomain::Binary => &[0.0, 1.0],
+            NetworkDomain::Bipolar => &[-1.0, 1.0],
+            _ => &[-1.0, 0.0, 1.0],
+        }
+    }
+
+    /// Sample a new value for one unit at a given temperature, updating the local fields if it changes.
+    fn sample_unit(
+        self: &mut Self,
+        state: &mut DVector<f64>,
+        fields: &mut DVector<f64>,
+        unit_index: usize,
+        temperature: f64,
+    ) -> bool {
+        let current_value = state[unit_index];
+        let self_coupling = self.matrix[(unit_index, unit_index)];
+        // The energy of each value relative to the current state: -2 Δ h_i - Δ² W_ii for a change Δ
+        let relative_energies: Vec<f64> = self
+            .unit_values()
+            .iter()
+            .map(|value| {
+                let delta = value - current_value;
+                -2.0 * delta * fields[unit_index] - delta * delta * self_coupling
+            })
+            .collect();
+        let lowest_energy = relative_energies
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+        let weights: Vec<f64> = relative_energies
+            .iter()
+            .map(|energy| (-(energy - lowest_energy) / temperature).exp())
+            .collect();
+
+        let mut threshold = self.rng.gen::<f64>() * weights.iter().sum::<f64>();
+        let mut new_value = *self.unit_values().last().unwrap();
+        for (value, weight) in self.unit_values().iter().zip(weights.iter()) {
+            if threshold < *weight {
+                new_value = *value;
+                break;
+            }
+            threshold -= weight;
+        }
+
+        if new_value == current_value {
+            return false;
+        }
+        LocalFieldOperator::Dense(&self.matrix).add_unit_change(
+            fields,
+            unit_index,
+            new_value - current_value,
+        );
+        state[unit_index] = new_value;
+        true
+    }
+
+    /// Sample every unit once, in a random order, at a given temperature.
+    fn gibbs_sweep_at(
+        self: &mut Self,
+        state: &mut DVector<f64>,
+        fields: &mut DVector<f64>,
+        temperature: f64,
+    ) -> usize {
+        let mut unit_indices: Vec<usize> = (0..self.get_dimension()).collect();
+        unit_indices.shuffle(&mut self.rng);
+        unit_indices
+            .into_iter()
+            .filter(|unit_index| self.sample_unit(state, fields, *unit_index, temperature))
+            .count()
+    }
+
+    /// Sample every unit of a state once, in a random order, at the temperature of this machine.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to update, in place.
+    ///
+    /// # Returns
+    ///
+    /// The number of units that changed.
+    pub fn gibbs_sweep(self: &mut Self, state: &mut DVector<f64>) -> usize {
+        let mut fields = &self.matrix * &*state;
+        self.gibbs_sweep_at(state, &mut fields, self.temperature)
+    }
+
+    /// Draw states from the equilibrium (Boltzmann) distribution at the temperature of this machine by Gibbs
+    /// sampling from an initial state.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_state`: The state to start the chain from.
+    /// * `burn_in_sweeps`: The number of sweeps to discard before the first sample, so the chain can equilibrate.
+    /// * `sample_count`: The number of states to draw.
+    /// * `sweeps_between_samples`: The number of sweeps between samples, at least 1. More sweeps give less
+    ///   correlated samples.
+    ///
+    /// # Returns
+    ///
+    /// The sampled states, in the order they were drawn.
+    pub fn sample(
+        self: &mut Self,
+        initial_state: DVector<f64>,
+        burn_in_sweeps: usize,
+        sample_count: usize,
+        sweeps_between_samples: usize,
+    ) -> Vec<DVector<f64>> {
+        assert!(
+            sweeps_between_samples > 0,
+            "There must be at least one sweep between samples!"
+        );
+
+        let mut state = initial_state;
+        let mut fields = &self.matrix * &state;
+        for _ in 0..burn_in_sweeps {
+            self.gibbs_sweep_at(&mut state, &mut fields, self.temperature);
+        }
+
+        let mut samples = Vec::with_capacity(sample_count);
+        for _ in 0..sample_count {
+            for _ in 0..sweeps_between_samples {
+                self.gibbs_sweep_at(&mut state, &mut fields, self.temperature);
+            }
+            samples.push(state.clone());
+        }
+        samples
+    }
+
+    /// Anneal a state: Gibbs sample with a temperature lowered every sweep according to a schedule, so the state
+    /// can escape shallow minima early and settle into a deep minimum late. The temperature of this machine is not
+    /// changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_state`: The state to anneal.
+    /// * `schedule`: The cooling schedule.
+    ///
+    /// # Returns
+    ///
+    /// The annealed state.
+    pub fn anneal(
+        self: &mut Self,
+        initial_state: DVector<f64>,
+        schedule: &AnnealingSchedule,
+    ) -> DVector<f64> {
+        assert!(
+            schedule.initial_temperature > 0.0 && schedule.final_temperature > 0.0,
+            "Annealing temperatures must be strictly positive!"
+        );
+
+        let mut state = initial_state;
+        let mut fields = &self.matrix * &state;
+        for sweep in 0..schedule.sweeps {
+            self.gibbs_sweep_at(&mut state, &mut fields, schedule.temperature_at(sweep));
+        }
+        state
+    }
+}
+
+impl HopfieldNetwork {
+    /// Create a Boltzmann machine over a copy of the weight matrix of this network, see BoltzmannMachine.
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature`: The temperature of the machine. Must be strictly positive.
+    /// * `seed`: The seed of the unit update order and samples.
+    pub fn boltzmann_machine(self: &Self, temperature: f64, seed: u64) -> BoltzmannMachine {
+        BoltzmannMachine::new_boltzmann_machine(self.matrix.clone(), self.domain, temperature, seed)
+    }
+}
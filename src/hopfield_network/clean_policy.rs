@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use super::HopfieldNetwork;
+
+/// When the network cleans its matrix (see clean_matrix), set with set_clean_policy on the builder.
+///
+/// Cleaning forces the zero diagonal and symmetry set in the builder, a full pass over the matrix. For huge matrices
+/// cleaning after every weight change is expensive, so it may be left to the user instead. Weights between units that
+/// are not connected in the topology of the network are always removed, whatever the policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CleanPolicy {
+    /// Clean the matrix after every learning or unlearning step, the default.
+    OnEveryLearn,
+    /// Clean the matrix only when the user calls clean_matrix.
+    Manual,
+    /// Clean the matrix once when the network is built (e.g. a random initial matrix), then only when the user calls
+    /// clean_matrix.
+    OnBuild,
+}
+
+impl HopfieldNetwork {
+    /// Set when the network cleans its matrix. Changing the policy does not clean the matrix, call clean_matrix for
+    /// that.
+    ///
+    /// # Arguments
+    ///
+    /// * `clean_policy`: When to clean the matrix.
+    pub fn set_clean_policy(self: &mut Self, clean_policy: CleanPolicy) {
+        self.clean_policy = clean_policy;
+    }
+
+    /// Get when the network cleans its matrix.
+    pub fn get_clean_policy(self: &Self) -> CleanPolicy {
+        self.clean_policy
+    }
+
+    /// Clean the matrix after the weights were changed by learning, if the clean policy asks for it. Otherwise only
+    /// the topology of the network is applied.
+    ///
+    /// The factorized local fields always leave out the diagonal when force_zero_diagonal is set, so until the
+    /// matrix is cleaned the dense matrix is used instead. Cleaning makes the factorized fields available again.
+    pub(super) fn clean_matrix_after_learning(self: &mut Self) {
+        if self.clean_policy == CleanPolicy::OnEveryLearn {
+            self.clean_matrix();
+            return;
+        }
+
+        if self.force_zero_diagonal {
+            self.uncleaned_diagonal = true;
+        }
+        self.apply_connectivity();
+    }
+}
@@ -0,0 +1,128 @@
+use super::{HopfieldNetwork, NetworkDomain};
+use image::{GrayImage, ImageResult, Luma};
+use nalgebra::DVector;
+use std::path::Path;
+
+/// The gray level of the padding between tiles, and of pixels past the end of a state.
+const BACKGROUND_LEVEL: u8 = 128;
+
+/// The layout of a contact sheet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ContactSheetLayout {
+    /// The number of units in each row of a tile. States are drawn row by row, so for image patterns
+    /// this should be the width of the image.
+    pub tile_width: usize,
+    /// The size of each unit in pixels.
+    pub unit_pixels: u32,
+    /// The gap between tiles in pixels.
+    pub padding: u32,
+}
+
+/// Render rows of states as a single grayscale contact sheet, one tile per state.
+///
+/// Unit values are mapped from value_range to black (low) through white (high), clamping values outside the range.
+///
+/// # Arguments
+///
+/// * `rows`: The rows of the sheet. Rows may have different lengths.
+/// * `layout`: The layout of the tiles.
+/// * `value_range`: The (low, high) range of unit values.
+///
+/// # Returns
+///
+/// The contact sheet as a GrayImage.
+pub fn contact_sheet(
+    rows: &[&[DVector<f64>]],
+    layout: ContactSheetLayout,
+    value_range: (f64, f64),
+) -> GrayImage {
+    assert!(
+        layout.tile_width > 0,
+        "Contact sheet tile width must be positive!"
+    );
+    let dimension = rows
+        .iter()
+        .flat_map(|row| row.iter())
+        .map(|state| state.len())
+        .max()
+        .unwrap_or(0);
+    let tile_height = dimension.div_ceil(layout.tile_width) as u32;
+    let tile_pixel_width = layout.tile_width as u32 * layout.unit_pixels;
+    let tile_pixel_height = tile_height * layout.unit_pixels;
+    let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0) as u32;
+
+    let sheet_width = columns * (tile_pixel_width + layout.padding) + layout.padding;
+    let sheet_height = rows.len() as u32 * (tile_pixel_height + layout.padding) + layout.padding;
+    let mut sheet = GrayImage::from_pixel(sheet_width, sheet_height, Luma([BACKGROUND_LEVEL]));
+
+    let (low, high) = value_range;
+    let span = if high > low { high - low } else { 1.0 };
+    for (row_index, row) in rows.iter().enumerate() {
+        let tile_y = layout.padding + row_index as u32 * (tile_pixel_height + layout.padding);
+        for (column_index, state) in row.iter().enumerate() {
+            let tile_x = layout.padding + column_index as u32 * (tile_pixel_width + layout.padding);
+            for (unit_index, value) in state.iter().enumerate() {
+                let level = (((value - low) / span).clamp(0.0, 1.0) * 255.0).round() as u8;
+                let unit_x = tile_x + (unit_index % layout.tile_width) as u32 * layout.unit_pixels;
+                let unit_y = tile_y + (unit_index / layout.tile_width) as u32 * layout.unit_pixels;
+                for dy in 0..layout.unit_pixels {
+                    for dx in 0..layout.unit_pixels {
+                        sheet.put_pixel(unit_x + dx, unit_y + dy, Luma([level]));
+                    }
+                }
+            }
+        }
+    }
+
+    sheet
+}
+
+/// Write a contact sheet of a recall batch as a PNG: the first row holds the stored patterns of the network,
+/// the second the cues, and the third the recalled states (so each recalled state sits below its cue).
+///
+/// Values are mapped to gray levels using the range of the network domain. In the Continuous domain
+/// the range is taken from the states drawn.
+///
+/// # Arguments
+///
+/// * `path`: The path to write the PNG to.
+/// * `network`: The network holding the stored patterns.
+/// * `cues`: The cues of the batch.
+/// * `recalled_states`: The relaxed states of the batch, in the same order as the cues.
+/// * `layout`: The layout of the tiles.
+///
+/// # Returns
+///
+/// An error if the image could not be written.
+pub fn write_recall_contact_sheet(
+    path: impl AsRef<Path>,
+    network: &HopfieldNetwork,
+    cues: &[DVector<f64>],
+    recalled_states: &[DVector<f64>],
+    layout: ContactSheetLayout,
+) -> ImageResult<()> {
+    let stored_patterns: Vec<DVector<f64>> = network
+        .stored_patterns
+        .column_iter()
+        .map(|pattern| pattern.into_owned())
+        .collect();
+    let rows: [&[DVector<f64>]; 3] = [&stored_patterns, cues, recalled_states];
+
+    let value_range = match network.domain {
+        NetworkDomain::Binary => (0.0, 1.0),
+        NetworkDomain::BoundedContinuous => (
+            network.activation_parameters.continuous_lower_bound,
+            network.activation_parameters.continuous_upper_bound,
+        ),
+        NetworkDomain::Bipolar | NetworkDomain::Ternary | NetworkDomain::Tanh => (-1.0, 1.0),
+        _ => rows
+            .iter()
+            .flat_map(|row| row.iter())
+            .flat_map(|state| state.iter())
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| {
+                (low.min(*value), high.max(*value))
+            }),
+    };
+
+    contact_sheet(&rows, layout, value_range).save(path)
+}
@@ -0,0 +1,174 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+use super::{HopfieldNetwork, NetworkDomain};
+
+/// The largest magnitude of a Tanh state inverted by internal_potentials, so that saturated units have a finite
+/// internal potential.
+const TANH_INVERSION_LIMIT: f64 = 1.0 - 1e-12;
+
+/// The numerical integrator of the continuous-time dynamics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OdeIntegrator {
+    /// The forward Euler method, u ← u + h du/dt. Cheap, but needs a small step size to stay accurate.
+    Euler,
+    /// The classical fourth order Runge-Kutta method, with four derivative evaluations per step.
+    RungeKutta4,
+}
+
+/// The parameters of continuous-time relaxation.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ContinuousDynamicsParameters {
+    pub integrator: OdeIntegrator,
+    /// The time step h of the integrator, in units of the time constant of the units.
+    pub step_size: f64,
+    /// The maximum number of steps to take.
+    pub maximum_steps: usize,
+    /// Integration stops once the largest |du/dt| of any unit is at most this tolerance, i.e. at an equilibrium.
+    pub tolerance: f64,
+}
+
+impl Default for ContinuousDynamicsParameters {
+    fn default() -> Self {
+        Self {
+            integrator: OdeIntegrator::RungeKutta4,
+            step_size: 0.1,
+            maximum_steps: 10000,
+            tolerance: 1e-6,
+        }
+    }
+}
+
+/// The result of continuous-time relaxation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContinuousDynamicsResult {
+    /// The output of the units, g(u), in the domain of the network.
+    pub state: DVector<f64>,
+    /// The internal potentials u of the units.
+    pub internal_potentials: DVector<f64>,
+    pub steps: usize,
+    /// The time integrated over, steps × step size.
+    pub time: f64,
+    /// Whether integration stopped at an equilibrium, rather than the maximum number of steps.
+    pub converged: bool,
+}
+
+impl HopfieldNetwork {
+    /// Get the internal potentials u that give a state as output, u = g⁻¹(s), so continuous-time relaxation can
+    /// start from a state of the network. Tanh states are clamped just inside (-1, 1) before inverting.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state, in the domain of the network.
+    pub fn internal_potentials(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        let gain = self.activation_parameters.gain;
+        match self.domain {
+            NetworkDomain::Continuous => state.clone(),
+            NetworkDomain::BoundedContinuous => state / gain,
+            NetworkDomain::Tanh => state.map(|value| {
+                value
+                    .clamp(-TANH_INVERSION_LIMIT, TANH_INVERSION_LIMIT)
+                    .atanh()
+                    / gain
+            }),
+            _ => panic!("Continuous-time dynamics are only defined for continuous domains!"),
+        }
+    }
+
+    /// Get the time derivative of the internal potentials under the Hopfield-Tank dynamics,
+    /// du/dt = -u + W g(u) + I(t).
+    ///
+    /// The activation g is the activation function of the network domain (with its gain). The external input I(t)
+    /// is that of set_external_input, where time t is taken as the sweep floor(t), as one unit of time corresponds
+    /// to one update sweep of the discrete dynamics.
+    ///
+    /// # Arguments
+    ///
+    /// * `internal_potentials`: The internal potentials u.
+    /// * `time`: The time t, used only for the external input.
+    pub fn continuous_dynamics_derivative(
+        self: &Self,
+        internal_potentials: &DVector<f64>,
+        time: f64,
+    ) -> DVector<f64> {
+        let output = (self.activation_fn)(internal_potentials.clone(), &self.activation_parameters);
+        let mut derivative = self.local_fields(&output) - internal_potentials;
+        if let Some(input) = self
+            .external_input
+            .as_ref()
+            .and_then(|external_input| external_input.at(time.max(0.0).floor() as usize))
+        {
+            derivative += input;
+        }
+        derivative
+    }
+
+    /// Relax a state by integrating the continuous-time Hopfield-Tank dynamics, du/dt = -u + W g(u) + I(t), rather than
+    /// flipping units. See continuous_dynamics_derivative.
+    ///
+    /// For symmetric weights and a monotonic activation the dynamics descend a Lyapunov function, so they settle on
+    /// an equilibrium, which in the Tanh domain with a high gain is near a corner of the hypercube. This makes them
+    /// suited to optimization problems encoded in the weights and input. In the Continuous domain the activation is
+    /// the identity and the dynamics are linear, so they only settle if every eigenvalue of the weights is below 1.
+    ///
+    /// Weight masks and the summation order of the network are respected. Fatigue is not applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax, in the domain of the network. The internal potentials start at
+    ///   internal_potentials(state). Consumes the state.
+    /// * `parameters`: The integrator, step size and stopping conditions.
+    ///
+    /// # Returns
+    ///
+    /// A ContinuousDynamicsResult with the relaxed state.
+    pub fn relax_state_continuous_time(
+        self: &Self,
+        state: DVector<f64>,
+        parameters: ContinuousDynamicsParameters,
+    ) -> ContinuousDynamicsResult {
+        assert!(
+            parameters.step_size > 0.0,
+            "Continuous-time step size must be strictly positive!"
+        );
+
+        let h = parameters.step_size;
+        let mut potentials = self.internal_potentials(&state);
+        let mut steps = 0;
+        let mut converged = false;
+        while steps < parameters.maximum_steps {
+            let time = steps as f64 * h;
+            let k1 = self.continuous_dynamics_derivative(&potentials, time);
+            if k1.amax() <= parameters.tolerance {
+                converged = true;
+                break;
+            }
+
+            potentials += match parameters.integrator {
+                OdeIntegrator::Euler => k1 * h,
+                OdeIntegrator::RungeKutta4 => {
+                    let k2 = self.continuous_dynamics_derivative(
+                        &(&potentials + &k1 * (h / 2.0)),
+                        time + h / 2.0,
+                    );
+                    let k3 = self.continuous_dynamics_derivative(
+                        &(&potentials + &k2 * (h / 2.0)),
+                        time + h / 2.0,
+                    );
+                    let k4 =
+                        self.continuous_dynamics_derivative(&(&potentials + &k3 * h), time + h);
+                    (k1 + k2 * 2.0 + k3 * 2.0 + k4) * (h / 6.0)
+                }
+            };
+            steps += 1;
+        }
+
+        ContinuousDynamicsResult {
+            state: (self.activation_fn)(potentials.clone(), &self.activation_parameters),
+            internal_potentials: potentials,
+            steps,
+            time: steps as f64 * h,
+            converged,
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+
+/// A monitor for batch relaxations that aborts the batch if the rolling convergence rate collapses,
+/// which usually means the network is overloaded and the rest of the batch is not worth relaxing.
+///
+/// A state has converged if it finished relaxation with fewer unstable units than the maximum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceMonitor {
+    /// The number of most recently relaxed states the convergence rate is calculated over.
+    /// The batch is never aborted before this many states are relaxed.
+    pub window: usize,
+    /// The batch is aborted if the rolling convergence rate drops below this rate.
+    pub minimum_convergence_rate: f64,
+}
+
+/// The result of a monitored batch relaxation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceReport {
+    /// The number of states relaxed and passed to the sink, including those completed after an abort.
+    pub states_relaxed: usize,
+    /// The number of relaxed states that converged.
+    pub states_converged: usize,
+    /// Whether the batch was aborted before every state was relaxed.
+    pub aborted: bool,
+    /// The convergence rate over the final window of states.
+    pub rolling_convergence_rate: f64,
+}
+
+/// A policy for re-relaxing states that did not converge in a batch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// The maximum number of times the unconverged states are retried.
+    pub max_retries: usize,
+    /// The factor the maximum number of relaxation iterations is multiplied by on every retry.
+    pub iteration_growth: i32,
+}
+
+/// The result of a batch relaxation with retries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RetryReport {
+    /// The number of states relaxed again on each retry.
+    pub states_retried: Vec<usize>,
+    /// The indices of the states that had still not converged after the final retry.
+    pub unconverged_indices: Vec<usize>,
+}
+
+/// Tracks the rolling convergence rate for a ConvergenceMonitor.
+#[derive(Debug)]
+pub(super) struct RollingConvergence {
+    monitor: ConvergenceMonitor,
+    recent: VecDeque<bool>,
+    recent_converged: usize,
+}
+
+impl RollingConvergence {
+    pub(super) fn new_rolling_convergence(monitor: ConvergenceMonitor) -> Self {
+        assert!(
+            monitor.window > 0,
+            "ConvergenceMonitor window must be positive!"
+        );
+        Self {
+            monitor,
+            recent: VecDeque::with_capacity(monitor.window),
+            recent_converged: 0,
+        }
+    }
+
+    /// Record the result of one relaxation.
+    ///
+    /// # Returns
+    ///
+    /// True if the batch should be aborted.
+    pub(super) fn observe(self: &mut Self, converged: bool) -> bool {
+        if self.recent.len() == self.monitor.window && self.recent.pop_front() == Some(true) {
+            self.recent_converged -= 1;
+        }
+        self.recent.push_back(converged);
+        if converged {
+            self.recent_converged += 1;
+        }
+
+        self.recent.len() == self.monitor.window
+            && self.rate() < self.monitor.minimum_convergence_rate
+    }
+
+    /// Get the convergence rate over the current window, or 1 if nothing has been observed.
+    pub(super) fn rate(self: &Self) -> f64 {
+        if self.recent.is_empty() {
+            return 1.0;
+        }
+        self.recent_converged as f64 / self.recent.len() as f64
+    }
+}
@@ -0,0 +1,118 @@
+use nalgebra::{DMatrix, DVector};
+
+use super::{HopfieldNetwork, NetworkDomain};
+
+/// Eigenvalues with magnitude at most this are treated as zero when classifying a critical point.
+const ZERO_CURVATURE_TOLERANCE: f64 = 1e-9;
+
+/// Activation derivatives at most this mark a unit as saturated, and so pinned in place.
+const SATURATION_TOLERANCE: f64 = 1e-12;
+
+/// The kind of critical point found by a curvature probe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CriticalPointKind {
+    /// Every curvature is positive.
+    Minimum,
+    /// Every curvature is negative.
+    Maximum,
+    /// Curvature is positive in some directions and negative in others. Holds the number of negative directions.
+    Saddle { index: usize },
+    /// Some curvature is zero, so the second order test is inconclusive.
+    Degenerate,
+}
+
+/// The curvature of the energy at a state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CurvatureReport {
+    /// The eigenvalues of the effective Hessian over the free units, in ascending order.
+    pub eigenvalues: DVector<f64>,
+    /// The number of units not saturated by the activation, which the Hessian is taken over.
+    pub free_units: usize,
+    /// The largest difference between a unit and the activation of its field. Near 0 if the state is a fixed point.
+    pub fixed_point_residual: f64,
+    /// The Frobenius norm of the antisymmetric part of the weights. The Hessian only uses the symmetric part,
+    /// so if this is large the dynamics are not a gradient flow and the classification is only indicative.
+    pub weight_asymmetry: f64,
+    pub kind: CriticalPointKind,
+}
+
+impl HopfieldNetwork {
+    /// Probe the local curvature of the energy at a converged continuous attractor, to classify it as a minimum or saddle.
+    ///
+    /// For continuous dynamics s = g(W s), the effective Hessian of the energy is H = -W_sym + diag(1 / g'(h)),
+    /// where W_sym is the symmetric part of the weights and g' the derivative of the activation at the local fields.
+    /// Units saturated by the activation (g' = 0) are pinned, so the Hessian is taken over the remaining free units.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to probe, normally the result of relaxation.
+    ///
+    /// # Returns
+    ///
+    /// A CurvatureReport with the curvature spectrum and classification.
+    pub fn probe_curvature(self: &Self, state: &DVector<f64>) -> CurvatureReport {
+        let fields = self.local_fields(state);
+        let gain = self.activation_parameters.gain;
+        let derivatives: DVector<f64> = match self.domain {
+            NetworkDomain::Continuous => DVector::from_element(self.dimension, 1.0),
+            NetworkDomain::BoundedContinuous => fields.map(|field| {
+                let value = gain * field;
+                if value > self.activation_parameters.continuous_lower_bound
+                    && value < self.activation_parameters.continuous_upper_bound
+                {
+                    gain
+                } else {
+                    0.0
+                }
+            }),
+            NetworkDomain::Tanh => fields.map(|field| gain * (1.0 - (gain * field).tanh().powi(2))),
+            _ => panic!("Curvature probes are only defined for continuous domains!"),
+        };
+
+        let fixed_point_residual =
+            ((self.activation_fn)(fields, &self.activation_parameters) - state).amax();
+        let symmetric_weights = (&self.matrix + self.matrix.transpose()) * 0.5;
+        let weight_asymmetry = ((&self.matrix - self.matrix.transpose()) * 0.5).norm();
+
+        let free_units: Vec<usize> = (0..self.dimension)
+            .filter(|unit| derivatives[*unit] > SATURATION_TOLERANCE)
+            .collect();
+        let hessian = DMatrix::<f64>::from_fn(free_units.len(), free_units.len(), |i, j| {
+            let (unit_i, unit_j) = (free_units[i], free_units[j]);
+            let diagonal = if i == j {
+                1.0 / derivatives[unit_i]
+            } else {
+                0.0
+            };
+            diagonal - symmetric_weights[(unit_i, unit_j)]
+        });
+
+        let mut eigenvalues: Vec<f64> = hessian.symmetric_eigenvalues().iter().copied().collect();
+        eigenvalues.sort_by(f64::total_cmp);
+        let negative = eigenvalues
+            .iter()
+            .filter(|value| **value < -ZERO_CURVATURE_TOLERANCE)
+            .count();
+        let positive = eigenvalues
+            .iter()
+            .filter(|value| **value > ZERO_CURVATURE_TOLERANCE)
+            .count();
+        let kind = if negative + positive < eigenvalues.len() {
+            CriticalPointKind::Degenerate
+        } else if negative == 0 {
+            CriticalPointKind::Minimum
+        } else if positive == 0 {
+            CriticalPointKind::Maximum
+        } else {
+            CriticalPointKind::Saddle { index: negative }
+        };
+
+        CurvatureReport {
+            eigenvalues: DVector::from_vec(eigenvalues),
+            free_units: free_units.len(),
+            fixed_point_residual,
+            weight_asymmetry,
+            kind,
+        }
+    }
+}
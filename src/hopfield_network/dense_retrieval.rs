@@ -0,0 +1,103 @@
+use nalgebra::{DMatrix, DVector};
+
+use super::HopfieldNetwork;
+
+/// Retrieve memories for a batch of queries with the dense associative memory (modern Hopfield) update,
+/// ξ_new = Ξ softmax(β Ξᵀ q), using two matrix products for the whole batch.
+///
+/// # Arguments
+///
+/// * `patterns`: The stored patterns, one per column.
+/// * `queries`: The queries, one per column. Must have as many rows as the patterns.
+/// * `beta`: The inverse temperature β of the softmax. Large β retrieves the single closest pattern.
+///
+/// # Returns
+///
+/// The retrieved states, one per column, in the same order as the queries.
+pub fn softmax_retrieval(
+    patterns: &DMatrix<f64>,
+    queries: &DMatrix<f64>,
+    beta: f64,
+) -> DMatrix<f64> {
+    assert_eq!(
+        patterns.nrows(),
+        queries.nrows(),
+        "Queries must have the same dimension as the stored patterns!"
+    );
+
+    let mut weights = patterns.tr_mul(queries) * beta;
+    softmax_columns(&mut weights);
+    patterns * weights
+}
+
+/// Apply the softmax to every column of a matrix of scores in place, so each column sums to 1.
+///
+/// The largest score of each column is subtracted before exponentiating, so large scores do not overflow.
+pub fn softmax_columns(scores: &mut DMatrix<f64>) {
+    for mut column in scores.column_iter_mut() {
+        let max_score = column.max();
+        column.apply(|score| *score = (*score - max_score).exp());
+        let total = column.sum();
+        column /= total;
+    }
+}
+
+impl HopfieldNetwork {
+    /// Retrieve memories for a batch of queries in one step of the dense associative memory update over the stored
+    /// patterns of this network, ξ_new = Ξ softmax(β Ξᵀ q). See softmax_retrieval.
+    ///
+    /// This reads only the stored patterns, not the weight matrix, so thousands of queries can be retrieved
+    /// without relaxing each one in turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `queries`: The queries, one per column.
+    /// * `beta`: The inverse temperature β of the softmax.
+    ///
+    /// # Returns
+    ///
+    /// The retrieved states, one per column. If no patterns are stored, every retrieved state is zero.
+    pub fn retrieve_batch_dense(self: &Self, queries: &DMatrix<f64>, beta: f64) -> DMatrix<f64> {
+        if self.stored_patterns.ncols() == 0 {
+            return DMatrix::zeros(self.dimension, queries.ncols());
+        }
+        softmax_retrieval(&self.stored_patterns, queries, beta)
+    }
+
+    /// Retrieve a memory for a single query with the dense associative memory update restricted to the stored
+    /// patterns nearest the query, found with nearest_memories (and so the pattern index, if set).
+    ///
+    /// With many thousands of stored patterns and a large β the softmax weight of every distant pattern is
+    /// negligible, so this closely approximates retrieve_batch_dense at a fraction of the cost.
+    ///
+    /// # Arguments
+    ///
+    /// * `query`: The query.
+    /// * `beta`: The inverse temperature β of the softmax.
+    /// * `candidates`: The number of nearest stored patterns to retrieve from.
+    ///
+    /// # Returns
+    ///
+    /// The retrieved state. If no patterns are stored, the retrieved state is zero.
+    pub fn retrieve_dense_approximate(
+        self: &Self,
+        query: &DVector<f64>,
+        beta: f64,
+        candidates: usize,
+    ) -> DVector<f64> {
+        let nearest_patterns: Vec<DVector<f64>> = self
+            .nearest_memories(query, candidates)
+            .into_iter()
+            .map(|(_, pattern, _)| pattern)
+            .collect();
+        if nearest_patterns.is_empty() {
+            return DVector::zeros(self.dimension);
+        }
+
+        let patterns = DMatrix::from_columns(&nearest_patterns);
+        let queries = DMatrix::from_column_slice(query.len(), 1, query.as_slice());
+        softmax_retrieval(&patterns, &queries, beta)
+            .column(0)
+            .into_owned()
+    }
+}
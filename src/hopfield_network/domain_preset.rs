@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use super::NetworkDomain;
+
+/// Relaxation settings that suit a network domain, applied by the builder to every setting that is not set
+/// explicitly.
+///
+/// Settings that work for one domain often fail in another: discrete domains reach an exact fixed point in a few
+/// sweeps, while continuous units approach their fixed point geometrically and need more sweeps, and tanh units
+/// with unit gain decay towards the zero state instead of retrieving anything.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DomainPreset {
+    /// The maximum number of update sweeps of a relaxation.
+    pub maximum_relaxation_iterations: i32,
+    /// Relaxation stops once fewer units than this are unstable (see set_maximum_relaxation_unstable_units).
+    pub maximum_relaxation_unstable_units: i32,
+    /// The gain of the continuous activation functions, the inverse of the activation temperature.
+    pub activation_gain: f64,
+    /// The temperature of the unit updates (see set_temperature on the builder).
+    pub temperature: f64,
+}
+
+impl DomainPreset {
+    /// Get the preset of a domain.
+    ///
+    /// Every domain stops relaxing as soon as no unit is unstable. The discrete domains (Binary, Bipolar, Ternary)
+    /// allow 100 sweeps, and the continuous domains (Continuous, BoundedContinuous, Tanh) 200. The Tanh domain uses a
+    /// gain of 4, well above the gain of 1 below which stored patterns are not attractors, and every other domain a
+    /// gain of 1.
+    ///
+    /// Every domain relaxes at zero temperature. Glauber dynamics are only defined for the Binary and Bipolar domains,
+    /// and at a positive temperature units keep flipping, so a relaxation rarely reaches a state with no unstable units
+    /// and runs to the iteration limit without converging. Noise is an experimental choice, not a safe default.
+    pub fn for_domain(domain: NetworkDomain) -> Self {
+        match domain {
+            NetworkDomain::Binary | NetworkDomain::Bipolar | NetworkDomain::Ternary => Self {
+                maximum_relaxation_iterations: 100,
+                maximum_relaxation_unstable_units: 1,
+                activation_gain: 1.0,
+                temperature: 0.0,
+            },
+            NetworkDomain::Continuous | NetworkDomain::BoundedContinuous => Self {
+                maximum_relaxation_iterations: 200,
+                maximum_relaxation_unstable_units: 1,
+                activation_gain: 1.0,
+                temperature: 0.0,
+            },
+            NetworkDomain::Tanh => Self {
+                maximum_relaxation_iterations: 200,
+                maximum_relaxation_unstable_units: 1,
+                activation_gain: 4.0,
+                temperature: 0.0,
+            },
+            NetworkDomain::Unspecified => {
+                panic!("Error finding the domain preset. Domain is unspecified.")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_domain_has_its_preset() {
+        for (domain, maximum_relaxation_iterations, activation_gain) in [
+            (NetworkDomain::Binary, 100, 1.0),
+            (NetworkDomain::Bipolar, 100, 1.0),
+            (NetworkDomain::Ternary, 100, 1.0),
+            (NetworkDomain::Continuous, 200, 1.0),
+            (NetworkDomain::BoundedContinuous, 200, 1.0),
+            (NetworkDomain::Tanh, 200, 4.0),
+        ] {
+            assert_eq!(
+                DomainPreset::for_domain(domain),
+                DomainPreset {
+                    maximum_relaxation_iterations,
+                    maximum_relaxation_unstable_units: 1,
+                    activation_gain,
+                    temperature: 0.0,
+                },
+                "{domain:?}"
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn unspecified_domain_has_no_preset() {
+        DomainPreset::for_domain(NetworkDomain::Unspecified);
+    }
+}
@@ -0,0 +1,98 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+use super::HopfieldNetwork;
+
+/// What to do when learning a pattern that is a near-duplicate of another, see set_duplicate_policy on the builder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DuplicatePolicy {
+    /// Store every pattern without checking for duplicates.
+    Allow,
+    /// Store every pattern, but print a warning for each near-duplicate.
+    Warn,
+    /// Do not store near-duplicates.
+    Skip,
+    /// Combine near-duplicates in the same batch into a single consensus pattern (the activation of their sum).
+    /// Near-duplicates of patterns stored by earlier calls are skipped, as those patterns are already in the weights.
+    Merge,
+}
+
+/// How a network checks newly learned patterns for near-duplicates.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DuplicateHandling {
+    pub policy: DuplicatePolicy,
+    /// Patterns with a normalized overlap at least this large are near-duplicates. An overlap of 1 is an exact duplicate.
+    pub overlap_threshold: f64,
+}
+
+impl HopfieldNetwork {
+    /// Apply the duplicate policy of the network to a batch of patterns about to be learned.
+    ///
+    /// Each pattern is compared to the stored patterns and to the earlier patterns of the batch by normalized overlap
+    /// of their learning vectors (the bipolar mapping in the Binary domain).
+    ///
+    /// # Returns
+    ///
+    /// The patterns that should be learned.
+    pub(super) fn filter_duplicate_patterns(
+        self: &Self,
+        patterns: &[DVector<f64>],
+    ) -> Vec<DVector<f64>> {
+        let DuplicateHandling {
+            policy,
+            overlap_threshold,
+        } = self.duplicate_handling;
+        if policy == DuplicatePolicy::Allow {
+            return patterns.to_vec();
+        }
+
+        let overlap = |a: &DVector<f64>, b: &DVector<f64>| {
+            self.learning_vector(a).dot(&self.learning_vector(b)) / self.dimension as f64
+        };
+        let stored_duplicate = |pattern: &DVector<f64>| {
+            self.stored_patterns
+                .column_iter()
+                .position(|stored_pattern| {
+                    overlap(pattern, &stored_pattern.into_owned()) >= overlap_threshold
+                })
+        };
+
+        // The patterns to learn, and for Merge the sum of the learning vectors of the patterns merged into each
+        let mut accepted: Vec<(DVector<f64>, DVector<f64>)> = Vec::with_capacity(patterns.len());
+        for (pattern_index, pattern) in patterns.iter().enumerate() {
+            let stored_match = stored_duplicate(pattern);
+            let batch_match = accepted.iter().position(|(accepted_pattern, _)| {
+                overlap(pattern, accepted_pattern) >= overlap_threshold
+            });
+
+            match (policy, stored_match, batch_match) {
+                (_, None, None) => accepted.push((pattern.clone(), self.learning_vector(pattern))),
+                (DuplicatePolicy::Warn, _, _) => {
+                    match stored_match {
+                        Some(stored_index) => eprintln!(
+                            "Warning: pattern {} of this batch is a near-duplicate of stored pattern {}",
+                            pattern_index, stored_index
+                        ),
+                        None => eprintln!(
+                            "Warning: pattern {} of this batch is a near-duplicate of an earlier pattern in the batch",
+                            pattern_index
+                        ),
+                    }
+                    accepted.push((pattern.clone(), self.learning_vector(pattern)));
+                }
+                (DuplicatePolicy::Merge, None, Some(accepted_index)) => {
+                    let (merged_pattern, pattern_sum) = &mut accepted[accepted_index];
+                    *pattern_sum += self.learning_vector(pattern);
+                    // Ties in the sum keep the value of the consensus so far
+                    let tie_broken_sum =
+                        &*pattern_sum + self.learning_vector(merged_pattern) * 1e-9;
+                    *merged_pattern =
+                        (self.activation_fn)(tie_broken_sum, &self.activation_parameters);
+                }
+                _ => {}
+            }
+        }
+
+        accepted.into_iter().map(|(pattern, _)| pattern).collect()
+    }
+}
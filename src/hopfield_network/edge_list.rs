@@ -0,0 +1,163 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use super::{HopfieldNetwork, NetworkDomain};
+
+/// A weighted edge of a WeightGraph, named as in the node-link format of NetworkX.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeightEdge {
+    pub source: usize,
+    pub target: usize,
+    pub weight: f64,
+}
+
+/// A node of a WeightGraph, one per unit of the network, with metadata on its activity.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeightNode {
+    /// The index of the unit.
+    pub id: usize,
+    /// The value of the unit in the state given to weight_graph, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activity: Option<f64>,
+    /// The mean value of the unit over the stored patterns, or None if no patterns are stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mean_pattern_activity: Option<f64>,
+    /// The weight from the unit to itself, which is never exported as an edge.
+    pub self_weight: f64,
+}
+
+/// The attributes of a WeightGraph as a whole.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeightGraphAttributes {
+    pub dimension: usize,
+    pub domain: NetworkDomain,
+    /// The smallest absolute weight kept as an edge.
+    pub threshold: f64,
+}
+
+/// The weights of a network as a thresholded weighted graph, for graph-theoretic analysis with existing tools.
+///
+/// Serialized with to_json, this is the node-link format read by networkx.node_link_graph, with the edges under the
+/// `edges` key of recent NetworkX versions (pass edges="edges", or link="edges" before 3.4, to older versions).
+/// write_edge_csv and write_node_csv give tables for e.g. networkx.from_pandas_edgelist or graph-tool. The graph is
+/// undirected if the weight matrix is symmetric, with each pair of units listed once (source < target). Otherwise it is
+/// directed, and the edge from source j to target i has the weight W_ij with which unit j drives the local field of
+/// unit i.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightGraph {
+    pub directed: bool,
+    pub multigraph: bool,
+    pub graph: WeightGraphAttributes,
+    pub nodes: Vec<WeightNode>,
+    pub edges: Vec<WeightEdge>,
+}
+
+impl WeightGraph {
+    /// Get this graph in the node-link JSON format of NetworkX.
+    pub fn to_json(self: &Self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Write the edges as CSV, with a header row `source,target,weight`.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The destination of the CSV, e.g. a File.
+    pub fn write_edge_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(writer, "source,target,weight")?;
+        for edge in &self.edges {
+            writeln!(writer, "{},{},{}", edge.source, edge.target, edge.weight)?;
+        }
+        Ok(())
+    }
+
+    /// Write the nodes as CSV, with a header row. Missing activities are left empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The destination of the CSV, e.g. a File.
+    pub fn write_node_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(writer, "id,activity,mean_pattern_activity,self_weight")?;
+        for node in &self.nodes {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                node.id,
+                node.activity
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+                node.mean_pattern_activity
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+                node.self_weight
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl HopfieldNetwork {
+    /// Get the weights of this network as a thresholded weighted graph, see WeightGraph.
+    ///
+    /// # Arguments
+    ///
+    /// * `threshold`: The smallest absolute weight kept as an edge. Weights of zero are never kept.
+    /// * `state`: A state whose unit values are recorded as the activity of each node, e.g. a relaxed state.
+    ///
+    /// # Returns
+    ///
+    /// The WeightGraph of the weights.
+    pub fn weight_graph(self: &Self, threshold: f64, state: Option<&DVector<f64>>) -> WeightGraph {
+        assert!(
+            threshold >= 0.0,
+            "Edge list weight threshold must be non-negative!"
+        );
+        if let Some(state) = state {
+            assert_eq!(
+                state.len(),
+                self.dimension,
+                "State must have the same dimension as the network!"
+            );
+        }
+
+        let directed = self.matrix != self.matrix.transpose();
+        let pattern_count = self.stored_patterns.ncols();
+        let nodes = (0..self.dimension)
+            .map(|unit| WeightNode {
+                id: unit,
+                activity: state.map(|state| state[unit]),
+                mean_pattern_activity: (pattern_count > 0)
+                    .then(|| self.stored_patterns.row(unit).mean()),
+                self_weight: self.matrix[(unit, unit)],
+            })
+            .collect();
+
+        let mut edges = Vec::new();
+        for source in 0..self.dimension {
+            let first_target = if directed { 0 } else { source + 1 };
+            for target in first_target..self.dimension {
+                let weight = self.matrix[(target, source)];
+                if target != source && weight != 0.0 && weight.abs() >= threshold {
+                    edges.push(WeightEdge {
+                        source,
+                        target,
+                        weight,
+                    });
+                }
+            }
+        }
+
+        WeightGraph {
+            directed,
+            multigraph: false,
+            graph: WeightGraphAttributes {
+                dimension: self.dimension,
+                domain: self.domain,
+                threshold,
+            },
+            nodes,
+            edges,
+        }
+    }
+}
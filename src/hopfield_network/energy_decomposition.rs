@@ -0,0 +1,118 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+use super::HopfieldNetwork;
+
+/// The part of the energy of a state attributable to one stored pattern, see energy_decomposition.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PatternEnergyContribution {
+    /// The index of the pattern in the stored patterns.
+    pub pattern_index: usize,
+    /// The overlap of the state with the (bipolar form of the) pattern, ξ·s / N.
+    pub overlap: f64,
+    /// The energy of the state under the Hebbian weights of this pattern alone. More negative contributions pull the
+    /// state more strongly towards the pattern (or its inverse).
+    pub energy: f64,
+}
+
+/// The energy of a state decomposed over the stored patterns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnergyDecomposition {
+    /// The energy of the state, see state_energy.
+    pub total_energy: f64,
+    /// The contribution of every stored pattern, in stored order.
+    pub contributions: Vec<PatternEnergyContribution>,
+    /// The energy not explained by the Hebbian contributions, from weights not learned by the Hebbian rule (e.g. other
+    /// learning rules, random initialization, weight decay or clipping, a topology, or sequence weights).
+    pub residual_energy: f64,
+}
+
+impl EnergyDecomposition {
+    /// Get the contributions with the most negative energy first, i.e. the memories pulling the state the most.
+    ///
+    /// # Arguments
+    ///
+    /// * `count`: The largest number of contributions to return.
+    pub fn dominant_patterns(self: &Self, count: usize) -> Vec<PatternEnergyContribution> {
+        let mut contributions = self.contributions.clone();
+        contributions.sort_by(|a, b| a.energy.total_cmp(&b.energy));
+        contributions.truncate(count);
+        contributions
+    }
+
+    /// Get the fraction of the Hebbian energy contributed by each pattern, in stored order. A state near a single
+    /// memory has one fraction near 1, while a mixture state spreads the energy over a few patterns.
+    pub fn energy_fractions(self: &Self) -> Vec<f64> {
+        let hebbian_energy: f64 = self
+            .contributions
+            .iter()
+            .map(|contribution| contribution.energy)
+            .sum();
+        self.contributions
+            .iter()
+            .map(|contribution| {
+                if hebbian_energy == 0.0 {
+                    0.0
+                } else {
+                    contribution.energy / hebbian_energy
+                }
+            })
+            .collect()
+    }
+}
+
+impl HopfieldNetwork {
+    /// Decompose the energy of a state into the contributions of the stored patterns.
+    ///
+    /// Hebbian weights are a sum over the stored patterns, W = Σ_μ ξ^μ ξ^μᵀ / N (less the diagonal if it is forced to
+    /// zero), so the energy -sᵀWs is a sum of one term per pattern, -((ξ^μ·s)² - Σ_i (ξ^μ_i s_i)²) / N. Binary
+    /// patterns are used in the bipolar form they were learned with. Any energy from weights not learned this way is
+    /// reported as the residual.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to decompose the energy of.
+    ///
+    /// # Returns
+    ///
+    /// The EnergyDecomposition of the state.
+    pub fn energy_decomposition(self: &Self, state: &DVector<f64>) -> EnergyDecomposition {
+        assert_eq!(
+            state.len(),
+            self.dimension,
+            "State must have the same dimension as the network!"
+        );
+
+        let dimension = self.dimension as f64;
+        let contributions: Vec<PatternEnergyContribution> = self
+            .stored_patterns
+            .column_iter()
+            .enumerate()
+            .map(|(pattern_index, pattern)| {
+                let learning_vector = self.learning_vector(&pattern.into_owned());
+                let projection = learning_vector.dot(state);
+                let self_coupling = if self.force_zero_diagonal {
+                    learning_vector.component_mul(state).norm_squared()
+                } else {
+                    0.0
+                };
+                PatternEnergyContribution {
+                    pattern_index,
+                    overlap: projection / dimension,
+                    energy: -(projection * projection - self_coupling) / dimension,
+                }
+            })
+            .collect();
+
+        let total_energy = self.state_energy(state);
+        let hebbian_energy: f64 = contributions
+            .iter()
+            .map(|contribution| contribution.energy)
+            .sum();
+        EnergyDecomposition {
+            total_energy,
+            contributions,
+            residual_energy: total_energy - hebbian_energy,
+        }
+    }
+}
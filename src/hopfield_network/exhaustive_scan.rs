@@ -0,0 +1,243 @@
+use nalgebra::DVector;
+
+use super::{network_domain::FIXED_POINT_STABILITY_TOLERANCE, HopfieldNetwork, NetworkDomain};
+
+/// The largest dimension an exhaustive scan is allowed for, as the scan visits and stores every one of the 2^N states.
+pub const MAXIMUM_EXHAUSTIVE_SCAN_DIMENSION: usize = 24;
+
+/// The largest number of Gray code steps between full recalculations of the local fields during a scan, bounding the
+/// rounding error accumulated by the incremental updates.
+const FIELD_REFRESH_INTERVAL: usize = 1024;
+
+/// Marks a state whose attractor has not been resolved yet.
+const UNRESOLVED: u32 = u32::MAX;
+/// Marks a state currently being resolved, so a cycle of the dynamics is noticed.
+const RESOLVING: u32 = u32::MAX - 1;
+/// Marks a state that never reaches a stable state, as its dynamics cycle.
+const CYCLING: u32 = u32::MAX - 2;
+
+/// The exact basin of one stable state, found by an exhaustive scan.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExactBasin {
+    /// The stable state.
+    pub attractor: DVector<f64>,
+    /// The index of the stable state, see ExhaustiveScan::state_from_index.
+    pub attractor_index: usize,
+    /// The energy of the stable state.
+    pub energy: f64,
+    /// The number of states whose deterministic dynamics end at this stable state, including itself.
+    pub size: usize,
+}
+
+/// The result of an exhaustive stability scan: the stability of every state of a small network, and the exact basin
+/// of every stable state. This is the ground truth that sampling-based analyses (e.g. AttractorCounter over random
+/// cues) estimate.
+///
+/// States are indexed by the bits of their units: bit i of the index is set when unit i has its high value
+/// (1 in both domains), and is clear when it has its low value (0 for Binary, -1 for Bipolar).
+#[derive(Debug, Clone)]
+pub struct ExhaustiveScan {
+    domain: NetworkDomain,
+    dimension: usize,
+    stable: Vec<bool>,
+    basin_of_state: Vec<u32>,
+    basins: Vec<ExactBasin>,
+    cycling_states: usize,
+}
+
+impl ExhaustiveScan {
+    /// Get the number of states scanned, 2^N.
+    pub fn state_count(self: &Self) -> usize {
+        self.stable.len()
+    }
+
+    /// Get the state with the given index.
+    pub fn state_from_index(self: &Self, state_index: usize) -> DVector<f64> {
+        state_from_bits(state_index, self.dimension, self.domain)
+    }
+
+    /// Get the index of a state, i.e. the bits of the units with their high value.
+    pub fn state_index(self: &Self, state: &DVector<f64>) -> usize {
+        assert_eq!(
+            state.len(),
+            self.dimension,
+            "State must have the same dimension as the scanned network!"
+        );
+        state
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value > 0.0)
+            .fold(0, |index, (unit, _)| index | 1 << unit)
+    }
+
+    /// Check whether the state with the given index is stable, i.e. has no unstable units.
+    pub fn is_stable(self: &Self, state_index: usize) -> bool {
+        self.stable[state_index]
+    }
+
+    /// Get every stable state, in order of index.
+    pub fn stable_states(self: &Self) -> Vec<DVector<f64>> {
+        self.basins
+            .iter()
+            .map(|basin| basin.attractor.clone())
+            .collect()
+    }
+
+    /// Get the exact basin of every stable state, in order of the index of the stable state.
+    pub fn basins(self: &Self) -> &[ExactBasin] {
+        &self.basins
+    }
+
+    /// Get the basin the state with the given index falls into, as an index into basins,
+    /// or None if the dynamics from the state cycle without reaching a stable state.
+    pub fn basin_of(self: &Self, state_index: usize) -> Option<usize> {
+        match self.basin_of_state[state_index] {
+            CYCLING => None,
+            basin_index => Some(basin_index as usize),
+        }
+    }
+
+    /// Get the number of states whose dynamics cycle without reaching a stable state.
+    /// This is always 0 for symmetric weights.
+    pub fn cycling_states(self: &Self) -> usize {
+        self.cycling_states
+    }
+}
+
+impl HopfieldNetwork {
+    /// Scan every state of a small Binary or Bipolar network, classifying each as stable or unstable and finding the
+    /// exact basin of every stable state.
+    ///
+    /// States are visited in Gray code order, so each state differs from the last in one unit and the local fields are
+    /// updated incrementally, with one column of the weights, rather than recalculated. Stability is checked as in
+    /// count_unstable_units, except that a field within rounding error of the threshold is recalculated for its unit
+    /// alone (see LocalFieldOperator::local_field). Basins are those of deterministic dynamics that flip the unstable
+    /// unit with the lowest index until no unit is unstable. For symmetric weights every flip lowers the energy, so
+    /// every state reaches a stable state; otherwise the dynamics may cycle (see ExhaustiveScan::cycling_states).
+    ///
+    /// External input and fatigue are ignored. Weight masks and the topology are respected.
+    /// The scan takes O(N 2^N) time and stores a few bytes per state, so is limited to
+    /// MAXIMUM_EXHAUSTIVE_SCAN_DIMENSION units.
+    ///
+    /// # Returns
+    ///
+    /// An ExhaustiveScan with the stability and basin of every state.
+    pub fn exhaustive_stability_scan(self: &Self) -> ExhaustiveScan {
+        assert!(
+            self.domain == NetworkDomain::Binary || self.domain == NetworkDomain::Bipolar,
+            "Exhaustive scans are only defined for the Binary and Bipolar domains!"
+        );
+        assert!(
+            self.dimension <= MAXIMUM_EXHAUSTIVE_SCAN_DIMENSION,
+            "Exhaustive scans are limited to {MAXIMUM_EXHAUSTIVE_SCAN_DIMENSION} units!"
+        );
+
+        let state_count = 1usize << self.dimension;
+        let local_field_operator = self.local_field_operator();
+        // The field at which a unit changes value: the binary threshold, or 0 for Bipolar
+        let threshold = match self.domain {
+            NetworkDomain::Binary => self.activation_parameters.binary_threshold,
+            _ => 0.0,
+        };
+        let low_value = self.domain.invert_value(1.0);
+        let unit_is_unstable = |unit: usize, state: &DVector<f64>, fields: &DVector<f64>| {
+            // A field at the threshold decides stability by its rounding, so it is recalculated for this unit alone
+            // rather than trusted to the rounding accumulated by the incremental updates
+            let field = if (fields[unit] - threshold).abs() <= FIXED_POINT_STABILITY_TOLERANCE {
+                local_field_operator.local_field(state, unit)
+            } else {
+                fields[unit]
+            };
+            match self.domain {
+                NetworkDomain::Binary => (field > threshold) != (state[unit] > 0.0),
+                _ => field * state[unit] < 0.0,
+            }
+        };
+
+        let mut stable = vec![false; state_count];
+        let mut next_state = vec![0u32; state_count];
+        let mut state = DVector::<f64>::from_element(self.dimension, low_value);
+        let mut fields = local_field_operator.local_fields(&state);
+        for step in 0..state_count {
+            if step > 0 {
+                let unit = step.trailing_zeros() as usize;
+                let next_value = self.domain.invert_value(state[unit]);
+                local_field_operator.add_unit_change(&mut fields, unit, next_value - state[unit]);
+                state[unit] = next_value;
+                if step % FIELD_REFRESH_INTERVAL == 0 {
+                    fields = local_field_operator.local_fields(&state);
+                }
+            }
+
+            let state_index = step ^ (step >> 1);
+            match (0..self.dimension).find(|unit| unit_is_unstable(*unit, &state, &fields)) {
+                Some(unit) => next_state[state_index] = (state_index ^ 1 << unit) as u32,
+                None => {
+                    stable[state_index] = true;
+                    next_state[state_index] = state_index as u32;
+                }
+            }
+        }
+
+        let mut basin_of_state = vec![UNRESOLVED; state_count];
+        let mut basins = Vec::new();
+        for state_index in 0..state_count {
+            if stable[state_index] {
+                basin_of_state[state_index] = basins.len() as u32;
+                let attractor = state_from_bits(state_index, self.dimension, self.domain);
+                basins.push(ExactBasin {
+                    energy: self.state_energy(&attractor),
+                    attractor,
+                    attractor_index: state_index,
+                    size: 0,
+                });
+            }
+        }
+
+        // Follow the dynamics from each unresolved state until reaching a resolved state, then resolve the whole path
+        let mut path = Vec::new();
+        let mut cycling_states = 0;
+        for start in 0..state_count {
+            let mut current = start;
+            while basin_of_state[current] == UNRESOLVED {
+                basin_of_state[current] = RESOLVING;
+                path.push(current);
+                current = next_state[current] as usize;
+            }
+            let basin = match basin_of_state[current] {
+                RESOLVING | CYCLING => CYCLING,
+                basin_index => basin_index,
+            };
+            for state_index in path.drain(..) {
+                basin_of_state[state_index] = basin;
+            }
+        }
+        for basin in basin_of_state.iter() {
+            match *basin {
+                CYCLING => cycling_states += 1,
+                basin_index => basins[basin_index as usize].size += 1,
+            }
+        }
+
+        ExhaustiveScan {
+            domain: self.domain,
+            dimension: self.dimension,
+            stable,
+            basin_of_state,
+            basins,
+            cycling_states,
+        }
+    }
+}
+
+/// Build the state whose units have their high value exactly where the bits of the index are set.
+fn state_from_bits(state_index: usize, dimension: usize, domain: NetworkDomain) -> DVector<f64> {
+    let low_value = domain.invert_value(1.0);
+    DVector::from_fn(dimension, |unit, _| {
+        if state_index >> unit & 1 == 1 {
+            1.0
+        } else {
+            low_value
+        }
+    })
+}
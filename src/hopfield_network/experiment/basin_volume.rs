@@ -0,0 +1,230 @@
+use super::{corrupt_state, derive_seed, wilson_interval};
+use crate::hopfield_network::{
+    attractor_counter::AttractorCounter, HopfieldNetwork, NetworkDomain,
+};
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::io;
+
+/// How the states whose attractors are counted are sampled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BasinSampling {
+    /// Uniformly random states, so each estimate is the fraction of the whole state space in a basin.
+    Uniform,
+    /// A uniformly random stored pattern with exactly `distance` units flipped, so each estimate is the fraction of
+    /// the Hamming shell at that distance around the stored patterns in a basin.
+    HammingShell { distance: usize },
+}
+
+/// The estimated basin volume of one attractor.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasinVolumeEstimate {
+    /// The attractor, as first reached by a sample.
+    pub attractor: DVector<f64>,
+    /// The number of samples that relaxed to the attractor.
+    pub hits: usize,
+    /// The estimated fraction of the sampled states in the basin, hits / samples.
+    pub fraction: f64,
+    /// The lower bound of the Wilson confidence interval of the fraction.
+    pub lower_bound: f64,
+    /// The upper bound of the Wilson confidence interval of the fraction.
+    pub upper_bound: f64,
+    /// The stored pattern with the highest overlap with the attractor, if any patterns are stored.
+    pub nearest_pattern: Option<usize>,
+    /// The overlap of the attractor with the nearest pattern, or 0 if no patterns are stored.
+    pub nearest_overlap: f64,
+    /// The index of the stored pattern the attractor is equal to, or None for a spurious attractor.
+    pub stored_pattern: Option<usize>,
+}
+
+/// The result of estimate_basin_volumes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BasinVolumeReport {
+    pub sampling: BasinSampling,
+    pub samples: usize,
+    /// The standard normal quantile the confidence intervals were calculated with.
+    pub z: f64,
+    /// The estimate of every attractor reached, from most to least hits.
+    pub basins: Vec<BasinVolumeEstimate>,
+}
+
+impl BasinVolumeReport {
+    /// Get the estimates of the basins of the stored patterns: for each stored pattern, the estimate of the
+    /// attractor equal to it, or None if no sample reached it.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern_count`: The number of stored patterns.
+    pub fn pattern_basins(self: &Self, pattern_count: usize) -> Vec<Option<&BasinVolumeEstimate>> {
+        (0..pattern_count)
+            .map(|pattern_index| {
+                self.basins
+                    .iter()
+                    .find(|basin| basin.stored_pattern == Some(pattern_index))
+            })
+            .collect()
+    }
+
+    /// Write the estimates as CSV, with one row per attractor. The attractors themselves are not written.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the CSV to.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "hits,fraction,lower_bound,upper_bound,nearest_pattern,nearest_overlap,stored_pattern"
+        )?;
+        for basin in &self.basins {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                basin.hits,
+                basin.fraction,
+                basin.lower_bound,
+                basin.upper_bound,
+                basin
+                    .nearest_pattern
+                    .map_or(String::new(), |pattern_index| pattern_index.to_string()),
+                basin.nearest_overlap,
+                basin
+                    .stored_pattern
+                    .map_or(String::new(), |pattern_index| pattern_index.to_string())
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Estimate the basin volume of every attractor of a network by Monte Carlo: sample states, relax them concurrently,
+/// and count the samples reaching each attractor.
+///
+/// The fraction of samples reaching an attractor estimates the fraction of the sampled states in its basin, and
+/// each estimate comes with a Wilson confidence interval, so that differences between networks (e.g. learning
+/// rules) can be checked against the sampling error. States that did not converge are counted as the states they
+/// stopped at. For an exact answer on tiny networks see exhaustive_stability_scan.
+///
+/// # Arguments
+///
+/// * `network`: The network to estimate the basins of. Must have the Binary or Bipolar domain.
+/// * `sampling`: How the states are sampled.
+/// * `samples`: The number of states to sample.
+/// * `z`: The standard normal quantile of the confidence level, e.g. 1.96 for 95% confidence.
+/// * `seed`: The seed the samples are drawn from.
+/// * `threads`: The number of threads to relax with. If None, the thread count is chosen automatically.
+///
+/// # Returns
+///
+/// A BasinVolumeReport with one estimate per attractor reached.
+pub fn estimate_basin_volumes(
+    network: &mut HopfieldNetwork,
+    sampling: BasinSampling,
+    samples: usize,
+    z: f64,
+    seed: u64,
+    threads: Option<usize>,
+) -> BasinVolumeReport {
+    let domain = network.get_domain();
+    assert!(
+        domain == NetworkDomain::Binary || domain == NetworkDomain::Bipolar,
+        "Basin volume estimation requires a network with the Binary or Bipolar domain!"
+    );
+    assert!(
+        samples > 0,
+        "Basin volume estimation requires at least one sample!"
+    );
+
+    let mut rng = StdRng::seed_from_u64(derive_seed(seed, 0));
+    let dimension = network.get_dimension();
+    let low_value = domain.invert_value(1.0);
+    let stored_patterns = network.get_stored_patterns().clone();
+    let sampled_states: Vec<DVector<f64>> = match sampling {
+        BasinSampling::Uniform => (0..samples)
+            .map(|_| {
+                DVector::from_fn(
+                    dimension,
+                    |_, _| {
+                        if rng.gen_bool(0.5) {
+                            1.0
+                        } else {
+                            low_value
+                        }
+                    },
+                )
+            })
+            .collect(),
+        BasinSampling::HammingShell { distance } => {
+            assert!(
+                stored_patterns.ncols() > 0,
+                "Hamming shell sampling requires at least one stored pattern!"
+            );
+            assert!(
+                distance <= dimension,
+                "Hamming shell distance must be at most the network dimension!"
+            );
+            (0..samples)
+                .map(|_| {
+                    let pattern_index = rng.gen_range(0..stored_patterns.ncols());
+                    let pattern = stored_patterns.column(pattern_index).into_owned();
+                    corrupt_state(&pattern, domain, distance, &mut rng)
+                })
+                .collect()
+        }
+    };
+
+    let mut counter = AttractorCounter::new_attractor_counter(domain, false);
+    for state in network.concurrent_relax_state_collection(sampled_states, threads) {
+        counter.add(state);
+    }
+
+    let mut attractors = counter.into_attractors();
+    // Break ties in hits by the attractors themselves, so the order does not depend on hashing
+    attractors.sort_by(|a, b| {
+        b.1.cmp(&a.1).then_with(|| {
+            a.0.iter()
+                .zip(b.0.iter())
+                .map(|(a, b)| a.total_cmp(b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+    let attractor_states: Vec<DVector<f64>> = attractors
+        .iter()
+        .map(|(attractor, _)| attractor.clone())
+        .collect();
+    let overlaps = network.overlaps_matrix(&attractor_states);
+
+    let basins = attractors
+        .into_iter()
+        .enumerate()
+        .map(|(attractor_index, (attractor, hits))| {
+            let (lower_bound, upper_bound) = wilson_interval(hits, samples, z);
+            let nearest = overlaps
+                .column(attractor_index)
+                .iter()
+                .copied()
+                .enumerate()
+                .max_by(|a, b| a.1.total_cmp(&b.1));
+            let stored_pattern = stored_patterns
+                .column_iter()
+                .position(|pattern| pattern == attractor);
+            BasinVolumeEstimate {
+                attractor,
+                hits,
+                fraction: hits as f64 / samples as f64,
+                lower_bound,
+                upper_bound,
+                nearest_pattern: nearest.map(|(pattern_index, _)| pattern_index),
+                nearest_overlap: nearest.map_or(0.0, |(_, overlap)| overlap),
+                stored_pattern,
+            }
+        })
+        .collect();
+
+    BasinVolumeReport {
+        sampling,
+        samples,
+        z,
+        basins,
+    }
+}
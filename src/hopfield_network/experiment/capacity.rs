@@ -0,0 +1,294 @@
+use super::{
+    derive_seed, mean_and_std,
+    metric::{MetricRegistry, MetricSummary},
+    shard_trials,
+    trial_sink::{CapacityTrialRecord, TrialSink},
+    ExperimentShard, ExperimentTrial, LearningFunction,
+};
+use crate::hopfield_network::{state_generator::StateGeneratorBuilder, HopfieldNetworkBuilder};
+use crossbeam::channel::Receiver;
+use nalgebra::DVector;
+use std::sync::mpsc::{self, Sender};
+
+/// A single point on a capacity curve.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityCurvePoint {
+    pub num_patterns: usize,
+    pub mean_recall: f64,
+    pub std_recall: f64,
+    pub metrics: Vec<MetricSummary>,
+}
+
+/// Run a capacity experiment, measuring how well a network recalls its stored patterns as more patterns are stored.
+///
+/// For each pattern count P, `trials` independent networks are built, have P random patterns learned
+/// and then relax each of the learned patterns. The recall of a trial is the fraction of patterns that
+/// are relaxed back to exactly themselves.
+///
+/// Every (P, trial) pair is independent, so these are shared out to the threads through a work queue as each thread
+/// becomes free. The seed of each pair is derived from the master seed, and the results are summarized in trial order,
+/// so the curve is the same regardless of thread count. The pair of the trial_index-th trial of the count_index-th
+/// pattern count is trial `count_index * trials + trial_index` of the experiment, which a shard selects from.
+///
+/// # Arguments
+///
+/// * `network_builder`: The builder to create every network from. The rng seed is overwritten per trial.
+/// * `generator_builder`: The builder to create the pattern generators from. The generator seed is overwritten per trial.
+/// * `learning_fn`: The function used to store patterns in each network.
+/// * `metrics`: Additional metrics to collect for each trial, computed over the relaxed patterns.
+/// * `pattern_counts`: The numbers of patterns to test.
+/// * `trials`: The number of independent networks to test for each pattern count.
+/// * `master_seed`: The seed of the entire experiment.
+/// * `shard`: If given, only the trials of this shard are run, and the curve summarizes only those trials.
+/// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+/// * `sink`: If given, every trial is recorded here once all trials have finished, in trial order.
+///
+/// # Returns
+///
+/// The capacity curve, one point for each entry of pattern_counts (in the same order). A point with no trials in the
+/// shard has a NaN mean.
+#[allow(clippy::too_many_arguments)]
+pub fn capacity_experiment(
+    network_builder: &HopfieldNetworkBuilder,
+    generator_builder: &StateGeneratorBuilder,
+    learning_fn: LearningFunction,
+    metrics: &MetricRegistry,
+    pattern_counts: &[usize],
+    trials: usize,
+    master_seed: u64,
+    shard: Option<ExperimentShard>,
+    threads: Option<usize>,
+    mut sink: Option<&mut dyn TrialSink>,
+) -> Vec<CapacityCurvePoint> {
+    let experiment_trials = shard_trials(master_seed, pattern_counts.len() * trials, shard);
+    let total_tasks = experiment_trials.len();
+    let threads = threads
+        .unwrap_or_else(|| network_builder.clone().build().default_thread_count())
+        .min(total_tasks)
+        .max(1);
+
+    // Every task is queued up front, and each thread takes the next task as soon as it is free, so threads given
+    // the larger pattern counts do not hold up the rest
+    let (work_channel_tx, work_channel_rx) = crossbeam::channel::unbounded();
+    for trial in experiment_trials {
+        let count_index = trial.trial_index / trials;
+        work_channel_tx
+            .send((count_index, pattern_counts[count_index], trial))
+            .unwrap();
+    }
+    drop(work_channel_tx);
+
+    let (result_channel_tx, result_channel_rx) = mpsc::channel();
+
+    crossbeam::scope(|scope| {
+        for _ in 0..threads {
+            let network_builder = network_builder.clone();
+            let generator_builder = generator_builder.clone();
+            let work_rx_clone = work_channel_rx.clone();
+            let result_tx_clone = result_channel_tx.clone();
+            scope.spawn(move |_| {
+                capacity_thread_fn(
+                    network_builder,
+                    generator_builder,
+                    learning_fn,
+                    metrics,
+                    work_rx_clone,
+                    result_tx_clone,
+                )
+            });
+        }
+    })
+    .unwrap();
+
+    // Results arrive in whatever order the threads finish, so sort them back into trial order before summarizing
+    drop(result_channel_tx);
+    let mut results: Vec<_> = result_channel_rx.into_iter().collect();
+    results.sort_by_key(|(_, _, trial, _, _)| trial.trial_index);
+
+    let metric_names = metrics.names();
+    let mut recalls = vec![Vec::with_capacity(trials); pattern_counts.len()];
+    let mut metric_values = vec![Vec::with_capacity(trials); pattern_counts.len()];
+    for (count_index, dimension, trial, recall, trial_metric_values) in results {
+        if let Some(sink) = sink.as_deref_mut() {
+            sink.record_capacity_trial(&CapacityTrialRecord {
+                dimension,
+                num_patterns: pattern_counts[count_index],
+                trial_index: trial.trial_index,
+                trial_seed: trial.trial_seed,
+                recall,
+                metrics: metric_names
+                    .iter()
+                    .cloned()
+                    .zip(trial_metric_values.iter().copied())
+                    .collect(),
+            });
+        }
+        recalls[count_index].push(recall);
+        metric_values[count_index].push(trial_metric_values);
+    }
+
+    pattern_counts
+        .iter()
+        .zip(recalls)
+        .zip(metric_values)
+        .map(|((num_patterns, recalls), metric_values)| {
+            let (mean_recall, std_recall) = mean_and_std(&recalls);
+            CapacityCurvePoint {
+                num_patterns: *num_patterns,
+                mean_recall,
+                std_recall,
+                metrics: metrics.summarize(&metric_values),
+            }
+        })
+        .collect()
+}
+
+/// Defines the thread function for capacity_experiment.
+fn capacity_thread_fn(
+    network_builder: HopfieldNetworkBuilder,
+    generator_builder: StateGeneratorBuilder,
+    learning_fn: LearningFunction,
+    metrics: &MetricRegistry,
+    work_channel_rx: Receiver<(usize, usize, ExperimentTrial)>,
+    result_channel_tx: Sender<(usize, usize, ExperimentTrial, f64, Vec<f64>)>,
+) {
+    for (count_index, num_patterns, trial) in work_channel_rx {
+        let mut network = network_builder
+            .clone()
+            .set_rng_seed(trial.network_seed())
+            .build();
+        let mut state_generator = generator_builder
+            .clone()
+            .set_generator_seed(trial.generator_seed())
+            .build();
+
+        let patterns = state_generator.create_state_collection(num_patterns);
+        learning_fn(&mut network, &patterns);
+
+        let relaxed_states: Vec<DVector<f64>> = patterns
+            .iter()
+            .map(|pattern| network.relax_state(pattern.clone()).state)
+            .collect();
+        let recalled = patterns
+            .iter()
+            .zip(&relaxed_states)
+            .filter(|(pattern, relaxed_state)| pattern == relaxed_state)
+            .count();
+        let recall = if num_patterns > 0 {
+            recalled as f64 / num_patterns as f64
+        } else {
+            1.0
+        };
+
+        result_channel_tx
+            .send((
+                count_index,
+                network.get_dimension(),
+                trial,
+                recall,
+                metrics.compute_all(&network, &relaxed_states),
+            ))
+            .unwrap();
+    }
+}
+
+/// A single point on a capacity curve specified by storage load, α = P/N.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoadCurvePoint {
+    pub dimension: usize,
+    pub load: f64,
+    pub num_patterns: usize,
+    pub mean_recall: f64,
+    pub std_recall: f64,
+    pub metrics: Vec<MetricSummary>,
+}
+
+/// Get the number of patterns to store in a network of some dimension to reach a storage load α = P/N.
+///
+/// # Arguments
+///
+/// * `load`: The storage load α.
+/// * `dimension`: The dimension of the network, N.
+///
+/// # Returns
+///
+/// The number of patterns P, rounded to the nearest integer.
+pub fn patterns_for_load(load: f64, dimension: usize) -> usize {
+    (load * dimension as f64).round() as usize
+}
+
+/// Run a capacity experiment over several network dimensions, with pattern counts specified by storage load
+/// α = P/N rather than absolute counts. Curves for different dimensions then sit on the same axis.
+///
+/// Each dimension is run as a capacity_experiment with its own master seed, derived from the master seed of this
+/// experiment and the index of the dimension.
+///
+/// # Arguments
+///
+/// * `network_builder`: The builder to create every network from. The dimension and rng seed are overwritten.
+/// * `generator_builder`: The builder to create the pattern generators from. The dimension and generator seed are overwritten.
+/// * `learning_fn`: The function used to store patterns in each network.
+/// * `metrics`: Additional metrics to collect for each trial, computed over the relaxed patterns.
+/// * `dimensions`: The network dimensions to test.
+/// * `loads`: The storage loads to test for each dimension.
+/// * `trials`: The number of independent networks to test for each (dimension, load) pair.
+/// * `master_seed`: The seed of the entire experiment.
+/// * `shard`: If given, only the trials of this shard are run for each dimension, see capacity_experiment.
+/// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+/// * `sink`: If given, every trial is recorded here in trial order, see capacity_experiment.
+///
+/// # Returns
+///
+/// The capacity curves of every dimension, ordered by dimension and then by load.
+#[allow(clippy::too_many_arguments)]
+pub fn load_capacity_experiment(
+    network_builder: &HopfieldNetworkBuilder,
+    generator_builder: &StateGeneratorBuilder,
+    learning_fn: LearningFunction,
+    metrics: &MetricRegistry,
+    dimensions: &[usize],
+    loads: &[f64],
+    trials: usize,
+    master_seed: u64,
+    shard: Option<ExperimentShard>,
+    threads: Option<usize>,
+    mut sink: Option<&mut dyn TrialSink>,
+) -> Vec<LoadCurvePoint> {
+    let mut load_curve = Vec::with_capacity(dimensions.len() * loads.len());
+
+    for (dimension_index, dimension) in dimensions.iter().enumerate() {
+        let pattern_counts: Vec<usize> = loads
+            .iter()
+            .map(|load| patterns_for_load(*load, *dimension))
+            .collect();
+
+        let capacity_curve = capacity_experiment(
+            &network_builder.clone().set_network_dimension(*dimension),
+            &generator_builder.clone().set_dimension(*dimension),
+            learning_fn,
+            metrics,
+            &pattern_counts,
+            trials,
+            derive_seed(master_seed, dimension_index as u64),
+            shard,
+            threads,
+            sink.as_mut().map(|sink| &mut **sink as &mut dyn TrialSink),
+        );
+
+        load_curve.extend(
+            loads
+                .iter()
+                .zip(capacity_curve)
+                .map(|(load, point)| LoadCurvePoint {
+                    dimension: *dimension,
+                    load: *load,
+                    num_patterns: point.num_patterns,
+                    mean_recall: point.mean_recall,
+                    std_recall: point.std_recall,
+                    metrics: point.metrics,
+                }),
+        );
+    }
+
+    load_curve
+}
@@ -0,0 +1,132 @@
+use super::derive_seed;
+use crate::hopfield_network::{noise_channel::NoiseChannel, HopfieldNetwork};
+use nalgebra::DVector;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// A pattern with a class label, for classification style benchmarks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LabeledPattern {
+    /// The index of the label of this pattern, into the label names of the benchmark.
+    pub label: usize,
+    pub pattern: DVector<f64>,
+}
+
+/// A label confusion matrix from relaxing corrupted cues of labeled patterns.
+///
+/// Entry (actual, predicted) counts the cues of label `actual` that were relaxed to a state classified as `predicted`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfusionMatrix {
+    pub labels: Vec<String>,
+    pub counts: Vec<Vec<usize>>,
+}
+
+impl ConfusionMatrix {
+    /// Get the number of cues of each label that were classified correctly, as a fraction of all cues of that label.
+    ///
+    /// # Returns
+    ///
+    /// The accuracy of each label, in label order. Labels with no cues have an accuracy of NaN.
+    pub fn per_label_accuracy(self: &Self) -> Vec<f64> {
+        self.counts
+            .iter()
+            .enumerate()
+            .map(|(label, row)| row[label] as f64 / row.iter().sum::<usize>() as f64)
+            .collect()
+    }
+
+    /// Get the fraction of all cues that were classified correctly.
+    pub fn accuracy(self: &Self) -> f64 {
+        let correct: usize = (0..self.counts.len())
+            .map(|label| self.counts[label][label])
+            .sum();
+        let total: usize = self.counts.iter().flatten().sum();
+        correct as f64 / total as f64
+    }
+
+    /// Write the matrix as CSV, with one row per actual label and one column per predicted label,
+    /// followed by a column of per label accuracy.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the CSV to.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(writer, "actual,{},accuracy", self.labels.join(","))?;
+        for ((label, row), accuracy) in self
+            .labels
+            .iter()
+            .zip(&self.counts)
+            .zip(self.per_label_accuracy())
+        {
+            let counts: Vec<String> = row.iter().map(|count| count.to_string()).collect();
+            writeln!(writer, "{},{},{}", label, counts.join(","), accuracy)?;
+        }
+        Ok(())
+    }
+}
+
+/// Measure how well a network classifies corrupted versions of labeled patterns.
+///
+/// For every labeled pattern, a number of cues are created with a noise channel. All cues are relaxed
+/// concurrently, and each relaxed state is classified with the label of the labeled pattern it has the highest
+/// overlap with. The patterns should already be learned by the network.
+///
+/// # Arguments
+///
+/// * `network`: The network to relax cues in.
+/// * `labels`: The names of the labels.
+/// * `labeled_patterns`: The patterns to create cues from. Every label index must be less than the number of labels.
+/// * `cues_per_pattern`: The number of corrupted cues to create from each pattern.
+/// * `noise_channel`: The channel used to corrupt each cue.
+/// * `seed`: The seed used to corrupt cues.
+/// * `threads`: The number of threads to relax with. If None, the thread count is chosen automatically.
+///
+/// # Returns
+///
+/// The confusion matrix of the labels.
+pub fn labeled_recall_confusion(
+    network: &mut HopfieldNetwork,
+    labels: &[String],
+    labeled_patterns: &[LabeledPattern],
+    cues_per_pattern: usize,
+    noise_channel: &dyn NoiseChannel,
+    seed: u64,
+    threads: Option<usize>,
+) -> ConfusionMatrix {
+    assert!(
+        labeled_patterns
+            .iter()
+            .all(|labeled_pattern| labeled_pattern.label < labels.len()),
+        "Every labeled pattern must have a label index less than the number of labels!"
+    );
+
+    let domain = network.get_domain();
+    let mut cue_labels = Vec::with_capacity(labeled_patterns.len() * cues_per_pattern);
+    let mut cues = Vec::with_capacity(labeled_patterns.len() * cues_per_pattern);
+    for (pattern_index, labeled_pattern) in labeled_patterns.iter().enumerate() {
+        let mut rng = StdRng::seed_from_u64(derive_seed(seed, pattern_index as u64));
+        for _ in 0..cues_per_pattern {
+            cue_labels.push(labeled_pattern.label);
+            cues.push(noise_channel.corrupt_state(&labeled_pattern.pattern, domain, &mut rng));
+        }
+    }
+
+    let relaxed_states = network.concurrent_relax_state_collection(cues, threads);
+
+    let mut counts = vec![vec![0; labels.len()]; labels.len()];
+    for (actual_label, state) in cue_labels.into_iter().zip(relaxed_states) {
+        let predicted_label = labeled_patterns
+            .iter()
+            .map(|labeled_pattern| (labeled_pattern.label, labeled_pattern.pattern.dot(&state)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(label, _)| label)
+            .unwrap();
+        counts[actual_label][predicted_label] += 1;
+    }
+
+    ConfusionMatrix {
+        labels: labels.to_vec(),
+        counts,
+    }
+}
@@ -0,0 +1,209 @@
+use super::derive_seed;
+use crate::hopfield_network::{
+    first_passage::FirstPassageCriterion, HopfieldNetwork, NetworkDomain,
+};
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The plan of a critical temperature search, see pattern_critical_temperatures.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CriticalTemperatureConfig {
+    /// A run escapes its pattern once its overlap with the pattern falls below this, see
+    /// FirstPassageCriterion::EscapeStart.
+    pub escape_overlap: f64,
+    /// The number of sweeps each run is observed for.
+    pub observation_sweeps: usize,
+    /// The number of runs at each temperature tested.
+    pub trials: usize,
+    /// A pattern is (meta)stable at a temperature if at most this fraction of its runs escape.
+    pub maximum_escape_fraction: f64,
+    /// The highest temperature searched.
+    pub maximum_temperature: f64,
+    /// The number of bisection steps, each halving the bracket of the critical temperature.
+    pub bisection_steps: usize,
+}
+
+/// The critical temperature of one stored pattern: the temperature above which it is no longer (meta)stable.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PatternCriticalTemperature {
+    /// The index of the pattern in the stored patterns.
+    pub pattern_index: usize,
+    /// The highest temperature found at which the pattern is stable, or 0 if it was unstable at every temperature.
+    pub stable_temperature: f64,
+    /// The lowest temperature found at which the pattern is unstable, or None if it was stable at the maximum
+    /// temperature.
+    pub unstable_temperature: Option<f64>,
+    /// The fraction of runs that escaped at the stable temperature, or at 0 if it was unstable at every temperature.
+    pub stable_escape_fraction: f64,
+}
+
+impl PatternCriticalTemperature {
+    /// Get the estimate of the critical temperature, the middle of the bracket, or None if the pattern was stable at
+    /// the maximum temperature.
+    pub fn critical_temperature(self: &Self) -> Option<f64> {
+        self.unstable_temperature
+            .map(|unstable_temperature| (self.stable_temperature + unstable_temperature) / 2.0)
+    }
+}
+
+/// The result of pattern_critical_temperatures.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CriticalTemperatureReport {
+    pub config: CriticalTemperatureConfig,
+    /// The critical temperature of every stored pattern, in stored order.
+    pub patterns: Vec<PatternCriticalTemperature>,
+}
+
+impl CriticalTemperatureReport {
+    /// Get the indices of the patterns ordered from least to most robust to noise. Patterns stable at the maximum
+    /// temperature come last.
+    pub fn patterns_by_robustness(self: &Self) -> Vec<usize> {
+        let mut patterns = self.patterns.clone();
+        patterns.sort_by(|a, b| {
+            let a = a.critical_temperature().unwrap_or(f64::INFINITY);
+            let b = b.critical_temperature().unwrap_or(f64::INFINITY);
+            a.total_cmp(&b)
+        });
+        patterns
+            .iter()
+            .map(|pattern| pattern.pattern_index)
+            .collect()
+    }
+
+    /// Write the critical temperatures as CSV, with one row per pattern. Patterns stable at the maximum temperature
+    /// have an empty unstable and critical temperature.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the CSV to.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "pattern_index,stable_temperature,unstable_temperature,critical_temperature,stable_escape_fraction"
+        )?;
+        for pattern in &self.patterns {
+            writeln!(
+                writer,
+                "{},{},{},{},{}",
+                pattern.pattern_index,
+                pattern.stable_temperature,
+                pattern
+                    .unstable_temperature
+                    .map_or(String::new(), |temperature| temperature.to_string()),
+                pattern
+                    .critical_temperature()
+                    .map_or(String::new(), |temperature| temperature.to_string()),
+                pattern.stable_escape_fraction
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Find the critical temperature of every stored pattern: the temperature at which it ceases to be (meta)stable
+/// under Glauber dynamics, mapping how robust each memory is to noise.
+///
+/// At each temperature tested, many runs start from the pattern and are observed for a number of sweeps (see
+/// first_passage_times); the pattern is stable if few enough of them escape it. The critical temperature is then
+/// bracketed by bisection between 0 and the maximum temperature, assuming escape becomes more likely as the
+/// temperature rises. All temperatures tested for a pattern share the same run seeds, so the bisection is not misled by
+/// sampling noise between steps.
+///
+/// # Arguments
+///
+/// * `network`: The network to test. Must have the Binary or Bipolar domain.
+/// * `config`: The search to run.
+/// * `seed`: The seed of the experiment. The runs of each pattern are seeded from this and the pattern index.
+/// * `threads`: The number of threads to run with. If None, the thread count is chosen automatically.
+///
+/// # Returns
+///
+/// A CriticalTemperatureReport with one critical temperature per stored pattern.
+pub fn pattern_critical_temperatures(
+    network: &mut HopfieldNetwork,
+    config: &CriticalTemperatureConfig,
+    seed: u64,
+    threads: Option<usize>,
+) -> CriticalTemperatureReport {
+    let domain = network.get_domain();
+    assert!(
+        domain == NetworkDomain::Binary || domain == NetworkDomain::Bipolar,
+        "Critical temperatures require a network with the Binary or Bipolar domain!"
+    );
+    assert!(
+        config.trials > 0 && config.maximum_temperature > 0.0,
+        "Critical temperatures require at least one trial and a positive maximum temperature!"
+    );
+
+    let stored_patterns: Vec<DVector<f64>> = network
+        .get_stored_patterns()
+        .column_iter()
+        .map(|pattern| pattern.into_owned())
+        .collect();
+    let criterion = FirstPassageCriterion::EscapeStart {
+        maximum_overlap: config.escape_overlap,
+    };
+
+    let patterns = stored_patterns
+        .iter()
+        .enumerate()
+        .map(|(pattern_index, pattern)| {
+            let pattern_seed = derive_seed(seed, pattern_index as u64);
+            let mut escape_fraction = |temperature: f64| {
+                network
+                    .first_passage_times(
+                        pattern,
+                        &criterion,
+                        temperature,
+                        config.trials,
+                        config.observation_sweeps,
+                        pattern_seed,
+                        threads,
+                    )
+                    .passed_fraction()
+            };
+
+            let mut stable = (0.0, escape_fraction(0.0));
+            if stable.1 > config.maximum_escape_fraction {
+                return PatternCriticalTemperature {
+                    pattern_index,
+                    stable_temperature: 0.0,
+                    unstable_temperature: Some(0.0),
+                    stable_escape_fraction: stable.1,
+                };
+            }
+            let maximum_escape_fraction = escape_fraction(config.maximum_temperature);
+            if maximum_escape_fraction <= config.maximum_escape_fraction {
+                return PatternCriticalTemperature {
+                    pattern_index,
+                    stable_temperature: config.maximum_temperature,
+                    unstable_temperature: None,
+                    stable_escape_fraction: maximum_escape_fraction,
+                };
+            }
+
+            let mut unstable_temperature = config.maximum_temperature;
+            for _ in 0..config.bisection_steps {
+                let temperature = (stable.0 + unstable_temperature) / 2.0;
+                let fraction = escape_fraction(temperature);
+                if fraction <= config.maximum_escape_fraction {
+                    stable = (temperature, fraction);
+                } else {
+                    unstable_temperature = temperature;
+                }
+            }
+            PatternCriticalTemperature {
+                pattern_index,
+                stable_temperature: stable.0,
+                unstable_temperature: Some(unstable_temperature),
+                stable_escape_fraction: stable.1,
+            }
+        })
+        .collect();
+
+    CriticalTemperatureReport {
+        config: *config,
+        patterns,
+    }
+}
@@ -0,0 +1,233 @@
+use super::{corrupt_state, derive_seed};
+use crate::hopfield_network::HopfieldNetwork;
+use nalgebra::DVector;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The plan of a curriculum, see run_curriculum.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurriculumSchedule {
+    /// The order the patterns are stored in, as indices into the patterns. Each pattern may appear at most once,
+    /// and patterns not listed are never stored.
+    pub order: Vec<usize>,
+    /// The number of new patterns stored in each stage, before the recall test of that stage.
+    pub patterns_per_stage: usize,
+    /// The importance each pattern is first stored with, see learn_states_weighted.
+    pub initial_strength: f64,
+    /// The number of units flipped in each recall cue.
+    pub test_flips: usize,
+    /// The number of recall cues of each pattern per test.
+    pub test_cues: usize,
+    /// The recall rate below which a pattern is struggling and is reinforced.
+    pub target_recall_rate: f64,
+    /// The strength a pattern is reinforced with when none of its cues are recalled. A struggling pattern is reinforced
+    /// with this scaled by the fraction of its cues that failed.
+    pub reinforcement_strength: f64,
+    /// The largest number of reinforcement rounds per stage, each followed by another recall test.
+    pub maximum_reinforcement_rounds: usize,
+}
+
+/// A recall test of one stored pattern during a curriculum.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CurriculumRecord {
+    /// The index of the stage, counting from 0.
+    pub stage: usize,
+    /// The test within the stage: 0 for the test after storing the new patterns, then one per reinforcement round.
+    pub round: usize,
+    /// The index of the pattern, into the patterns given to run_curriculum.
+    pub pattern_index: usize,
+    /// The fraction of the cues of the pattern relaxed exactly onto it.
+    pub recall_rate: f64,
+    /// The number of units of the pattern itself that are unstable.
+    pub unstable_units: usize,
+    /// The total strength the pattern has been stored and reinforced with, when tested.
+    pub total_strength: f64,
+    /// The strength the pattern was reinforced with after this test, 0 if it was not struggling.
+    pub reinforcement: f64,
+}
+
+/// The full history of a curriculum, with one record per pattern per recall test.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CurriculumHistory {
+    pub schedule: CurriculumSchedule,
+    pub records: Vec<CurriculumRecord>,
+    /// The total strength of each pattern at the end of the curriculum, 0 for patterns never stored.
+    pub final_strengths: Vec<f64>,
+}
+
+impl CurriculumHistory {
+    /// Get the records of the last recall test of the curriculum, one per stored pattern.
+    pub fn final_records(self: &Self) -> Vec<CurriculumRecord> {
+        let Some(last) = self.records.last() else {
+            return Vec::new();
+        };
+        self.records
+            .iter()
+            .filter(|record| record.stage == last.stage && record.round == last.round)
+            .copied()
+            .collect()
+    }
+
+    /// Write the history as CSV, with one row per record.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the CSV to.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "stage,round,pattern_index,recall_rate,unstable_units,total_strength,reinforcement"
+        )?;
+        for record in &self.records {
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{}",
+                record.stage,
+                record.round,
+                record.pattern_index,
+                record.recall_rate,
+                record.unstable_units,
+                record.total_strength,
+                record.reinforcement
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Store patterns in a network in a prescribed order, with recall tests between stages that reinforce struggling
+/// patterns, and log every test.
+///
+/// Each stage stores the next patterns_per_stage patterns of the order with learn_states_weighted, at the initial
+/// strength. Every pattern stored so far is then tested: its cues (each with test_flips flipped units) are relaxed,
+/// and the fraction relaxed exactly onto the pattern is its recall rate. Each pattern below the target recall rate is
+/// reinforced with reinforce_states, with the reinforcement strength scaled by its failure rate, and every pattern is
+/// tested again, until none is struggling or the maximum number of reinforcement rounds is used.
+///
+/// # Arguments
+///
+/// * `network`: The network to train. Patterns are added to any already stored.
+/// * `patterns`: The patterns of the curriculum.
+/// * `schedule`: The curriculum.
+/// * `seed`: The seed of the recall cues. Each test draws its cues from a seed derived from this and its index.
+/// * `threads`: The number of threads to relax with. If None, the thread count is chosen automatically.
+///
+/// # Returns
+///
+/// The CurriculumHistory of every recall test.
+pub fn run_curriculum(
+    network: &mut HopfieldNetwork,
+    patterns: &[DVector<f64>],
+    schedule: &CurriculumSchedule,
+    seed: u64,
+    threads: Option<usize>,
+) -> CurriculumHistory {
+    assert!(
+        schedule.patterns_per_stage > 0,
+        "Curriculum must store at least one pattern per stage!"
+    );
+    let mut scheduled = vec![false; patterns.len()];
+    for pattern_index in &schedule.order {
+        assert!(
+            *pattern_index < patterns.len() && !scheduled[*pattern_index],
+            "Curriculum order must list distinct indices of the patterns!"
+        );
+        scheduled[*pattern_index] = true;
+    }
+    assert!(
+        schedule.initial_strength >= 0.0 && schedule.reinforcement_strength >= 0.0,
+        "Curriculum strengths must be non-negative!"
+    );
+    assert!(
+        schedule.test_cues > 0 && schedule.test_flips <= network.get_dimension(),
+        "Curriculum tests need at least one cue, and at most as many flips as units!"
+    );
+
+    let domain = network.get_domain();
+    let mut strengths = vec![0.0; patterns.len()];
+    let mut records = Vec::new();
+    let mut test_index = 0;
+    for (stage, stage_patterns) in schedule
+        .order
+        .chunks(schedule.patterns_per_stage)
+        .enumerate()
+    {
+        let weighted_patterns: Vec<(DVector<f64>, f64)> = stage_patterns
+            .iter()
+            .map(|pattern_index| (patterns[*pattern_index].clone(), schedule.initial_strength))
+            .collect();
+        network.learn_states_weighted(&weighted_patterns);
+        for pattern_index in stage_patterns {
+            strengths[*pattern_index] += schedule.initial_strength;
+        }
+
+        let stored = &schedule.order[..schedule
+            .order
+            .len()
+            .min((stage + 1) * schedule.patterns_per_stage)];
+        for round in 0..=schedule.maximum_reinforcement_rounds {
+            let mut rng = StdRng::seed_from_u64(derive_seed(seed, test_index));
+            test_index += 1;
+            let cues: Vec<DVector<f64>> = stored
+                .iter()
+                .flat_map(|pattern_index| {
+                    (0..schedule.test_cues)
+                        .map(|_| {
+                            corrupt_state(
+                                &patterns[*pattern_index],
+                                domain,
+                                schedule.test_flips,
+                                &mut rng,
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            let relaxed_states = network.concurrent_relax_state_collection(cues, threads);
+
+            let mut reinforcements = Vec::new();
+            for (pattern_index, pattern_states) in
+                stored.iter().zip(relaxed_states.chunks(schedule.test_cues))
+            {
+                let pattern = &patterns[*pattern_index];
+                let recalled = pattern_states
+                    .iter()
+                    .filter(|state| *state == pattern)
+                    .count();
+                let recall_rate = recalled as f64 / schedule.test_cues as f64;
+                let reinforcement = if recall_rate < schedule.target_recall_rate
+                    && round < schedule.maximum_reinforcement_rounds
+                {
+                    schedule.reinforcement_strength * (1.0 - recall_rate)
+                } else {
+                    0.0
+                };
+                records.push(CurriculumRecord {
+                    stage,
+                    round,
+                    pattern_index: *pattern_index,
+                    recall_rate,
+                    unstable_units: network.count_unstable_units(pattern),
+                    total_strength: strengths[*pattern_index],
+                    reinforcement,
+                });
+                if reinforcement > 0.0 {
+                    reinforcements.push((pattern.clone(), reinforcement));
+                    strengths[*pattern_index] += reinforcement;
+                }
+            }
+
+            if reinforcements.is_empty() {
+                break;
+            }
+            network.reinforce_states(&reinforcements);
+        }
+    }
+
+    CurriculumHistory {
+        schedule: schedule.clone(),
+        records,
+        final_strengths: strengths,
+    }
+}
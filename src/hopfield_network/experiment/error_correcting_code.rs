@@ -0,0 +1,261 @@
+use super::{derive_seed, LearningFunction};
+use crate::hopfield_network::{noise_channel::NoiseChannel, HopfieldNetworkBuilder, NetworkDomain};
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// A simple binary block code, whose codewords are stored as the patterns of a network.
+///
+/// A Hebbian network stores only a limited number of patterns well, so these are codes with few, well separated
+/// codewords relative to their length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BinaryCode {
+    /// Each of `message_bits` bits repeated `repetitions` times, with 2^message_bits codewords. The Hebbian weights
+    /// of the full codebook couple only the copies of each bit, so relaxation decodes each bit by majority vote.
+    Repetition {
+        message_bits: usize,
+        repetitions: usize,
+    },
+    /// The first `codewords` rows of the Sylvester Hadamard matrix of length 2^order, which are mutually orthogonal
+    /// and so all stable under Hebbian learning while fewer than 2^order are stored. Any two codewords differ in
+    /// exactly half of their bits.
+    Hadamard { order: u32, codewords: usize },
+    /// `codewords` uniformly random words of length `length`, drawn from a seed: the classical Hopfield setting.
+    Random {
+        length: usize,
+        codewords: usize,
+        seed: u64,
+    },
+}
+
+impl BinaryCode {
+    /// Get the length of each codeword, which is the dimension of the network.
+    pub fn length(self: &Self) -> usize {
+        match *self {
+            BinaryCode::Repetition {
+                message_bits,
+                repetitions,
+            } => message_bits * repetitions,
+            BinaryCode::Hadamard { order, .. } => 1 << order,
+            BinaryCode::Random { length, .. } => length,
+        }
+    }
+
+    /// Get the bits of every codeword of this code.
+    pub fn codeword_bits(self: &Self) -> Vec<Vec<bool>> {
+        match *self {
+            BinaryCode::Repetition {
+                message_bits,
+                repetitions,
+            } => (0..1usize << message_bits)
+                .map(|message| {
+                    (0..message_bits * repetitions)
+                        .map(|bit| message >> (bit / repetitions) & 1 == 1)
+                        .collect()
+                })
+                .collect(),
+            BinaryCode::Hadamard { order, codewords } => {
+                assert!(
+                    codewords < 1 << order,
+                    "A Hadamard code must store fewer codewords than its length!"
+                );
+                // Entry (row, column) of the Sylvester Hadamard matrix is -1 when row & column has odd parity
+                (0..codewords)
+                    .map(|row| {
+                        (0..1usize << order)
+                            .map(|column| (row & column).count_ones() % 2 == 1)
+                            .collect()
+                    })
+                    .collect()
+            }
+            BinaryCode::Random {
+                length,
+                codewords,
+                seed,
+            } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                (0..codewords)
+                    .map(|_| (0..length).map(|_| rng.gen_bool(0.5)).collect())
+                    .collect()
+            }
+        }
+    }
+
+    /// Get every codeword of this code as a Bipolar state, mapping each bit to (-1)^bit.
+    pub fn codewords(self: &Self) -> Vec<DVector<f64>> {
+        self.codeword_bits()
+            .into_iter()
+            .map(|bits| {
+                DVector::from_iterator(
+                    bits.len(),
+                    bits.into_iter().map(|bit| if bit { -1.0 } else { 1.0 }),
+                )
+            })
+            .collect()
+    }
+}
+
+/// The decoding error rates over one noise channel.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodingErrorRate {
+    /// The name of the noise channel, see NoiseChannel::name.
+    pub channel: String,
+    /// The fraction of received bits that do not have the sign of the sent bit (flipped or erased by the channel),
+    /// i.e. the error rate without decoding.
+    pub received_bit_error_rate: f64,
+    /// The fraction of bits of the decoded codewords that differ from the sent codewords.
+    pub bit_error_rate: f64,
+    /// The fraction of received words decoded to the wrong codeword.
+    pub word_error_rate: f64,
+    /// The fraction of received words that did not relax exactly onto a codeword, before mapping to the nearest.
+    pub non_codeword_fraction: f64,
+}
+
+/// The result of decoding_error_rates: the error rates of a code over a range of noise channels.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DecodingReport {
+    pub code: BinaryCode,
+    pub words_per_channel: usize,
+    pub error_rates: Vec<DecodingErrorRate>,
+}
+
+impl DecodingReport {
+    /// Write the error rates as CSV, with one row per noise channel.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the CSV to.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "channel,received_bit_error_rate,bit_error_rate,word_error_rate,non_codeword_fraction"
+        )?;
+        for error_rate in &self.error_rates {
+            writeln!(
+                writer,
+                "\"{}\",{},{},{},{}",
+                error_rate.channel,
+                error_rate.received_bit_error_rate,
+                error_rate.bit_error_rate,
+                error_rate.word_error_rate,
+                error_rate.non_codeword_fraction
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Measure how well a network decodes a binary code over a noisy channel.
+///
+/// The codewords of the code are learned by a new network. For each noise channel, random codewords are sent
+/// through the channel (e.g. BitFlip, the binary symmetric channel) and the received words relaxed concurrently;
+/// each relaxed state is decoded to its nearest codeword (by overlap), so spurious attractors still decode to a
+/// codeword.
+///
+/// # Arguments
+///
+/// * `network_builder`: The builder to create the network from. The dimension is set to the code length and the rng
+///   seed is derived from the seed. The domain must be Bipolar.
+/// * `code`: The code to decode.
+/// * `learning_fn`: The function used to store the codewords.
+/// * `noise_channels`: The noise channels to test.
+/// * `words_per_channel`: The number of words to send through each channel.
+/// * `seed`: The seed of the experiment.
+/// * `threads`: The number of threads to relax with. If None, the thread count is chosen automatically.
+///
+/// # Returns
+///
+/// A DecodingReport with one DecodingErrorRate per noise channel, in the same order.
+pub fn decoding_error_rates(
+    network_builder: &HopfieldNetworkBuilder,
+    code: &BinaryCode,
+    learning_fn: LearningFunction,
+    noise_channels: &[&dyn NoiseChannel],
+    words_per_channel: usize,
+    seed: u64,
+    threads: Option<usize>,
+) -> DecodingReport {
+    let mut network = network_builder
+        .clone()
+        .set_network_dimension(code.length())
+        .set_rng_seed(derive_seed(seed, 0))
+        .build();
+    let domain = network.get_domain();
+    assert!(
+        domain == NetworkDomain::Bipolar,
+        "Decoding requires a network with the Bipolar domain!"
+    );
+    let codewords = code.codewords();
+    learning_fn(&mut network, &codewords);
+
+    let length = code.length() as f64;
+    let total_words = words_per_channel as f64;
+
+    let error_rates = noise_channels
+        .iter()
+        .enumerate()
+        .map(|(channel_index, noise_channel)| {
+            let mut rng = StdRng::seed_from_u64(derive_seed(seed, 1 + channel_index as u64));
+            let sent: Vec<usize> = (0..words_per_channel)
+                .map(|_| rng.gen_range(0..codewords.len()))
+                .collect();
+            let received: Vec<DVector<f64>> = sent
+                .iter()
+                .map(|codeword| {
+                    noise_channel.corrupt_state(&codewords[*codeword], domain, &mut rng)
+                })
+                .collect();
+            let received_bit_errors: usize = sent
+                .iter()
+                .zip(&received)
+                .map(|(codeword, word)| {
+                    // A received unit is in error unless it has the sign of the sent unit, so erased (zero)
+                    // units count as errors and small Gaussian perturbations do not
+                    codewords[*codeword]
+                        .component_mul(word)
+                        .iter()
+                        .filter(|product| **product <= 0.0)
+                        .count()
+                })
+                .sum();
+
+            let relaxed_states = network.concurrent_relax_state_collection(received, threads);
+
+            let mut bit_errors = 0;
+            let mut word_errors = 0;
+            let mut non_codewords = 0;
+            for (codeword, state) in sent.iter().zip(relaxed_states) {
+                let (decoded, overlap) = codewords
+                    .iter()
+                    .map(|candidate| candidate.dot(&state))
+                    .enumerate()
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .unwrap();
+                if overlap != length {
+                    non_codewords += 1;
+                }
+                if decoded != *codeword {
+                    word_errors += 1;
+                    // Codewords differ in (N - overlap) / 2 units
+                    bit_errors +=
+                        ((length - codewords[*codeword].dot(&codewords[decoded])) / 2.0) as usize;
+                }
+            }
+
+            DecodingErrorRate {
+                channel: noise_channel.name(),
+                received_bit_error_rate: received_bit_errors as f64 / (total_words * length),
+                bit_error_rate: bit_errors as f64 / (total_words * length),
+                word_error_rate: word_errors as f64 / total_words,
+                non_codeword_fraction: non_codewords as f64 / total_words,
+            }
+        })
+        .collect();
+
+    DecodingReport {
+        code: *code,
+        words_per_channel,
+        error_rates,
+    }
+}
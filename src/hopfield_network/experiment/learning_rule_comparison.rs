@@ -0,0 +1,187 @@
+use super::{
+    corrupt_state, mean_and_std, shard_trials,
+    trial_sink::{LearningRuleTrialRecord, TrialSink},
+    ExperimentShard, LearningFunction,
+};
+use crate::hopfield_network::{
+    state_generator::StateGeneratorBuilder, HopfieldNetwork, HopfieldNetworkBuilder,
+};
+use nalgebra::DVector;
+use rand::{rngs::StdRng, SeedableRng};
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+/// The step in the fraction of flipped units when probing the basin of attraction of a pattern.
+const BASIN_PROBE_STEP: f64 = 0.05;
+
+/// The summary of how a single learning rule performed in compare_learning_rules.
+#[derive(Debug, Clone)]
+pub struct LearningRuleReport {
+    pub name: String,
+    /// The mean fraction of learned patterns that are stable under relaxation.
+    pub mean_recall: f64,
+    pub std_recall: f64,
+    /// The mean over patterns of the smallest aligned local field (-unit energy) of any unit in the pattern.
+    pub mean_margin: f64,
+    /// The mean fraction of units that can be flipped in a pattern and still relax back to it.
+    pub mean_basin_radius: f64,
+    /// The mean time taken to learn the pattern set.
+    pub mean_training_time: Duration,
+}
+
+impl fmt::Display for LearningRuleReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<20} {:>8.4} ± {:<8.4} {:>10.4} {:>12.4} {:>14?}",
+            self.name,
+            self.mean_recall,
+            self.std_recall,
+            self.mean_margin,
+            self.mean_basin_radius,
+            self.mean_training_time
+        )
+    }
+}
+
+/// Format a collection of learning rule reports as a side-by-side table, one rule per row.
+///
+/// # Arguments
+///
+/// * `reports`: The reports to format, typically from compare_learning_rules.
+///
+/// # Returns
+///
+/// The table as a `String`.
+pub fn format_learning_rule_reports(reports: &[LearningRuleReport]) -> String {
+    let mut table = format!(
+        "{:<20} {:>19} {:>10} {:>12} {:>14}\n",
+        "Rule", "Recall", "Margin", "Basin Radius", "Training Time"
+    );
+    for report in reports {
+        table.push_str(&format!("{}\n", report));
+    }
+    table
+}
+
+/// Train networks with every given learning rule on identical pattern sets and compare the results.
+///
+/// Each trial generates one pattern set and network seed, which every learning rule then uses,
+/// so the rules are compared on exactly the same problems. Trials are run serially so training times
+/// are not skewed by other threads.
+///
+/// # Arguments
+///
+/// * `network_builder`: The builder to create every network from. The rng seed is overwritten per trial.
+/// * `generator_builder`: The builder to create the pattern generators from. The generator seed is overwritten per trial.
+/// * `learning_rules`: The learning rules to compare, as `(name, learning_fn)` pairs.
+/// * `num_patterns`: The number of patterns to learn in each trial.
+/// * `trials`: The number of pattern sets to test every rule on.
+/// * `master_seed`: The seed of the entire experiment.
+/// * `shard`: If given, only the trials of this shard are run for every rule, and the reports summarize only these.
+/// * `sink`: If given, every trial of every rule is recorded here as soon as it finishes.
+///
+/// # Returns
+///
+/// A report for each learning rule, in the same order as learning_rules.
+#[allow(clippy::too_many_arguments)]
+pub fn compare_learning_rules(
+    network_builder: &HopfieldNetworkBuilder,
+    generator_builder: &StateGeneratorBuilder,
+    learning_rules: &[(&str, LearningFunction)],
+    num_patterns: usize,
+    trials: usize,
+    master_seed: u64,
+    shard: Option<ExperimentShard>,
+    mut sink: Option<&mut dyn TrialSink>,
+) -> Vec<LearningRuleReport> {
+    let experiment_trials = shard_trials(master_seed, trials, shard);
+    learning_rules
+        .iter()
+        .map(|(name, learning_fn)| {
+            let mut recalls = Vec::with_capacity(experiment_trials.len());
+            let mut margins = Vec::with_capacity(experiment_trials.len() * num_patterns);
+            let mut basin_radii = Vec::with_capacity(experiment_trials.len() * num_patterns);
+            let mut training_time = Duration::ZERO;
+
+            for trial in &experiment_trials {
+                let mut network = network_builder
+                    .clone()
+                    .set_rng_seed(trial.network_seed())
+                    .build();
+                let patterns = generator_builder
+                    .clone()
+                    .set_generator_seed(trial.generator_seed())
+                    .build()
+                    .create_state_collection(num_patterns);
+
+                let now = Instant::now();
+                learning_fn(&mut network, &patterns);
+                let trial_training_time = now.elapsed();
+                training_time += trial_training_time;
+
+                let mut corruption_rng = StdRng::seed_from_u64(trial.trial_seed);
+                let mut recalled = 0;
+                for pattern in &patterns {
+                    margins.push(-network.all_unit_energies(pattern).max());
+
+                    if network.relax_state(pattern.clone()).state == *pattern {
+                        recalled += 1;
+                    }
+
+                    basin_radii.push(probe_basin_radius(
+                        &mut network,
+                        pattern,
+                        &mut corruption_rng,
+                    ));
+                }
+                let recall = recalled as f64 / num_patterns.max(1) as f64;
+                if let Some(sink) = sink.as_deref_mut() {
+                    sink.record_learning_rule_trial(&LearningRuleTrialRecord {
+                        rule: name.to_string(),
+                        trial_index: trial.trial_index,
+                        trial_seed: trial.trial_seed,
+                        recall,
+                        training_time: trial_training_time,
+                    });
+                }
+                recalls.push(recall);
+            }
+
+            let (mean_recall, std_recall) = mean_and_std(&recalls);
+            LearningRuleReport {
+                name: name.to_string(),
+                mean_recall,
+                std_recall,
+                mean_margin: mean_and_std(&margins).0,
+                mean_basin_radius: mean_and_std(&basin_radii).0,
+                mean_training_time: training_time / experiment_trials.len().max(1) as u32,
+            }
+        })
+        .collect()
+}
+
+/// Find the largest fraction of flipped units (in steps of BASIN_PROBE_STEP) from which a pattern is still recalled.
+fn probe_basin_radius(
+    network: &mut HopfieldNetwork,
+    pattern: &DVector<f64>,
+    rng: &mut StdRng,
+) -> f64 {
+    let dimension = pattern.len();
+    let mut basin_radius = 0.0;
+    let mut flip_fraction = BASIN_PROBE_STEP;
+
+    while flip_fraction <= 0.5 {
+        let num_flipped = (flip_fraction * dimension as f64).round() as usize;
+        let cue = corrupt_state(pattern, network.get_domain(), num_flipped, rng);
+        if network.relax_state(cue).state != *pattern {
+            break;
+        }
+        basin_radius = flip_fraction;
+        flip_fraction += BASIN_PROBE_STEP;
+    }
+
+    basin_radius
+}
@@ -0,0 +1,165 @@
+use super::mean_and_std;
+use crate::hopfield_network::HopfieldNetwork;
+use nalgebra::DVector;
+
+/// Define an observable that can be collected for every trial of an experiment.
+///
+/// Implement this trait and register it in a MetricRegistry to collect custom measurements
+/// without changing the experiment loop.
+pub trait Metric: Sync {
+    /// The name of this metric, used to label the collected values.
+    fn name(self: &Self) -> String;
+
+    /// Compute the value of this metric for a single trial.
+    ///
+    /// # Arguments
+    ///
+    /// * `network`: The network of the trial, after learning.
+    /// * `batch_results`: The relaxed states of the trial.
+    ///
+    /// # Returns
+    ///
+    /// The value of the metric for this trial as an `f64`.
+    fn compute(self: &Self, network: &HopfieldNetwork, batch_results: &[DVector<f64>]) -> f64;
+}
+
+/// A summary of a metric collected over many trials.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetricSummary {
+    pub name: String,
+    pub mean: f64,
+    pub std: f64,
+}
+
+/// A collection of metrics to be computed for every trial of an experiment.
+pub struct MetricRegistry {
+    metrics: Vec<Box<dyn Metric>>,
+}
+
+impl MetricRegistry {
+    /// Create a new, empty MetricRegistry.
+    pub fn new_metric_registry() -> Self {
+        Self {
+            metrics: Vec::new(),
+        }
+    }
+
+    /// Add a metric to this registry. Note this consumes and returns the registry, like a builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `metric`: The metric to collect.
+    pub fn register(mut self: Self, metric: impl Metric + 'static) -> Self {
+        self.metrics.push(Box::new(metric));
+        self
+    }
+
+    /// Get the name of every registered metric, in the order they were registered.
+    pub fn names(self: &Self) -> Vec<String> {
+        self.metrics.iter().map(|metric| metric.name()).collect()
+    }
+
+    /// Compute every registered metric for a single trial.
+    ///
+    /// # Returns
+    ///
+    /// The value of each metric, in the order they were registered.
+    pub fn compute_all(
+        self: &Self,
+        network: &HopfieldNetwork,
+        batch_results: &[DVector<f64>],
+    ) -> Vec<f64> {
+        self.metrics
+            .iter()
+            .map(|metric| metric.compute(network, batch_results))
+            .collect()
+    }
+
+    /// Summarize the values of every registered metric collected over many trials.
+    ///
+    /// # Arguments
+    ///
+    /// * `trial_values`: The result of compute_all for each trial.
+    ///
+    /// # Returns
+    ///
+    /// A summary of each metric, in the order they were registered.
+    pub fn summarize(self: &Self, trial_values: &[Vec<f64>]) -> Vec<MetricSummary> {
+        self.metrics
+            .iter()
+            .enumerate()
+            .map(|(metric_index, metric)| {
+                let values: Vec<f64> = trial_values
+                    .iter()
+                    .map(|values| values[metric_index])
+                    .collect();
+                let (mean, std) = mean_and_std(&values);
+                MetricSummary {
+                    name: metric.name(),
+                    mean,
+                    std,
+                }
+            })
+            .collect()
+    }
+}
+
+/// The mean energy of the relaxed states.
+pub struct MeanStateEnergy;
+
+impl Metric for MeanStateEnergy {
+    fn name(self: &Self) -> String {
+        "mean_state_energy".to_string()
+    }
+
+    fn compute(self: &Self, network: &HopfieldNetwork, batch_results: &[DVector<f64>]) -> f64 {
+        batch_results
+            .iter()
+            .map(|state| network.state_energy(state))
+            .sum::<f64>()
+            / batch_results.len() as f64
+    }
+}
+
+/// The fraction of relaxed states that have no unstable units, checked against the activation of the network domain
+/// as relaxation does (see count_unstable_units).
+pub struct StableStateFraction;
+
+impl Metric for StableStateFraction {
+    fn name(self: &Self) -> String {
+        "stable_state_fraction".to_string()
+    }
+
+    fn compute(self: &Self, network: &HopfieldNetwork, batch_results: &[DVector<f64>]) -> f64 {
+        batch_results
+            .iter()
+            .filter(|state| network.count_unstable_units(state) == 0)
+            .count() as f64
+            / batch_results.len() as f64
+    }
+}
+
+/// The mean over relaxed states of the largest overlap with any stored pattern, or 0 if no patterns are stored.
+/// The overlaps of the whole batch are calculated at once, see overlaps_matrix.
+pub struct MeanMaximumOverlap;
+
+impl Metric for MeanMaximumOverlap {
+    fn name(self: &Self) -> String {
+        "mean_maximum_overlap".to_string()
+    }
+
+    fn compute(self: &Self, network: &HopfieldNetwork, batch_results: &[DVector<f64>]) -> f64 {
+        network
+            .overlaps_matrix(batch_results)
+            .column_iter()
+            .map(|overlaps| {
+                overlaps
+                    .iter()
+                    .copied()
+                    .max_by(f64::total_cmp)
+                    .unwrap_or(0.0)
+            })
+            .sum::<f64>()
+            / batch_results.len() as f64
+    }
+}
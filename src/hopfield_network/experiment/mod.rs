@@ -0,0 +1,373 @@
+pub mod basin_volume;
+pub mod capacity;
+pub mod confusion;
+pub mod critical_temperature;
+pub mod curriculum;
+pub mod error_correcting_code;
+pub mod learning_rule_comparison;
+pub mod metric;
+#[cfg(feature = "sqlite")]
+pub mod sqlite_sink;
+pub mod stochastic_resonance;
+pub mod trial_sink;
+
+use super::{
+    noise_channel::{FixedFlips, NoiseChannel},
+    HopfieldNetwork, NetworkDomain,
+};
+use nalgebra::DVector;
+use rand::Rng;
+
+/// Define a function that stores a collection of patterns in a network.
+///
+/// Experiments take one of these so they can be run against any way of learning patterns. Learning methods can be
+/// passed directly (e.g. `&HopfieldNetwork::learn_states`), and closures can capture parameters, e.g. a learning rule
+/// with its learning rate: `&|network, patterns| network.learn_states_with(patterns, &rule)`. Experiments may learn
+/// on several threads at once, so the function must be Sync.
+pub type LearningFunction<'a> = &'a (dyn Fn(&mut HopfieldNetwork, &[DVector<f64>]) + Sync);
+
+/// Calculate the mean and sample standard deviation of some measurements.
+///
+/// # Arguments
+///
+/// * `values`: The measurements to summarize.
+///
+/// # Returns
+///
+/// A tuple of `(mean, standard_deviation)`. The standard deviation is 0 if there are fewer than two values,
+/// and both are NaN if there are no values at all.
+pub fn mean_and_std(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    if values.len() < 2 {
+        return (mean, 0.0);
+    }
+
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance.sqrt())
+}
+
+/// Calculate the Wilson score interval of a binomial proportion, which unlike the normal approximation stays inside
+/// [0, 1] and behaves well for proportions near 0 or 1 and for few trials.
+///
+/// # Arguments
+///
+/// * `successes`: The number of successes.
+/// * `trials`: The number of trials. Must be at least the number of successes.
+/// * `z`: The standard normal quantile of the confidence level, e.g. 1.96 for 95% confidence.
+///
+/// # Returns
+///
+/// A tuple of `(lower, upper)` bounds. Both are NaN if there are no trials.
+pub fn wilson_interval(successes: usize, trials: usize, z: f64) -> (f64, f64) {
+    assert!(
+        successes <= trials,
+        "Wilson interval encountered an error! successes must be at most trials!"
+    );
+
+    let n = trials as f64;
+    let proportion = successes as f64 / n;
+    let z_squared = z * z;
+    let denominator = 1.0 + z_squared / n;
+    let center = (proportion + z_squared / (2.0 * n)) / denominator;
+    let half_width =
+        z * (proportion * (1.0 - proportion) / n + z_squared / (4.0 * n * n)).sqrt() / denominator;
+    (
+        (center - half_width).max(0.0),
+        (center + half_width).min(1.0),
+    )
+}
+
+/// Corrupt a state by flipping a number of randomly chosen units, with the FixedFlips noise channel.
+///
+/// Flipping a unit maps it to the opposite value in the domain, see NetworkDomain::invert_value.
+///
+/// # Arguments
+///
+/// * `state`: The state to corrupt. This is not modified.
+/// * `domain`: The domain of the state.
+/// * `num_flipped`: The number of distinct units to flip. Must be at most the dimension of the state.
+/// * `rng`: The random number generator used to choose units.
+///
+/// # Returns
+///
+/// A corrupted copy of the state.
+pub fn corrupt_state(
+    state: &DVector<f64>,
+    domain: NetworkDomain,
+    num_flipped: usize,
+    rng: &mut impl Rng,
+) -> DVector<f64> {
+    FixedFlips { count: num_flipped }.corrupt_state(state, domain, rng)
+}
+
+/// Derive a new seed from an existing seed and a stream index.
+///
+/// This is the SplitMix64 finalizer applied to the combination of both values, so nearby inputs
+/// (e.g. consecutive trial indices) give completely unrelated seeds. The result depends only on the
+/// arguments, so it is identical on every machine and every run.
+///
+/// A derived seed is never 0, as builders interpret a seed of 0 as "pick a random seed".
+///
+/// # Arguments
+///
+/// * `seed`: The seed to derive from.
+/// * `stream`: The index of the derived stream.
+///
+/// # Returns
+///
+/// A new `u64` seed.
+pub fn derive_seed(seed: u64, stream: u64) -> u64 {
+    let mut z = seed ^ stream.wrapping_add(1).wrapping_mul(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^= z >> 31;
+
+    if z == 0 {
+        1
+    } else {
+        z
+    }
+}
+
+/// A single trial of an experiment.
+///
+/// Every seed a trial uses is derived from the master seed of the experiment and the trial index alone,
+/// so any trial can be re-run individually (or on another machine) and reproduce exactly the numbers
+/// of the full experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExperimentTrial {
+    pub trial_index: usize,
+    pub trial_seed: u64,
+}
+
+impl ExperimentTrial {
+    /// Create the trial with the given index of an experiment.
+    ///
+    /// # Arguments
+    ///
+    /// * `master_seed`: The seed of the entire experiment.
+    /// * `trial_index`: The index of this trial within the experiment.
+    pub fn new(master_seed: u64, trial_index: usize) -> Self {
+        Self {
+            trial_index,
+            trial_seed: derive_seed(master_seed, trial_index as u64),
+        }
+    }
+
+    /// Get the seed to give to the HopfieldNetworkBuilder for this trial.
+    pub fn network_seed(self: &Self) -> u64 {
+        derive_seed(self.trial_seed, 0)
+    }
+
+    /// Get the seed to give to the StateGeneratorBuilder for this trial.
+    pub fn generator_seed(self: &Self) -> u64 {
+        derive_seed(self.trial_seed, 1)
+    }
+}
+
+/// Get every trial of an experiment.
+///
+/// # Arguments
+///
+/// * `master_seed`: The seed of the entire experiment.
+/// * `num_trials`: The total number of trials in the experiment.
+///
+/// # Returns
+///
+/// A Vec of all trials, ordered by trial index.
+pub fn experiment_trials(master_seed: u64, num_trials: usize) -> Vec<ExperimentTrial> {
+    (0..num_trials)
+        .map(|trial_index| ExperimentTrial::new(master_seed, trial_index))
+        .collect()
+}
+
+/// Get the trials of an experiment belonging to a single shard, for distributing an experiment
+/// across processes or machines. Trials are dealt out to shards in turn, so shard `i` gets every
+/// trial with `trial_index % num_shards == i`.
+///
+/// Running every shard gives exactly the same trials (and seeds) as running the experiment all at once.
+///
+/// # Arguments
+///
+/// * `master_seed`: The seed of the entire experiment.
+/// * `num_trials`: The total number of trials in the experiment.
+/// * `shard_index`: The shard to get the trials of. Must be less than num_shards.
+/// * `num_shards`: The total number of shards the experiment is split into.
+///
+/// # Returns
+///
+/// A Vec of the trials in this shard, ordered by trial index.
+pub fn sharded_experiment_trials(
+    master_seed: u64,
+    num_trials: usize,
+    shard_index: usize,
+    num_shards: usize,
+) -> Vec<ExperimentTrial> {
+    assert!(
+        shard_index < num_shards,
+        "Experiment sharding encountered an error! shard_index must be less than num_shards!"
+    );
+
+    (shard_index..num_trials)
+        .step_by(num_shards)
+        .map(|trial_index| ExperimentTrial::new(master_seed, trial_index))
+        .collect()
+}
+
+/// A single shard of an experiment, for running the experiment across processes or machines. Each shard runs the
+/// trials given by sharded_experiment_trials, so running every shard runs exactly the trials of the full experiment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExperimentShard {
+    pub shard_index: usize,
+    pub num_shards: usize,
+}
+
+/// Get the trials an experiment runner runs: the trials of a shard if one is given, otherwise every trial.
+///
+/// # Arguments
+///
+/// * `master_seed`: The seed of the entire experiment.
+/// * `num_trials`: The total number of trials in the experiment.
+/// * `shard`: The shard to run, if any.
+///
+/// # Returns
+///
+/// A Vec of the trials to run, ordered by trial index.
+fn shard_trials(
+    master_seed: u64,
+    num_trials: usize,
+    shard: Option<ExperimentShard>,
+) -> Vec<ExperimentTrial> {
+    match shard {
+        Some(shard) => {
+            sharded_experiment_trials(master_seed, num_trials, shard.shard_index, shard.num_shards)
+        }
+        None => experiment_trials(master_seed, num_trials),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        capacity::{capacity_experiment, CapacityCurvePoint},
+        metric::{MeanStateEnergy, MetricRegistry, StableStateFraction},
+        trial_sink::{CapacityTrialRecord, TrialSink},
+        *,
+    };
+    use crate::hopfield_network::{state_generator::StateGeneratorBuilder, HopfieldNetworkBuilder};
+
+    const DIMENSION: usize = 32;
+    const PATTERN_COUNTS: [usize; 3] = [2, 4, 6];
+    const TRIALS: usize = 4;
+
+    #[derive(Default)]
+    struct RecordingSink {
+        records: Vec<CapacityTrialRecord>,
+    }
+
+    impl TrialSink for RecordingSink {
+        fn record_capacity_trial(&mut self, record: &CapacityTrialRecord) {
+            self.records.push(record.clone());
+        }
+    }
+
+    fn network_builder() -> HopfieldNetworkBuilder {
+        HopfieldNetworkBuilder::new_hopfield_network_builder()
+            .set_network_dimension(DIMENSION)
+            .set_network_domain(NetworkDomain::Bipolar)
+    }
+
+    fn generator_builder() -> StateGeneratorBuilder {
+        StateGeneratorBuilder::new_state_generator_builder()
+            .set_dimension(DIMENSION)
+            .set_domain(NetworkDomain::Bipolar)
+    }
+
+    fn run_capacity_experiment(
+        master_seed: u64,
+        shard: Option<ExperimentShard>,
+        threads: Option<usize>,
+    ) -> (Vec<CapacityCurvePoint>, Vec<CapacityTrialRecord>) {
+        let metrics = MetricRegistry::new_metric_registry()
+            .register(MeanStateEnergy)
+            .register(StableStateFraction);
+        let mut sink = RecordingSink::default();
+        let curve = capacity_experiment(
+            &network_builder(),
+            &generator_builder(),
+            &HopfieldNetwork::learn_states,
+            &metrics,
+            &PATTERN_COUNTS,
+            TRIALS,
+            master_seed,
+            shard,
+            threads,
+            Some(&mut sink),
+        );
+        (curve, sink.records)
+    }
+
+    #[test]
+    fn seeded_experiments_reproduce_whatever_the_thread_count() {
+        let (curve, records) = run_capacity_experiment(42, None, Some(1));
+        assert_eq!(records.len(), PATTERN_COUNTS.len() * TRIALS);
+        for threads in [Some(1), Some(3), Some(8), None] {
+            assert_eq!(
+                run_capacity_experiment(42, None, threads),
+                (curve.clone(), records.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn trials_rerun_individually_reproduce_the_experiment() {
+        let (_, records) = run_capacity_experiment(42, None, Some(4));
+        for record in records {
+            let trial = ExperimentTrial::new(42, record.trial_index);
+            assert_eq!(trial.trial_seed, record.trial_seed);
+
+            let mut network = network_builder().set_rng_seed(trial.network_seed()).build();
+            let patterns = generator_builder()
+                .set_generator_seed(trial.generator_seed())
+                .build()
+                .create_state_collection(record.num_patterns);
+            network.learn_states(&patterns);
+            let recalled = patterns
+                .iter()
+                .filter(|pattern| network.relax_state((*pattern).clone()).state == **pattern)
+                .count();
+            assert_eq!(
+                recalled as f64 / record.num_patterns as f64,
+                record.recall,
+                "trial {}",
+                record.trial_index
+            );
+        }
+    }
+
+    #[test]
+    fn shards_cover_every_trial_once() {
+        let mut sharded_trials: Vec<ExperimentTrial> = (0..3)
+            .flat_map(|shard_index| sharded_experiment_trials(42, 10, shard_index, 3))
+            .collect();
+        sharded_trials.sort_by_key(|trial| trial.trial_index);
+        assert_eq!(sharded_trials, experiment_trials(42, 10));
+    }
+
+    #[test]
+    fn sharded_experiments_reproduce_the_full_experiment() {
+        let (_, records) = run_capacity_experiment(42, None, Some(2));
+        let mut sharded_records: Vec<CapacityTrialRecord> = (0..5)
+            .flat_map(|shard_index| {
+                let shard = ExperimentShard {
+                    shard_index,
+                    num_shards: 5,
+                };
+                run_capacity_experiment(42, Some(shard), Some(2)).1
+            })
+            .collect();
+        sharded_records.sort_by_key(|record| record.trial_index);
+        assert_eq!(sharded_records, records);
+    }
+}
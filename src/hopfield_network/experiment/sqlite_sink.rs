@@ -0,0 +1,132 @@
+use super::trial_sink::{CapacityTrialRecord, LearningRuleTrialRecord, TrialSink};
+use rusqlite::{params, Connection};
+use std::path::Path;
+
+/// The tables of every experiment type, created if they do not already exist.
+///
+/// Seeds are stored as INTEGER, so seeds above i64::MAX read back as negative numbers with the same bits.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS capacity_trials (
+    run TEXT NOT NULL,
+    dimension INTEGER NOT NULL,
+    num_patterns INTEGER NOT NULL,
+    trial_index INTEGER NOT NULL,
+    trial_seed INTEGER NOT NULL,
+    recall REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS capacity_trial_metrics (
+    run TEXT NOT NULL,
+    dimension INTEGER NOT NULL,
+    num_patterns INTEGER NOT NULL,
+    trial_index INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    value REAL NOT NULL
+);
+CREATE TABLE IF NOT EXISTS learning_rule_trials (
+    run TEXT NOT NULL,
+    rule TEXT NOT NULL,
+    trial_index INTEGER NOT NULL,
+    trial_seed INTEGER NOT NULL,
+    recall REAL NOT NULL,
+    training_seconds REAL NOT NULL
+);
+";
+
+/// A TrialSink writing every trial record to a SQLite database, one table per experiment type
+/// (capacity_trials with capacity_trial_metrics, and learning_rule_trials).
+///
+/// Every record is committed as soon as it arrives and the database uses write-ahead logging,
+/// so the results of a long sweep can be queried from another process while it is still running.
+/// Each row is labeled with the run name given when the sink was opened, so many runs can share a database.
+pub struct SqliteTrialSink {
+    connection: Connection,
+    run: String,
+}
+
+impl SqliteTrialSink {
+    /// Open (or create) a SQLite database to write trial records to, creating any missing tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: The path of the database file.
+    /// * `run`: The name to label every record written by this sink with.
+    ///
+    /// # Returns
+    ///
+    /// The sink, or an error if the database could not be opened or its tables created.
+    pub fn open(path: impl AsRef<Path>, run: &str) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.pragma_update(None, "journal_mode", "WAL")?;
+        connection.execute_batch(SCHEMA)?;
+        Ok(Self {
+            connection,
+            run: run.to_string(),
+        })
+    }
+
+    /// Get the underlying connection, e.g. to query the results written so far.
+    pub fn connection(self: &Self) -> &Connection {
+        &self.connection
+    }
+
+    fn write_capacity_trial(self: &mut Self, record: &CapacityTrialRecord) -> rusqlite::Result<()> {
+        let transaction = self.connection.transaction()?;
+        transaction.execute(
+            "INSERT INTO capacity_trials VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.run,
+                record.dimension,
+                record.num_patterns,
+                record.trial_index,
+                record.trial_seed as i64,
+                record.recall,
+            ],
+        )?;
+        for (name, value) in &record.metrics {
+            transaction.execute(
+                "INSERT INTO capacity_trial_metrics VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    self.run,
+                    record.dimension,
+                    record.num_patterns,
+                    record.trial_index,
+                    name,
+                    value,
+                ],
+            )?;
+        }
+        transaction.commit()
+    }
+
+    fn write_learning_rule_trial(
+        self: &mut Self,
+        record: &LearningRuleTrialRecord,
+    ) -> rusqlite::Result<()> {
+        self.connection.execute(
+            "INSERT INTO learning_rule_trials VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                self.run,
+                record.rule,
+                record.trial_index,
+                record.trial_seed as i64,
+                record.recall,
+                record.training_time.as_secs_f64(),
+            ],
+        )?;
+        Ok(())
+    }
+}
+
+impl TrialSink for SqliteTrialSink {
+    fn record_capacity_trial(&mut self, record: &CapacityTrialRecord) {
+        if let Err(error) = self.write_capacity_trial(record) {
+            panic!("SqliteTrialSink encountered an error writing a capacity trial! {error}");
+        }
+    }
+
+    fn record_learning_rule_trial(&mut self, record: &LearningRuleTrialRecord) {
+        if let Err(error) = self.write_learning_rule_trial(record) {
+            panic!("SqliteTrialSink encountered an error writing a learning rule trial! {error}");
+        }
+    }
+}
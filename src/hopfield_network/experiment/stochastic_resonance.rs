@@ -0,0 +1,271 @@
+use super::{derive_seed, mean_and_std, wilson_interval};
+use crate::hopfield_network::{
+    external_input::ExternalInput, noise_channel::NoiseChannel, HopfieldNetwork, NetworkDomain,
+};
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The plan of a stochastic resonance sweep, see stochastic_resonance_sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StochasticResonanceConfig {
+    /// The noise amplitudes to sweep, as temperatures of the Glauber dynamics (see set_temperature), in the order
+    /// they are tested. Including 0 measures the deterministic baseline.
+    pub temperatures: Vec<f64>,
+    /// The strength of the cues: each cue is a noisy copy of the target pattern (in bipolar form) scaled by this,
+    /// applied as a constant external input. A cue is weak if it is too small to move the network out of the attractor it starts in.
+    pub cue_amplitude: f64,
+    /// The number of cues relaxed at each temperature.
+    pub trials_per_temperature: usize,
+    /// The standard normal quantile of the confidence intervals, e.g. 1.96 for 95% confidence.
+    pub z: f64,
+}
+
+/// The recall performance at one noise amplitude of a stochastic resonance sweep.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StochasticResonancePoint {
+    /// The temperature the cues were relaxed at.
+    pub temperature: f64,
+    /// The fraction of cues whose readout state is exactly the target pattern.
+    pub recall_rate: f64,
+    /// The lower bound of the Wilson confidence interval of the recall rate.
+    pub lower_bound: f64,
+    /// The upper bound of the Wilson confidence interval of the recall rate.
+    pub upper_bound: f64,
+    /// The mean overlap of the readout states with their target patterns, 1 - 2d/N for d differing units.
+    pub mean_overlap: f64,
+    /// The sample standard deviation of the overlaps of the readout states with their target patterns.
+    pub std_overlap: f64,
+    /// The fraction of cues whose readout state is still the pattern the network started in.
+    pub trapped_rate: f64,
+}
+
+/// The result of stochastic_resonance_sweep.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StochasticResonanceReport {
+    pub config: StochasticResonanceConfig,
+    /// The name of the noise channel the cues were corrupted with, see NoiseChannel::name.
+    pub channel: String,
+    /// The fraction of cues that were sub-threshold, i.e. could not move the network out of its starting pattern
+    /// at zero temperature. The sweep only shows stochastic resonance if this is near 1.
+    pub subthreshold_fraction: f64,
+    /// The recall performance at every temperature, in the order of the config.
+    pub points: Vec<StochasticResonancePoint>,
+}
+
+impl StochasticResonanceReport {
+    /// Get the index of the point with the highest recall rate, the first if several are equal, or None if no
+    /// temperatures were swept.
+    pub fn peak_index(self: &Self) -> Option<usize> {
+        self.points
+            .iter()
+            .enumerate()
+            .fold(
+                None,
+                |peak: Option<(usize, f64)>, (index, point)| match peak {
+                    Some((_, recall_rate)) if recall_rate >= point.recall_rate => peak,
+                    _ => Some((index, point.recall_rate)),
+                },
+            )
+            .map(|(index, _)| index)
+    }
+
+    /// Get the point with the highest recall rate, see peak_index.
+    pub fn peak(self: &Self) -> Option<&StochasticResonancePoint> {
+        self.peak_index().map(|index| &self.points[index])
+    }
+
+    /// Check whether the sweep shows stochastic resonance: the peak lies at an intermediate temperature, and the
+    /// lower bound of its confidence interval exceeds the upper bounds at both the lowest and the highest temperature,
+    /// so recall is significantly better with some noise than with too little or too much.
+    pub fn is_resonant(self: &Self) -> bool {
+        let Some(peak) = self.peak() else {
+            return false;
+        };
+        let coldest = self
+            .points
+            .iter()
+            .min_by(|a, b| a.temperature.total_cmp(&b.temperature))
+            .unwrap();
+        let hottest = self
+            .points
+            .iter()
+            .max_by(|a, b| a.temperature.total_cmp(&b.temperature))
+            .unwrap();
+        coldest.temperature < peak.temperature
+            && peak.temperature < hottest.temperature
+            && peak.lower_bound > coldest.upper_bound
+            && peak.lower_bound > hottest.upper_bound
+    }
+
+    /// Write the sweep as CSV, with one row per temperature.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the CSV to.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "channel,cue_amplitude,temperature,recall_rate,lower_bound,upper_bound,mean_overlap,std_overlap,trapped_rate"
+        )?;
+        for point in &self.points {
+            writeln!(
+                writer,
+                "\"{}\",{},{},{},{},{},{},{},{}",
+                self.channel,
+                self.config.cue_amplitude,
+                point.temperature,
+                point.recall_rate,
+                point.lower_bound,
+                point.upper_bound,
+                point.mean_overlap,
+                point.std_overlap,
+                point.trapped_rate
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Measure recall of weak cues against the amplitude of the noise in the dynamics, to find the noise level at which
+/// recall peaks (stochastic resonance).
+///
+/// Each trial starts the network in a stored pattern and cues a different, randomly chosen target pattern: the
+/// target is corrupted by the noise channel, scaled by the cue amplitude, and applied as a constant external input.
+/// A weak cue cannot move the network out of the starting attractor by itself, so without noise recall fails. The
+/// state is relaxed with Glauber dynamics at the temperature of the point, then read out by relaxing at zero
+/// temperature without the cue, so that only the attractor the noise and cue led the state to is scored. A little
+/// noise lets the cue pull the state over the barrier to the target, while too much noise drowns the cue.
+///
+/// Every trial at every temperature uses the same cues, start patterns and relaxation seeds, so differences between
+/// points come from the temperature alone. The external input and random number generator of the network are restored
+/// afterwards.
+///
+/// # Arguments
+///
+/// * `network`: The network to test, with at least two patterns stored. Must have the Binary or Bipolar domain.
+/// * `noise_channel`: The noise channel the target patterns are corrupted with to make the cues.
+/// * `config`: The sweep to run.
+/// * `seed`: The seed of the experiment. Each trial draws its cue from a seed derived from this and its index.
+///
+/// # Returns
+///
+/// A StochasticResonanceReport with one point per temperature.
+pub fn stochastic_resonance_sweep(
+    network: &mut HopfieldNetwork,
+    noise_channel: &dyn NoiseChannel,
+    config: &StochasticResonanceConfig,
+    seed: u64,
+) -> StochasticResonanceReport {
+    let domain = network.get_domain();
+    assert!(
+        domain == NetworkDomain::Binary || domain == NetworkDomain::Bipolar,
+        "Stochastic resonance requires a network with the Binary or Bipolar domain!"
+    );
+    let stored_patterns: Vec<DVector<f64>> = network
+        .get_stored_patterns()
+        .column_iter()
+        .map(|pattern| pattern.into_owned())
+        .collect();
+    assert!(
+        stored_patterns.len() >= 2,
+        "Stochastic resonance requires at least two stored patterns!"
+    );
+    assert!(
+        config.trials_per_temperature > 0,
+        "Stochastic resonance requires at least one trial per temperature!"
+    );
+
+    // Each trial is (target pattern, start pattern, cue, relaxation seed)
+    let trials: Vec<(usize, usize, DVector<f64>, u64)> = (0..config.trials_per_temperature)
+        .map(|trial_index| {
+            let mut rng = StdRng::seed_from_u64(derive_seed(seed, trial_index as u64));
+            let target = rng.gen_range(0..stored_patterns.len());
+            let start = (target + rng.gen_range(1..stored_patterns.len())) % stored_patterns.len();
+            let corrupted = noise_channel.corrupt(&stored_patterns[target], domain, &mut rng);
+            // The cue is given in the bipolar form the patterns are learned in, with erased units carrying no input
+            let bipolar_cue = network.learning_vector(&corrupted.state);
+            let cue = DVector::from_iterator(
+                bipolar_cue.len(),
+                bipolar_cue
+                    .iter()
+                    .zip(&corrupted.known_units)
+                    .map(|(value, known)| {
+                        if *known {
+                            value * config.cue_amplitude
+                        } else {
+                            0.0
+                        }
+                    }),
+            );
+            (target, start, cue, rng.gen())
+        })
+        .collect();
+
+    let network_input = network.external_input.take();
+    let network_rng = network.rng.clone();
+
+    let mut subthreshold = 0;
+    for (_, start, cue, _) in &trials {
+        network.set_external_input(Some(ExternalInput::constant(cue.clone())));
+        let (state, _) = network.relax_state_at_temperature(stored_patterns[*start].clone(), 0.0);
+        if state == stored_patterns[*start] {
+            subthreshold += 1;
+        }
+    }
+
+    let dimension = network.get_dimension() as f64;
+    let points = config
+        .temperatures
+        .iter()
+        .map(|temperature| {
+            let mut recalled = 0;
+            let mut trapped = 0;
+            let mut overlaps = Vec::with_capacity(trials.len());
+            for (target, start, cue, relaxation_seed) in &trials {
+                network.rng = StdRng::seed_from_u64(*relaxation_seed);
+                network.set_external_input(Some(ExternalInput::constant(cue.clone())));
+                let (state, _) = network
+                    .relax_state_at_temperature(stored_patterns[*start].clone(), *temperature);
+                network.set_external_input(None);
+                let (state, _) = network.relax_state_at_temperature(state, 0.0);
+
+                if state == stored_patterns[*target] {
+                    recalled += 1;
+                }
+                if state == stored_patterns[*start] {
+                    trapped += 1;
+                }
+                let matching_units = state
+                    .iter()
+                    .zip(stored_patterns[*target].iter())
+                    .filter(|(value, target_value)| value == target_value)
+                    .count();
+                overlaps.push(2.0 * matching_units as f64 / dimension - 1.0);
+            }
+
+            let (lower_bound, upper_bound) = wilson_interval(recalled, trials.len(), config.z);
+            let (mean_overlap, std_overlap) = mean_and_std(&overlaps);
+            StochasticResonancePoint {
+                temperature: *temperature,
+                recall_rate: recalled as f64 / trials.len() as f64,
+                lower_bound,
+                upper_bound,
+                mean_overlap,
+                std_overlap,
+                trapped_rate: trapped as f64 / trials.len() as f64,
+            }
+        })
+        .collect();
+
+    network.set_external_input(network_input);
+    network.rng = network_rng;
+
+    StochasticResonanceReport {
+        config: config.clone(),
+        channel: noise_channel.name(),
+        subthreshold_fraction: subthreshold as f64 / trials.len() as f64,
+        points,
+    }
+}
@@ -0,0 +1,40 @@
+use std::time::Duration;
+
+/// The record of a single finished trial of capacity_experiment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CapacityTrialRecord {
+    pub dimension: usize,
+    pub num_patterns: usize,
+    pub trial_index: usize,
+    pub trial_seed: u64,
+    /// The fraction of learned patterns relaxed back to exactly themselves.
+    pub recall: f64,
+    /// The value of every registered metric, as `(name, value)` pairs in the order they were registered.
+    pub metrics: Vec<(String, f64)>,
+}
+
+/// The record of a single finished trial of one rule in compare_learning_rules.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearningRuleTrialRecord {
+    pub rule: String,
+    pub trial_index: usize,
+    pub trial_seed: u64,
+    /// The fraction of learned patterns that are stable under relaxation.
+    pub recall: f64,
+    pub training_time: Duration,
+}
+
+/// A destination for trial records, written as each trial of an experiment finishes rather than at the end.
+///
+/// Long sweeps can then be inspected (or resumed after a crash) while still running.
+/// Each experiment type has its own record, and every method defaults to ignoring the record,
+/// so a sink only needs to implement the experiments it is interested in.
+///
+/// Sinks are always called from the thread that started the experiment, so they need not be Send.
+pub trait TrialSink {
+    /// Record a finished trial of capacity_experiment (or load_capacity_experiment).
+    fn record_capacity_trial(&mut self, _record: &CapacityTrialRecord) {}
+
+    /// Record a finished trial of one rule in compare_learning_rules.
+    fn record_learning_rule_trial(&mut self, _record: &LearningRuleTrialRecord) {}
+}
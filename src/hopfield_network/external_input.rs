@@ -0,0 +1,54 @@
+use nalgebra::DVector;
+use std::{fmt, sync::Arc};
+
+/// A time-varying external input h(t), added to the local field of every unit during relaxation.
+///
+/// Time is measured in update sweeps: the input at sweep t is applied while every unit is updated for the t-th time.
+#[derive(Clone)]
+pub enum ExternalInput {
+    /// The input is calculated from the sweep index.
+    Function(Arc<dyn Fn(usize) -> DVector<f64> + Send + Sync>),
+    /// The input is sampled at each sweep. After the last sample, the last sample is held.
+    Sequence(Vec<DVector<f64>>),
+}
+
+impl fmt::Debug for ExternalInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Function(_) => write!(f, "ExternalInput::Function"),
+            Self::Sequence(samples) => {
+                write!(f, "ExternalInput::Sequence({} samples)", samples.len())
+            }
+        }
+    }
+}
+
+impl ExternalInput {
+    /// Create an external input from a function of the sweep index.
+    pub fn from_function(input_fn: impl Fn(usize) -> DVector<f64> + Send + Sync + 'static) -> Self {
+        Self::Function(Arc::new(input_fn))
+    }
+
+    /// Create an external input that is the same at every sweep, e.g. the static external field of a spin glass.
+    pub fn constant(input: DVector<f64>) -> Self {
+        Self::Sequence(vec![input])
+    }
+
+    /// Get the input at a given sweep.
+    ///
+    /// # Arguments
+    ///
+    /// * `sweep`: The index of the update sweep, starting from 0.
+    ///
+    /// # Returns
+    ///
+    /// The input to add to the local fields, or None if this is an empty sequence.
+    pub fn at(self: &Self, sweep: usize) -> Option<DVector<f64>> {
+        match self {
+            Self::Function(input_fn) => Some(input_fn(sweep)),
+            Self::Sequence(samples) => samples
+                .get(sweep.min(samples.len().saturating_sub(1)))
+                .cloned(),
+        }
+    }
+}
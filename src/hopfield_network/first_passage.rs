@@ -0,0 +1,289 @@
+use nalgebra::DVector;
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use super::{
+    experiment::{derive_seed, mean_and_std},
+    glauber::is_valid_temperature,
+    HopfieldNetwork, NetworkDomain, RelaxationDynamics,
+};
+
+/// The event whose first passage time is measured, see HopfieldNetwork::first_passage_times.
+///
+/// Overlaps are 1 - 2d/N for states differing in d of N units, so 1 is the same state and 0 an unrelated one.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FirstPassageCriterion {
+    /// The state reaches a target: its overlap with the target is at least the minimum overlap. A minimum overlap of 1
+    /// requires the target exactly.
+    ReachTarget {
+        target: DVector<f64>,
+        minimum_overlap: f64,
+    },
+    /// The state escapes the starting state (e.g. an attractor): its overlap with the start falls below the maximum
+    /// overlap.
+    EscapeStart { maximum_overlap: f64 },
+}
+
+/// A bin of a first passage time histogram, see FirstPassageTimes::histogram.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FirstPassageBin {
+    /// The first sweep of the bin, inclusive.
+    pub start_sweep: usize,
+    /// The last sweep of the bin, exclusive.
+    pub end_sweep: usize,
+    /// The number of runs that first passed during the bin.
+    pub count: usize,
+}
+
+/// The first passage times of many noisy relaxations from the same starting state.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FirstPassageTimes {
+    /// The temperature the runs were relaxed at.
+    pub temperature: f64,
+    /// The number of sweeps each run was allowed.
+    pub maximum_sweeps: usize,
+    /// The first passage time of each run in sweeps, 0 if the starting state already met the criterion, or None if the
+    /// run did not pass within the maximum number of sweeps (i.e. the time is censored).
+    pub passage_times: Vec<Option<usize>>,
+}
+
+impl FirstPassageTimes {
+    /// Get the fraction of runs that passed within the maximum number of sweeps.
+    pub fn passed_fraction(self: &Self) -> f64 {
+        self.passed_times().len() as f64 / self.passage_times.len() as f64
+    }
+
+    /// Get the mean and sample standard deviation of the first passage times of the runs that passed, see
+    /// mean_and_std. Censored runs are left out, so with many censored runs these underestimate the true times.
+    pub fn mean_and_std(self: &Self) -> (f64, f64) {
+        let passed_times: Vec<f64> = self
+            .passed_times()
+            .into_iter()
+            .map(|time| time as f64)
+            .collect();
+        mean_and_std(&passed_times)
+    }
+
+    /// Get the survival function of the first passage time: for every sweep t from 0 to the maximum number of sweeps,
+    /// the fraction of runs that had not yet passed after t sweeps. Unlike the mean, this accounts for censored runs.
+    pub fn survival_function(self: &Self) -> Vec<f64> {
+        let runs = self.passage_times.len() as f64;
+        let mut passed_at = vec![0; self.maximum_sweeps + 1];
+        for time in self.passed_times() {
+            passed_at[time] += 1;
+        }
+
+        let mut surviving = self.passage_times.len();
+        passed_at
+            .into_iter()
+            .map(|passed| {
+                surviving -= passed;
+                surviving as f64 / runs
+            })
+            .collect()
+    }
+
+    /// Get a histogram of the first passage times of the runs that passed. Censored runs are not counted, see
+    /// passed_fraction.
+    ///
+    /// # Arguments
+    ///
+    /// * `bin_width`: The number of sweeps per bin. Must be strictly positive.
+    ///
+    /// # Returns
+    ///
+    /// The bins covering every sweep from 0 to the maximum number of sweeps, in order.
+    pub fn histogram(self: &Self, bin_width: usize) -> Vec<FirstPassageBin> {
+        assert!(
+            bin_width > 0,
+            "Histogram bin width must be strictly positive!"
+        );
+        let mut bins: Vec<FirstPassageBin> = (0..=self.maximum_sweeps)
+            .step_by(bin_width)
+            .map(|start_sweep| FirstPassageBin {
+                start_sweep,
+                end_sweep: (start_sweep + bin_width).min(self.maximum_sweeps + 1),
+                count: 0,
+            })
+            .collect();
+        for time in self.passed_times() {
+            bins[time / bin_width].count += 1;
+        }
+        bins
+    }
+
+    /// Write the histogram of the first passage times as CSV, with one row per bin. See histogram.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the CSV to.
+    /// * `bin_width`: The number of sweeps per bin. Must be strictly positive.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write, bin_width: usize) -> io::Result<()> {
+        writeln!(writer, "temperature,start_sweep,end_sweep,count")?;
+        for bin in self.histogram(bin_width) {
+            writeln!(
+                writer,
+                "{},{},{},{}",
+                self.temperature, bin.start_sweep, bin.end_sweep, bin.count
+            )?;
+        }
+        Ok(())
+    }
+
+    fn passed_times(self: &Self) -> Vec<usize> {
+        self.passage_times.iter().flatten().copied().collect()
+    }
+}
+
+/// Get the overlap of two states of the Binary or Bipolar domain, 1 - 2d/N for d differing units.
+fn overlap(state: &DVector<f64>, other: &DVector<f64>) -> f64 {
+    let matching_units = state
+        .iter()
+        .zip(other.iter())
+        .filter(|(value, other_value)| value == other_value)
+        .count();
+    2.0 * matching_units as f64 / state.len() as f64 - 1.0
+}
+
+/// Run noisy relaxations from a starting state, returning the first passage time of each in sweeps.
+fn first_passage_thread_fn(
+    relaxation_dynamics: RelaxationDynamics,
+    mut unit_indices: Vec<usize>,
+    start: &DVector<f64>,
+    criterion: &FirstPassageCriterion,
+    maximum_sweeps: usize,
+    rng_seeds: &[u64],
+) -> Vec<Option<usize>> {
+    let has_passed = |state: &DVector<f64>| match criterion {
+        FirstPassageCriterion::ReachTarget {
+            target,
+            minimum_overlap,
+        } => overlap(state, target) >= *minimum_overlap,
+        FirstPassageCriterion::EscapeStart { maximum_overlap } => {
+            overlap(state, start) < *maximum_overlap
+        }
+    };
+
+    rng_seeds
+        .iter()
+        .map(|rng_seed| {
+            if has_passed(start) {
+                return Some(0);
+            }
+
+            let mut rng = StdRng::seed_from_u64(*rng_seed);
+            let mut state = start.clone();
+            let mut adaptation = (!relaxation_dynamics.fatigue.is_disabled())
+                .then(|| DVector::<f64>::zeros(state.len()));
+            // Unlike relax, the runs do not stop at a stable state, only once they pass
+            (0..maximum_sweeps).find_map(|sweep| {
+                relaxation_dynamics.advance(
+                    sweep,
+                    &mut unit_indices,
+                    &mut rng,
+                    &mut state,
+                    &mut adaptation,
+                );
+                has_passed(&state).then_some(sweep + 1)
+            })
+        })
+        .collect()
+}
+
+impl HopfieldNetwork {
+    /// Measure the first passage times of noisy relaxations from a starting state: the number of sweeps until each
+    /// run first reaches a target or escapes the start (see FirstPassageCriterion), e.g. the escape times of an
+    /// attractor at a temperature.
+    ///
+    /// Each run starts from the starting state and sweeps the units with the dynamics of the network at the given
+    /// temperature (a custom update rule takes precedence, see set_update_rule), including any external input and
+    /// fatigue. Unlike relaxation, a run does not stop at a stable state, only once it passes or after the maximum
+    /// number of sweeps.
+    ///
+    /// # Arguments
+    ///
+    /// * `start`: The state every run starts from.
+    /// * `criterion`: The event to measure the first passage time of.
+    /// * `temperature`: The temperature to relax at. Must be non-negative.
+    /// * `runs`: The number of runs.
+    /// * `maximum_sweeps`: The largest number of sweeps per run.
+    /// * `seed`: The seed of the runs. Each run draws its update order and noise from a seed derived from this and
+    ///   its index, so the result does not depend on the number of threads.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    ///
+    /// # Returns
+    ///
+    /// The FirstPassageTimes of every run, in order.
+    #[allow(clippy::too_many_arguments)]
+    pub fn first_passage_times(
+        self: &mut Self,
+        start: &DVector<f64>,
+        criterion: &FirstPassageCriterion,
+        temperature: f64,
+        runs: usize,
+        maximum_sweeps: usize,
+        seed: u64,
+        threads: Option<usize>,
+    ) -> FirstPassageTimes {
+        assert!(
+            self.domain == NetworkDomain::Binary || self.domain == NetworkDomain::Bipolar,
+            "First passage times require a network with the Binary or Bipolar domain!"
+        );
+        assert!(
+            is_valid_temperature(self.domain, temperature),
+            "Temperature must be non-negative, and zero unless the network domain is Binary or Bipolar!"
+        );
+        assert_eq!(
+            start.len(),
+            self.dimension,
+            "State must have the same dimension as the network!"
+        );
+        if let FirstPassageCriterion::ReachTarget { target, .. } = criterion {
+            assert_eq!(
+                target.len(),
+                self.dimension,
+                "Target must have the same dimension as the network!"
+            );
+        }
+
+        let network_temperature = self.temperature;
+        self.temperature = temperature;
+        let update_rule = self.get_update_rule();
+        self.temperature = network_temperature;
+
+        let rng_seeds: Vec<u64> = (0..runs as u64).map(|run| derive_seed(seed, run)).collect();
+        let threads = threads.unwrap_or_else(|| self.default_thread_count());
+        let chunk_size = runs.div_ceil(threads.max(1)).max(1);
+        let relaxation_dynamics = self.relaxation_dynamics(update_rule.as_ref());
+        let passage_times = crossbeam::scope(|scope| {
+            let handles: Vec<_> = rng_seeds
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    let unit_indices = self.get_unit_indices();
+                    scope.spawn(move |_| {
+                        first_passage_thread_fn(
+                            relaxation_dynamics,
+                            unit_indices,
+                            start,
+                            criterion,
+                            maximum_sweeps,
+                            chunk,
+                        )
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect()
+        })
+        .unwrap();
+
+        FirstPassageTimes {
+            temperature,
+            maximum_sweeps,
+            passage_times,
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng};
+
+use super::{
+    activation_function::ActivationParameters, network_event::NetworkEvent,
+    update_rule::UpdateRule, HopfieldNetwork, NetworkDomain,
+};
+
+/// The update rule of Glauber dynamics at a positive temperature, see HopfieldNetwork::set_temperature.
+///
+/// A Bipolar unit with local field h is set to +1 with probability 1 / (1 + exp(-2h / T)), so a unit aligned with
+/// its field flips with probability 1 / (1 + exp(2|h| / T)). A Binary unit is set to 1 with probability
+/// 1 / (1 + exp(-(h - θ) / T)) for the binary threshold θ. Both become the deterministic activations as T goes to 0.
+#[derive(Debug, Clone, Copy)]
+pub struct GlauberRule {
+    /// The domain of the units, Binary or Bipolar.
+    pub domain: NetworkDomain,
+    /// The temperature T. Must be strictly positive.
+    pub temperature: f64,
+    pub activation_parameters: ActivationParameters,
+}
+
+impl UpdateRule for GlauberRule {
+    fn next_value(&self, _unit_index: usize, field: f64, _value: f64, rng: &mut StdRng) -> f64 {
+        let (low_value, high_value, field) = match self.domain {
+            NetworkDomain::Binary => (
+                0.0,
+                1.0,
+                field - self.activation_parameters.binary_threshold,
+            ),
+            _ => (-1.0, 1.0, field),
+        };
+        let high_probability =
+            1.0 / (1.0 + (-(high_value - low_value) * field / self.temperature).exp());
+        if rng.gen::<f64>() < high_probability {
+            high_value
+        } else {
+            low_value
+        }
+    }
+}
+
+/// Check a temperature is valid for a domain: non-negative, and zero unless the domain is Binary or Bipolar.
+pub(super) fn is_valid_temperature(domain: NetworkDomain, temperature: f64) -> bool {
+    temperature == 0.0
+        || (temperature > 0.0 && matches!(domain, NetworkDomain::Binary | NetworkDomain::Bipolar))
+}
+
+impl HopfieldNetwork {
+    /// Get the temperature of the unit updates, 0 for deterministic dynamics.
+    pub fn get_temperature(self: &Self) -> f64 {
+        self.temperature
+    }
+
+    /// Set the temperature of the unit updates. At a positive temperature every unit update samples the unit from
+    /// its local field with Glauber dynamics (see GlauberRule), so relaxation can escape shallow spurious minima.
+    /// A custom update rule (see set_update_rule) takes precedence. This applies to all following relaxations, serial
+    /// or concurrent, and emits NetworkEvent::TemperatureChanged. See relax_state_at_temperature to change the
+    /// temperature for a single relaxation.
+    ///
+    /// Relaxation still stops once no unit is unstable against its deterministic activation, which at a high
+    /// temperature rarely happens before the maximum number of iterations. The attractor cache is bypassed while the
+    /// temperature is positive, as the relaxed states are random.
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature`: The new temperature T. Must be non-negative, and zero unless the domain is Binary or Bipolar.
+    pub fn set_temperature(self: &mut Self, temperature: f64) {
+        assert!(
+            is_valid_temperature(self.domain, temperature),
+            "Temperature must be non-negative, and zero unless the network domain is Binary or Bipolar!"
+        );
+        self.temperature = temperature;
+        self.event_hooks
+            .emit(&NetworkEvent::TemperatureChanged { temperature });
+    }
+
+    /// Relax a state at a different temperature for this relaxation only, see set_temperature. The attractor cache
+    /// is bypassed.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state the relax. Consumes the state.
+    /// * `temperature` - The temperature to relax at. Must be non-negative, and zero unless the domain is Binary or
+    ///   Bipolar.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of update iterations performed.
+    pub fn relax_state_at_temperature(
+        self: &mut Self,
+        state: DVector<f64>,
+        temperature: f64,
+    ) -> (DVector<f64>, usize) {
+        assert!(
+            is_valid_temperature(self.domain, temperature),
+            "Temperature must be non-negative, and zero unless the network domain is Binary or Bipolar!"
+        );
+        let network_temperature = self.temperature;
+        self.temperature = temperature;
+        let result = self.relax_state_iterations(state);
+        self.temperature = network_temperature;
+        result
+    }
+}
@@ -0,0 +1,96 @@
+use nalgebra::DVector;
+
+use super::{HopfieldNetwork, NetworkDomain};
+
+/// The parameters of gradient descent relaxation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GradientDescentParameters {
+    /// The step size η of each update, s ← s - η ∇E.
+    pub step_size: f64,
+    /// The maximum number of steps to take.
+    pub maximum_steps: usize,
+    /// Descent stops once the largest change of any unit in a step is at most this tolerance.
+    pub tolerance: f64,
+}
+
+/// The result of gradient descent relaxation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GradientDescentResult {
+    pub state: DVector<f64>,
+    pub steps: usize,
+    /// Whether the descent stopped by reaching the tolerance, rather than the maximum number of steps.
+    pub converged: bool,
+}
+
+impl HopfieldNetwork {
+    /// Get the gradient of the energy with respect to a state, -W s.
+    ///
+    /// This is the gradient of E = -½ sᵀ W s for symmetric weights. state_energy omits the factor of ½, so its gradient
+    /// is twice this, in the same direction. For asymmetric weights there is no energy and this is only the
+    /// negated local field.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to take the gradient at.
+    ///
+    /// # Returns
+    ///
+    /// The gradient as a DVector of `f64`.
+    pub fn energy_gradient(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        -self.local_fields(state)
+    }
+
+    /// Relax a continuous state by gradient descent on the energy, s ← s - η ∇E, as an alternative to the
+    /// discrete update dynamics.
+    ///
+    /// In the BoundedContinuous domain the state is projected back into the activation bounds after each step,
+    /// and in the Tanh domain into [-1, 1]. In the Continuous domain the state is unconstrained, so the descent
+    /// only stops if the weights have no positive eigenvalues along the trajectory.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax. Consumes the state.
+    /// * `parameters`: The step size and stopping conditions of the descent.
+    ///
+    /// # Returns
+    ///
+    /// A GradientDescentResult with the relaxed state.
+    pub fn relax_state_gradient_descent(
+        self: &Self,
+        mut state: DVector<f64>,
+        parameters: GradientDescentParameters,
+    ) -> GradientDescentResult {
+        let bounds = match self.domain {
+            NetworkDomain::Continuous => None,
+            NetworkDomain::BoundedContinuous => Some((
+                self.activation_parameters.continuous_lower_bound,
+                self.activation_parameters.continuous_upper_bound,
+            )),
+            NetworkDomain::Tanh => Some((-1.0, 1.0)),
+            _ => panic!("Gradient descent relaxation is only defined for continuous domains!"),
+        };
+
+        for step in 0..parameters.maximum_steps {
+            let mut next_state = &state - self.energy_gradient(&state) * parameters.step_size;
+            if let Some((lower_bound, upper_bound)) = bounds {
+                next_state.apply(|value| *value = value.clamp(lower_bound, upper_bound));
+            }
+
+            let change = (&next_state - &state).amax();
+            state = next_state;
+            if change <= parameters.tolerance {
+                return GradientDescentResult {
+                    state,
+                    steps: step + 1,
+                    converged: true,
+                };
+            }
+        }
+
+        GradientDescentResult {
+            state,
+            steps: parameters.maximum_steps,
+            converged: false,
+        }
+    }
+}
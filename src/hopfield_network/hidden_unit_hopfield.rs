@@ -0,0 +1,368 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// The weights of a network with visible and hidden units, held as the blocks of the joint weight matrix
+///
+/// W = [ W_vv    W_vh ]
+///     [ W_vhᵀ   W_hh ],
+///
+/// so the couplings between the layers need not be stored twice. The square blocks are symmetric with a zero
+/// diagonal, so W is too.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockWeights {
+    /// The couplings between visible units, N × N.
+    pub visible_visible: DMatrix<f64>,
+    /// The couplings from visible to hidden units, one row per visible unit and one column per hidden unit, N × M.
+    pub visible_hidden: DMatrix<f64>,
+    /// The couplings between hidden units, M × M.
+    pub hidden_hidden: DMatrix<f64>,
+}
+
+impl BlockWeights {
+    /// Create blocks of zero weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `visible_units`: The number of visible units N.
+    /// * `hidden_units`: The number of hidden units M.
+    pub fn new_zeroed(visible_units: usize, hidden_units: usize) -> Self {
+        Self {
+            visible_visible: DMatrix::zeros(visible_units, visible_units),
+            visible_hidden: DMatrix::zeros(visible_units, hidden_units),
+            hidden_hidden: DMatrix::zeros(hidden_units, hidden_units),
+        }
+    }
+
+    /// Get the number of visible units.
+    pub fn visible_units(self: &Self) -> usize {
+        self.visible_visible.nrows()
+    }
+
+    /// Get the number of hidden units.
+    pub fn hidden_units(self: &Self) -> usize {
+        self.hidden_hidden.nrows()
+    }
+
+    /// Assemble the joint (N + M) × (N + M) weight matrix, with the visible units first.
+    pub fn joint_matrix(self: &Self) -> DMatrix<f64> {
+        let visible_units = self.visible_units();
+        let hidden_units = self.hidden_units();
+        let mut matrix = DMatrix::zeros(visible_units + hidden_units, visible_units + hidden_units);
+        matrix
+            .view_mut((0, 0), (visible_units, visible_units))
+            .copy_from(&self.visible_visible);
+        matrix
+            .view_mut((0, visible_units), (visible_units, hidden_units))
+            .copy_from(&self.visible_hidden);
+        matrix
+            .view_mut((visible_units, 0), (hidden_units, visible_units))
+            .copy_from(&self.visible_hidden.transpose());
+        matrix
+            .view_mut((visible_units, visible_units), (hidden_units, hidden_units))
+            .copy_from(&self.hidden_hidden);
+        matrix
+    }
+
+    /// Add hidden units with the given couplings to the visible units and no couplings to other hidden units.
+    ///
+    /// # Arguments
+    ///
+    /// * `visible_hidden`: The couplings of the new hidden units, one column per new unit.
+    fn append_hidden_units(self: &mut Self, visible_hidden: &DMatrix<f64>) {
+        let hidden_units = self.hidden_units();
+        let new_units = visible_hidden.ncols();
+        self.visible_hidden =
+            self.visible_hidden
+                .clone()
+                .insert_columns(hidden_units, new_units, 0.0);
+        self.visible_hidden
+            .columns_mut(hidden_units, new_units)
+            .copy_from(visible_hidden);
+        self.hidden_hidden = self
+            .hidden_hidden
+            .clone()
+            .insert_columns(hidden_units, new_units, 0.0)
+            .insert_rows(hidden_units, new_units, 0.0);
+    }
+}
+
+/// The result of relaxing a HiddenUnitHopfieldNetwork.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HiddenRelaxationResult {
+    /// The relaxed visible units, in the Bipolar domain.
+    pub visible: DVector<f64>,
+    /// The relaxed hidden units, in the Binary domain.
+    pub hidden: DVector<f64>,
+    pub sweeps: usize,
+    /// Whether the last sweep changed no unit, rather than the maximum number of sweeps being reached.
+    pub converged: bool,
+}
+
+/// An associative memory of Bipolar visible units and Binary hidden units, relaxed jointly. The energy of a joint
+/// state is
+///
+/// E(v, h) = -½vᵀW_vv v - vᵀW_vh h - ½hᵀW_hh h - aᵀv - bᵀh,
+///
+/// over the block weights (see BlockWeights) and the visible and hidden biases a and b. Each unit is updated
+/// asynchronously to the sign of its field (a hidden unit is active when its field is positive), which never raises
+/// the energy, so relaxation reaches a stable joint state.
+///
+/// learn_states gives each pattern its own hidden unit, coupled to the visible units by ξ/N with bias -θ for the
+/// recognition threshold θ: the unit activates when the overlap of the visible state with its pattern exceeds θ, and
+/// then pulls the visible units towards the pattern. The patterns are never superimposed on a shared N × N matrix as
+/// in the Hebbian rule, so far more than N patterns are stored, limited by how often an unrelated pattern has an
+/// overlap above θ with the cue. This is the network with hidden units of Krotov and Hopfield's dense associative
+/// memories, with a step function as the hidden nonlinearity. A cue near no pattern activates no hidden unit and is
+/// left unchanged (unless the visible units are coupled directly).
+#[derive(Debug, Clone)]
+pub struct HiddenUnitHopfieldNetwork {
+    weights: BlockWeights,
+    visible_bias: DVector<f64>,
+    hidden_bias: DVector<f64>,
+    recognition_threshold: f64,
+    maximum_relaxation_iterations: usize,
+    rng: StdRng,
+}
+
+impl HiddenUnitHopfieldNetwork {
+    /// Create a new network with no hidden units and zero weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `visible_units`: The number of visible units, the dimension of the patterns.
+    /// * `recognition_threshold`: The overlap θ with its pattern above which a hidden unit added by learn_states
+    ///   activates. Must be in [0, 1).
+    /// * `maximum_relaxation_iterations`: The maximum number of update sweeps of a relaxation.
+    /// * `seed`: The seed of the random update order.
+    pub fn new_hidden_unit_hopfield_network(
+        visible_units: usize,
+        recognition_threshold: f64,
+        maximum_relaxation_iterations: usize,
+        seed: u64,
+    ) -> Self {
+        assert!(
+            visible_units > 0,
+            "Hidden unit network must have at least one visible unit!"
+        );
+        assert!(
+            (0.0..1.0).contains(&recognition_threshold),
+            "Hidden unit network recognition threshold must be in [0, 1)!"
+        );
+
+        Self {
+            weights: BlockWeights::new_zeroed(visible_units, 0),
+            visible_bias: DVector::zeros(visible_units),
+            hidden_bias: DVector::zeros(0),
+            recognition_threshold,
+            maximum_relaxation_iterations,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Get the number of visible units.
+    pub fn get_visible_units(self: &Self) -> usize {
+        self.weights.visible_units()
+    }
+
+    /// Get the number of hidden units.
+    pub fn get_hidden_units(self: &Self) -> usize {
+        self.weights.hidden_units()
+    }
+
+    /// Get the recognition threshold of the hidden units added by learn_states.
+    pub fn get_recognition_threshold(self: &Self) -> f64 {
+        self.recognition_threshold
+    }
+
+    /// Get the block weights.
+    pub fn get_weights(self: &Self) -> &BlockWeights {
+        &self.weights
+    }
+
+    /// Get the visible biases.
+    pub fn get_visible_bias(self: &Self) -> &DVector<f64> {
+        &self.visible_bias
+    }
+
+    /// Get the hidden biases.
+    pub fn get_hidden_bias(self: &Self) -> &DVector<f64> {
+        &self.hidden_bias
+    }
+
+    /// Set the weights and biases directly, e.g. for an energy function not built by learn_states. The square blocks
+    /// are made symmetric, W = (W + Wᵀ) / 2, and their diagonals set to zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `weights`: The block weights. The number of visible units must match the network.
+    /// * `visible_bias`: The visible biases a.
+    /// * `hidden_bias`: The hidden biases b, one per hidden unit of the weights.
+    pub fn set_weights(
+        self: &mut Self,
+        mut weights: BlockWeights,
+        visible_bias: DVector<f64>,
+        hidden_bias: DVector<f64>,
+    ) {
+        let visible_units = self.get_visible_units();
+        let hidden_units = weights.hidden_hidden.nrows();
+        assert!(
+            weights.visible_visible.shape() == (visible_units, visible_units)
+                && weights.visible_hidden.shape() == (visible_units, hidden_units)
+                && weights.hidden_hidden.is_square(),
+            "Block weights must match the visible units of the network and each other!"
+        );
+        assert!(
+            visible_bias.len() == visible_units && hidden_bias.len() == hidden_units,
+            "Biases must have one entry per unit of their layer!"
+        );
+
+        for block in [&mut weights.visible_visible, &mut weights.hidden_hidden] {
+            *block = (&*block + block.transpose()) / 2.0;
+            block.fill_diagonal(0.0);
+        }
+        self.weights = weights;
+        self.visible_bias = visible_bias;
+        self.hidden_bias = hidden_bias;
+    }
+
+    /// Store a collection of Bipolar patterns, adding one hidden unit per pattern, see HiddenUnitHopfieldNetwork.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have one value per visible unit.
+    pub fn learn_states(self: &mut Self, patterns: &[DVector<f64>]) {
+        let visible_units = self.get_visible_units();
+        assert!(
+            patterns
+                .iter()
+                .all(|pattern| pattern.len() == visible_units),
+            "Every pattern must have the same dimension as the visible layer!"
+        );
+        if patterns.is_empty() {
+            return;
+        }
+
+        let couplings = DMatrix::from_columns(patterns) / visible_units as f64;
+        self.weights.append_hidden_units(&couplings);
+        let hidden_units = self.hidden_bias.len();
+        self.hidden_bias = self.hidden_bias.clone().insert_rows(
+            hidden_units,
+            patterns.len(),
+            -self.recognition_threshold,
+        );
+    }
+
+    /// Get the energy of a joint state, see HiddenUnitHopfieldNetwork.
+    pub fn energy(self: &Self, visible: &DVector<f64>, hidden: &DVector<f64>) -> f64 {
+        -(0.5 * visible.dot(&(&self.weights.visible_visible * visible))
+            + visible.dot(&(&self.weights.visible_hidden * hidden))
+            + 0.5 * hidden.dot(&(&self.weights.hidden_hidden * hidden))
+            + self.visible_bias.dot(visible)
+            + self.hidden_bias.dot(hidden))
+    }
+
+    /// Get the energy of a visible state with every hidden unit at its best value, min_h E(v, h) =
+    /// -½vᵀW_vv v - aᵀv - Σ_μ max(0, x_μ) where x = W_vhᵀv + b. This is the energy of the visible units alone that
+    /// relaxation descends, and is only defined while the hidden units are not coupled to each other.
+    pub fn visible_energy(self: &Self, visible: &DVector<f64>) -> f64 {
+        assert!(
+            self.weights
+                .hidden_hidden
+                .iter()
+                .all(|weight| *weight == 0.0),
+            "The visible energy is only defined without couplings between hidden units!"
+        );
+        let hidden_term: f64 = (self.weights.visible_hidden.tr_mul(visible) + &self.hidden_bias)
+            .iter()
+            .map(|field| field.max(0.0))
+            .sum();
+        -(0.5 * visible.dot(&(&self.weights.visible_visible * visible))
+            + self.visible_bias.dot(visible)
+            + hidden_term)
+    }
+
+    /// Relax a visible state, starting with every hidden unit inactive. See relax_joint_state.
+    pub fn relax_state(self: &mut Self, visible: DVector<f64>) -> HiddenRelaxationResult {
+        let hidden = DVector::zeros(self.get_hidden_units());
+        self.relax_joint_state(visible, hidden)
+    }
+
+    /// Relax a joint state. Each sweep updates every hidden unit and then every visible unit, each layer in a random
+    /// order, until a sweep changes no unit. A unit with a field of exactly zero keeps its value if visible, and is
+    /// inactive if hidden.
+    ///
+    /// # Arguments
+    ///
+    /// * `visible`: The Bipolar visible state to relax. Consumes the state.
+    /// * `hidden`: The Binary hidden state to start from. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// A HiddenRelaxationResult with the relaxed joint state.
+    pub fn relax_joint_state(
+        self: &mut Self,
+        mut visible: DVector<f64>,
+        mut hidden: DVector<f64>,
+    ) -> HiddenRelaxationResult {
+        assert!(
+            visible.len() == self.get_visible_units() && hidden.len() == self.get_hidden_units(),
+            "Visible and hidden states must have the dimensions of their layers!"
+        );
+
+        let mut visible_indices: Vec<usize> = (0..self.get_visible_units()).collect();
+        let mut hidden_indices: Vec<usize> = (0..self.get_hidden_units()).collect();
+        let mut sweeps = 0;
+        let mut converged = false;
+        while sweeps < self.maximum_relaxation_iterations {
+            sweeps += 1;
+            let mut changed_units = 0;
+
+            hidden_indices.shuffle(&mut self.rng);
+            for unit in hidden_indices.iter() {
+                let field = self.weights.visible_hidden.column(*unit).dot(&visible)
+                    + self.weights.hidden_hidden.column(*unit).dot(&hidden)
+                    + self.hidden_bias[*unit];
+                let next = if field > 0.0 { 1.0 } else { 0.0 };
+                if next != hidden[*unit] {
+                    hidden[*unit] = next;
+                    changed_units += 1;
+                }
+            }
+
+            visible_indices.shuffle(&mut self.rng);
+            for unit in visible_indices.iter() {
+                let field = self.weights.visible_visible.column(*unit).dot(&visible)
+                    + self
+                        .weights
+                        .visible_hidden
+                        .row(*unit)
+                        .transpose()
+                        .dot(&hidden)
+                    + self.visible_bias[*unit];
+                let next = if field > 0.0 {
+                    1.0
+                } else if field < 0.0 {
+                    -1.0
+                } else {
+                    visible[*unit]
+                };
+                if next != visible[*unit] {
+                    visible[*unit] = next;
+                    changed_units += 1;
+                }
+            }
+
+            if changed_units == 0 {
+                converged = true;
+                break;
+            }
+        }
+
+        HiddenRelaxationResult {
+            visible,
+            hidden,
+            sweeps,
+            converged,
+        }
+    }
+}
@@ -1,18 +1,56 @@
 use nalgebra::DMatrix;
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
 
-use super::HopfieldNetwork;
+use super::{
+    activation_function::ActivationParameters,
+    adaptation::FatigueParameters,
+    attractor_cache::AttractorCache,
+    clean_policy::CleanPolicy,
+    domain_preset::DomainPreset,
+    duplicate_policy::{DuplicateHandling, DuplicatePolicy},
+    glauber,
+    network_event::{EventHookRegistry, RelaxationCallbackRegistry},
+    relaxation_result::TrajectoryRecording,
+    summation::SummationOrder,
+    topology::Topology,
+    update_algorithm::{FieldStorage, UpdateAlgorithm, UpdateDynamics},
+    weight_init::RandomWeightScaling,
+    weight_regularization::WeightRegularization,
+    HopfieldNetwork,
+};
 
 use super::network_domain::NetworkDomain;
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HopfieldNetworkBuilder {
     rand_matrix_init: bool,
+    random_weight_scaling: RandomWeightScaling,
+    rng_seed: u64,
     dimension: usize,
     force_symmetric: bool,
     force_zero_diagonal: bool,
+    clean_policy: CleanPolicy,
+    topology: Topology,
     domain: NetworkDomain,
-    maximum_relaxation_unstable_units: i32,
-    maximum_relaxation_iterations: i32,
+    maximum_relaxation_unstable_units: Option<i32>,
+    maximum_relaxation_iterations: Option<i32>,
+    maximum_in_flight_states: usize,
+    memory_budget_bytes: usize,
+    attractor_cache: bool,
+    fatigue_strength: f64,
+    fatigue_decay: f64,
+    activation_parameters: ActivationParameters,
+    activation_gain: Option<f64>,
+    duplicate_handling: DuplicateHandling,
+    palimpsest_decay: f64,
+    weight_regularization: WeightRegularization,
+    update_algorithm: UpdateAlgorithm,
+    update_dynamics: UpdateDynamics,
+    temperature: Option<f64>,
+    trajectory_recording: TrajectoryRecording,
+    field_storage: FieldStorage,
+    summation_order: SummationOrder,
 }
 
 #[allow(dead_code)]
@@ -24,17 +62,43 @@ impl HopfieldNetworkBuilder {
     pub fn new_hopfield_network_builder() -> Self {
         Self {
             rand_matrix_init: false,
+            random_weight_scaling: RandomWeightScaling::DomainCoupled,
+            rng_seed: 0,
             dimension: 0,
             force_symmetric: true,
             force_zero_diagonal: true,
+            clean_policy: CleanPolicy::OnEveryLearn,
+            topology: Topology::FullyConnected,
             domain: NetworkDomain::Unspecified,
-            maximum_relaxation_unstable_units: 0,
-            maximum_relaxation_iterations: 100,
+            maximum_relaxation_unstable_units: None,
+            maximum_relaxation_iterations: None,
+            maximum_in_flight_states: 0,
+            memory_budget_bytes: 0,
+            attractor_cache: false,
+            fatigue_strength: 0.0,
+            fatigue_decay: 0.0,
+            activation_parameters: ActivationParameters::default(),
+            activation_gain: None,
+            duplicate_handling: DuplicateHandling {
+                policy: DuplicatePolicy::Allow,
+                overlap_threshold: 1.0,
+            },
+            palimpsest_decay: 0.0,
+            weight_regularization: WeightRegularization {
+                decay: 0.0,
+                clip: None,
+            },
+            update_algorithm: UpdateAlgorithm::Auto,
+            update_dynamics: UpdateDynamics::Asynchronous,
+            temperature: None,
+            trajectory_recording: TrajectoryRecording::Disabled,
+            field_storage: FieldStorage::Auto,
+            summation_order: SummationOrder::Native,
         }
     }
 
     /// Set the randMatrixInit flag in the builder. If true, the new network will have a weight matrix.
-    /// initialized with random standard Gaussian values. If false (default) the matrix will have a zero weight matrix.
+    /// initialized with random Gaussian values, scaled as set by set_random_weight_scaling. If false (default) the matrix will have a zero weight matrix.
     ///
     /// # Arguments
     ///
@@ -44,6 +108,36 @@ impl HopfieldNetworkBuilder {
         self
     }
 
+    /// Set how the random weights of randMatrixInit are scaled with the dimension and domain of the network.
+    /// Unscaled weights give local fields that grow with the dimension and saturate every activation immediately.
+    ///
+    /// Defaults to RandomWeightScaling::DomainCoupled, giving random states local fields of unit variance.
+    /// A StandardDeviation must be strictly positive, and a SherringtonKirkpatrick standard deviation non-negative.
+    ///
+    /// # Arguments
+    ///
+    /// * `random_weight_scaling` - the scaling rule of the random weights.
+    pub fn set_random_weight_scaling(
+        mut self: Self,
+        random_weight_scaling: RandomWeightScaling,
+    ) -> Self {
+        self.random_weight_scaling = random_weight_scaling;
+        self
+    }
+
+    /// Set the random seed of the network. This seed is used for the random matrix initialization (if set)
+    /// and to choose the order units are updated in during relaxation.
+    ///
+    /// If the seed is left at the default value (0) then a random seed is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng_seed` - the seed for the random number generator of the network.
+    pub fn set_rng_seed(mut self: Self, rng_seed: u64) -> Self {
+        self.rng_seed = rng_seed;
+        self
+    }
+
     /// Set the dimension of the HopfieldNetwork - i.e. the dimension of the square matrix.
     ///
     /// # Arguments
@@ -64,6 +158,7 @@ impl HopfieldNetworkBuilder {
     ///
     /// * `force_symmetric_flag` - a boolean flag to set the networks weight matrix behavior
     ///     with respect to having a symmetric matrix.
+    #[allow(clippy::doc_overindented_list_items)]
     pub fn set_force_symmetrix(mut self: Self, force_symmetric_flag: bool) -> Self {
         self.force_symmetric = force_symmetric_flag;
         self
@@ -77,11 +172,40 @@ impl HopfieldNetworkBuilder {
     ///
     /// * `force_zero_diagonal_flag` - a boolean flag to set the networks weight matrix behavior
     ///     with respect to having a zero values on the diagonal.
+    #[allow(clippy::doc_overindented_list_items)]
     pub fn set_zero_diagonal_flag(mut self: Self, force_zero_diagonal_flag: bool) -> Self {
         self.force_zero_diagonal = force_zero_diagonal_flag;
         self
     }
 
+    /// Set when the network cleans its matrix, forcing the zero diagonal and symmetry set above (see clean_matrix).
+    /// Cleaning after every learning step is a full pass over the matrix, which is expensive for huge networks.
+    /// With CleanPolicy::Manual or CleanPolicy::OnBuild the user calls clean_matrix when needed.
+    ///
+    /// Defaults to CleanPolicy::OnEveryLearn.
+    ///
+    /// # Arguments
+    ///
+    /// * `clean_policy` - when to clean the weight matrix.
+    pub fn set_clean_policy(mut self: Self, clean_policy: CleanPolicy) -> Self {
+        self.clean_policy = clean_policy;
+        self
+    }
+
+    /// Set the topology of the network, i.e. which pairs of units are coupled (see Topology).
+    ///
+    /// Weights between unconnected units are zero, and stay zero through learning.
+    ///
+    /// This value defaults to Topology::FullyConnected if not explicitly set.
+    ///
+    /// # Arguments
+    ///
+    /// * `topology` - the topology to build the network on.
+    pub fn set_topology(mut self: Self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
     /// Set the domain of the HopfieldNetwork - i.e. what numbers are allowed to exist in states.
     ///
     /// Valid options are taken from the NetworkDomain enum (Binary, Bipolar, Continuous).
@@ -93,6 +217,7 @@ impl HopfieldNetworkBuilder {
     ///
     /// * `domain` - a value from the NetworkDomain enum to set the networks domain.
     ///     This will in turn set the networks activation function and energy function.
+    #[allow(clippy::doc_overindented_list_items)]
     pub fn set_network_domain(mut self: Self, domain: NetworkDomain) -> Self {
         self.domain = domain;
         self
@@ -100,36 +225,332 @@ impl HopfieldNetworkBuilder {
 
     /// Set the maximum number of units that are allowed to be unstable for a state to be considered relaxed.
     ///
-    /// Defaults to 0 (state must be perfectly stable). Typically this value should be around 0.01 - 0.1 of the network dimension
+    /// Relaxation stops once fewer units than this are unstable, so 1 requires a perfectly stable state.
+    /// Defaults to the preset of the network domain (see DomainPreset), which is 1 for every domain.
+    /// Typically this value should be around 0.01 - 0.1 of the network dimension
     ///
     /// # Arguments
     ///
     /// * `maximum_relaxation_unstable_units` - an integer to set the number of states that are allowed to
     ///     be unstable (E>0) for a state to be considered stable overall.
+    #[allow(clippy::doc_overindented_list_items)]
     pub fn set_maximum_relaxation_unstable_units(
         mut self: Self,
         maximum_relaxation_unstable_units: i32,
     ) -> Self {
-        self.maximum_relaxation_unstable_units = maximum_relaxation_unstable_units;
+        self.maximum_relaxation_unstable_units = Some(maximum_relaxation_unstable_units);
         self
     }
 
     /// Set the maximum number iterations allowed to occur before erroring out from the relaxation.
     ///
-    /// Defaults to 100. This is typically a large enough value.
+    /// Defaults to the preset of the network domain (see DomainPreset): 100 for discrete domains and 200 for
+    /// continuous domains. This is typically a large enough value.
     ///
     /// # Arguments
     ///
     /// * `maximum_relaxation_iterations` - an integer to determine the number of iterations to undertake
     ///     before a state is considered unstable during relaxation.
+    #[allow(clippy::doc_overindented_list_items)]
     pub fn set_maximum_relaxation_iterations(
         mut self: Self,
         maximum_relaxation_iterations: i32,
     ) -> Self {
-        self.maximum_relaxation_iterations = maximum_relaxation_iterations;
+        self.maximum_relaxation_iterations = Some(maximum_relaxation_iterations);
+        self
+    }
+
+    /// Set the maximum number of states allowed to be in flight at once during a streaming relaxation.
+    ///
+    /// States count as in flight from when they are taken from the producer until they are given to the sink.
+    /// When the limit is reached the producer is blocked until states finish relaxing.
+    ///
+    /// Defaults to 0, meaning no limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `maximum_in_flight_states` - the maximum number of in flight states, or 0 for no limit.
+    pub fn set_maximum_in_flight_states(mut self: Self, maximum_in_flight_states: usize) -> Self {
+        self.maximum_in_flight_states = maximum_in_flight_states;
+        self
+    }
+
+    /// Set the memory budget (in bytes) for the states in flight during a streaming relaxation.
+    /// This is converted into a number of states from the network dimension, and applied exactly like
+    /// set_maximum_in_flight_states. If both are set, the stricter limit is used.
+    ///
+    /// Defaults to 0, meaning no limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_budget_bytes` - the memory budget of in flight states in bytes, or 0 for no limit.
+    pub fn set_memory_budget_bytes(mut self: Self, memory_budget_bytes: usize) -> Self {
+        self.memory_budget_bytes = memory_budget_bytes;
+        self
+    }
+
+    /// Set the attractor cache flag in the builder. If true, the network memoizes relaxation results by cue,
+    /// so duplicate or previously seen cues are not relaxed again. Hit rate statistics are available from the network.
+    ///
+    /// Defaults to false.
+    ///
+    /// # Arguments
+    ///
+    /// * `attractor_cache` - a boolean flag to enable the attractor cache.
+    pub fn set_attractor_cache(mut self: Self, attractor_cache: bool) -> Self {
+        self.attractor_cache = attractor_cache;
+        self
+    }
+
+    /// Set the threshold of the binary activation function in the builder. Units with a local field above the
+    /// threshold map to 1, and the rest to 0. Only used in the Binary domain. Sparse patterns need a positive
+    /// threshold to be retrievable, see learn_states_sparse.
+    ///
+    /// Defaults to 0.0.
+    ///
+    /// # Arguments
+    ///
+    /// * `binary_threshold` - the local field a unit must exceed to become active.
+    pub fn set_binary_threshold(mut self: Self, binary_threshold: f64) -> Self {
+        self.activation_parameters.binary_threshold = binary_threshold;
+        self
+    }
+
+    /// Set the threshold of the ternary activation function in the builder. Local fields within ±threshold
+    /// map a unit to 0, while larger fields map it to ±1. Only used in the Ternary domain.
+    ///
+    /// Defaults to 0.5. Must be non-negative.
+    ///
+    /// # Arguments
+    ///
+    /// * `ternary_threshold` - the half-width of the dead zone of the ternary activation.
+    pub fn set_ternary_threshold(mut self: Self, ternary_threshold: f64) -> Self {
+        self.activation_parameters.ternary_threshold = ternary_threshold;
         self
     }
 
+    /// Set the bounds of the clipping activation function in the builder. Local fields are clipped into
+    /// [lower_bound, upper_bound]. Only used in the BoundedContinuous domain.
+    ///
+    /// Defaults to [-1, 1]. The lower bound must be strictly less than the upper bound.
+    ///
+    /// # Arguments
+    ///
+    /// * `lower_bound` - the smallest value a unit can take.
+    /// * `upper_bound` - the largest value a unit can take.
+    pub fn set_continuous_bounds(mut self: Self, lower_bound: f64, upper_bound: f64) -> Self {
+        self.activation_parameters.continuous_lower_bound = lower_bound;
+        self.activation_parameters.continuous_upper_bound = upper_bound;
+        self
+    }
+
+    /// Set the gain of the continuous activation functions in the builder, the inverse of the activation temperature.
+    /// Fields are multiplied by the gain before being mapped, so a high gain gives near-binary, deterministic
+    /// retrieval. This is independent of any stochastic dynamics temperature.
+    /// Only used in the BoundedContinuous and Tanh domains, and can be overridden per relaxation with relax_state_with_gain.
+    ///
+    /// Defaults to the preset of the network domain (see DomainPreset): 4.0 for Tanh and 1.0 otherwise.
+    /// Must be strictly positive.
+    ///
+    /// # Arguments
+    ///
+    /// * `gain` - the activation gain.
+    pub fn set_activation_gain(mut self: Self, gain: f64) -> Self {
+        self.activation_gain = Some(gain);
+        self
+    }
+
+    /// Set the strength of unit fatigue in the builder. Fatigue accumulates while a unit stays active and is
+    /// subtracted from its local field, which destabilizes attractors and allows latching dynamics.
+    ///
+    /// Defaults to 0.0, no fatigue.
+    ///
+    /// # Arguments
+    ///
+    /// * `fatigue_strength` - the amount of adaptation added per sweep, relative to the unit state.
+    pub fn set_fatigue_strength(mut self: Self, fatigue_strength: f64) -> Self {
+        self.fatigue_strength = fatigue_strength;
+        self
+    }
+
+    /// Set the decay of unit fatigue in the builder, the fraction of adaptation lost every sweep.
+    ///
+    /// Defaults to 0.0. Must be in the range [0, 1].
+    ///
+    /// # Arguments
+    ///
+    /// * `fatigue_decay` - the decay rate of adaptation.
+    pub fn set_fatigue_decay(mut self: Self, fatigue_decay: f64) -> Self {
+        self.fatigue_decay = fatigue_decay;
+        self
+    }
+
+    /// Set how the network handles near-duplicate patterns when learning. Storing duplicates silently
+    /// skews capacity and basin measurements, so experiments may want to warn about or skip them.
+    ///
+    /// Defaults to DuplicatePolicy::Allow.
+    ///
+    /// # Arguments
+    ///
+    /// * `policy` - what to do with near-duplicate patterns.
+    /// * `overlap_threshold` - the normalized overlap at which two patterns are near-duplicates, in (0, 1].
+    pub fn set_duplicate_policy(
+        mut self: Self,
+        policy: DuplicatePolicy,
+        overlap_threshold: f64,
+    ) -> Self {
+        self.duplicate_handling = DuplicateHandling {
+            policy,
+            overlap_threshold,
+        };
+        self
+    }
+
+    /// Set the decay factor of palimpsest learning in the builder, the fraction of the existing weights forgotten
+    /// before each pattern is stored by learn_states_palimpsest.
+    ///
+    /// Defaults to 0.0, no forgetting, which is plain Hebbian learning. Must be in the range [0, 1).
+    ///
+    /// # Arguments
+    ///
+    /// * `palimpsest_decay` - the fraction of weights forgotten per stored pattern.
+    pub fn set_palimpsest_decay(mut self: Self, palimpsest_decay: f64) -> Self {
+        self.palimpsest_decay = palimpsest_decay;
+        self
+    }
+
+    /// Set the L2 weight decay λ in the builder. Before each pattern is learned, by any learning method,
+    /// the existing weights are scaled by (1 - λ), so the weights stay bounded over long online training runs.
+    ///
+    /// Defaults to 0.0, no decay. Must be in the range [0, 1).
+    ///
+    /// # Arguments
+    ///
+    /// * `weight_decay` - the fraction of the weights removed per learned pattern.
+    pub fn set_weight_decay(mut self: Self, weight_decay: f64) -> Self {
+        self.weight_regularization.decay = weight_decay;
+        self
+    }
+
+    /// Set the weight clipping bound in the builder. After learning, by any learning method, every weight is
+    /// clipped into [-clip, clip].
+    ///
+    /// Defaults to None, no clipping. Must be strictly positive if set.
+    ///
+    /// # Arguments
+    ///
+    /// * `weight_clip` - the largest magnitude any weight may have after learning, or None.
+    pub fn set_weight_clip(mut self: Self, weight_clip: Option<f64>) -> Self {
+        self.weight_regularization.clip = weight_clip;
+        self
+    }
+
+    /// Set the algorithm local fields are recalculated with while relaxing states.
+    ///
+    /// Defaults to UpdateAlgorithm::Auto, which chooses by the network dimension.
+    /// The fastest algorithm for a workload can also be found with auto_tune_update_algorithm on the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `update_algorithm` - the update algorithm to relax states with.
+    pub fn set_update_algorithm(mut self: Self, update_algorithm: UpdateAlgorithm) -> Self {
+        self.update_algorithm = update_algorithm;
+        self
+    }
+
+    /// Set whether units are updated one at a time (the Hopfield model) or all at once (the Little model) while
+    /// relaxing states.
+    ///
+    /// Defaults to UpdateDynamics::Asynchronous. Synchronous relaxation may end in a 2-cycle rather than a fixed point.
+    ///
+    /// # Arguments
+    ///
+    /// * `update_dynamics` - the dynamics to relax states with.
+    pub fn set_update_dynamics(mut self: Self, update_dynamics: UpdateDynamics) -> Self {
+        self.update_dynamics = update_dynamics;
+        self
+    }
+
+    /// Set the temperature of the unit updates. At a positive temperature units are sampled from their local fields
+    /// with Glauber dynamics instead of being set to their activations, see HopfieldNetwork::set_temperature.
+    ///
+    /// Defaults to the preset of the network domain (see DomainPreset), which is 0, deterministic dynamics, for every
+    /// domain. Must be non-negative, and zero unless the domain is Binary or Bipolar.
+    ///
+    /// # Arguments
+    ///
+    /// * `temperature` - the temperature T of the unit updates.
+    pub fn set_temperature(mut self: Self, temperature: f64) -> Self {
+        self.temperature = Some(temperature);
+        self
+    }
+
+    /// Set what relax_state records after every update sweep, see HopfieldNetwork::set_trajectory_recording.
+    ///
+    /// Defaults to TrajectoryRecording::Disabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `trajectory_recording` - what to record after every update sweep.
+    pub fn set_trajectory_recording(
+        mut self: Self,
+        trajectory_recording: TrajectoryRecording,
+    ) -> Self {
+        self.trajectory_recording = trajectory_recording;
+        self
+    }
+
+    /// Set how the weights are stored for calculating local fields.
+    ///
+    /// Defaults to FieldStorage::Auto, which uses the factorized Hebbian form while few patterns are stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_storage` - the weight storage to calculate local fields with.
+    pub fn set_field_storage(mut self: Self, field_storage: FieldStorage) -> Self {
+        self.field_storage = field_storage;
+        self
+    }
+
+    /// Set the order in which local fields and energies are summed.
+    ///
+    /// Defaults to SummationOrder::Native. Use SummationOrder::Pairwise for results that are bit-identical across
+    /// platforms and linear algebra backends, e.g. for published experiments, or SummationOrder::Compensated for
+    /// accurate energies in networks with more than ~10⁵ units.
+    ///
+    /// # Arguments
+    ///
+    /// * `summation_order` - the summation order of local fields and energies.
+    pub fn set_summation_order(mut self: Self, summation_order: SummationOrder) -> Self {
+        self.summation_order = summation_order;
+        self
+    }
+
+    /// Serialize the parameters of this builder to a JSON config, so the exact construction of a network
+    /// can be stored alongside results and repeated later with from_config.
+    ///
+    /// Note that if the rng seed is left at 0 the rebuilt network will use a different random seed.
+    ///
+    /// # Returns
+    ///
+    /// The config as a JSON `String`.
+    pub fn to_config(self: &Self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Create a builder from a JSON config created by to_config.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - the JSON config to parse.
+    ///
+    /// # Returns
+    ///
+    /// The builder, or an error if the config is not valid.
+    pub fn from_config(config: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(config)
+    }
+
     /// Build and return a new HopfieldNetwork using the parameters specified with builder methods.
     /// Note this consumes the builder.
     pub fn build(self: Self) -> HopfieldNetwork {
@@ -140,29 +561,135 @@ impl HopfieldNetworkBuilder {
         assert!(self.domain != NetworkDomain::Unspecified,
             "HopfieldNetworkBuilder encountered an error during build! Domain must be explicitly set to a valid network domain!");
 
-        let mut rng = StdRng::from_entropy();
-        let matrix = if self.rand_matrix_init {
-            DMatrix::<f64>::from_iterator(
-                self.dimension,
-                self.dimension,
-                (0..self.dimension * self.dimension).map(|_| {
-                    rng.sample::<f64, rand_distr::StandardNormal>(rand_distr::StandardNormal) % 1.
-                }),
-            )
+        assert!((0.0..=1.0).contains(&self.fatigue_decay),
+            "HopfieldNetworkBuilder encountered an error during build! Fatigue decay must be in the range [0, 1]!");
+
+        assert!(self.activation_parameters.ternary_threshold >= 0.0,
+            "HopfieldNetworkBuilder encountered an error during build! Ternary threshold must be non-negative!");
+
+        assert!(self.activation_parameters.continuous_lower_bound < self.activation_parameters.continuous_upper_bound,
+            "HopfieldNetworkBuilder encountered an error during build! Continuous lower bound must be strictly less than the upper bound!");
+
+        let preset = DomainPreset::for_domain(self.domain);
+        let maximum_relaxation_iterations = self
+            .maximum_relaxation_iterations
+            .unwrap_or(preset.maximum_relaxation_iterations);
+        let maximum_relaxation_unstable_units = self
+            .maximum_relaxation_unstable_units
+            .unwrap_or(preset.maximum_relaxation_unstable_units);
+        let activation_parameters = ActivationParameters {
+            gain: self.activation_gain.unwrap_or(preset.activation_gain),
+            ..self.activation_parameters
+        };
+        let temperature = self.temperature.unwrap_or(preset.temperature);
+
+        assert!(activation_parameters.gain > 0.0,
+            "HopfieldNetworkBuilder encountered an error during build! Activation gain must be strictly positive!");
+
+        assert!(self.duplicate_handling.overlap_threshold > 0.0 && self.duplicate_handling.overlap_threshold <= 1.0,
+            "HopfieldNetworkBuilder encountered an error during build! Duplicate overlap threshold must be in the range (0, 1]!");
+
+        assert!((0.0..1.0).contains(&self.palimpsest_decay),
+            "HopfieldNetworkBuilder encountered an error during build! Palimpsest decay must be in the range [0, 1)!");
+
+        assert!((0.0..1.0).contains(&self.weight_regularization.decay),
+            "HopfieldNetworkBuilder encountered an error during build! Weight decay must be in the range [0, 1)!");
+
+        assert!(self.weight_regularization.clip.is_none_or(|clip| clip > 0.0),
+            "HopfieldNetworkBuilder encountered an error during build! Weight clip must be strictly positive!");
+
+        if let RandomWeightScaling::StandardDeviation(standard_deviation) =
+            self.random_weight_scaling
+        {
+            assert!(standard_deviation > 0.0,
+                "HopfieldNetworkBuilder encountered an error during build! Random weight standard deviation must be strictly positive!");
+        }
+
+        if let RandomWeightScaling::SherringtonKirkpatrick {
+            coupling_standard_deviation,
+            ..
+        } = self.random_weight_scaling
+        {
+            assert!(coupling_standard_deviation >= 0.0,
+                "HopfieldNetworkBuilder encountered an error during build! Spin glass coupling standard deviation must be non-negative!");
+        }
+
+        assert!(glauber::is_valid_temperature(self.domain, temperature),
+            "HopfieldNetworkBuilder encountered an error during build! Temperature must be non-negative, and zero unless the domain is Binary or Bipolar!");
+
+        assert!(self.topology.is_valid_for(self.dimension),
+            "HopfieldNetworkBuilder encountered an error during build! Topology parameters are not valid for the network dimension!");
+
+        let mut rng = if self.rng_seed != 0 {
+            StdRng::seed_from_u64(self.rng_seed)
+        } else {
+            StdRng::from_entropy()
+        };
+        let connectivity = match self.topology {
+            Topology::FullyConnected => None,
+            topology => Some(topology.connectivity_matrix(self.dimension)),
+        };
+        let mut matrix = if self.rand_matrix_init {
+            self.random_weight_scaling
+                .random_matrix(self.dimension, self.domain, &mut rng)
         } else {
             DMatrix::<f64>::zeros(self.dimension, self.dimension)
         };
+        if let Some(connectivity) = &connectivity {
+            matrix.component_mul_assign(connectivity);
+        }
 
-        HopfieldNetwork {
+        let mut network = HopfieldNetwork {
             matrix,
+            stored_patterns: DMatrix::<f64>::zeros(self.dimension, 0),
+            // A zero matrix is the Hebbian matrix of no patterns, but a random matrix is not,
+            // and the factorized local fields do not account for a topology
+            hebbian_weights: !self.rand_matrix_init && connectivity.is_none(),
+            uncleaned_diagonal: false,
             rng,
             dimension: self.dimension,
             force_symmetric: self.force_symmetric,
+            sequence_weights: None,
             force_zero_diagonal: self.force_zero_diagonal,
+            clean_policy: self.clean_policy,
+            topology: self.topology,
+            connectivity,
             domain: self.domain,
             activation_fn: self.domain.activation_fn(),
-            maximum_relaxation_iterations: self.maximum_relaxation_iterations,
-            maximum_relaxation_unstable_units: self.maximum_relaxation_unstable_units,
+            activation_parameters,
+            maximum_relaxation_iterations,
+            maximum_relaxation_unstable_units,
+            maximum_in_flight_states: self.maximum_in_flight_states,
+            memory_budget_bytes: self.memory_budget_bytes,
+            tuned_threads: None,
+            external_input: None,
+            duplicate_handling: self.duplicate_handling,
+            palimpsest_decay: self.palimpsest_decay,
+            weight_regularization: self.weight_regularization,
+            weight_mask: None,
+            weight_delta_format: None,
+            update_algorithm: self.update_algorithm,
+            update_dynamics: self.update_dynamics,
+            temperature,
+            update_rule: None,
+            trajectory_recording: self.trajectory_recording,
+            field_storage: self.field_storage,
+            summation_order: self.summation_order,
+            verification_mode: None,
+            pattern_index: None,
+            fatigue: FatigueParameters {
+                strength: self.fatigue_strength,
+                decay: self.fatigue_decay,
+            },
+            attractor_cache: self
+                .attractor_cache
+                .then(|| AttractorCache::new_attractor_cache(self.domain)),
+            event_hooks: EventHookRegistry::new_event_hook_registry(),
+            relaxation_callbacks: RelaxationCallbackRegistry::new_relaxation_callback_registry(),
+        };
+        if self.clean_policy == CleanPolicy::OnBuild {
+            network.clean_matrix();
         }
+        network
     }
 }
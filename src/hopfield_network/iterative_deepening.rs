@@ -0,0 +1,124 @@
+use nalgebra::DVector;
+
+use super::HopfieldNetwork;
+
+/// A policy for relaxing a batch with iterative deepening: every state gets a small iteration budget first, and only
+/// states close to convergence are granted larger budgets, while hopeless states are abandoned early.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IterativeDeepeningPolicy {
+    /// The maximum number of relaxation iterations in the first round.
+    pub initial_iterations: usize,
+    /// The factor the iteration budget is multiplied by every round.
+    pub iteration_growth: usize,
+    /// The maximum number of rounds, including the first.
+    pub maximum_rounds: usize,
+    /// A state that has not converged after a round continues to the next round only if it has at most this many
+    /// unstable units, and is abandoned otherwise.
+    pub maximum_unstable_units_to_continue: usize,
+}
+
+/// The result of a batch relaxation with iterative deepening.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IterativeDeepeningReport {
+    /// The number of states relaxed in each round.
+    pub states_per_round: Vec<usize>,
+    /// The total number of update iterations (sweeps) performed over every state and round.
+    pub total_iterations: usize,
+    /// The indices of the states abandoned for having too many unstable units after a round.
+    pub abandoned_indices: Vec<usize>,
+    /// The indices of the states that were close to convergence but had still not converged after the final round.
+    pub unconverged_indices: Vec<usize>,
+}
+
+impl HopfieldNetwork {
+    /// Relax a collection of states concurrently with iterative deepening, see IterativeDeepeningPolicy.
+    ///
+    /// Each round continues relaxing the states still in play from where the previous round stopped, with the
+    /// iteration budget multiplied by the iteration growth. After each round, states that have converged are done,
+    /// states with at most maximum_unstable_units_to_continue unstable units go on to the next round, and the rest
+    /// are abandoned. In an overloaded network most states wander without converging, so abandoning them early
+    /// saves most of the sweeps a fixed large budget would spend on them.
+    ///
+    /// A state converges as in concurrent_relax_state_collection, with fewer unstable units than the maximum
+    /// set in the builder. External input and fatigue restart from the first sweep every round.
+    /// The attractor cache is not used.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_collection`: A collection of states to relax.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    /// * `policy`: The policy deciding the budgets and which states to abandon.
+    ///
+    /// # Returns
+    ///
+    /// The relaxed states in the original order, where abandoned and unconverged states are as far as they got,
+    /// and an IterativeDeepeningReport describing the rounds.
+    pub fn concurrent_relax_state_collection_deepening(
+        self: &mut Self,
+        state_collection: Vec<DVector<f64>>,
+        threads: Option<usize>,
+        policy: IterativeDeepeningPolicy,
+    ) -> (Vec<DVector<f64>>, IterativeDeepeningReport) {
+        assert!(
+            policy.initial_iterations > 0 && policy.iteration_growth > 0 && policy.maximum_rounds > 0,
+            "Iterative deepening needs a positive initial budget, iteration growth and number of rounds!"
+        );
+
+        let threads = threads.unwrap_or_else(|| self.default_thread_count());
+        let queue_capacity = self.in_flight_queue_capacity(threads);
+        let network_maximum_relaxation_iterations = self.maximum_relaxation_iterations;
+
+        let mut relaxed_states = state_collection;
+        let mut active_indices: Vec<usize> = (0..relaxed_states.len()).collect();
+        let mut report = IterativeDeepeningReport {
+            states_per_round: Vec::new(),
+            total_iterations: 0,
+            abandoned_indices: Vec::new(),
+            unconverged_indices: Vec::new(),
+        };
+        let mut budget = policy.initial_iterations;
+        for round in 0..policy.maximum_rounds {
+            if active_indices.is_empty() {
+                break;
+            }
+            report.states_per_round.push(active_indices.len());
+            self.maximum_relaxation_iterations = budget.min(i32::MAX as usize) as i32;
+            let final_round = round + 1 == policy.maximum_rounds;
+
+            let round_states: Vec<DVector<f64>> = active_indices
+                .iter()
+                .map(|index| relaxed_states[*index].clone())
+                .collect();
+            let mut continuing_indices = Vec::new();
+            self.concurrent_relax_state_stream_inner(
+                round_states.into_iter(),
+                threads,
+                queue_capacity,
+                None,
+                |round_index, state, converged, iterations, unstable_units| {
+                    let index = active_indices[round_index];
+                    relaxed_states[index] = state;
+                    report.total_iterations += iterations;
+                    if converged {
+                        return;
+                    }
+                    if unstable_units as usize > policy.maximum_unstable_units_to_continue {
+                        report.abandoned_indices.push(index);
+                    } else if final_round {
+                        report.unconverged_indices.push(index);
+                    } else {
+                        continuing_indices.push(index);
+                    }
+                },
+            );
+            continuing_indices.sort_unstable();
+            active_indices = continuing_indices;
+            budget = budget.saturating_mul(policy.iteration_growth);
+        }
+        self.maximum_relaxation_iterations = network_maximum_relaxation_iterations;
+
+        report.abandoned_indices.sort_unstable();
+        report.unconverged_indices.sort_unstable();
+        (relaxed_states, report)
+    }
+}
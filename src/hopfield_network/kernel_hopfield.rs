@@ -0,0 +1,230 @@
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+
+use super::NetworkDomain;
+
+/// The largest change in any unit between updates for relax to consider a state converged.
+const CONVERGENCE_TOLERANCE: f64 = 1e-9;
+
+/// The kernel function of a kernelized Hopfield network, comparing a stored pattern with a state.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Kernel {
+    /// k(x, y) = x · y / N. With the discrete update this is the classic Hebbian network (with self couplings).
+    Linear,
+    /// k(x, y) = (x · y / N + offset)^degree. Higher degrees favour the nearest pattern more sharply, raising the
+    /// capacity as in the polynomial network.
+    Polynomial { degree: u32, offset: f64 },
+    /// k(x, y) = exp(-|x - y|² / (2 ℓ²)) with length scale ℓ: a similarity that decays with distance and is never
+    /// negative, suited to continuous patterns.
+    RadialBasis { length_scale: f64 },
+}
+
+impl Kernel {
+    /// Evaluate the kernel between two vectors of the same dimension.
+    pub fn evaluate(self: &Self, x: &DVector<f64>, y: &DVector<f64>) -> f64 {
+        match *self {
+            Kernel::Linear => x.dot(y) / x.len() as f64,
+            Kernel::Polynomial { degree, offset } => {
+                (x.dot(y) / x.len() as f64 + offset).powi(degree as i32)
+            }
+            Kernel::RadialBasis { length_scale } => {
+                (-(x - y).norm_squared() / (2.0 * length_scale * length_scale)).exp()
+            }
+        }
+    }
+}
+
+/// A kernelized Hopfield network, whose update rule works on the stored patterns through a kernel function k rather
+/// than on an explicit weight matrix. The field of a state s is
+///
+/// h = Σ_μ k(ξ_μ, s) ξ_μ,
+///
+/// which for the linear kernel is the Hebbian local field W s. Storing the P patterns takes O(PN) memory against
+/// O(N²) for the weights, and each update O(PN) time, so this suits many units and few patterns, and kernels with no
+/// finite weight matrix.
+///
+/// Units are updated synchronously. In the Binary and Bipolar domains each unit takes the sign of its field (with
+/// Binary values mapped to bipolar values, 2ξ - 1, as in learn_states of HopfieldNetwork). In the Continuous and Tanh
+/// domains the state becomes the kernel weighted average of the patterns, h / Σ_μ |k(ξ_μ, s)|, so continuous patterns
+/// are retrieved exactly; the RBF kernel then behaves like mean shift over the stored patterns.
+#[derive(Debug, Clone)]
+pub struct KernelHopfieldNetwork {
+    dimension: usize,
+    domain: NetworkDomain,
+    kernel: Kernel,
+    /// The stored patterns, one per row, as bipolar values in the Binary domain.
+    patterns: DMatrix<f64>,
+}
+
+impl KernelHopfieldNetwork {
+    /// Create a new kernelized Hopfield network with no stored patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The dimension of the stored patterns.
+    /// * `domain`: The domain of the stored patterns. Must be Binary, Bipolar, Continuous, or Tanh.
+    /// * `kernel`: The kernel function.
+    pub fn new_kernel_hopfield_network(
+        dimension: usize,
+        domain: NetworkDomain,
+        kernel: Kernel,
+    ) -> Self {
+        assert!(
+            dimension > 0,
+            "Kernel Hopfield network dimension must be a positive integer!"
+        );
+        assert!(
+            matches!(
+                domain,
+                NetworkDomain::Binary
+                    | NetworkDomain::Bipolar
+                    | NetworkDomain::Continuous
+                    | NetworkDomain::Tanh
+            ),
+            "Kernel Hopfield network domain must be Binary, Bipolar, Continuous, or Tanh!"
+        );
+        match kernel {
+            Kernel::Polynomial { degree, .. } => assert!(
+                degree >= 1,
+                "Kernel Hopfield network polynomial degree must be at least 1!"
+            ),
+            Kernel::RadialBasis { length_scale } => assert!(
+                length_scale > 0.0,
+                "Kernel Hopfield network RBF length scale must be strictly positive!"
+            ),
+            Kernel::Linear => {}
+        }
+
+        Self {
+            dimension,
+            domain,
+            kernel,
+            patterns: DMatrix::<f64>::zeros(0, dimension),
+        }
+    }
+
+    /// Get the dimension of this network.
+    pub fn get_dimension(self: &Self) -> usize {
+        self.dimension
+    }
+
+    /// Get the domain of this network.
+    pub fn get_domain(self: &Self) -> NetworkDomain {
+        self.domain
+    }
+
+    /// Get the kernel function of this network.
+    pub fn get_kernel(self: &Self) -> Kernel {
+        self.kernel
+    }
+
+    /// Get the stored patterns, one per row. Binary patterns are held as bipolar values.
+    pub fn get_patterns(self: &Self) -> &DMatrix<f64> {
+        &self.patterns
+    }
+
+    /// Get the number of stored patterns.
+    pub fn pattern_count(self: &Self) -> usize {
+        self.patterns.nrows()
+    }
+
+    /// Map a state to the values the kernel is evaluated on: 2v - 1 in the Binary domain, and v otherwise.
+    fn signed_state(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        match self.domain {
+            NetworkDomain::Binary => state.map(|value| 2.0 * value - 1.0),
+            _ => state.clone(),
+        }
+    }
+
+    /// Store a collection of patterns, appending each as a row of the pattern matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    pub fn learn_states(self: &mut Self, patterns: &[DVector<f64>]) {
+        assert!(
+            patterns
+                .iter()
+                .all(|pattern| pattern.len() == self.dimension),
+            "Every pattern must have the same dimension as the network!"
+        );
+
+        let first_row = self.patterns.nrows();
+        self.patterns = self
+            .patterns
+            .clone()
+            .insert_rows(first_row, patterns.len(), 0.0);
+        for (offset, pattern) in patterns.iter().enumerate() {
+            self.patterns
+                .set_row(first_row + offset, &self.signed_state(pattern).transpose());
+        }
+    }
+
+    /// Get the kernel k(ξ_μ, s) between a state and every stored pattern.
+    ///
+    /// # Returns
+    ///
+    /// The kernel value of each stored pattern, in the order they were stored.
+    pub fn kernel_values(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        let signed_state = self.signed_state(state);
+        DVector::from_iterator(
+            self.pattern_count(),
+            self.patterns
+                .row_iter()
+                .map(|pattern| self.kernel.evaluate(&pattern.transpose(), &signed_state)),
+        )
+    }
+
+    /// Get the field of a state, h = Σ_μ k(ξ_μ, s) ξ_μ.
+    pub fn fields(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        self.patterns.tr_mul(&self.kernel_values(state))
+    }
+
+    /// Update every unit of a state once, see KernelHopfieldNetwork.
+    ///
+    /// # Returns
+    ///
+    /// The updated state. If no patterns are stored, or every kernel value is zero, the state is returned unchanged.
+    pub fn update_state(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        let kernel_values = self.kernel_values(state);
+        let total_weight = kernel_values.abs().sum();
+        if total_weight == 0.0 {
+            return state.clone();
+        }
+
+        let fields = self.patterns.tr_mul(&kernel_values);
+        match self.domain {
+            NetworkDomain::Continuous | NetworkDomain::Tanh => fields / total_weight,
+            NetworkDomain::Binary => fields.map(|field| if field > 0.0 { 1.0 } else { 0.0 }),
+            _ => fields.map(|field| if field > 0.0 { 1.0 } else { -1.0 }),
+        }
+    }
+
+    /// Update a state until it stops changing.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax.
+    /// * `maximum_iterations`: The maximum number of updates.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of updates performed.
+    pub fn relax_state(
+        self: &Self,
+        mut state: DVector<f64>,
+        maximum_iterations: usize,
+    ) -> (DVector<f64>, usize) {
+        let mut iterations = 0;
+        while iterations < maximum_iterations {
+            iterations += 1;
+            let next_state = self.update_state(&state);
+            let converged = (&next_state - &state).amax() <= CONVERGENCE_TOLERANCE;
+            state = next_state;
+            if converged {
+                break;
+            }
+        }
+        (state, iterations)
+    }
+}
@@ -0,0 +1,100 @@
+use nalgebra::DVector;
+
+use super::HopfieldNetwork;
+
+/// A single stay of the state near one attractor during latching dynamics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttractorVisit {
+    /// The index of the stored pattern the state was closest to, or None if no pattern passed the overlap threshold.
+    pub pattern_index: Option<usize>,
+    /// The sweep the visit started on.
+    pub entered_sweep: usize,
+    /// The number of sweeps the visit lasted.
+    pub duration: usize,
+    /// The highest overlap reached with the pattern during the visit.
+    pub peak_overlap: f64,
+}
+
+/// The attractor itinerary of a run of latching dynamics.
+#[derive(Debug, Clone)]
+pub struct LatchingItinerary {
+    pub visits: Vec<AttractorVisit>,
+    pub final_state: DVector<f64>,
+}
+
+impl LatchingItinerary {
+    /// Get the sequence of patterns visited, skipping the periods between attractors.
+    pub fn pattern_sequence(self: &Self) -> Vec<usize> {
+        self.visits
+            .iter()
+            .filter_map(|visit| visit.pattern_index)
+            .collect()
+    }
+}
+
+impl HopfieldNetwork {
+    /// Run latching dynamics: update a state for a fixed number of sweeps under unit fatigue, so that instead of
+    /// settling the state hops from attractor to attractor as each one is destabilized by adaptation.
+    ///
+    /// Fatigue is configured on the builder. With symmetric weights the hops are driven only by fatigue and noise,
+    /// while asymmetric sequence weights (with force_symmetric disabled) bias the order of the hops.
+    /// Any external input is also applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The initial state. Consumes the state.
+    /// * `sweeps`: The number of update sweeps to run for. Latching dynamics do not stop early.
+    /// * `minimum_overlap`: The overlap a stored pattern must reach for the state to count as visiting it.
+    ///
+    /// # Returns
+    ///
+    /// The itinerary of attractors visited, and the final state.
+    pub fn latching_dynamics(
+        self: &mut Self,
+        mut state: DVector<f64>,
+        sweeps: usize,
+        minimum_overlap: f64,
+    ) -> LatchingItinerary {
+        assert!(
+            !self.fatigue.is_disabled(),
+            "Latching dynamics requires unit fatigue! Set a fatigue strength on the HopfieldNetworkBuilder."
+        );
+
+        let mut adaptation = DVector::<f64>::zeros(self.dimension);
+        let mut visits: Vec<AttractorVisit> = Vec::new();
+
+        for sweep in 0..sweeps {
+            let input = self
+                .external_input
+                .as_ref()
+                .and_then(|external_input| external_input.at(sweep));
+
+            state = self.update_state_with_input(state, input.as_ref(), Some(&adaptation));
+            self.fatigue.update_adaptation(&mut adaptation, &state);
+
+            let (pattern_index, overlap) = match self.nearest_memories(&state, 1).first() {
+                Some((index, _, overlap)) if *overlap >= minimum_overlap => {
+                    (Some(*index), *overlap)
+                }
+                _ => (None, 0.0),
+            };
+            match visits.last_mut() {
+                Some(visit) if visit.pattern_index == pattern_index => {
+                    visit.duration += 1;
+                    visit.peak_overlap = visit.peak_overlap.max(overlap);
+                }
+                _ => visits.push(AttractorVisit {
+                    pattern_index,
+                    entered_sweep: sweep,
+                    duration: 1,
+                    peak_overlap: overlap,
+                }),
+            }
+        }
+
+        LatchingItinerary {
+            visits,
+            final_state: state,
+        }
+    }
+}
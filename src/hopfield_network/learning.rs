@@ -0,0 +1,479 @@
+use nalgebra::DVector;
+
+use super::{
+    learning_rule::{
+        HebbianRule, LearningRule, PalimpsestRule, ParallelHebbianRule, SparseCovarianceRule,
+        TrainingReport, WillshawRule,
+    },
+    network_domain::FIXED_POINT_STABILITY_TOLERANCE,
+    network_event::NetworkEvent,
+    summation::local_fields_in_order,
+    HopfieldNetwork, NetworkDomain,
+};
+
+impl HopfieldNetwork {
+    /// Store a collection of patterns in the network using the Hebbian rule, W += ξξᵀ / N for each pattern ξ.
+    ///
+    /// Binary patterns are mapped to bipolar values (2ξ - 1) before the outer product is taken, so that
+    /// inactive units contribute to the weights. The matrix is cleaned afterwards as set by the clean policy (see
+    /// set_clean_policy).
+    ///
+    /// Learning changes the weights, so the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    pub fn learn_states(self: &mut Self, patterns: &[DVector<f64>]) {
+        self.learn_states_with(patterns, &HebbianRule);
+    }
+
+    /// Store a collection of patterns using the Hebbian rule, with the outer products computed across several threads
+    /// (see ParallelHebbianRule). This is much faster for thousands of high-dimensional patterns, and for discrete
+    /// domains gives exactly the same weights as learn_states.
+    ///
+    /// Learning changes the weights, so the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    pub fn learn_states_parallel(
+        self: &mut Self,
+        patterns: &[DVector<f64>],
+        threads: Option<usize>,
+    ) {
+        let threads = threads.unwrap_or_else(|| self.default_thread_count());
+        self.learn_states_with(patterns, &ParallelHebbianRule { threads });
+    }
+
+    /// Store a collection of patterns in the network one at a time using palimpsest learning (see PalimpsestRule),
+    /// with the decay factor set in the builder. Existing weights decay before each pattern is stored, so old
+    /// memories fade instead of the network failing catastrophically past capacity.
+    ///
+    /// Learning changes the weights, so the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    pub fn learn_states_palimpsest(self: &mut Self, patterns: &[DVector<f64>]) {
+        self.learn_states_with(
+            patterns,
+            &PalimpsestRule {
+                decay: self.palimpsest_decay,
+            },
+        );
+    }
+
+    /// Store a collection of sparse binary patterns using the Tsodyks-Feigel'man covariance rule
+    /// (see SparseCovarianceRule), and set the binary activation threshold so that they are retrievable.
+    ///
+    /// The threshold is overwritten with the retrieval threshold of the rule, 1/2 - f, replacing any threshold
+    /// set in the builder. Learning changes the weights, so the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    /// * `coding_level`: The coding level f of the patterns, the expected fraction of active units, in (0, 1).
+    pub fn learn_states_sparse(self: &mut Self, patterns: &[DVector<f64>], coding_level: f64) {
+        assert!(
+            self.domain == NetworkDomain::Binary,
+            "Sparse learning is only defined in the Binary domain!"
+        );
+        assert!(
+            coding_level > 0.0 && coding_level < 1.0,
+            "Coding level must be in the range (0, 1)!"
+        );
+
+        let learning_rule = SparseCovarianceRule { coding_level };
+        self.learn_states_with(patterns, &learning_rule);
+        self.activation_parameters.binary_threshold = learning_rule.retrieval_threshold();
+    }
+
+    /// Store a collection of sparse binary patterns with the Willshaw rule (see WillshawRule), clipping every weight
+    /// to {0, 1}, and set the binary activation threshold so that they are retrievable.
+    ///
+    /// The threshold is overwritten with the retrieval threshold of the rule for patterns of K active units,
+    /// replacing any threshold set in the builder. Relaxation then recovers patterns from cues with K active units,
+    /// while willshaw_retrieve thresholds on the activity of each cue, as in the classic model, so it also completes
+    /// partial cues. The classic model keeps self couplings, so build the network with set_zero_diagonal_flag(false).
+    ///
+    /// Learning changes the weights, so the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    /// * `active_units`: The number of active units K in each pattern.
+    pub fn learn_states_willshaw(self: &mut Self, patterns: &[DVector<f64>], active_units: usize) {
+        assert!(
+            self.domain == NetworkDomain::Binary,
+            "Willshaw learning is only defined in the Binary domain!"
+        );
+        assert!(
+            active_units > 0,
+            "Willshaw patterns must have at least one active unit!"
+        );
+
+        let learning_rule = WillshawRule { active_units };
+        self.learn_states_with(patterns, &learning_rule);
+        self.activation_parameters.binary_threshold =
+            learning_rule.retrieval_threshold(self.force_zero_diagonal);
+    }
+
+    /// Retrieve a pattern from a binary cue in one step of classic Willshaw retrieval: each unit is active if it is
+    /// connected to every active unit of the cue, i.e. its local field reaches the number of active cue units.
+    ///
+    /// Any spurious units of the cue lower the field of every unit below the threshold, so this suits partial cues
+    /// (a subset of the active units of a stored pattern). Without self couplings an active cue unit receives one
+    /// less input, which is allowed for.
+    ///
+    /// # Arguments
+    ///
+    /// * `cue`: The binary cue.
+    ///
+    /// # Returns
+    ///
+    /// The retrieved binary state.
+    pub fn willshaw_retrieve(self: &Self, cue: &DVector<f64>) -> DVector<f64> {
+        let cue_activity = cue.iter().filter(|value| **value > 0.0).count() as f64;
+        let fields = self.local_fields(cue);
+        DVector::<f64>::from_fn(self.dimension, |unit_index, _| {
+            let self_input = if self.force_zero_diagonal && cue[unit_index] > 0.0 {
+                1.0
+            } else {
+                0.0
+            };
+            if fields[unit_index] + self_input >= cue_activity - 0.5 {
+                1.0
+            } else {
+                0.0
+            }
+        })
+    }
+
+    /// Store a collection of patterns in the network using any learning rule, including user provided rules.
+    ///
+    /// Near-duplicate patterns are first handled by the duplicate policy set in the builder.
+    /// Binary patterns are mapped to bipolar values (2ξ - 1) before learning, for every rule.
+    /// Any weight decay set in the builder is applied before the rule, once per pattern, and any weight clipping
+    /// after. The matrix is cleaned afterwards as set by the clean policy (see set_clean_policy), and the attractor cache
+    /// is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    /// * `learning_rule`: The rule to update the weights with.
+    pub fn learn_states_with(
+        self: &mut Self,
+        patterns: &[DVector<f64>],
+        learning_rule: &(impl LearningRule + ?Sized),
+    ) {
+        self.check_pattern_dimensions(patterns);
+        let patterns = &self.filter_duplicate_patterns(patterns);
+
+        let stored_learning_vectors: Vec<DVector<f64>> = if learning_rule.uses_stored_patterns() {
+            self.stored_patterns
+                .column_iter()
+                .map(|pattern| self.learning_vector(&pattern.into_owned()))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        let learning_vectors: Vec<DVector<f64>> = patterns
+            .iter()
+            .map(|pattern| self.learning_vector(pattern))
+            .collect();
+        let weights_before = self.weight_delta_snapshot();
+        self.decay_weights(patterns.len());
+        learning_rule.apply_with_stored_patterns(
+            &mut self.matrix,
+            &stored_learning_vectors,
+            &learning_vectors,
+        );
+        self.clean_matrix_after_learning();
+        self.clip_weights();
+
+        // Binary patterns are stored through their bipolar mapping, which the factorized
+        // local fields (built from the stored patterns themselves) do not account for
+        if !learning_rule.keeps_hebbian_weights() || self.domain == NetworkDomain::Binary {
+            self.hebbian_weights = false;
+        }
+        self.emit_weight_delta(weights_before, patterns.len());
+        self.record_stored_patterns(patterns);
+        self.verify_hebbian_weights();
+    }
+
+    /// Forget a stored pattern, subtracting its Hebbian contribution ξξᵀ / N from the weights and removing it from
+    /// the stored patterns. If the pattern was stored more than once, only the first copy is forgotten.
+    ///
+    /// This exactly undoes learn_states (and learn_states_parallel) for that pattern. Weights learned by other rules
+    /// only have the Hebbian contribution removed, which may not be how the pattern was stored.
+    /// The matrix is cleaned afterwards, and the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern`: The pattern to forget.
+    ///
+    /// # Returns
+    ///
+    /// The index the pattern was stored at, or None (leaving the network unchanged) if it is not stored.
+    pub fn forget_state(self: &mut Self, pattern: &DVector<f64>) -> Option<usize> {
+        let pattern_index = self
+            .stored_patterns
+            .column_iter()
+            .position(|stored_pattern| stored_pattern == *pattern)?;
+        self.forget_stored_pattern(pattern_index);
+        Some(pattern_index)
+    }
+
+    /// Forget the stored pattern at an index, see forget_state. Every later stored pattern moves down one index.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern_index`: The index of the pattern to forget, in the order patterns were stored.
+    ///
+    /// # Returns
+    ///
+    /// The forgotten pattern.
+    pub fn forget_stored_pattern(self: &mut Self, pattern_index: usize) -> DVector<f64> {
+        assert!(
+            pattern_index < self.stored_patterns.ncols(),
+            "Pattern index must be less than the number of stored patterns!"
+        );
+
+        let pattern = self.stored_patterns.column(pattern_index).into_owned();
+        let learning_vector = self.learning_vector(&pattern);
+        let weights_before = self.weight_delta_snapshot();
+        self.matrix.ger(
+            -1.0 / self.dimension as f64,
+            &learning_vector,
+            &learning_vector,
+            1.0,
+        );
+        self.clean_matrix_after_learning();
+
+        // Removing the pattern keeps Hebbian weights Hebbian, as the factorized fields forget it too
+        self.stored_patterns = self.stored_patterns.clone().remove_column(pattern_index);
+        self.rebuild_pattern_index();
+        self.emit_weight_delta(weights_before, 0);
+        self.event_hooks.emit(&NetworkEvent::PatternForgotten {
+            pattern_index,
+            pattern: &pattern,
+        });
+        self.clear_attractor_cache();
+        pattern
+    }
+
+    /// Store a collection of patterns with the Hebbian rule, each scaled by an importance weight,
+    /// W += c ξξᵀ / N for each pattern ξ with importance c. Patterns with larger importance get deeper and wider
+    /// basins, e.g. to model rehearsed or consolidated memories. An importance of 1 is the plain Hebbian rule.
+    ///
+    /// Binary patterns are mapped to bipolar values as in learn_states. The duplicate policy is not applied,
+    /// as storing a pattern again is how rehearsal is modelled. Weight decay and clipping are applied as in
+    /// learn_states_with. The matrix is cleaned afterwards, and the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `weighted_patterns`: The patterns to store, each with its importance. Every importance must be non-negative.
+    pub fn learn_states_weighted(self: &mut Self, weighted_patterns: &[(DVector<f64>, f64)]) {
+        let patterns = self.apply_weighted_hebbian(weighted_patterns, true);
+        self.record_stored_patterns(&patterns);
+    }
+
+    /// Strengthen the memories of patterns that are already stored, W += c ξξᵀ / N for each pattern ξ with
+    /// strength c, without storing the patterns again. This is learn_states_weighted for rehearsal: the stored
+    /// patterns are unchanged, and no PatternStored events are emitted.
+    ///
+    /// Weight decay is not applied, as no new patterns are stored. Clipping is applied, the matrix is cleaned
+    /// afterwards, and the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `weighted_patterns`: The patterns to strengthen, each with its strength. Every strength must be non-negative.
+    pub fn reinforce_states(self: &mut Self, weighted_patterns: &[(DVector<f64>, f64)]) {
+        self.apply_weighted_hebbian(weighted_patterns, false);
+        self.clear_attractor_cache();
+    }
+
+    /// Add the weighted Hebbian outer products of some patterns to the weights, see learn_states_weighted.
+    ///
+    /// # Arguments
+    ///
+    /// * `weighted_patterns`: The patterns, each with its importance.
+    /// * `new_patterns`: Whether the patterns are being stored, so weight decay is applied first and the weight delta
+    ///   is reported as learning them.
+    ///
+    /// # Returns
+    ///
+    /// The patterns without their importance.
+    fn apply_weighted_hebbian(
+        self: &mut Self,
+        weighted_patterns: &[(DVector<f64>, f64)],
+        new_patterns: bool,
+    ) -> Vec<DVector<f64>> {
+        let patterns: Vec<DVector<f64>> = weighted_patterns
+            .iter()
+            .map(|(pattern, _)| pattern.clone())
+            .collect();
+        self.check_pattern_dimensions(&patterns);
+        assert!(
+            weighted_patterns
+                .iter()
+                .all(|(_, importance)| *importance >= 0.0),
+            "Every pattern importance must be non-negative!"
+        );
+
+        let weights_before = self.weight_delta_snapshot();
+        if new_patterns {
+            self.decay_weights(patterns.len());
+        }
+        let scale = 1.0 / self.dimension as f64;
+        for (pattern, importance) in weighted_patterns {
+            let learning_vector = self.learning_vector(pattern);
+            self.matrix
+                .ger(scale * importance, &learning_vector, &learning_vector, 1.0);
+        }
+        self.clean_matrix_after_learning();
+        self.clip_weights();
+
+        // The weights are no longer the unweighted outer products of the stored patterns
+        self.hebbian_weights = false;
+        self.emit_weight_delta(
+            weights_before,
+            if new_patterns { patterns.len() } else { 0 },
+        );
+        patterns
+    }
+
+    /// Store a collection of patterns by iterative delta rule (perceptron style) training.
+    ///
+    /// Every epoch each pattern is presented in turn. Each unit whose activation from the local field of the pattern
+    /// differs from its target value is nudged towards it, ΔW_ij = η t_i ξ_j / N, where t is the bipolar target
+    /// (see learn_states) and ξ the pattern. If force_symmetric is set the update is symmetrized. Training stops when
+    /// every pattern is a fixed point, or after the maximum number of epochs.
+    ///
+    /// Training starts from the current weights, so this can also refine weights from another rule.
+    /// Any weight decay set in the builder is applied before every presentation of a pattern,
+    /// and any weight clipping after every update. The matrix is cleaned after every update as set by the clean
+    /// policy (see set_clean_policy).
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    /// * `learning_rate`: The learning rate η.
+    /// * `maximum_epochs`: The maximum number of epochs to train for.
+    ///
+    /// # Returns
+    ///
+    /// A TrainingReport with the epochs used and the final stability of each pattern.
+    pub fn learn_states_delta_rule(
+        self: &mut Self,
+        patterns: &[DVector<f64>],
+        learning_rate: f64,
+        maximum_epochs: usize,
+    ) -> TrainingReport {
+        self.check_pattern_dimensions(patterns);
+
+        let weights_before = self.weight_delta_snapshot();
+        let scale = learning_rate / self.dimension as f64;
+        let mut epochs = 0;
+        let mut unstable_units = self.delta_rule_errors(patterns);
+        while epochs < maximum_epochs && unstable_units.iter().any(|count| *count > 0) {
+            epochs += 1;
+            for pattern in patterns {
+                self.decay_weights(1);
+                let target = self.learning_vector(pattern);
+                let next_pattern = (self.activation_fn)(
+                    local_fields_in_order(&self.matrix, pattern, self.summation_order),
+                    &self.activation_parameters,
+                );
+                let error = DVector::<f64>::from_fn(self.dimension, |unit, _| {
+                    if (next_pattern[unit] - pattern[unit]).abs() > FIXED_POINT_STABILITY_TOLERANCE
+                    {
+                        target[unit]
+                    } else {
+                        0.0
+                    }
+                });
+
+                if self.force_symmetric {
+                    self.matrix.ger(0.5 * scale, &error, pattern, 1.0);
+                    self.matrix.ger(0.5 * scale, pattern, &error, 1.0);
+                } else {
+                    self.matrix.ger(scale, &error, pattern, 1.0);
+                }
+                self.clean_matrix_after_learning();
+                self.clip_weights();
+            }
+            unstable_units = self.delta_rule_errors(patterns);
+        }
+
+        self.hebbian_weights = false;
+        self.emit_weight_delta(weights_before, patterns.len());
+        self.record_stored_patterns(patterns);
+        TrainingReport {
+            epochs,
+            unstable_units,
+        }
+    }
+
+    /// Count the units of each pattern that are not fixed by one update from the current weights.
+    fn delta_rule_errors(self: &Self, patterns: &[DVector<f64>]) -> Vec<usize> {
+        patterns
+            .iter()
+            .map(|pattern| {
+                (self.activation_fn)(
+                    local_fields_in_order(&self.matrix, pattern, self.summation_order),
+                    &self.activation_parameters,
+                )
+                .iter()
+                .zip(pattern.iter())
+                .filter(|(next_value, value)| {
+                    (*next_value - *value).abs() > FIXED_POINT_STABILITY_TOLERANCE
+                })
+                .count()
+            })
+            .collect()
+    }
+
+    /// Assert that every pattern has the same dimension as the network.
+    pub(super) fn check_pattern_dimensions(self: &Self, patterns: &[DVector<f64>]) {
+        assert!(
+            patterns
+                .iter()
+                .all(|pattern| pattern.len() == self.dimension),
+            "Every pattern must have the same dimension as the network!"
+        );
+    }
+
+    /// Get the vector a pattern contributes to the weights through: the bipolar mapping of a Binary pattern,
+    /// or the pattern itself in every other domain.
+    pub(super) fn learning_vector(self: &Self, pattern: &DVector<f64>) -> DVector<f64> {
+        match self.domain {
+            NetworkDomain::Binary => pattern.map(|value| 2.0 * value - 1.0),
+            _ => pattern.clone(),
+        }
+    }
+
+    /// Record newly learned patterns: append them to the stored patterns (and the pattern index, if set),
+    /// emit a PatternStored event for each, and clear the now stale attractor cache.
+    pub(super) fn record_stored_patterns(self: &mut Self, patterns: &[DVector<f64>]) {
+        let first_index = self.stored_patterns.ncols();
+        self.stored_patterns =
+            self.stored_patterns
+                .clone()
+                .insert_columns(first_index, patterns.len(), 0.0);
+        for (offset, pattern) in patterns.iter().enumerate() {
+            self.stored_patterns
+                .set_column(first_index + offset, pattern);
+            if let Some(pattern_index) = &mut self.pattern_index {
+                pattern_index.insert(first_index + offset, pattern);
+            }
+            self.event_hooks.emit(&NetworkEvent::PatternStored {
+                pattern_index: first_index + offset,
+                pattern,
+            });
+        }
+
+        self.clear_attractor_cache();
+    }
+}
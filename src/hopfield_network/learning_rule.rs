@@ -0,0 +1,390 @@
+use nalgebra::{DMatrix, DVector};
+
+/// Singular values below this are treated as zero when taking the pseudo-inverse of the pattern matrix.
+const PSEUDO_INVERSE_EPSILON: f64 = 1e-10;
+
+/// A rule for storing patterns in the weights of a network, used with HopfieldNetwork::learn_states_with.
+///
+/// Implement this to add a new learning rule without touching the network internals. The network takes care of
+/// mapping Binary patterns to bipolar values beforehand, and of cleaning the matrix, recording the stored patterns,
+/// and clearing the attractor cache afterwards.
+pub trait LearningRule {
+    /// Update a weight matrix to store some patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `matrix`: The weight matrix to update.
+    /// * `patterns`: The patterns to store, in bipolar form for Binary networks.
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]);
+
+    /// Update a weight matrix to store some patterns, with access to the patterns already stored.
+    ///
+    /// This is the method the network calls. The default ignores the stored patterns and calls apply,
+    /// so only rules that compute weights from every stored pattern need to override it
+    /// (along with uses_stored_patterns).
+    ///
+    /// # Arguments
+    ///
+    /// * `matrix`: The weight matrix to update.
+    /// * `stored_patterns`: The patterns already stored, in bipolar form for Binary networks.
+    ///     Empty unless uses_stored_patterns returns true.
+    /// * `patterns`: The new patterns to store, in bipolar form for Binary networks.
+    #[allow(clippy::doc_overindented_list_items)]
+    fn apply_with_stored_patterns(
+        &self,
+        matrix: &mut DMatrix<f64>,
+        _stored_patterns: &[DVector<f64>],
+        patterns: &[DVector<f64>],
+    ) {
+        self.apply(matrix, patterns)
+    }
+
+    /// Whether this rule needs the patterns already stored in apply_with_stored_patterns.
+    /// Defaults to false, which saves gathering the stored patterns on every call.
+    fn uses_stored_patterns(&self) -> bool {
+        false
+    }
+
+    /// Whether the weights remain exactly the sum of outer products of the stored patterns, W = ΞΞᵀ / N,
+    /// which lets the network compute local fields from the patterns instead of the matrix.
+    /// Defaults to false, which is always safe.
+    fn keeps_hebbian_weights(&self) -> bool {
+        false
+    }
+}
+
+/// The outer product rule, W += ξξᵀ / N.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HebbianRule;
+
+impl LearningRule for HebbianRule {
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]) {
+        if patterns.is_empty() {
+            return;
+        }
+
+        // The outer products are summed before scaling, which is exact for discrete patterns,
+        // so ParallelHebbianRule gives bit-identical weights
+        let scale = 1.0 / matrix.nrows() as f64;
+        *matrix += outer_product_sum(patterns) * scale;
+    }
+
+    fn keeps_hebbian_weights(&self) -> bool {
+        true
+    }
+}
+
+/// The Hebbian rule computed across several threads, for storing large pattern sets. The patterns are split into
+/// contiguous chunks, each thread sums the outer products of one chunk, and the partial sums are added in order.
+///
+/// For discrete domains every partial sum is exact, so the weights are bit-identical to HebbianRule.
+/// For continuous domains they agree up to floating point rounding.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParallelHebbianRule {
+    /// The number of threads to split the patterns across.
+    pub threads: usize,
+}
+
+impl LearningRule for ParallelHebbianRule {
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]) {
+        if patterns.is_empty() {
+            return;
+        }
+
+        let chunk_size = patterns.len().div_ceil(self.threads.max(1));
+        let partial_sums: Vec<DMatrix<f64>> = crossbeam::scope(|scope| {
+            let handles: Vec<_> = patterns
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move |_| outer_product_sum(chunk)))
+                .collect();
+            handles
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .collect()
+        })
+        .unwrap();
+
+        let mut outer_products = DMatrix::<f64>::zeros(matrix.nrows(), matrix.ncols());
+        for partial_sum in partial_sums {
+            outer_products += partial_sum;
+        }
+        let scale = 1.0 / matrix.nrows() as f64;
+        *matrix += outer_products * scale;
+    }
+
+    fn keeps_hebbian_weights(&self) -> bool {
+        true
+    }
+}
+
+/// Sum the outer products ξξᵀ of a non-empty collection of patterns, computed as a single product ΞΞᵀ.
+fn outer_product_sum(patterns: &[DVector<f64>]) -> DMatrix<f64> {
+    let pattern_matrix = DMatrix::from_columns(patterns);
+    &pattern_matrix * pattern_matrix.transpose()
+}
+
+/// The Storkey rule, which corrects each update by the local fields of the existing weights.
+/// This gives a higher capacity and fewer spurious minima than the Hebbian rule, at O(N²) cost per pattern:
+///
+/// Δw_ij = (ξ_i ξ_j - ξ_i h_ji - h_ij ξ_j) / N, where h_ij = Σ_{k≠i,j} w_ik ξ_k
+///
+/// Patterns are learned one at a time, each against the weights left by the previous patterns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StorkeyRule;
+
+impl LearningRule for StorkeyRule {
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]) {
+        let dimension = matrix.nrows();
+        let scale = 1.0 / dimension as f64;
+        for pattern in patterns {
+            let fields = &*matrix * pattern;
+            // h_ij for every pair, from the full local field of unit i less the terms of units i and j
+            let partial_fields = DMatrix::<f64>::from_fn(dimension, dimension, |i, j| {
+                fields[i] - matrix[(i, i)] * pattern[i] - matrix[(i, j)] * pattern[j]
+            });
+
+            let update = DMatrix::<f64>::from_fn(dimension, dimension, |i, j| {
+                pattern[i] * pattern[j]
+                    - pattern[i] * partial_fields[(j, i)]
+                    - partial_fields[(i, j)] * pattern[j]
+            });
+            *matrix += update * scale;
+        }
+    }
+}
+
+/// The projection rule, W = Ξ Ξ⁺ for the matrix Ξ of all stored patterns, computed with the pseudo-inverse.
+/// Every linearly independent pattern is a fixed point, so correlated patterns can be stored reliably.
+/// The weights are recomputed from every stored pattern, replacing any weights from other rules.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PseudoInverseRule;
+
+impl LearningRule for PseudoInverseRule {
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]) {
+        if patterns.is_empty() {
+            return;
+        }
+
+        let pattern_matrix = DMatrix::from_columns(patterns);
+        let pseudo_inverse = pattern_matrix
+            .clone()
+            .pseudo_inverse(PSEUDO_INVERSE_EPSILON)
+            .unwrap();
+        *matrix = pattern_matrix * pseudo_inverse;
+    }
+
+    fn apply_with_stored_patterns(
+        &self,
+        matrix: &mut DMatrix<f64>,
+        stored_patterns: &[DVector<f64>],
+        patterns: &[DVector<f64>],
+    ) {
+        if patterns.is_empty() {
+            return;
+        }
+        self.apply(matrix, &[stored_patterns, patterns].concat());
+    }
+
+    fn uses_stored_patterns(&self) -> bool {
+        true
+    }
+}
+
+/// Hebbian learning with Oja's normalization, Δw_ij = η ξ_i (ξ_j - ξ_i w_ij). Each row of weights decays
+/// as it learns, so weights stay bounded however many patterns are stored, with recent patterns weighted most.
+/// This suits online learning, with patterns stored one at a time as they arrive.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OjaRule {
+    pub learning_rate: f64,
+}
+
+impl LearningRule for OjaRule {
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]) {
+        for pattern in patterns {
+            for (row_index, mut row) in matrix.row_iter_mut().enumerate() {
+                row *= 1.0 - self.learning_rate * pattern[row_index].powi(2);
+            }
+            matrix.ger(self.learning_rate, pattern, pattern, 1.0);
+        }
+    }
+}
+
+/// Palimpsest learning, the Hebbian rule with forgetting: before each pattern is stored the existing weights
+/// decay by a constant factor, W ← (1 - λ)W + ξξᵀ / N. Weights stay bounded, so storing patterns past capacity
+/// gracefully forgets the oldest memories instead of catastrophically corrupting every memory.
+/// Roughly the last 1/λ patterns remain retrievable, provided that is within the capacity of the network.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PalimpsestRule {
+    /// The decay factor λ, the fraction of the existing weights forgotten per stored pattern.
+    pub decay: f64,
+}
+
+impl LearningRule for PalimpsestRule {
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]) {
+        let scale = 1.0 / matrix.nrows() as f64;
+        for pattern in patterns {
+            matrix.ger(scale, pattern, pattern, 1.0 - self.decay);
+        }
+    }
+}
+
+/// The Tsodyks-Feigel'man covariance rule for sparse binary patterns with coding level (fraction of active units)
+/// f « 0.5, W += (ξ - f)(ξ - f)ᵀ / (N f (1 - f)).
+///
+/// The Hebbian rule stores the bipolar mapping of binary patterns, which for sparse patterns is dominated by the
+/// shared inactive units, so every pattern looks alike and capacity collapses. Centering on the coding level instead
+/// gives a capacity that grows as f falls. Retrieval also needs a threshold: the local field of a pattern unit is
+/// about 1 - f if active and -f if inactive, so units should activate above the midpoint (see retrieval_threshold).
+///
+/// Only meaningful in the Binary domain. The rule maps the bipolar learning vectors it is given back to 0/1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SparseCovarianceRule {
+    /// The coding level f of the patterns, the expected fraction of active units. Must be in (0, 1).
+    pub coding_level: f64,
+}
+
+impl SparseCovarianceRule {
+    /// Get the binary activation threshold that best separates the active and inactive units of a stored pattern,
+    /// 1/2 - f.
+    pub fn retrieval_threshold(self: &Self) -> f64 {
+        0.5 - self.coding_level
+    }
+}
+
+impl LearningRule for SparseCovarianceRule {
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]) {
+        let coding_level = self.coding_level;
+        let scale = 1.0 / (matrix.nrows() as f64 * coding_level * (1.0 - coding_level));
+        for pattern in patterns {
+            let centered_pattern = pattern.map(|value| (value + 1.0) / 2.0 - coding_level);
+            matrix.ger(scale, &centered_pattern, &centered_pattern, 1.0);
+        }
+    }
+}
+
+/// The Willshaw rule for sparse binary patterns, storing each pattern by switching on every weight between two of
+/// its active units, W_ij = min(1, W_ij + ξ_i ξ_j). Every weight is clipped to {0, 1}, so the weights only record
+/// whether two units were ever active together.
+///
+/// With K active units per pattern, an active unit of a stored pattern receives a local field of K (K - 1 without
+/// self couplings), and an inactive unit less unless spurious weights connect it to every active unit.
+/// Retrieval needs a threshold between the two (see retrieval_threshold).
+///
+/// Only meaningful in the Binary domain. The rule maps the bipolar learning vectors it is given back to 0/1.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WillshawRule {
+    /// The number of active units K in each pattern.
+    pub active_units: usize,
+}
+
+impl WillshawRule {
+    /// Get the binary activation threshold that separates the active and inactive units of a stored pattern,
+    /// just below the local field of an active unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `zero_diagonal`: Whether the network has no self couplings, so an active unit does not count itself.
+    pub fn retrieval_threshold(self: &Self, zero_diagonal: bool) -> f64 {
+        let active_field = if zero_diagonal {
+            self.active_units as f64 - 1.0
+        } else {
+            self.active_units as f64
+        };
+        active_field - 0.5
+    }
+}
+
+impl LearningRule for WillshawRule {
+    fn apply(&self, matrix: &mut DMatrix<f64>, patterns: &[DVector<f64>]) {
+        for pattern in patterns {
+            let active_units: Vec<usize> = pattern
+                .iter()
+                .enumerate()
+                .filter(|(_, value)| **value > 0.0)
+                .map(|(unit_index, _)| unit_index)
+                .collect();
+            for row in &active_units {
+                for column in &active_units {
+                    matrix[(*row, *column)] = 1.0;
+                }
+            }
+        }
+        matrix.apply(|weight| *weight = if *weight > 0.0 { 1.0 } else { 0.0 });
+    }
+}
+
+/// The result of iterative delta rule training.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingReport {
+    /// The number of epochs (presentations of every pattern) used.
+    pub epochs: usize,
+    /// The number of unstable units of each pattern after training, in the order the patterns were given.
+    pub unstable_units: Vec<usize>,
+}
+
+impl TrainingReport {
+    /// Check if every pattern is a fixed point of the trained network.
+    pub fn all_stable(self: &Self) -> bool {
+        self.unstable_units.iter().all(|count| *count == 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hopfield_network::{
+        state_generator::StateGeneratorBuilder, HopfieldNetworkBuilder, NetworkDomain,
+    };
+
+    /// Learn the same patterns sequentially and with every thread count, returning each weight matrix.
+    fn learned_matrices(domain: NetworkDomain) -> (DMatrix<f64>, Vec<DMatrix<f64>>) {
+        let mut state_generator = StateGeneratorBuilder::new_state_generator_builder()
+            .set_domain(domain)
+            .set_dimension(64)
+            .set_generator_seed(7)
+            .build();
+        let patterns = state_generator.create_state_collection(100);
+        let build = || {
+            HopfieldNetworkBuilder::new_hopfield_network_builder()
+                .set_network_dimension(64)
+                .set_network_domain(domain)
+                .build()
+        };
+
+        let mut network = build();
+        network.learn_states(&patterns);
+        let parallel_matrices = [1, 2, 3, 8, 200]
+            .into_iter()
+            .map(|threads| {
+                let mut network = build();
+                network.learn_states_parallel(&patterns, Some(threads));
+                network.get_matrix().clone()
+            })
+            .collect();
+        (network.get_matrix().clone(), parallel_matrices)
+    }
+
+    #[test]
+    fn parallel_hebbian_matches_sequential_for_discrete_domains() {
+        for domain in [
+            NetworkDomain::Binary,
+            NetworkDomain::Bipolar,
+            NetworkDomain::Ternary,
+        ] {
+            let (matrix, parallel_matrices) = learned_matrices(domain);
+            for parallel_matrix in parallel_matrices {
+                assert_eq!(parallel_matrix, matrix, "{domain:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_hebbian_matches_sequential_for_continuous_domains() {
+        for domain in [NetworkDomain::BoundedContinuous, NetworkDomain::Tanh] {
+            let (matrix, parallel_matrices) = learned_matrices(domain);
+            for parallel_matrix in parallel_matrices {
+                assert!((parallel_matrix - &matrix).amax() < 1e-12, "{domain:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,243 @@
+use super::{
+    energy_function,
+    summation::{self, SummationOrder},
+    weight_mask::WeightMask,
+};
+use nalgebra::{DMatrix, DVector};
+
+/// Defines how the local fields (W * state) of a network are calculated.
+///
+/// A Hebbian weight matrix W = Ξ Ξᵀ / N (where the columns of Ξ are the stored patterns) has rank at most P,
+/// so while P is small compared to N it is much faster to calculate the local fields as Ξ (Ξᵀ s) / N
+/// than to multiply by the dense matrix.
+#[derive(Debug, Clone, Copy)]
+pub enum LocalFieldOperator<'a> {
+    /// Multiply by the dense weight matrix.
+    Dense(&'a DMatrix<f64>),
+    /// Multiply by the stored patterns, removing the self-coupling terms if the diagonal is forced to zero.
+    Factorized {
+        patterns: &'a DMatrix<f64>,
+        zero_diagonal: bool,
+    },
+    /// Multiply by the dense weight matrix with a connectivity mask applied.
+    Masked {
+        matrix: &'a DMatrix<f64>,
+        mask: &'a WeightMask,
+    },
+    /// Multiply by the dense weight matrix, with an optional connectivity mask, summing every local field and energy
+    /// in a fixed order (see SummationOrder). The summation order is never Native.
+    FixedOrder {
+        matrix: &'a DMatrix<f64>,
+        mask: Option<&'a WeightMask>,
+        summation_order: SummationOrder,
+    },
+    /// Another operator with a global inhibition term subtracted from every local field, h_i - γ(a_i - f), where a_i
+    /// is the fraction of the other units that are active, f the target coding level and γ the strength. See
+    /// ActivityControl::GlobalInhibition.
+    GlobalInhibition {
+        operator: &'a LocalFieldOperator<'a>,
+        coding_level: f64,
+        strength: f64,
+    },
+}
+
+/// Get the number of other units each unit is inhibited by, see LocalFieldOperator::GlobalInhibition.
+fn other_units(dimension: usize) -> f64 {
+    // The inhibition of a unit counts only the other units, so no unit inhibits itself
+    (dimension.max(2) - 1) as f64
+}
+
+impl LocalFieldOperator<'_> {
+    /// Calculate the local field of every unit in a state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to calculate the local fields of.
+    ///
+    /// # Returns
+    ///
+    /// A DVector of `f64` holding the local field of each unit.
+    pub fn local_fields(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        match *self {
+            Self::Dense(matrix) => matrix * state,
+            Self::Factorized {
+                patterns,
+                zero_diagonal,
+            } => {
+                let dimension = patterns.nrows() as f64;
+                let mut fields = patterns * patterns.tr_mul(state) / dimension;
+                if zero_diagonal {
+                    // The diagonal of Ξ Ξᵀ is the sum of squares of each row of Ξ
+                    let diagonal = patterns.map(|value| value * value).column_sum();
+                    fields -= diagonal.component_mul(state) / dimension;
+                }
+                fields
+            }
+            Self::Masked { matrix, mask } => mask.masked_local_fields(matrix, state),
+            Self::FixedOrder {
+                matrix,
+                mask,
+                summation_order,
+            } => summation::fixed_order_local_fields(matrix, state, mask, summation_order),
+            Self::GlobalInhibition {
+                operator,
+                coding_level,
+                strength,
+            } => {
+                let other_units = other_units(state.len());
+                let active_units = state.sum();
+                let mut fields = operator.local_fields(state);
+                for (field, value) in fields.iter_mut().zip(state.iter()) {
+                    *field -= strength * ((active_units - value) / other_units - coding_level);
+                }
+                fields
+            }
+        }
+    }
+
+    /// Calculate the local field of a single unit in a state, without calculating every field.
+    ///
+    /// The terms of the field are summed directly, so the field may differ from local_fields by rounding error,
+    /// except for FixedOrder operators where both sum in the same order.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to calculate the local field of.
+    /// * `unit_index`: The unit to calculate the local field of.
+    ///
+    /// # Returns
+    ///
+    /// The local field of the unit.
+    pub fn local_field(self: &Self, state: &DVector<f64>, unit_index: usize) -> f64 {
+        match *self {
+            Self::Dense(matrix) => summation::fixed_order_local_field(
+                matrix,
+                state,
+                None,
+                SummationOrder::Native,
+                unit_index,
+            ),
+            Self::Factorized {
+                patterns,
+                zero_diagonal,
+            } => {
+                let dimension = patterns.nrows() as f64;
+                let pattern_values = patterns.row(unit_index).transpose();
+                let mut field = pattern_values.dot(&patterns.tr_mul(state)) / dimension;
+                if zero_diagonal {
+                    field -= pattern_values.norm_squared() * state[unit_index] / dimension;
+                }
+                field
+            }
+            Self::Masked { matrix, mask } => summation::fixed_order_local_field(
+                matrix,
+                state,
+                Some(mask),
+                SummationOrder::Native,
+                unit_index,
+            ),
+            Self::FixedOrder {
+                matrix,
+                mask,
+                summation_order,
+            } => {
+                summation::fixed_order_local_field(matrix, state, mask, summation_order, unit_index)
+            }
+            Self::GlobalInhibition {
+                operator,
+                coding_level,
+                strength,
+            } => {
+                let active_units = state.sum() - state[unit_index];
+                operator.local_field(state, unit_index)
+                    - strength * (active_units / other_units(state.len()) - coding_level)
+            }
+        }
+    }
+
+    /// Update the local fields of a state after a single unit changes, without recalculating every field.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields`: The local fields of the state before the change, updated in place.
+    /// * `unit_index`: The unit that changed.
+    /// * `delta`: The change in the value of the unit.
+    pub fn add_unit_change(self: &Self, fields: &mut DVector<f64>, unit_index: usize, delta: f64) {
+        match *self {
+            Self::Dense(matrix) => fields.axpy(delta, &matrix.column(unit_index), 1.0),
+            Self::Factorized {
+                patterns,
+                zero_diagonal,
+            } => {
+                let dimension = patterns.nrows() as f64;
+                let pattern_values = patterns.row(unit_index).transpose();
+                fields.gemv(delta / dimension, patterns, &pattern_values, 1.0);
+                if zero_diagonal {
+                    fields[unit_index] -= delta * pattern_values.norm_squared() / dimension;
+                }
+            }
+            Self::Masked { matrix, mask } => {
+                mask.add_masked_unit_change(matrix, fields, unit_index, delta)
+            }
+            // Each field changes by a single product, so the result does not depend on any summation order
+            Self::FixedOrder { matrix, mask, .. } => match mask {
+                Some(mask) => mask.add_masked_unit_change(matrix, fields, unit_index, delta),
+                None => fields.axpy(delta, &matrix.column(unit_index), 1.0),
+            },
+            // The change inhibits every other unit equally
+            Self::GlobalInhibition {
+                operator, strength, ..
+            } => {
+                operator.add_unit_change(fields, unit_index, delta);
+                let inhibition = strength * delta / other_units(fields.len());
+                fields.add_scalar_mut(-inhibition);
+                fields[unit_index] += inhibition;
+            }
+        }
+    }
+
+    /// Get the energy of all the units in a given state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The vector to calculate the energy of.
+    ///
+    /// # Returns
+    ///
+    /// A DVector of `f64` representing the energies of each unit in the state.
+    pub fn all_unit_energies(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        match *self {
+            Self::Dense(matrix) => energy_function::all_unit_energies(matrix, state),
+            Self::Factorized { .. }
+            | Self::Masked { .. }
+            | Self::FixedOrder { .. }
+            | Self::GlobalInhibition { .. } => {
+                self.local_fields(state).scale(-1.0).component_mul(state)
+            }
+        }
+    }
+
+    /// Get the energy of a given state - the entire state, all at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The vector to calculate the energy of.
+    ///
+    /// # Returns
+    ///
+    /// An `f64` representing the overall energy of the given state.
+    pub fn state_energy(self: &Self, state: &DVector<f64>) -> f64 {
+        match *self {
+            Self::Dense(matrix) => energy_function::state_energy_function(matrix, state),
+            Self::Factorized { .. } | Self::Masked { .. } | Self::GlobalInhibition { .. } => {
+                -self.local_fields(state).dot(state)
+            }
+            Self::FixedOrder {
+                summation_order, ..
+            } => -summation::sum_in_order(
+                self.local_fields(state).component_mul(state).as_slice(),
+                summation_order,
+            ),
+        }
+    }
+}
@@ -1,36 +1,162 @@
 #![allow(dead_code)]
 
 pub mod activation_function;
+pub mod adaptation;
+pub mod anomaly_detector;
+pub mod attractor_cache;
+pub mod attractor_counter;
+pub mod bidirectional_associative_memory;
+pub mod boltzmann_machine;
+pub mod clean_policy;
+#[cfg(feature = "image")]
+pub mod contact_sheet;
+pub mod continuous_dynamics;
+pub mod convergence_monitor;
+pub mod curvature;
+pub mod dense_retrieval;
+pub mod domain_preset;
+pub mod duplicate_policy;
+pub mod edge_list;
+pub mod energy_decomposition;
+pub mod exhaustive_scan;
+pub mod experiment;
+pub mod external_input;
+pub mod first_passage;
+pub mod glauber;
+pub mod gradient;
+pub mod hidden_unit_hopfield;
+pub mod iterative_deepening;
+pub mod kernel_hopfield;
+pub mod latching;
+pub mod learning_rule;
+pub mod modern_hopfield;
+pub mod modular_network;
+pub mod network_event;
+pub mod noise_channel;
+pub mod pattern_index;
+pub mod phasor_hopfield;
+pub mod pipeline;
+pub mod polynomial_hopfield;
+pub mod potts_network;
+pub mod precision;
+pub mod preprocessing;
+pub mod reference;
+pub mod relaxation_result;
+#[cfg(feature = "websocket")]
+pub mod relaxation_stream;
+pub mod restricted_boltzmann_machine;
+pub mod results_table;
+pub mod sequence_memory;
+pub mod service_metrics;
+pub mod sparse_activity;
+pub mod sparse_cue;
+pub mod spin_glass;
 pub mod state_generator;
+pub mod summation;
+pub mod topology;
+pub mod unlearning;
+pub mod update_algorithm;
+pub mod update_rule;
+pub mod weight_block;
+pub mod weight_delta;
+pub mod weight_init;
+pub mod weight_mask;
+pub mod weight_regularization;
 
 mod energy_function;
 mod hopfield_network_builder;
+mod learning;
+mod local_field;
 mod network_domain;
 
 pub use hopfield_network_builder::HopfieldNetworkBuilder;
 pub use network_domain::NetworkDomain;
 
 use {
-    activation_function::ActivationFunction,
+    activation_function::{ActivationFunction, ActivationParameters},
+    adaptation::FatigueParameters,
+    attractor_cache::{AttractorCache, AttractorCacheStatistics},
+    attractor_counter::AttractorCounter,
+    clean_policy::CleanPolicy,
+    convergence_monitor::{
+        ConvergenceMonitor, ConvergenceReport, RetryPolicy, RetryReport, RollingConvergence,
+    },
+    duplicate_policy::DuplicateHandling,
+    external_input::ExternalInput,
+    local_field::LocalFieldOperator,
     nalgebra::{DMatrix, DVector},
+    network_event::{EventHookRegistry, NetworkEvent, RelaxationCallbackRegistry},
+    pattern_index::PatternIndex,
     rand::{rngs::StdRng, seq::SliceRandom, RngCore, SeedableRng},
+    reference::VerificationMode,
+    relaxation_result::{RelaxationResult, TrajectoryRecording},
+    sparse_activity::ActivityControl,
     std::{
         fmt,
-        sync::mpsc::{self, Sender},
+        ops::ControlFlow,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc::{self, Sender},
+            Arc,
+        },
+        time::{Duration, Instant},
     },
+    summation::SummationOrder,
+    topology::Topology,
+    update_algorithm::{FieldStorage, UpdateAlgorithm, UpdateDynamics},
+    update_rule::UpdateRule,
+    weight_delta::WeightDeltaFormat,
+    weight_mask::WeightMask,
+    weight_regularization::WeightRegularization,
 };
 
+/// The smallest number of units per thread for the default thread count heuristic.
+/// Below this, the overhead of a thread outweighs the work it does.
+const MINIMUM_UNITS_PER_THREAD: usize = 16;
+
 #[derive(Debug)]
 pub struct HopfieldNetwork {
     matrix: DMatrix<f64>,
+    stored_patterns: DMatrix<f64>,
+    hebbian_weights: bool,
+    /// Whether learning left a nonzero diagonal that force_zero_diagonal will remove at the next clean_matrix, see
+    /// CleanPolicy.
+    uncleaned_diagonal: bool,
     rng: StdRng,
     dimension: usize,
     force_symmetric: bool,
+    sequence_weights: Option<DMatrix<f64>>,
     force_zero_diagonal: bool,
+    clean_policy: CleanPolicy,
+    topology: Topology,
+    connectivity: Option<DMatrix<f64>>,
     domain: NetworkDomain,
     activation_fn: ActivationFunction,
+    activation_parameters: ActivationParameters,
     maximum_relaxation_iterations: i32,
     maximum_relaxation_unstable_units: i32,
+    maximum_in_flight_states: usize,
+    memory_budget_bytes: usize,
+    tuned_threads: Option<usize>,
+    external_input: Option<ExternalInput>,
+    fatigue: FatigueParameters,
+    duplicate_handling: DuplicateHandling,
+    palimpsest_decay: f64,
+    weight_regularization: WeightRegularization,
+    weight_mask: Option<WeightMask>,
+    weight_delta_format: Option<WeightDeltaFormat>,
+    update_algorithm: UpdateAlgorithm,
+    update_dynamics: UpdateDynamics,
+    temperature: f64,
+    update_rule: Option<Arc<dyn UpdateRule>>,
+    trajectory_recording: TrajectoryRecording,
+    field_storage: FieldStorage,
+    summation_order: SummationOrder,
+    verification_mode: Option<VerificationMode>,
+    pattern_index: Option<PatternIndex>,
+    attractor_cache: Option<AttractorCache>,
+    event_hooks: EventHookRegistry,
+    relaxation_callbacks: RelaxationCallbackRegistry,
 }
 
 impl fmt::Display for HopfieldNetwork {
@@ -40,12 +166,14 @@ impl fmt::Display for HopfieldNetwork {
             "HopfieldNetwork
 \tDimension: {}
 \tDomain: {:?}
+\tStored Patterns: {}
 \tForce Symmetric: {}
 \tForce Zero Diagonal: {}
 \tMaximum Relaxation Iterations: {}
 \tMaximum Relaxation Unstable Units: {}",
             self.dimension,
             self.domain,
+            self.stored_patterns.ncols(),
             self.force_symmetric,
             self.force_zero_diagonal,
             self.maximum_relaxation_iterations,
@@ -55,19 +183,202 @@ impl fmt::Display for HopfieldNetwork {
 }
 
 impl HopfieldNetwork {
+    /// Returns the dimension of this network.
+    ///
+    /// # Returns
+    ///
+    /// The dimension of this network as a `usize`.
+    pub fn get_dimension(self: &Self) -> usize {
+        self.dimension
+    }
+
+    /// Returns the domain of this network.
+    ///
+    /// # Returns
+    ///
+    /// The domain of this network as a `NetworkDomain`.
+    pub fn get_domain(self: &Self) -> NetworkDomain {
+        self.domain
+    }
+
+    /// Returns the weight matrix of this network.
+    ///
+    /// # Returns
+    ///
+    /// A reference to the weight matrix as a `DMatrix<f64>`.
+    pub fn get_matrix(self: &Self) -> &DMatrix<f64> {
+        &self.matrix
+    }
+
+    /// Returns the patterns stored in this network, in the order they were stored.
+    ///
+    /// # Returns
+    ///
+    /// A reference to a `DMatrix<f64>` with one stored pattern per column.
+    pub fn get_stored_patterns(self: &Self) -> &DMatrix<f64> {
+        &self.stored_patterns
+    }
+
+    /// Register a hook to be called with every lifecycle event of this network.
+    ///
+    /// Hooks are called in the order they are registered.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook`: The function to call with each event.
+    pub fn add_event_hook(self: &mut Self, hook: impl FnMut(&NetworkEvent) + Send + 'static) {
+        self.event_hooks.add_hook(Box::new(hook));
+    }
+
+    /// Register a callback to be called after every update sweep of relax_state, with the number of sweeps so far, the
+    /// state, and its energy, e.g. to log or visualize relaxation. A callback returning ControlFlow::Break stops the
+    /// relaxation early, and the RelaxationResult is marked as aborted.
+    ///
+    /// Callbacks are called in the order they are registered. The energy is only calculated while callbacks are
+    /// registered. Cached attractors and concurrent relaxations do not call the callbacks.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback`: The function to call after every sweep.
+    pub fn add_relaxation_callback(
+        self: &mut Self,
+        callback: impl FnMut(usize, &DVector<f64>, f64) -> ControlFlow<()> + Send + 'static,
+    ) {
+        self.relaxation_callbacks.add_callback(Box::new(callback));
+    }
+
+    /// Remove every relaxation callback, see add_relaxation_callback.
+    pub fn clear_relaxation_callbacks(self: &mut Self) {
+        self.relaxation_callbacks = RelaxationCallbackRegistry::new_relaxation_callback_registry();
+    }
+
+    /// Set the time-varying external input applied during relaxation, or None to remove it.
+    ///
+    /// The input is added to the local fields of every unit during every relaxation, serial or concurrent,
+    /// with time measured in update sweeps from the start of each relaxation. Units count as unstable
+    /// relative to the combined local field and input.
+    ///
+    /// # Arguments
+    ///
+    /// * `external_input`: The input signal to drive the network with.
+    pub fn set_external_input(self: &mut Self, external_input: Option<ExternalInput>) {
+        self.external_input = external_input;
+    }
+
+    /// Set the gain of the continuous activation functions, the inverse of the activation temperature.
+    /// This applies to all following relaxations, serial or concurrent. See relax_state_with_gain to
+    /// change the gain for a single relaxation.
+    ///
+    /// # Arguments
+    ///
+    /// * `gain`: The new activation gain. Must be strictly positive.
+    pub fn set_activation_gain(self: &mut Self, gain: f64) {
+        assert!(gain > 0.0, "Activation gain must be strictly positive!");
+        self.activation_parameters.gain = gain;
+    }
+
+    /// Get the statistics of the attractor cache, if the cache is enabled.
+    ///
+    /// # Returns
+    ///
+    /// The hits, misses and number of entries of the cache, or None if the cache was not enabled in the builder.
+    pub fn attractor_cache_statistics(self: &Self) -> Option<AttractorCacheStatistics> {
+        self.attractor_cache
+            .as_ref()
+            .map(|cache| cache.statistics())
+    }
+
+    /// Remove every attractor from the attractor cache, if the cache is enabled.
+    ///
+    /// Cached attractors are only valid for the weights they were relaxed with. Learning clears the cache
+    /// automatically, so this only needs to be called after changing the weights in some other way.
+    pub fn clear_attractor_cache(self: &mut Self) {
+        if let Some(cache) = self.attractor_cache.as_mut() {
+            cache.clear();
+        }
+    }
+
+    /// Get the operator used to calculate local fields in this network.
+    ///
+    /// While the weight matrix is exactly the Hebbian matrix of the stored patterns and fewer than half as many
+    /// patterns are stored as there are units, the factorized form is cheaper and is chosen automatically.
+    /// Otherwise the dense matrix is used. The choice can be overridden with set_field_storage on the builder,
+    /// though the factorized form is only ever used for Hebbian weights. While a weight mask is applied (see with_weight_mask),
+    /// the dense matrix is always used with the mask.
+    fn local_field_operator(self: &Self) -> LocalFieldOperator<'_> {
+        if self.summation_order != SummationOrder::Native {
+            LocalFieldOperator::FixedOrder {
+                matrix: &self.matrix,
+                mask: self.weight_mask.as_ref(),
+                summation_order: self.summation_order,
+            }
+        } else if let Some(mask) = &self.weight_mask {
+            LocalFieldOperator::Masked {
+                matrix: &self.matrix,
+                mask,
+            }
+        } else if self.hebbian_weights
+            && !self.uncleaned_diagonal
+            && match self.field_storage {
+                FieldStorage::Auto => 2 * self.stored_patterns.ncols() < self.dimension,
+                FieldStorage::Dense => false,
+                FieldStorage::Factorized => true,
+            }
+        {
+            LocalFieldOperator::Factorized {
+                patterns: &self.stored_patterns,
+                zero_diagonal: self.force_zero_diagonal,
+            }
+        } else {
+            LocalFieldOperator::Dense(&self.matrix)
+        }
+    }
+
+    /// Get the local field of every unit in a state, i.e. the weighted sum of inputs to each unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The vector to calculate the local fields of.
+    ///
+    /// # Returns
+    ///
+    /// A DVector of `f64` holding the local field of each unit.
+    pub fn local_fields(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        self.local_field_operator().local_fields(state)
+    }
+
     /// Clean the matrix according to the parameters specified in the builder.
     ///
-    /// If force_zero_diagonal is set, the main diagonal of the matrix is set to 0.0
+    /// If force_zero_diagonal is set, the main diagonal of the matrix (and of any sequence weights) is set to 0.0
     ///
     /// If force_symmetric is set, the lower triangle of this matrix is filled with the upper triangle.
+    /// Symmetry is forced per component: any asymmetric sequence weights (see learn_sequence) are kept as learned,
+    /// and only the rest of the matrix is made symmetric.
+    ///
+    /// Every weight between units that are not connected in the topology of the network is set to 0.0.
+    ///
+    /// Learning cleans the matrix automatically only with CleanPolicy::OnEveryLearn, see set_clean_policy.
     pub fn clean_matrix(self: &mut Self) {
         if self.force_zero_diagonal {
             self.matrix.fill_diagonal(0.);
+            if let Some(sequence_weights) = &mut self.sequence_weights {
+                sequence_weights.fill_diagonal(0.);
+            }
         }
+        self.uncleaned_diagonal = false;
 
         if self.force_symmetric {
-            self.matrix.fill_lower_triangle_with_upper_triangle();
+            match &self.sequence_weights {
+                Some(sequence_weights) => {
+                    let mut symmetric_weights = &self.matrix - sequence_weights;
+                    symmetric_weights.fill_lower_triangle_with_upper_triangle();
+                    self.matrix = symmetric_weights + sequence_weights;
+                }
+                None => self.matrix.fill_lower_triangle_with_upper_triangle(),
+            }
         }
+
+        self.apply_connectivity();
     }
 
     /// Create an return an array of integers that contains every unit index once.
@@ -92,7 +403,7 @@ impl HopfieldNetwork {
     ///
     /// An `f64` representing the overall energy of the given state in this network.
     pub fn state_energy(self: &Self, state: &DVector<f64>) -> f64 {
-        energy_function::state_energy_function(&self.matrix, state)
+        self.local_field_operator().state_energy(state)
     }
 
     /// Get the energy of a single unit in a state.
@@ -106,7 +417,12 @@ impl HopfieldNetwork {
     ///
     /// An `f64` representing the energy of the single unit in question.
     pub fn unit_energy(self: &Self, state: &DVector<f64>, unit_index: usize) -> f64 {
-        energy_function::unit_energy_function(&self.matrix, state, unit_index)
+        match self.local_field_operator() {
+            LocalFieldOperator::Dense(matrix) => {
+                energy_function::unit_energy_function(matrix, state, unit_index)
+            }
+            operator => operator.all_unit_energies(state)[unit_index],
+        }
     }
 
     /// Get the energy of all the units in a given state
@@ -119,7 +435,121 @@ impl HopfieldNetwork {
     ///
     /// A DVector of `f64` representing the energies of each unit in the state.
     pub fn all_unit_energies(self: &Self, state: &DVector<f64>) -> DVector<f64> {
-        energy_function::all_unit_energies(&self.matrix, state)
+        self.local_field_operator().all_unit_energies(state)
+    }
+
+    /// Count the units of a state that would change if updated, from the local fields alone (without any external
+    /// input or fatigue). A state with no unstable units is a fixed point of the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to check.
+    pub fn count_unstable_units(self: &Self, state: &DVector<f64>) -> usize {
+        self.domain.count_unstable_units(
+            &self.local_fields(state),
+            state,
+            &self.activation_parameters,
+        ) as usize
+    }
+
+    /// Get the overlap of a state with every stored pattern.
+    ///
+    /// The overlaps are calculated all at once as a single product of the state against the stored pattern matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The vector to calculate the overlaps of.
+    ///
+    /// # Returns
+    ///
+    /// A DVector of `f64` where each entry is the dot product of a stored pattern with the state,
+    /// normalized by the network dimension.
+    pub fn pattern_overlaps(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        self.stored_patterns.tr_mul(state) / self.dimension as f64
+    }
+
+    /// Get the overlap of every state in a collection with every stored pattern.
+    ///
+    /// The overlaps are calculated all at once as a single product of the stored pattern matrix against the matrix of
+    /// states, which is much faster than calling pattern_overlaps for each state of a large collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The vectors to calculate the overlaps of.
+    ///
+    /// # Returns
+    ///
+    /// A DMatrix of `f64` with one row per stored pattern and one column per state, where each entry is the dot
+    /// product of the pattern with the state, normalized by the network dimension.
+    pub fn overlaps_matrix(self: &Self, states: &[DVector<f64>]) -> DMatrix<f64> {
+        if states.is_empty() {
+            return DMatrix::zeros(self.stored_patterns.ncols(), 0);
+        }
+
+        self.stored_patterns.transpose() * DMatrix::from_columns(states) / self.dimension as f64
+    }
+
+    /// Get the largest overlap of a state with any stored pattern, using the pattern index if one is set
+    /// (see nearest_memories).
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The vector to compare against the stored patterns.
+    ///
+    /// # Returns
+    ///
+    /// The largest overlap, or 0 if no patterns are stored.
+    pub fn maximum_overlap(self: &Self, state: &DVector<f64>) -> f64 {
+        self.nearest_memories(state, 1)
+            .first()
+            .map_or(0.0, |(_, _, overlap)| *overlap)
+    }
+
+    /// Find the k stored patterns with the highest overlap with a given state.
+    ///
+    /// This is cheap enough to use both before relaxation (to see what a cue looks like)
+    /// and after relaxation (to see what memory the state was recalled to).
+    ///
+    /// If a pattern index is set (see set_pattern_index), overlaps are only computed for its candidate patterns,
+    /// unless it finds fewer than k candidates.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The vector to compare against the stored patterns.
+    /// * `k`: The number of stored patterns to return. If fewer than k patterns are stored, all are returned.
+    ///
+    /// # Returns
+    ///
+    /// A vector of `(pattern_index, pattern, overlap)` tuples, ordered from highest to lowest overlap.
+    pub fn nearest_memories(
+        self: &Self,
+        state: &DVector<f64>,
+        k: usize,
+    ) -> Vec<(usize, DVector<f64>, f64)> {
+        let mut overlaps: Vec<(usize, f64)> = match self.candidate_patterns(state, k) {
+            Some(candidates) => candidates
+                .into_iter()
+                .map(|index| {
+                    (
+                        index,
+                        self.stored_patterns.column(index).dot(state) / self.dimension as f64,
+                    )
+                })
+                .collect(),
+            None => self
+                .pattern_overlaps(state)
+                .iter()
+                .copied()
+                .enumerate()
+                .collect(),
+        };
+        overlaps.sort_unstable_by(|a, b| b.1.total_cmp(&a.1));
+
+        overlaps
+            .into_iter()
+            .take(k)
+            .map(|(index, overlap)| (index, self.stored_patterns.column(index).into(), overlap))
+            .collect()
     }
 
     /// Update a given state once, randomly permuting units.
@@ -134,50 +564,285 @@ impl HopfieldNetwork {
     ///
     /// The newly updated state after all units have been updated once. The memory of the returned state
     /// is the same as the passed state.
-    pub fn update_state(self: &mut Self, mut state: DVector<f64>) -> DVector<f64> {
-        let mut unit_indices = self.get_unit_indices();
-        unit_indices.shuffle(&mut self.rng);
+    pub fn update_state(self: &mut Self, state: DVector<f64>) -> DVector<f64> {
+        self.update_state_with_input(state, None, None)
+    }
 
-        for unit_index in unit_indices {
-            let next_state = (self.activation_fn)(&self.matrix * &state);
-            state[(unit_index, 0)] = next_state[(unit_index, 0)];
-        }
+    /// Update a given state once, randomly permuting units, with an external input added to the local fields
+    /// and unit adaptation subtracted from them.
+    fn update_state_with_input(
+        self: &mut Self,
+        mut state: DVector<f64>,
+        input: Option<&DVector<f64>>,
+        adaptation: Option<&DVector<f64>>,
+    ) -> DVector<f64> {
+        let update_rule = self.get_update_rule();
+        // The sweep draws from its own generator, as the relaxation dynamics borrow the network
+        let mut rng = StdRng::seed_from_u64(self.rng.next_u64());
+        let mut unit_indices = self.get_unit_indices();
+        unit_indices.shuffle(&mut rng);
 
+        self.relaxation_dynamics(update_rule.as_ref()).sweep(
+            &unit_indices,
+            &mut state,
+            input,
+            adaptation,
+            &mut rng,
+        );
         state
     }
 
-    /// Update a given state until it is stable.
+    /// Get the settings of this network that relaxation needs, with a given update rule.
+    fn relaxation_dynamics<'a>(
+        self: &'a Self,
+        update_rule: &'a dyn UpdateRule,
+    ) -> RelaxationDynamics<'a> {
+        RelaxationDynamics {
+            local_field_operator: self.local_field_operator(),
+            external_input: self.external_input.as_ref(),
+            fatigue: self.fatigue,
+            domain: self.domain,
+            activation_parameters: self.activation_parameters,
+            update_rule,
+            update_algorithm: self.update_algorithm.single_state(self.dimension),
+            update_dynamics: self.update_dynamics,
+            activity_control: None,
+            maximum_relaxation_iterations: self.maximum_relaxation_iterations,
+            maximum_relaxation_unstable_units: self.maximum_relaxation_unstable_units,
+        }
+    }
+
+    /// Update a given state until it is stable, or until the maximum number of iterations is reached.
     ///
-    /// If you want to know if the state is stable, check using state_energy after this method.
+    /// If the attractor cache is enabled and this cue has been relaxed before, the cached attractor is returned instead.
     ///
     /// # Arguments
     ///
     /// * `state` - The state the relax. Consumes the state.
-    pub fn relax_state(self: &mut Self, mut state: DVector<f64>) -> DVector<f64> {
-        // We perform up to a maximum number of iterations
-        for _ in 0..self.maximum_relaxation_iterations {
-            // Each time, we update the state
-            state = self.update_state(state);
-            // We then get all the state energies and fold over them
-            // accumulating a count of the unstable states by checking if the energy is greater than 0
-            let unstable_units = self
-                .all_unit_energies(&state)
-                .fold::<i32>(0, |acc, i| acc + if i > 0. { 1 } else { 0 });
-
-            if unstable_units < self.maximum_relaxation_unstable_units {
-                break;
+    ///
+    /// # Returns
+    ///
+    /// A RelaxationResult of the relaxed state, including whether it converged, so a state stopped by the maximum
+    /// number of iterations can be told apart from a stable one.
+    pub fn relax_state(self: &mut Self, state: DVector<f64>) -> RelaxationResult {
+        if self.temperature == 0.0 {
+            if let Some(mut cache) = self.attractor_cache.take() {
+                let result = cache.relax_state(self, state);
+                self.attractor_cache = Some(cache);
+                return result;
             }
         }
 
-        state
+        self.relax_state_recorded(state, &RelaxationOverrides::default())
+    }
+
+    /// Update a given state until it is stable, also reporting how many update iterations were used.
+    ///
+    /// This always relaxes the state, bypassing the attractor cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state the relax. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of update iterations performed.
+    pub fn relax_state_iterations(self: &mut Self, state: DVector<f64>) -> (DVector<f64>, usize) {
+        let (state, iterations, _) =
+            self.relax_state_observed(state, &RelaxationOverrides::default(), |_, _, _| {
+                ControlFlow::Continue(())
+            });
+        (state, iterations)
+    }
+
+    /// Relax a state with a different activation gain for this relaxation only, so high gain retrieval
+    /// and low gain dynamics can be mixed on one network. The attractor cache is bypassed, as cached attractors
+    /// depend on the gain they were found with.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state the relax. Consumes the state.
+    /// * `gain` - The activation gain to relax with. Must be strictly positive.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of update iterations performed.
+    pub fn relax_state_with_gain(
+        self: &mut Self,
+        state: DVector<f64>,
+        gain: f64,
+    ) -> (DVector<f64>, usize) {
+        assert!(gain > 0.0, "Activation gain must be strictly positive!");
+        let overrides = RelaxationOverrides {
+            activation_parameters: Some(ActivationParameters {
+                gain,
+                ..self.activation_parameters
+            }),
+            ..Default::default()
+        };
+        let (state, iterations, _) =
+            self.relax_state_observed(state, &overrides, |_, _, _| ControlFlow::Continue(()));
+        (state, iterations)
+    }
+
+    /// Relax only a subset of the units of a state, treating the rest as frozen. The frozen units still contribute
+    /// to the local fields of the free units, acting as an external field, so this runs the dynamics of the
+    /// sub-network induced by the free units. This is useful for conditional inference (e.g. completing a pattern
+    /// given some known units) and for experiments on modules of a larger network.
+    ///
+    /// Only the free units are counted when checking stability. The attractor cache is bypassed,
+    /// as cached attractors were found with every unit free.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state the relax. Consumes the state.
+    /// * `free_units` - The indices of the units to update. Every index must be less than the network dimension.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of update iterations performed.
+    pub fn relax_state_subset(
+        self: &mut Self,
+        state: DVector<f64>,
+        free_units: &[usize],
+    ) -> (DVector<f64>, usize) {
+        assert!(
+            free_units.iter().all(|unit| *unit < self.dimension),
+            "Every free unit must be less than the network dimension!"
+        );
+        let mut free_units = free_units.to_vec();
+        free_units.sort_unstable();
+        free_units.dedup();
+
+        let overrides = RelaxationOverrides {
+            free_units: Some(free_units),
+            ..Default::default()
+        };
+        let (state, iterations, _) =
+            self.relax_state_observed(state, &overrides, |_, _, _| ControlFlow::Continue(()));
+        (state, iterations)
+    }
+
+    /// Complete many partial cues concurrently, where every cue shares the same known units. The known units of each
+    /// cue are held fixed while the unknown units relax (see relax_state_subset), and each cue stops relaxing once
+    /// its unknown units are stable, however unstable the known units are.
+    ///
+    /// The attractor cache is bypassed, as cached attractors were found with every unit free.
+    ///
+    /// # Arguments
+    ///
+    /// * `cues`: The cues to complete. The values of the unknown units are the initial values to relax from.
+    /// * `known_units`: The mask shared by every cue, true for each unit that is known. Must match the network dimension.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    ///
+    /// # Returns
+    ///
+    /// The completed states, in the same order as the cues.
+    pub fn complete_patterns(
+        self: &mut Self,
+        cues: &[DVector<f64>],
+        known_units: &[bool],
+        threads: Option<usize>,
+    ) -> Vec<DVector<f64>> {
+        assert_eq!(
+            known_units.len(),
+            self.dimension,
+            "The known unit mask must have one entry per network unit!"
+        );
+
+        let free_units: Vec<usize> = known_units
+            .iter()
+            .enumerate()
+            .filter(|(_, known)| !**known)
+            .map(|(unit, _)| unit)
+            .collect();
+        self.concurrent_relax_units(cues.to_vec(), &free_units, threads)
+    }
+
+    /// Relax a state while recording which stored pattern it most closely matches after every update sweep.
+    ///
+    /// This is most useful with an external input set, to see which attractor the state tracks as the input changes.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state the relax. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and, for each sweep, the index of the best matching stored pattern
+    /// (None if no patterns are stored).
+    pub fn relax_state_tracking_attractors(
+        self: &mut Self,
+        state: DVector<f64>,
+    ) -> (DVector<f64>, Vec<Option<usize>>) {
+        let mut best_matches = Vec::new();
+        let overrides = RelaxationOverrides::default();
+        let (state, _, _) = self.relax_state_observed(state, &overrides, |network, state, _| {
+            best_matches.push(network.nearest_memories(state, 1).first().map(|m| m.0));
+            ControlFlow::Continue(())
+        });
+        (state, best_matches)
+    }
+
+    /// Relax a state, calling an observer with the network, the state, and its number of unstable units after every
+    /// update sweep. The relaxation stops early if the observer returns ControlFlow::Break.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The state the relax. Consumes the state.
+    /// * `overrides` - The settings of this relaxation that differ from those of the network.
+    /// * `observer` - Called after every update sweep.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state, the number of update iterations performed, and the number of unstable units.
+    fn relax_state_observed(
+        self: &mut Self,
+        state: DVector<f64>,
+        overrides: &RelaxationOverrides,
+        mut observer: impl FnMut(&Self, &DVector<f64>, i32) -> ControlFlow<()>,
+    ) -> (DVector<f64>, usize, i32) {
+        self.event_hooks
+            .emit(&NetworkEvent::RelaxationStarted { state: &state });
+
+        let activation_parameters = overrides
+            .activation_parameters
+            .unwrap_or(self.activation_parameters);
+        let update_rule = self.update_rule_with(activation_parameters);
+        let mut rng = StdRng::seed_from_u64(self.rng.next_u64());
+        let mut unit_indices = match &overrides.free_units {
+            Some(free_units) => free_units.clone(),
+            None => self.get_unit_indices(),
+        };
+        let relaxation_dynamics = RelaxationDynamics {
+            activation_parameters,
+            activity_control: overrides.activity_control,
+            ..self.relaxation_dynamics(update_rule.as_ref())
+        };
+        let (state, iterations, unstable_units) = relaxation_dynamics.relax(
+            &mut unit_indices,
+            &mut rng,
+            state,
+            |state, unstable_units| observer(self, state, unstable_units),
+        );
+
+        self.event_hooks.emit(&NetworkEvent::RelaxationFinished {
+            state: &state,
+            iterations,
+            converged: has_converged(unstable_units, self.maximum_relaxation_unstable_units),
+        });
+        (state, iterations, unstable_units)
     }
 
     /// Relax a collection of states concurrently. The returned states will be in the same order as the original collections.
     ///
+    /// If the attractor cache is enabled, only the distinct cues that have not been relaxed before are relaxed.
+    /// If a verification mode is set, a sample of the batch is cross-checked against the reference implementation.
+    ///
     /// # Arguments
     ///
     /// * `state_collection`: A collection of states to relax.
-    /// * `threads`: The number of threads to spawn.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
     ///
     /// # Returns
     ///
@@ -185,9 +850,49 @@ impl HopfieldNetwork {
     pub fn concurrent_relax_state_collection(
         self: &mut Self,
         state_collection: Vec<DVector<f64>>,
-        threads: usize,
+        threads: Option<usize>,
+    ) -> Vec<DVector<f64>> {
+        if self.temperature == 0.0 {
+            if let Some(mut cache) = self.attractor_cache.take() {
+                let state_result_collection =
+                    cache.concurrent_relax_state_collection(self, state_collection, threads);
+                self.attractor_cache = Some(cache);
+                return state_result_collection;
+            }
+        }
+
+        let unit_indices = self.get_unit_indices();
+        self.concurrent_relax_units(state_collection, &unit_indices, threads)
+    }
+
+    /// Relax a collection of states concurrently, updating only some of the units of each state and bypassing the
+    /// attractor cache. The returned states will be in the same order as the original collection.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_collection`: A collection of states to relax.
+    /// * `unit_indices`: The units to update, with every other unit frozen. Only these are checked for stability.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    ///
+    /// # Returns
+    ///
+    /// A new collection of states that have now been relaxed.
+    fn concurrent_relax_units(
+        self: &mut Self,
+        state_collection: Vec<DVector<f64>>,
+        unit_indices: &[usize],
+        threads: Option<usize>,
     ) -> Vec<DVector<f64>> {
         let total_states = state_collection.len();
+        let verification_sample: Vec<(usize, DVector<f64>)> = self
+            .verification_sample_indices(total_states)
+            .into_iter()
+            .map(|index| (index, state_collection[index].clone()))
+            .collect();
+        let threads = threads
+            .unwrap_or_else(|| self.default_thread_count())
+            .min(total_states)
+            .max(1);
         let mut state_result_collection = Vec::with_capacity(state_collection.len());
 
         let mut thread_states = Vec::with_capacity(threads);
@@ -200,23 +905,26 @@ impl HopfieldNetwork {
 
         let (result_channel_tx, result_channel_rx) = mpsc::channel();
 
+        let rng_seeds: Vec<u64> = (0..threads).map(|_| self.rng.next_u64()).collect();
+        let update_algorithm = self.collection_update_algorithm();
+        let update_rule = self.get_update_rule();
+        let relaxation_dynamics = RelaxationDynamics {
+            local_field_operator: match update_algorithm {
+                UpdateAlgorithm::BatchedRows => LocalFieldOperator::Dense(&self.matrix),
+                _ => self.local_field_operator(),
+            },
+            update_algorithm,
+            ..self.relaxation_dynamics(update_rule.as_ref())
+        };
         crossbeam::scope(|scope| {
-            for thread_index in 0..threads {
-                let matrix = self.matrix.clone();
-                let activation_function = self.activation_fn;
-                let unit_indicies = self.get_unit_indices();
-                let maximum_relaxation_iterations = self.maximum_relaxation_iterations;
-                let maximum_relaxation_unstable_units = self.maximum_relaxation_unstable_units;
-                let rng_seed = self.rng.next_u64();
-                let thread_states = thread_states[thread_index].to_owned();
+            for (thread_index, rng_seed) in rng_seeds.into_iter().enumerate() {
+                let unit_indicies = unit_indices.to_vec();
+                let thread_states = std::mem::take(&mut thread_states[thread_index]);
                 let result_tx_clone = result_channel_tx.clone();
                 scope.spawn(move |_| {
                     concurrent_relax_thread_fn(
-                        matrix,
-                        activation_function,
+                        relaxation_dynamics,
                         unit_indicies,
-                        maximum_relaxation_iterations,
-                        maximum_relaxation_unstable_units,
                         rng_seed,
                         thread_states,
                         result_tx_clone,
@@ -231,18 +939,441 @@ impl HopfieldNetwork {
             state_result_collection.push(result_channel_rx.recv().unwrap())
         }
 
-        state_result_collection.sort_unstable_by_key(|k| (*k).0);
-        state_result_collection.into_iter().map(|i| i.1).collect()
+        self.event_hooks.emit(&NetworkEvent::BatchCompleted {
+            batch_size: total_states,
+        });
+
+        state_result_collection.sort_unstable_by_key(|k| k.0);
+        let state_result_collection: Vec<DVector<f64>> =
+            state_result_collection.into_iter().map(|i| i.1).collect();
+        self.verify_relaxation_sample(&verification_sample, &state_result_collection);
+        state_result_collection
+    }
+
+    /// Relax the same cue many times, each with a different random update order, and collect the resulting attractors.
+    ///
+    /// Asynchronous dynamics are order dependent, so a single relaxation may be misleading.
+    /// The runs are relaxed concurrently using the default thread count.
+    ///
+    /// # Arguments
+    ///
+    /// * `cue`: The state to relax. This is cloned for each run.
+    /// * `n_runs`: The number of times to relax the cue.
+    ///
+    /// # Returns
+    ///
+    /// Each distinct relaxed state with the number of runs that reached it, ordered from most to least common.
+    pub fn relax_state_multi(
+        self: &mut Self,
+        cue: &DVector<f64>,
+        n_runs: usize,
+    ) -> Vec<(DVector<f64>, usize)> {
+        let mut attractor_counter = AttractorCounter::new_attractor_counter(self.domain, false);
+        self.concurrent_relax_state_stream(
+            std::iter::repeat_n(cue.clone(), n_runs),
+            None,
+            |_, state| attractor_counter.add(state),
+        );
+
+        attractor_counter.into_attractors()
+    }
+
+    /// Get the number of threads used by concurrent methods when no thread count is given.
+    ///
+    /// If auto_tune_threads has been run, the tuned thread count is used. Otherwise this is the
+    /// available parallelism of the machine, capped so that each thread has at least
+    /// MINIMUM_UNITS_PER_THREAD units of network dimension - tiny networks do not benefit from many threads.
+    ///
+    /// # Returns
+    ///
+    /// The default number of threads as a `usize`.
+    pub fn default_thread_count(self: &Self) -> usize {
+        if let Some(tuned_threads) = self.tuned_threads {
+            return tuned_threads;
+        }
+
+        let available_threads = std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1);
+        available_threads
+            .min(self.dimension / MINIMUM_UNITS_PER_THREAD)
+            .max(1)
+    }
+
+    /// Benchmark concurrent relaxation of a small sample of states with 1, 2, 4, ... threads
+    /// (up to the available parallelism) and use the fastest thread count as the default from now on.
+    ///
+    /// The sample should be representative of the states in the full batch, but small enough to relax quickly.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample`: The states to benchmark with. These are cloned, not consumed.
+    ///
+    /// # Returns
+    ///
+    /// The fastest thread count, which is now the default thread count of this network.
+    pub fn auto_tune_threads(self: &mut Self, sample: &[DVector<f64>]) -> usize {
+        let available_threads = std::thread::available_parallelism()
+            .map(|threads| threads.get())
+            .unwrap_or(1);
+
+        let mut best_threads = 1;
+        let mut best_time = Duration::MAX;
+        let mut threads = 1;
+        while threads <= available_threads {
+            let now = Instant::now();
+            self.concurrent_relax_state_collection(sample.to_vec(), Some(threads));
+            let elapsed = now.elapsed();
+            if elapsed < best_time {
+                best_time = elapsed;
+                best_threads = threads;
+            }
+            threads *= 2;
+        }
+
+        self.tuned_threads = Some(best_threads);
+        best_threads
+    }
+
+    /// Get the number of states that may be queued at each stage of a streaming relaxation,
+    /// according to the resource limits specified in the builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `threads`: The number of threads relaxing states. These states count towards the limit.
+    ///
+    /// # Returns
+    ///
+    /// None if no limits are set, otherwise the capacity of each queue.
+    fn in_flight_queue_capacity(self: &Self, threads: usize) -> Option<usize> {
+        let state_bytes = self.dimension * std::mem::size_of::<f64>();
+        let memory_limit =
+            (self.memory_budget_bytes > 0).then(|| self.memory_budget_bytes / state_bytes);
+        let count_limit =
+            (self.maximum_in_flight_states > 0).then_some(self.maximum_in_flight_states);
+
+        let limit = match (memory_limit, count_limit) {
+            (Some(memory_limit), Some(count_limit)) => memory_limit.min(count_limit),
+            (limit, None) | (None, limit) => limit?,
+        };
+
+        // States are held in the work queue, by the threads, and in the result queue
+        Some((limit.saturating_sub(threads) / 2).max(1))
+    }
+
+    /// Relax a stream of states concurrently, passing each relaxed state to a sink as soon as it is ready.
+    ///
+    /// States are pulled from the iterator only as fast as the threads can relax them: if the limits on
+    /// in-flight states or memory set in the builder are reached, the producer blocks until space frees up.
+    /// This allows arbitrarily long streams to be relaxed without holding every state in memory.
+    ///
+    /// Each state is relaxed with its own random number generator derived from the network rng and the
+    /// index of the state, so results do not depend on which thread relaxed which state.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The states to relax.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    /// * `sink`: Called with the index (in the stream) and relaxed value of each state, in order of completion.
+    ///
+    /// # Returns
+    ///
+    /// The total number of states relaxed.
+    pub fn concurrent_relax_state_stream(
+        self: &mut Self,
+        states: impl Iterator<Item = DVector<f64>> + Send,
+        threads: Option<usize>,
+        sink: impl FnMut(usize, DVector<f64>),
+    ) -> usize {
+        let threads = threads.unwrap_or_else(|| self.default_thread_count());
+        let queue_capacity = self.in_flight_queue_capacity(threads);
+        self.concurrent_relax_state_stream_with_capacity(states, threads, queue_capacity, sink)
+    }
+
+    /// Relax a stream of states concurrently like concurrent_relax_state_stream, aborting the remaining work if the
+    /// rolling convergence rate drops below the threshold of the monitor.
+    ///
+    /// States already passed to the sink when the batch is aborted are kept, as are any states that were being
+    /// relaxed at the time, so the sink receives a partial batch.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The states to relax.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    /// * `monitor`: The convergence monitor deciding when to abort.
+    /// * `sink`: Called with the index (in the stream) and relaxed value of each state, in order of completion.
+    ///
+    /// # Returns
+    ///
+    /// A ConvergenceReport describing the relaxed states and whether the batch was aborted.
+    pub fn concurrent_relax_state_stream_monitored(
+        self: &mut Self,
+        states: impl Iterator<Item = DVector<f64>> + Send,
+        threads: Option<usize>,
+        monitor: ConvergenceMonitor,
+        mut sink: impl FnMut(usize, DVector<f64>),
+    ) -> ConvergenceReport {
+        let threads = threads.unwrap_or_else(|| self.default_thread_count());
+        let queue_capacity = self.in_flight_queue_capacity(threads);
+        self.concurrent_relax_state_stream_inner(
+            states,
+            threads,
+            queue_capacity,
+            Some(monitor),
+            |index, state, _, _, _| sink(index, state),
+        )
+    }
+
+    /// Relax a collection of states concurrently, aborting the remaining work if the rolling convergence rate
+    /// drops below the threshold of the monitor. See concurrent_relax_state_stream_monitored.
+    ///
+    /// The attractor cache is not used.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_collection`: A collection of states to relax.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    /// * `monitor`: The convergence monitor deciding when to abort.
+    ///
+    /// # Returns
+    ///
+    /// The relaxed states in the original order, with None for any state not relaxed before an abort,
+    /// and the ConvergenceReport of the batch.
+    pub fn concurrent_relax_state_collection_monitored(
+        self: &mut Self,
+        state_collection: Vec<DVector<f64>>,
+        threads: Option<usize>,
+        monitor: ConvergenceMonitor,
+    ) -> (Vec<Option<DVector<f64>>>, ConvergenceReport) {
+        let mut relaxed_states = vec![None; state_collection.len()];
+        let report = self.concurrent_relax_state_stream_monitored(
+            state_collection.into_iter(),
+            threads,
+            monitor,
+            |index, state| relaxed_states[index] = Some(state),
+        );
+        (relaxed_states, report)
+    }
+
+    /// Relax a collection of states concurrently, then re-relax any states that did not converge with a larger
+    /// iteration cap, so a batch does not silently include truncated relaxations.
+    ///
+    /// Each retry relaxes the original cues of the states that have still not converged, with the maximum number
+    /// of relaxation iterations multiplied by the iteration growth of the policy (compounding over retries).
+    /// Results of converged retries replace the truncated results. The attractor cache is not used.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_collection`: A collection of states to relax.
+    /// * `threads`: The number of threads to spawn. If None, the thread count is chosen automatically (see default_thread_count).
+    /// * `retry_policy`: The policy deciding how often and with how many iterations to retry.
+    ///
+    /// # Returns
+    ///
+    /// The relaxed states in the original order, and a RetryReport describing the retries.
+    pub fn concurrent_relax_state_collection_with_retry(
+        self: &mut Self,
+        state_collection: Vec<DVector<f64>>,
+        threads: Option<usize>,
+        retry_policy: RetryPolicy,
+    ) -> (Vec<DVector<f64>>, RetryReport) {
+        let threads = threads.unwrap_or_else(|| self.default_thread_count());
+        let queue_capacity = self.in_flight_queue_capacity(threads);
+        let network_maximum_relaxation_iterations = self.maximum_relaxation_iterations;
+
+        let mut relaxed_states = state_collection.clone();
+        let mut unconverged_indices: Vec<usize> = (0..state_collection.len()).collect();
+        let mut report = RetryReport {
+            states_retried: Vec::new(),
+            unconverged_indices: Vec::new(),
+        };
+        for attempt in 0..=retry_policy.max_retries {
+            if unconverged_indices.is_empty() {
+                break;
+            }
+            if attempt > 0 {
+                report.states_retried.push(unconverged_indices.len());
+                self.maximum_relaxation_iterations = self
+                    .maximum_relaxation_iterations
+                    .saturating_mul(retry_policy.iteration_growth);
+            }
+
+            let mut still_unconverged = Vec::new();
+            self.concurrent_relax_state_stream_inner(
+                unconverged_indices
+                    .iter()
+                    .map(|index| state_collection[*index].clone()),
+                threads,
+                queue_capacity,
+                None,
+                |attempt_index, state, converged, _, _| {
+                    let index = unconverged_indices[attempt_index];
+                    relaxed_states[index] = state;
+                    if !converged {
+                        still_unconverged.push(index);
+                    }
+                },
+            );
+            still_unconverged.sort_unstable();
+            unconverged_indices = still_unconverged;
+        }
+        self.maximum_relaxation_iterations = network_maximum_relaxation_iterations;
+
+        report.unconverged_indices = unconverged_indices;
+        (relaxed_states, report)
+    }
+
+    /// Relax a stream of states concurrently with explicit queue capacities, see concurrent_relax_state_stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The states to relax.
+    /// * `threads`: The number of threads to spawn.
+    /// * `queue_capacity`: The capacity of the work and result queues, or None for unbounded queues.
+    /// * `sink`: Called with the index (in the stream) and relaxed value of each state, in order of completion.
+    ///
+    /// # Returns
+    ///
+    /// The total number of states relaxed.
+    fn concurrent_relax_state_stream_with_capacity(
+        self: &mut Self,
+        states: impl Iterator<Item = DVector<f64>> + Send,
+        threads: usize,
+        queue_capacity: Option<usize>,
+        mut sink: impl FnMut(usize, DVector<f64>),
+    ) -> usize {
+        self.concurrent_relax_state_stream_inner(
+            states,
+            threads,
+            queue_capacity,
+            None,
+            |index, state, _, _, _| sink(index, state),
+        )
+        .states_relaxed
+    }
+
+    /// Relax a stream of states concurrently with explicit queue capacities and an optional convergence monitor.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The states to relax.
+    /// * `threads`: The number of threads to spawn.
+    /// * `queue_capacity`: The capacity of the work and result queues, or None for unbounded queues.
+    /// * `monitor`: The convergence monitor deciding when to abort, or None to relax every state.
+    /// * `sink`: Called with the index (in the stream), relaxed value, convergence, update iterations performed and
+    ///   final number of unstable units of each state, in order of completion.
+    ///
+    /// # Returns
+    ///
+    /// A ConvergenceReport describing the relaxed states.
+    fn concurrent_relax_state_stream_inner(
+        self: &mut Self,
+        states: impl Iterator<Item = DVector<f64>> + Send,
+        threads: usize,
+        queue_capacity: Option<usize>,
+        monitor: Option<ConvergenceMonitor>,
+        mut sink: impl FnMut(usize, DVector<f64>, bool, usize, i32),
+    ) -> ConvergenceReport {
+        let (work_channel_tx, work_channel_rx, result_channel_tx, result_channel_rx) =
+            match queue_capacity {
+                Some(capacity) => {
+                    let (work_tx, work_rx) = crossbeam::channel::bounded(capacity);
+                    let (result_tx, result_rx) = crossbeam::channel::bounded(capacity);
+                    (work_tx, work_rx, result_tx, result_rx)
+                }
+                None => {
+                    let (work_tx, work_rx) = crossbeam::channel::unbounded();
+                    let (result_tx, result_rx) = crossbeam::channel::unbounded();
+                    (work_tx, work_rx, result_tx, result_rx)
+                }
+            };
+        let stream_seed = self.rng.next_u64();
+        let mut rolling_convergence = monitor.map(RollingConvergence::new_rolling_convergence);
+        let mut report = ConvergenceReport {
+            states_relaxed: 0,
+            states_converged: 0,
+            aborted: false,
+            rolling_convergence_rate: 1.0,
+        };
+        let maximum_relaxation_unstable_units = self.maximum_relaxation_unstable_units;
+        let update_rule = self.get_update_rule();
+        let relaxation_dynamics = self.relaxation_dynamics(update_rule.as_ref());
+        let abort = AtomicBool::new(false);
+        let abort = &abort;
+
+        crossbeam::scope(|scope| {
+            scope.spawn(move |_| {
+                for (index, state) in states.enumerate() {
+                    // Sending fails once every worker has stopped after an abort
+                    if abort.load(Ordering::Relaxed)
+                        || work_channel_tx.send((index, state)).is_err()
+                    {
+                        break;
+                    }
+                }
+            });
+
+            for _ in 0..threads {
+                let unit_indices = self.get_unit_indices();
+                let work_rx_clone = work_channel_rx.clone();
+                let result_tx_clone = result_channel_tx.clone();
+                scope.spawn(move |_| {
+                    for (index, state) in work_rx_clone {
+                        if abort.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        // Shuffling starts from the same order for every state, so results are independent of scheduling
+                        let mut unit_indices = unit_indices.clone();
+                        let mut rng = StdRng::seed_from_u64(experiment::derive_seed(
+                            stream_seed,
+                            index as u64,
+                        ));
+                        let (state, iterations, unstable_units) = relaxation_dynamics.relax(
+                            &mut unit_indices,
+                            &mut rng,
+                            state,
+                            |_, _| ControlFlow::Continue(()),
+                        );
+                        result_tx_clone
+                            .send((index, state, iterations, unstable_units))
+                            .unwrap();
+                    }
+                });
+            }
+            // Drop our copies of the work receiver and result sender, so the producer notices when every
+            // thread has stopped and the loop below ends when every thread is done
+            drop(work_channel_rx);
+            drop(result_channel_tx);
+
+            for (index, state, iterations, unstable_units) in result_channel_rx {
+                let converged = has_converged(unstable_units, maximum_relaxation_unstable_units);
+                sink(index, state, converged, iterations, unstable_units);
+                report.states_relaxed += 1;
+                if converged {
+                    report.states_converged += 1;
+                }
+                if let Some(rolling_convergence) = rolling_convergence.as_mut() {
+                    if rolling_convergence.observe(converged) && !report.aborted {
+                        report.aborted = true;
+                        abort.store(true, Ordering::Relaxed);
+                    }
+                    report.rolling_convergence_rate = rolling_convergence.rate();
+                }
+            }
+        })
+        .unwrap();
+
+        self.event_hooks.emit(&NetworkEvent::BatchCompleted {
+            batch_size: report.states_relaxed,
+        });
+        report
     }
 }
 
 /// Defines the thread function for concurrent_relax_state_collection.
 fn concurrent_relax_thread_fn(
-    matrix: DMatrix<f64>,
-    activation_fn: ActivationFunction,
+    relaxation_dynamics: RelaxationDynamics,
     unit_indices: Vec<usize>,
-    maximum_relaxation_iterations: i32,
-    maximum_relaxation_unstable_units: i32,
     rng_seed: u64,
     state_collection: Vec<(usize, DVector<f64>)>,
     result_channel_tx: Sender<(usize, DVector<f64>)>,
@@ -250,34 +1381,254 @@ fn concurrent_relax_thread_fn(
     let mut rng = StdRng::seed_from_u64(rng_seed);
     // Get all of the unit indices for reuse across all states
     let mut unit_indices = unit_indices;
-    for (state_index, mut state) in state_collection {
+
+    // Batched relaxation works on the dense matrix, which the caller passes for this algorithm
+    if let (UpdateAlgorithm::BatchedRows, LocalFieldOperator::Dense(matrix)) = (
+        relaxation_dynamics.update_algorithm,
+        relaxation_dynamics.local_field_operator,
+    ) {
+        let (state_indices, states): (Vec<usize>, Vec<DVector<f64>>) =
+            state_collection.into_iter().unzip();
+        let relaxed_states = update_algorithm::relax_state_batch_with_rng(
+            matrix,
+            relaxation_dynamics.domain,
+            relaxation_dynamics.activation_parameters,
+            &mut unit_indices,
+            relaxation_dynamics.maximum_relaxation_iterations,
+            relaxation_dynamics.maximum_relaxation_unstable_units,
+            &mut rng,
+            &states,
+        );
+        for (state_index, (state, _)) in state_indices.into_iter().zip(relaxed_states) {
+            result_channel_tx.send((state_index, state)).unwrap();
+        }
+        return;
+    }
+
+    for (state_index, state) in state_collection {
+        let (state, _, _) =
+            relaxation_dynamics.relax(&mut unit_indices, &mut rng, state, |_, _| {
+                ControlFlow::Continue(())
+            });
+
+        // Now we have a relaxed state we send this back over the channel
+        result_channel_tx.send((state_index, state)).unwrap();
+    } // END state iteration loop
+}
+
+/// Count the unstable units of a state among the units being updated. Frozen units (see relax_state_subset) are
+/// never updated, so they are left out: otherwise an unstable frozen unit would stop relaxation ever finishing early.
+fn count_unstable_updated_units(
+    domain: NetworkDomain,
+    fields: &DVector<f64>,
+    state: &DVector<f64>,
+    activation_parameters: &ActivationParameters,
+    unit_indices: &[usize],
+) -> i32 {
+    if unit_indices.len() == state.len() {
+        domain.count_unstable_units(fields, state, activation_parameters)
+    } else {
+        domain.count_unstable_units(
+            &fields.select_rows(unit_indices),
+            &state.select_rows(unit_indices),
+            activation_parameters,
+        )
+    }
+}
+
+/// Whether a relaxation has converged: fewer units are unstable than the maximum number of unstable units. Relaxation
+/// stops as soon as this holds, and every converged flag is reported against it.
+fn has_converged(unstable_units: i32, maximum_relaxation_unstable_units: i32) -> bool {
+    unstable_units < maximum_relaxation_unstable_units
+}
+
+/// The settings of a single relaxation that differ from those of the network, so a relaxation can be changed without
+/// changing (and later restoring) the network.
+#[derive(Debug, Clone, Default)]
+struct RelaxationOverrides {
+    /// The units to update, with every other unit frozen, or None to update every unit. Only the free units are
+    /// counted when checking stability.
+    free_units: Option<Vec<usize>>,
+    /// The activation parameters to relax with, including those of the update rule unless it is custom, or None to
+    /// use those of the network.
+    activation_parameters: Option<ActivationParameters>,
+    /// How the activity is controlled, see relax_state_with_activity_control.
+    activity_control: Option<ActivityControl>,
+}
+
+/// The settings of a network that relaxation needs, borrowed from the network so states can be relaxed outside of
+/// it. Every relaxation, serial or concurrent, runs through relax.
+#[derive(Debug, Clone, Copy)]
+struct RelaxationDynamics<'a> {
+    local_field_operator: LocalFieldOperator<'a>,
+    external_input: Option<&'a ExternalInput>,
+    fatigue: FatigueParameters,
+    domain: NetworkDomain,
+    activation_parameters: ActivationParameters,
+    update_rule: &'a dyn UpdateRule,
+    /// A per-state algorithm (see UpdateAlgorithm::single_state), or BatchedRows for the concurrent relaxation threads.
+    update_algorithm: UpdateAlgorithm,
+    update_dynamics: UpdateDynamics,
+    /// Only set for relax_state_with_activity_control, see ActivityControl.
+    activity_control: Option<ActivityControl>,
+    maximum_relaxation_iterations: i32,
+    maximum_relaxation_unstable_units: i32,
+}
+
+impl RelaxationDynamics<'_> {
+    /// Get the operator to calculate local fields with, including any global inhibition.
+    fn field_operator(self: &Self) -> LocalFieldOperator<'_> {
+        match self.activity_control {
+            Some(ActivityControl::GlobalInhibition {
+                coding_level,
+                strength,
+            }) => LocalFieldOperator::GlobalInhibition {
+                operator: &self.local_field_operator,
+                coding_level,
+                strength,
+            },
+            _ => self.local_field_operator,
+        }
+    }
+
+    /// Calculate the local fields of a state with an external input added and unit adaptation subtracted.
+    fn total_fields(
+        self: &Self,
+        state: &DVector<f64>,
+        input: Option<&DVector<f64>>,
+        adaptation: Option<&DVector<f64>>,
+    ) -> DVector<f64> {
+        let mut fields = self.field_operator().local_fields(state);
+        if let Some(input) = input {
+            fields += input;
+        }
+        if let Some(adaptation) = adaptation {
+            fields -= adaptation;
+        }
+        fields
+    }
+
+    /// Update every given unit of a state once, in order, see update_algorithm::sweep_units. Under
+    /// k-winners-take-all the units are instead updated all at once, see sparse_activity::set_k_winners.
+    fn sweep(
+        self: &Self,
+        unit_indices: &[usize],
+        state: &mut DVector<f64>,
+        input: Option<&DVector<f64>>,
+        adaptation: Option<&DVector<f64>>,
+        rng: &mut StdRng,
+    ) {
+        if let Some(ActivityControl::KWinnersTakeAll { active_units }) = self.activity_control {
+            let fields = self.total_fields(state, input, adaptation);
+            sparse_activity::set_k_winners(&fields, unit_indices, active_units, state);
+            return;
+        }
+
+        update_algorithm::sweep_units(
+            self.field_operator(),
+            self.update_rule,
+            self.update_algorithm,
+            self.update_dynamics,
+            unit_indices,
+            state,
+            input,
+            adaptation,
+            rng,
+        );
+    }
+
+    /// Count the unstable units of a state among the units being updated, see count_unstable_updated_units. Under
+    /// k-winners-take-all a unit is unstable if it differs from the winners of the given fields.
+    fn count_unstable_units(
+        self: &Self,
+        fields: &DVector<f64>,
+        state: &DVector<f64>,
+        unit_indices: &[usize],
+    ) -> i32 {
+        if let Some(ActivityControl::KWinnersTakeAll { active_units }) = self.activity_control {
+            let mut next_state = state.clone();
+            sparse_activity::set_k_winners(fields, unit_indices, active_units, &mut next_state);
+            return unit_indices
+                .iter()
+                .filter(|unit_index| next_state[**unit_index] != state[**unit_index])
+                .count() as i32;
+        }
+
+        count_unstable_updated_units(
+            self.domain,
+            fields,
+            state,
+            &self.activation_parameters,
+            unit_indices,
+        )
+    }
+
+    /// Run one sweep of a relaxation: shuffle the units to update, update them against the external input at this
+    /// sweep, then update the adaptation of any fatigue.
+    ///
+    /// Returns the external input the units were updated with.
+    fn advance(
+        self: &Self,
+        sweep: usize,
+        unit_indices: &mut [usize],
+        rng: &mut StdRng,
+        state: &mut DVector<f64>,
+        adaptation: &mut Option<DVector<f64>>,
+    ) -> Option<DVector<f64>> {
+        let input = self
+            .external_input
+            .and_then(|external_input| external_input.at(sweep));
+
+        // Each time, we shuffle the indices and update the state
+        unit_indices.shuffle(rng);
+        self.sweep(
+            unit_indices,
+            state,
+            input.as_ref(),
+            adaptation.as_ref(),
+            rng,
+        );
+        if let Some(adaptation) = adaptation {
+            self.fatigue.update_adaptation(adaptation, state);
+        }
+        input
+    }
+
+    /// Relax a single state, shuffling the units to update before every sweep and calling an observer with the state
+    /// and its number of unstable units after every sweep. The relaxation stops early if the observer returns
+    /// ControlFlow::Break.
+    ///
+    /// Returns the relaxed state, the number of update iterations performed, and the number of unstable units it
+    /// finished with. The state converged if it finished with fewer unstable units than the maximum (see has_converged).
+    fn relax(
+        self: &Self,
+        unit_indices: &mut [usize],
+        rng: &mut StdRng,
+        mut state: DVector<f64>,
+        mut observer: impl FnMut(&DVector<f64>, i32) -> ControlFlow<()>,
+    ) -> (DVector<f64>, usize, i32) {
+        let mut adaptation =
+            (!self.fatigue.is_disabled()).then(|| DVector::<f64>::zeros(state.len()));
+
+        let mut iterations = 0;
+        let mut unstable_units = 0;
         // For every state we try relaxing the maximum number of iterations
-        for _ in 0..maximum_relaxation_iterations {
-            // Each time, we shuffle the indices and update the state
-            unit_indices.shuffle(&mut rng);
-            for unit_index in &unit_indices {
-                let next_state = (activation_fn)(&matrix * &state);
-                state[(*unit_index, 0)] = next_state[(*unit_index, 0)];
-            }
+        for sweep in 0..self.maximum_relaxation_iterations as usize {
+            iterations += 1;
+            let input = self.advance(sweep, unit_indices, rng, &mut state, &mut adaptation);
 
-            // We then get all the state energies and fold over them
-            // accumulating a count of the unstable states by checking if the energy is greater than 0
-            let unstable_units =
-                energy_function::all_unit_energies(&matrix, &state).fold::<i32>(0, |acc, i| {
-                    if i > 0. {
-                        acc + 1
-                    } else {
-                        acc
-                    }
-                });
+            // We then count the unstable units against the same fields the units were updated with
+            let fields = self.total_fields(&state, input.as_ref(), adaptation.as_ref());
+            unstable_units = self.count_unstable_units(&fields, &state, unit_indices);
 
-            // If we are stable then we break from the update loop
-            if unstable_units < maximum_relaxation_unstable_units {
+            // If we are stable, or the observer wants to stop, then we break from the update loop
+            if observer(&state, unstable_units).is_break()
+                || has_converged(unstable_units, self.maximum_relaxation_unstable_units)
+            {
                 break;
             }
         } // END relaxation iterations loop
 
-        // Now we have a relaxed state we send this back over the channel
-        result_channel_tx.send((state_index, state)).unwrap();
-    } // END state iteration loop
+        (state, iterations, unstable_units)
+    }
 }
@@ -0,0 +1,246 @@
+use nalgebra::{DMatrix, DVector};
+
+use super::{
+    activation_function::ActivationParameters, dense_retrieval::softmax_columns, NetworkDomain,
+};
+
+/// The largest change in any unit between updates for relax to consider a state converged.
+const CONVERGENCE_TOLERANCE: f64 = 1e-9;
+
+/// A modern Hopfield network (dense associative memory) with the log-sum-exp energy of Ramsauer et al.,
+///
+/// E(ξ) = -lse(β, Xξ) + ξᵀξ / 2 + log(P) / β + M² / 2,
+///
+/// where X holds the P stored patterns as rows, lse(β, z) = log(Σ exp(β z_μ)) / β, and M is the largest pattern norm.
+/// The update rule ξ_new = Xᵀ softmax(β X ξ) never increases the energy, and for well separated patterns retrieves
+/// a pattern in a single step. The capacity is exponential in the dimension.
+///
+/// There is no weight matrix: learning a pattern just appends a row to the pattern matrix.
+#[derive(Debug, Clone)]
+pub struct ModernHopfieldNetwork {
+    dimension: usize,
+    domain: NetworkDomain,
+    beta: f64,
+    /// The stored patterns, one per row.
+    patterns: DMatrix<f64>,
+    activation_parameters: ActivationParameters,
+}
+
+impl ModernHopfieldNetwork {
+    /// Create a new modern Hopfield network with no stored patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The dimension of the stored patterns.
+    /// * `domain`: The domain of the stored patterns, used to map retrieved states back into the domain.
+    /// * `beta`: The inverse temperature β. Large β retrieves single patterns, small β averages over many.
+    pub fn new_modern_hopfield_network(dimension: usize, domain: NetworkDomain, beta: f64) -> Self {
+        assert!(
+            dimension > 0,
+            "Modern Hopfield network dimension must be a positive integer!"
+        );
+        assert!(
+            domain != NetworkDomain::Unspecified,
+            "Modern Hopfield network domain must be a valid network domain!"
+        );
+        assert!(
+            beta > 0.0,
+            "Modern Hopfield network beta must be strictly positive!"
+        );
+
+        // Retrieved Binary states are averages of 0/1 patterns, so units are active above 1/2
+        let activation_parameters = ActivationParameters {
+            binary_threshold: 0.5,
+            ..ActivationParameters::default()
+        };
+        Self {
+            dimension,
+            domain,
+            beta,
+            patterns: DMatrix::<f64>::zeros(0, dimension),
+            activation_parameters,
+        }
+    }
+
+    /// Get the dimension of this network.
+    pub fn get_dimension(self: &Self) -> usize {
+        self.dimension
+    }
+
+    /// Get the domain of this network.
+    pub fn get_domain(self: &Self) -> NetworkDomain {
+        self.domain
+    }
+
+    /// Get the inverse temperature β of this network.
+    pub fn get_beta(self: &Self) -> f64 {
+        self.beta
+    }
+
+    /// Set the inverse temperature β of this network. Must be strictly positive.
+    pub fn set_beta(self: &mut Self, beta: f64) {
+        assert!(
+            beta > 0.0,
+            "Modern Hopfield network beta must be strictly positive!"
+        );
+        self.beta = beta;
+    }
+
+    /// Get the stored patterns, one per row.
+    pub fn get_patterns(self: &Self) -> &DMatrix<f64> {
+        &self.patterns
+    }
+
+    /// Get the number of stored patterns.
+    pub fn pattern_count(self: &Self) -> usize {
+        self.patterns.nrows()
+    }
+
+    /// Store a collection of patterns, appending each as a row of the pattern matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    pub fn learn_states(self: &mut Self, patterns: &[DVector<f64>]) {
+        assert!(
+            patterns
+                .iter()
+                .all(|pattern| pattern.len() == self.dimension),
+            "Every pattern must have the same dimension as the network!"
+        );
+
+        let first_row = self.patterns.nrows();
+        self.patterns = self
+            .patterns
+            .clone()
+            .insert_rows(first_row, patterns.len(), 0.0);
+        for (offset, pattern) in patterns.iter().enumerate() {
+            self.patterns
+                .set_row(first_row + offset, &pattern.transpose());
+        }
+    }
+
+    /// Get the energy of a state, see ModernHopfieldNetwork.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to calculate the energy of.
+    ///
+    /// # Returns
+    ///
+    /// The energy of the state. With no stored patterns only the quadratic term remains.
+    pub fn state_energy(self: &Self, state: &DVector<f64>) -> f64 {
+        let quadratic_term = 0.5 * state.norm_squared();
+        if self.pattern_count() == 0 {
+            return quadratic_term;
+        }
+
+        let scores = &self.patterns * state * self.beta;
+        let max_score = scores.max();
+        let log_sum_exp =
+            (max_score + scores.map(|score| (score - max_score).exp()).sum().ln()) / self.beta;
+        let max_pattern_norm = self
+            .patterns
+            .row_iter()
+            .map(|pattern| pattern.norm())
+            .fold(0.0, f64::max);
+
+        -log_sum_exp
+            + quadratic_term
+            + (self.pattern_count() as f64).ln() / self.beta
+            + 0.5 * max_pattern_norm * max_pattern_norm
+    }
+
+    /// Get the attention of a state over the stored patterns, softmax(β X ξ): the weight of each pattern in the
+    /// next update. With large β this is close to one-hot on the nearest pattern.
+    ///
+    /// # Returns
+    ///
+    /// The weight of each stored pattern, summing to 1. Empty if no patterns are stored.
+    pub fn pattern_attention(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        if self.pattern_count() == 0 {
+            return DVector::zeros(0);
+        }
+        let mut scores = DMatrix::from_column_slice(
+            self.pattern_count(),
+            1,
+            (&self.patterns * state * self.beta).as_slice(),
+        );
+        softmax_columns(&mut scores);
+        scores.column(0).into_owned()
+    }
+
+    /// Update a state once, ξ_new = Xᵀ softmax(β X ξ). The result is a weighted average of the stored patterns,
+    /// so it may not be in the network domain (see retrieve).
+    ///
+    /// # Returns
+    ///
+    /// The updated state. If no patterns are stored, the updated state is zero.
+    pub fn update_state(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        if self.pattern_count() == 0 {
+            return DVector::zeros(self.dimension);
+        }
+        self.patterns.tr_mul(&self.pattern_attention(state))
+    }
+
+    /// Update a batch of states once, with two matrix products for the whole batch. See update_state.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The states to update, one per column.
+    ///
+    /// # Returns
+    ///
+    /// The updated states, one per column, in the same order.
+    pub fn update_state_batch(self: &Self, states: &DMatrix<f64>) -> DMatrix<f64> {
+        if self.pattern_count() == 0 {
+            return DMatrix::zeros(self.dimension, states.ncols());
+        }
+        let mut attention = &self.patterns * states * self.beta;
+        softmax_columns(&mut attention);
+        self.patterns.tr_mul(&attention)
+    }
+
+    /// Map a state into the domain of this network with the domain activation function: the nearest valid state of a
+    /// discrete domain (Binary units are active above 1/2), or the clipped state of a bounded continuous domain.
+    /// States of the Continuous and Tanh domains are returned unchanged.
+    pub fn project_to_domain(self: &Self, state: DVector<f64>) -> DVector<f64> {
+        match self.domain {
+            NetworkDomain::Continuous | NetworkDomain::Tanh => state,
+            domain => (domain.activation_fn())(state, &self.activation_parameters),
+        }
+    }
+
+    /// Retrieve a memory from a cue in a single update, mapped into the network domain (see project_to_domain).
+    pub fn retrieve(self: &Self, cue: &DVector<f64>) -> DVector<f64> {
+        self.project_to_domain(self.update_state(cue))
+    }
+
+    /// Update a state until it stops changing, then map it into the network domain.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax.
+    /// * `maximum_iterations`: The maximum number of updates.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of updates performed.
+    pub fn relax_state(
+        self: &Self,
+        mut state: DVector<f64>,
+        maximum_iterations: usize,
+    ) -> (DVector<f64>, usize) {
+        let mut iterations = 0;
+        while iterations < maximum_iterations {
+            iterations += 1;
+            let next_state = self.update_state(&state);
+            let converged = (&next_state - &state).amax() <= CONVERGENCE_TOLERANCE;
+            state = next_state;
+            if converged {
+                break;
+            }
+        }
+        (self.project_to_domain(state), iterations)
+    }
+}
@@ -0,0 +1,355 @@
+use nalgebra::{DMatrix, DVector};
+
+use super::HopfieldNetwork;
+
+/// The couplings from the units of a source module to the units of a target module: the source state s drives the
+/// local fields of the target module by C s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InterModuleCoupling {
+    pub target: usize,
+    pub source: usize,
+    /// The coupling matrix C, one row per unit of the target module and one column per unit of the source module.
+    pub weights: DMatrix<f64>,
+}
+
+/// The result of relaxing a ModularHopfieldNetwork.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModularRelaxationResult {
+    /// The relaxed state of each module.
+    pub states: Vec<DVector<f64>>,
+    pub sweeps: usize,
+    /// The number of unstable units of each module after the last sweep, counted against the fields including the
+    /// coupling input.
+    pub unstable_units: Vec<usize>,
+    /// Whether every module finished with fewer unstable units than its maximum_relaxation_unstable_units, rather than
+    /// the maximum number of sweeps being reached.
+    pub converged: bool,
+}
+
+/// A network of HopfieldNetwork modules connected by inter-module coupling matrices, relaxed jointly.
+///
+/// Each module keeps its own weights, domain, and settings. During relaxation the coupling input Σ_k C_mk s_k from
+/// the other modules is added to the local fields of module m, together with the external input of the module. The
+/// network is the block matrix of joint_matrix without building it, so large modular networks stay cheap to relax and
+/// each module can still be trained and inspected alone.
+///
+/// Each sweep updates the modules in order, every module once with its units in a random order, using the latest
+/// states of the modules before it. With symmetric modules and couplings (C_mk = C_kmᵀ) and zero diagonals this is
+/// asynchronous relaxation of the joint network, so it descends state_energy.
+#[derive(Debug)]
+pub struct ModularHopfieldNetwork {
+    modules: Vec<HopfieldNetwork>,
+    couplings: Vec<InterModuleCoupling>,
+    maximum_relaxation_iterations: usize,
+}
+
+impl ModularHopfieldNetwork {
+    /// Create a new modular network with no couplings between the modules.
+    ///
+    /// # Arguments
+    ///
+    /// * `modules`: The modules of the network. Consumes the modules.
+    /// * `maximum_relaxation_iterations`: The maximum number of joint sweeps of a relaxation.
+    pub fn new_modular_hopfield_network(
+        modules: Vec<HopfieldNetwork>,
+        maximum_relaxation_iterations: usize,
+    ) -> Self {
+        assert!(
+            !modules.is_empty(),
+            "Modular network must have at least one module!"
+        );
+
+        Self {
+            modules,
+            couplings: Vec::new(),
+            maximum_relaxation_iterations,
+        }
+    }
+
+    /// Get the number of modules.
+    pub fn module_count(self: &Self) -> usize {
+        self.modules.len()
+    }
+
+    /// Get the modules of this network.
+    pub fn get_modules(self: &Self) -> &[HopfieldNetwork] {
+        &self.modules
+    }
+
+    /// Get a module mutably, e.g. to train it alone.
+    pub fn get_module_mut(self: &mut Self, module: usize) -> &mut HopfieldNetwork {
+        &mut self.modules[module]
+    }
+
+    /// Get the inter-module couplings.
+    pub fn get_couplings(self: &Self) -> &[InterModuleCoupling] {
+        &self.couplings
+    }
+
+    /// Get the total number of units over every module.
+    pub fn total_dimension(self: &Self) -> usize {
+        self.modules.iter().map(|module| module.dimension).sum()
+    }
+
+    /// Set the couplings from a source module to a target module, replacing any existing couplings between them in
+    /// that direction. The reverse couplings are not changed, see set_symmetric_coupling.
+    ///
+    /// # Arguments
+    ///
+    /// * `target`: The index of the target module.
+    /// * `source`: The index of the source module. Must differ from the target.
+    /// * `weights`: The coupling matrix, one row per unit of the target and one column per unit of the source.
+    pub fn set_coupling(self: &mut Self, target: usize, source: usize, weights: DMatrix<f64>) {
+        assert!(
+            target < self.modules.len() && source < self.modules.len() && target != source,
+            "Couplings must connect two different modules of the network!"
+        );
+        assert!(
+            weights.shape()
+                == (
+                    self.modules[target].dimension,
+                    self.modules[source].dimension
+                ),
+            "Coupling matrix must have one row per target unit and one column per source unit!"
+        );
+
+        match self
+            .couplings
+            .iter_mut()
+            .find(|coupling| coupling.target == target && coupling.source == source)
+        {
+            Some(coupling) => coupling.weights = weights,
+            None => self.couplings.push(InterModuleCoupling {
+                target,
+                source,
+                weights,
+            }),
+        }
+    }
+
+    /// Set symmetric couplings between two modules: C from the second module to the first, and Cᵀ back.
+    ///
+    /// # Arguments
+    ///
+    /// * `first`: The index of the first module.
+    /// * `second`: The index of the second module.
+    /// * `weights`: The coupling matrix C, one row per unit of the first module and one column per unit of the second.
+    pub fn set_symmetric_coupling(
+        self: &mut Self,
+        first: usize,
+        second: usize,
+        weights: DMatrix<f64>,
+    ) {
+        self.set_coupling(second, first, weights.transpose());
+        self.set_coupling(first, second, weights);
+    }
+
+    /// Store joint patterns, each a pattern of every module. Each module learns its part of every pattern with
+    /// learn_states, and every pair of modules is coupled by the Hebbian rule across the modules,
+    /// C_mk += λ ξ_m ξ_kᵀ / √(N_m N_k), which keeps the couplings symmetric. Binary patterns are mapped to bipolar
+    /// values as in learn_states.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The joint patterns to store, each with one pattern per module in order.
+    /// * `coupling_strength`: The strength λ of the couplings relative to the weights within the modules.
+    pub fn learn_joint_patterns(
+        self: &mut Self,
+        patterns: &[Vec<DVector<f64>>],
+        coupling_strength: f64,
+    ) {
+        assert!(
+            patterns
+                .iter()
+                .all(|pattern| pattern.len() == self.modules.len()),
+            "Every joint pattern must have one pattern per module!"
+        );
+
+        for (module_index, module) in self.modules.iter_mut().enumerate() {
+            let module_patterns: Vec<DVector<f64>> = patterns
+                .iter()
+                .map(|pattern| pattern[module_index].clone())
+                .collect();
+            module.learn_states(&module_patterns);
+        }
+
+        let learning_vectors: Vec<Vec<DVector<f64>>> = patterns
+            .iter()
+            .map(|pattern| {
+                pattern
+                    .iter()
+                    .zip(&self.modules)
+                    .map(|(module_pattern, module)| module.learning_vector(module_pattern))
+                    .collect()
+            })
+            .collect();
+        for target in 0..self.modules.len() {
+            for source in (0..self.modules.len()).filter(|source| *source != target) {
+                let target_dimension = self.modules[target].dimension;
+                let source_dimension = self.modules[source].dimension;
+                let scale =
+                    coupling_strength / ((target_dimension * source_dimension) as f64).sqrt();
+                let mut weights = self
+                    .couplings
+                    .iter()
+                    .find(|coupling| coupling.target == target && coupling.source == source)
+                    .map_or_else(
+                        || DMatrix::zeros(target_dimension, source_dimension),
+                        |coupling| coupling.weights.clone(),
+                    );
+                for pattern in &learning_vectors {
+                    weights.ger(scale, &pattern[target], &pattern[source], 1.0);
+                }
+                self.set_coupling(target, source, weights);
+            }
+        }
+    }
+
+    /// Get the input to a module from the couplings of the other modules, Σ_k C_mk s_k.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The state of every module.
+    /// * `target`: The index of the module receiving the input.
+    pub fn coupling_input(self: &Self, states: &[DVector<f64>], target: usize) -> DVector<f64> {
+        self.couplings
+            .iter()
+            .filter(|coupling| coupling.target == target)
+            .fold(
+                DVector::zeros(self.modules[target].dimension),
+                |input, coupling| input + &coupling.weights * &states[coupling.source],
+            )
+    }
+
+    /// Get the input to a module during a given sweep: its external input, if any, and its coupling input.
+    fn module_input(
+        self: &Self,
+        states: &[DVector<f64>],
+        module: usize,
+        sweep: usize,
+    ) -> DVector<f64> {
+        let mut input = self.coupling_input(states, module);
+        if let Some(external_input) = self.modules[module]
+            .external_input
+            .as_ref()
+            .and_then(|external_input| external_input.at(sweep))
+        {
+            input += external_input;
+        }
+        input
+    }
+
+    /// Assemble the weights of every module and coupling into the matrix of the joint network, with the units of each
+    /// module in order.
+    pub fn joint_matrix(self: &Self) -> DMatrix<f64> {
+        let offsets: Vec<usize> = self
+            .modules
+            .iter()
+            .scan(0, |offset, module| {
+                let module_offset = *offset;
+                *offset += module.dimension;
+                Some(module_offset)
+            })
+            .collect();
+        let total_dimension = self.total_dimension();
+        let mut matrix = DMatrix::zeros(total_dimension, total_dimension);
+        for (module, offset) in self.modules.iter().zip(&offsets) {
+            matrix
+                .view_mut((*offset, *offset), (module.dimension, module.dimension))
+                .copy_from(&module.matrix);
+        }
+        for coupling in &self.couplings {
+            matrix
+                .view_mut(
+                    (offsets[coupling.target], offsets[coupling.source]),
+                    coupling.weights.shape(),
+                )
+                .copy_from(&coupling.weights);
+        }
+        matrix
+    }
+
+    /// Get the energy of a joint state, the state_energy of every module less Σ_(m, k) s_mᵀ C_mk s_k over the couplings.
+    /// This is the state_energy of the joint state in the network of joint_matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The state of every module.
+    pub fn state_energy(self: &Self, states: &[DVector<f64>]) -> f64 {
+        let module_energy: f64 = self
+            .modules
+            .iter()
+            .zip(states)
+            .map(|(module, state)| module.state_energy(state))
+            .sum();
+        let coupling_energy: f64 = self
+            .couplings
+            .iter()
+            .map(|coupling| {
+                states[coupling.target].dot(&(&coupling.weights * &states[coupling.source]))
+            })
+            .sum();
+        module_energy - coupling_energy
+    }
+
+    /// Relax the states of every module jointly, see ModularHopfieldNetwork. Fatigue and the attractor caches of the
+    /// modules are not used.
+    ///
+    /// # Arguments
+    ///
+    /// * `states`: The state of every module, in the domain of that module. Consumes the states.
+    ///
+    /// # Returns
+    ///
+    /// A ModularRelaxationResult with the relaxed states.
+    pub fn relax_states(self: &mut Self, mut states: Vec<DVector<f64>>) -> ModularRelaxationResult {
+        assert!(
+            states.len() == self.modules.len()
+                && states
+                    .iter()
+                    .zip(&self.modules)
+                    .all(|(state, module)| state.len() == module.dimension),
+            "There must be one state per module, with the dimension of that module!"
+        );
+
+        let mut sweeps = 0;
+        let mut unstable_units = vec![0; self.modules.len()];
+        let mut converged = false;
+        while sweeps < self.maximum_relaxation_iterations {
+            for module in 0..self.modules.len() {
+                let input = self.module_input(&states, module, sweeps);
+                let state = std::mem::replace(&mut states[module], DVector::zeros(0));
+                states[module] =
+                    self.modules[module].update_state_with_input(state, Some(&input), None);
+            }
+            sweeps += 1;
+
+            for (module, unstable) in unstable_units.iter_mut().enumerate() {
+                let network = &self.modules[module];
+                let fields = network.local_fields(&states[module])
+                    + self.module_input(&states, module, sweeps - 1);
+                *unstable = network.domain.count_unstable_units(
+                    &fields,
+                    &states[module],
+                    &network.activation_parameters,
+                ) as usize;
+            }
+            converged = unstable_units
+                .iter()
+                .zip(&self.modules)
+                .all(|(unstable, module)| {
+                    super::has_converged(*unstable as i32, module.maximum_relaxation_unstable_units)
+                });
+            if converged {
+                break;
+            }
+        }
+
+        ModularRelaxationResult {
+            states,
+            sweeps,
+            unstable_units,
+            converged,
+        }
+    }
+}
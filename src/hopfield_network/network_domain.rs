@@ -1,11 +1,20 @@
 use super::activation_function::*;
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// The largest difference between a unit and the activation of its field for the unit to be considered stable,
+/// in domains where stability is checked as a fixed point of the activation.
+pub const FIXED_POINT_STABILITY_TOLERANCE: f64 = 1e-9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum NetworkDomain {
     Unspecified,
     Binary,
     Bipolar,
+    Ternary,
     Continuous,
+    BoundedContinuous,
+    Tanh,
 }
 
 impl NetworkDomain {
@@ -13,8 +22,90 @@ impl NetworkDomain {
         match *self {
         Self::Binary => binary_activation_function,
         Self::Bipolar => bipolar_activation_function,
+        Self::Ternary => ternary_activation_function,
         Self::Continuous => identity_activation_function,
+        Self::BoundedContinuous => clipping_activation_function,
+        Self::Tanh => tanh_activation_function,
         _ => panic!("Error mapping domain to activation function. Domain does not have an associated activation function."),
         }
     }
+
+    /// Get the activation of a single unit value in this domain, see ScalarActivationFunction.
+    pub fn scalar_activation_fn(&self) -> ScalarActivationFunction {
+        match *self {
+            Self::Binary => binary_activation,
+            Self::Bipolar => bipolar_activation,
+            Self::Ternary => ternary_activation,
+            Self::Continuous => identity_activation,
+            Self::BoundedContinuous => clipping_activation,
+            Self::Tanh => tanh_activation,
+            Self::Unspecified => panic!(
+                "Error mapping domain to activation function. Domain does not have an associated activation function."
+            ),
+        }
+    }
+
+    /// Get the mean square unit value E[s²] of a uniformly random state of this domain:
+    /// 1/2 for Binary, 1 for Bipolar, 2/3 for Ternary, and 1/3 for the continuous domains (uniform on [-1, 1]).
+    pub fn mean_square_unit_value(&self) -> f64 {
+        match *self {
+            Self::Binary => 0.5,
+            Self::Bipolar => 1.0,
+            Self::Ternary => 2.0 / 3.0,
+            Self::Continuous | Self::BoundedContinuous | Self::Tanh => 1.0 / 3.0,
+            Self::Unspecified => {
+                panic!("Error finding the mean square unit value. Domain is unspecified.")
+            }
+        }
+    }
+
+    /// Map a unit value to its opposite in this domain: 0 and 1 are swapped in the Binary domain,
+    /// while all other domains are negated. Zero is fixed in the Ternary domain.
+    pub fn invert_value(&self, value: f64) -> f64 {
+        match *self {
+            Self::Binary => 1.0 - value,
+            // Subtracting from zero avoids creating -0.0, which would fingerprint differently to 0.0
+            Self::Ternary => 0.0 - value,
+            _ => -value,
+        }
+    }
+
+    /// Count the unstable units of a state in this domain.
+    ///
+    /// In the Binary, Ternary, BoundedContinuous and Tanh domains a unit is unstable if the activation of its field
+    /// differs from its value (by more than FIXED_POINT_STABILITY_TOLERANCE), as a unit at 0 has no energy but may
+    /// still activate (or leave the dead zone), and a continuous unit with negative energy may still not have reached
+    /// the clipped field.
+    /// In all other domains a unit is unstable if its energy, -field * value, is positive.
+    ///
+    /// # Arguments
+    ///
+    /// * `fields`: The local fields of the state, including any external input or adaptation.
+    /// * `state`: The state to check.
+    /// * `activation_parameters`: The parameters of the activation function.
+    ///
+    /// # Returns
+    ///
+    /// The number of unstable units.
+    pub fn count_unstable_units(
+        &self,
+        fields: &DVector<f64>,
+        state: &DVector<f64>,
+        activation_parameters: &ActivationParameters,
+    ) -> i32 {
+        match *self {
+            Self::Binary | Self::Ternary | Self::BoundedContinuous | Self::Tanh => {
+                (self.activation_fn())(fields.clone(), activation_parameters)
+                    .iter()
+                    .zip(state.iter())
+                    .filter(|(next_value, value)| {
+                        (*next_value - *value).abs() > FIXED_POINT_STABILITY_TOLERANCE
+                    })
+                    .count() as i32
+            }
+            _ => fields
+                .component_mul(state)
+                .fold::<i32>(0, |acc, i| acc + if i < 0. { 1 } else { 0 }),
+        }
+    }
 }
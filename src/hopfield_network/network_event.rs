@@ -0,0 +1,152 @@
+use nalgebra::DVector;
+use std::{fmt, ops::ControlFlow};
+
+use super::weight_delta::WeightDelta;
+
+/// Lifecycle events emitted by a HopfieldNetwork.
+///
+/// Register a hook on the network with add_event_hook to observe these, e.g. for logging, plotting, or snapshotting.
+#[derive(Debug, Clone, Copy)]
+pub enum NetworkEvent<'a> {
+    /// A pattern was stored in the network.
+    PatternStored {
+        pattern_index: usize,
+        pattern: &'a DVector<f64>,
+    },
+    /// A stored pattern was forgotten. Every later stored pattern moved down one index.
+    PatternForgotten {
+        pattern_index: usize,
+        pattern: &'a DVector<f64>,
+    },
+    /// The weights were changed, by learning pattern_count patterns (which will be stored from
+    /// first_pattern_index) or by another weight change when pattern_count is 0.
+    /// Only emitted while weight delta export is enabled (see set_weight_delta_export).
+    WeightsChanged {
+        first_pattern_index: usize,
+        pattern_count: usize,
+        delta: &'a WeightDelta,
+    },
+    /// A single state is about to be relaxed.
+    RelaxationStarted { state: &'a DVector<f64> },
+    /// A single state has finished relaxing, after the given number of update iterations.
+    /// The state converged if fewer units were unstable than the network allows.
+    RelaxationFinished {
+        state: &'a DVector<f64>,
+        iterations: usize,
+        converged: bool,
+    },
+    /// A batch of states has finished relaxing.
+    BatchCompleted { batch_size: usize },
+    /// The temperature of the network dynamics was changed.
+    TemperatureChanged { temperature: f64 },
+}
+
+/// Define a hook that is called with every event a network emits.
+pub type EventHook = Box<dyn FnMut(&NetworkEvent) + Send>;
+
+/// The collection of hooks registered on a network.
+pub struct EventHookRegistry {
+    hooks: Vec<EventHook>,
+}
+
+impl fmt::Debug for EventHookRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EventHookRegistry {{ hooks: {} }}", self.hooks.len())
+    }
+}
+
+impl EventHookRegistry {
+    /// Create a new registry with no hooks.
+    pub fn new_event_hook_registry() -> Self {
+        Self { hooks: Vec::new() }
+    }
+
+    /// Add a hook to this registry. Hooks are called in the order they are added.
+    ///
+    /// # Arguments
+    ///
+    /// * `hook`: The hook to call for each event.
+    pub fn add_hook(self: &mut Self, hook: EventHook) {
+        self.hooks.push(hook);
+    }
+
+    /// Call every hook with an event.
+    ///
+    /// # Arguments
+    ///
+    /// * `event`: The event to pass to each hook.
+    pub fn emit(self: &mut Self, event: &NetworkEvent) {
+        for hook in self.hooks.iter_mut() {
+            hook(event);
+        }
+    }
+}
+
+/// Define a callback that is called after every update sweep of relax_state, with the number of sweeps so far, the
+/// state, and its energy. Return ControlFlow::Break to stop the relaxation early.
+pub type RelaxationCallback = Box<dyn FnMut(usize, &DVector<f64>, f64) -> ControlFlow<()> + Send>;
+
+/// The collection of relaxation callbacks registered on a network.
+pub struct RelaxationCallbackRegistry {
+    callbacks: Vec<RelaxationCallback>,
+}
+
+impl fmt::Debug for RelaxationCallbackRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RelaxationCallbackRegistry {{ callbacks: {} }}",
+            self.callbacks.len()
+        )
+    }
+}
+
+impl RelaxationCallbackRegistry {
+    /// Create a new registry with no callbacks.
+    pub fn new_relaxation_callback_registry() -> Self {
+        Self {
+            callbacks: Vec::new(),
+        }
+    }
+
+    /// Add a callback to this registry. Callbacks are called in the order they are added.
+    ///
+    /// # Arguments
+    ///
+    /// * `callback`: The callback to call after every sweep.
+    pub fn add_callback(self: &mut Self, callback: RelaxationCallback) {
+        self.callbacks.push(callback);
+    }
+
+    /// Check whether no callbacks are registered, so the energy of each sweep need not be calculated.
+    pub fn is_empty(self: &Self) -> bool {
+        self.callbacks.is_empty()
+    }
+
+    /// Call every callback with a sweep. Every callback is called even if an earlier one breaks, so logging callbacks
+    /// still see the final sweep.
+    ///
+    /// # Arguments
+    ///
+    /// * `iteration`: The number of sweeps performed so far, counting from 1.
+    /// * `state`: The state after the sweep.
+    /// * `energy`: The energy of the state.
+    ///
+    /// # Returns
+    ///
+    /// ControlFlow::Break if any callback broke, otherwise ControlFlow::Continue.
+    pub fn call(
+        self: &mut Self,
+        iteration: usize,
+        state: &DVector<f64>,
+        energy: f64,
+    ) -> ControlFlow<()> {
+        let mut control_flow = ControlFlow::Continue(());
+        for callback in self.callbacks.iter_mut() {
+            if callback(iteration, state, energy).is_break() {
+                control_flow = ControlFlow::Break(());
+            }
+        }
+        control_flow
+    }
+}
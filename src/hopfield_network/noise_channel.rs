@@ -0,0 +1,226 @@
+use nalgebra::DVector;
+use rand::{seq::index::sample, Rng, RngCore};
+use rand_distr::{Distribution, Normal};
+
+use super::{sparse_cue::SparseCue, NetworkDomain};
+
+/// A cue produced by a NoiseChannel: the corrupted state, and which units are still known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CorruptedCue {
+    /// The corrupted state. Erased units are set to 0.
+    pub state: DVector<f64>,
+    /// Whether each unit is known, i.e. was not erased. Flipped or noisy units are still known.
+    pub known_units: Vec<bool>,
+}
+
+impl CorruptedCue {
+    /// Get the indices of the erased units.
+    pub fn erased_units(self: &Self) -> Vec<usize> {
+        (0..self.known_units.len())
+            .filter(|unit| !self.known_units[*unit])
+            .collect()
+    }
+
+    /// Convert this cue to a SparseCue of only the known units, so erased units can be filled and completed
+    /// with complete_sparse_cue.
+    pub fn to_sparse_cue(self: &Self) -> SparseCue {
+        let known_units: Vec<usize> = (0..self.known_units.len())
+            .filter(|unit| self.known_units[*unit])
+            .collect();
+        SparseCue::from_state(&self.state, &known_units)
+    }
+}
+
+/// A model of how cues are corrupted, used by the experiment and evaluation modules so that every corruption model
+/// is applied the same way.
+///
+/// Implement this to add a new corruption model: every experiment taking a NoiseChannel can then use it.
+pub trait NoiseChannel: Send + Sync {
+    /// A short description of this channel and its parameters, used to label results.
+    fn name(self: &Self) -> String;
+
+    /// Corrupt a state.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to corrupt. This is not modified.
+    /// * `domain`: The domain of the state, so that flipped units stay in the domain.
+    /// * `rng`: The random number generator used to corrupt the state.
+    ///
+    /// # Returns
+    ///
+    /// The corrupted cue.
+    fn corrupt(
+        self: &Self,
+        state: &DVector<f64>,
+        domain: NetworkDomain,
+        rng: &mut dyn RngCore,
+    ) -> CorruptedCue;
+
+    /// Corrupt a state, keeping only the corrupted state. See corrupt.
+    fn corrupt_state(
+        self: &Self,
+        state: &DVector<f64>,
+        domain: NetworkDomain,
+        rng: &mut dyn RngCore,
+    ) -> DVector<f64> {
+        self.corrupt(state, domain, rng).state
+    }
+}
+
+/// Wrap a corrupted state in which every unit is still known.
+fn fully_known(state: DVector<f64>) -> CorruptedCue {
+    let known_units = vec![true; state.len()];
+    CorruptedCue { state, known_units }
+}
+
+/// Flip exactly `count` distinct units, chosen uniformly at random.
+///
+/// Flipping a unit maps it to the opposite value in the domain, see NetworkDomain::invert_value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedFlips {
+    pub count: usize,
+}
+
+impl NoiseChannel for FixedFlips {
+    fn name(self: &Self) -> String {
+        format!("fixed flips ({})", self.count)
+    }
+
+    fn corrupt(
+        self: &Self,
+        state: &DVector<f64>,
+        domain: NetworkDomain,
+        rng: &mut dyn RngCore,
+    ) -> CorruptedCue {
+        let mut corrupted_state = state.clone();
+        for unit_index in sample(rng, state.len(), self.count) {
+            corrupted_state[unit_index] = domain.invert_value(corrupted_state[unit_index]);
+        }
+        fully_known(corrupted_state)
+    }
+}
+
+/// Flip each unit independently with probability `rate`: the binary symmetric channel.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitFlip {
+    pub rate: f64,
+}
+
+impl NoiseChannel for BitFlip {
+    fn name(self: &Self) -> String {
+        format!("bit flip (p = {})", self.rate)
+    }
+
+    fn corrupt(
+        self: &Self,
+        state: &DVector<f64>,
+        domain: NetworkDomain,
+        rng: &mut dyn RngCore,
+    ) -> CorruptedCue {
+        fully_known(state.map(|value| {
+            if rng.gen_bool(self.rate) {
+                domain.invert_value(value)
+            } else {
+                value
+            }
+        }))
+    }
+}
+
+/// Flip `bursts` runs of `burst_length` consecutive units, each starting at a uniformly random unit and cut short at
+/// the end of the state. Runs may overlap, in which case the overlapping units are flipped once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BurstFlip {
+    pub bursts: usize,
+    pub burst_length: usize,
+}
+
+impl NoiseChannel for BurstFlip {
+    fn name(self: &Self) -> String {
+        format!("burst flip ({} x {})", self.bursts, self.burst_length)
+    }
+
+    fn corrupt(
+        self: &Self,
+        state: &DVector<f64>,
+        domain: NetworkDomain,
+        rng: &mut dyn RngCore,
+    ) -> CorruptedCue {
+        let mut flipped = vec![false; state.len()];
+        for _ in 0..self.bursts {
+            let start = rng.gen_range(0..state.len());
+            let end = (start + self.burst_length).min(state.len());
+            flipped[start..end].fill(true);
+        }
+
+        let mut corrupted_state = state.clone();
+        for (unit_index, _) in flipped.iter().enumerate().filter(|(_, flip)| **flip) {
+            corrupted_state[unit_index] = domain.invert_value(corrupted_state[unit_index]);
+        }
+        fully_known(corrupted_state)
+    }
+}
+
+/// Erase each unit independently with probability `rate`. Erased units are set to 0 and marked unknown, so they can
+/// be treated as free units (see CorruptedCue::to_sparse_cue).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Erasure {
+    pub rate: f64,
+}
+
+impl NoiseChannel for Erasure {
+    fn name(self: &Self) -> String {
+        format!("erasure (p = {})", self.rate)
+    }
+
+    fn corrupt(
+        self: &Self,
+        state: &DVector<f64>,
+        _domain: NetworkDomain,
+        rng: &mut dyn RngCore,
+    ) -> CorruptedCue {
+        let known_units: Vec<bool> = (0..state.len()).map(|_| !rng.gen_bool(self.rate)).collect();
+        let corrupted_state = DVector::from_fn(state.len(), |unit, _| {
+            if known_units[unit] {
+                state[unit]
+            } else {
+                0.0
+            }
+        });
+        CorruptedCue {
+            state: corrupted_state,
+            known_units,
+        }
+    }
+}
+
+/// Add independent Gaussian noise with standard deviation `standard_deviation` to every unit, for continuous
+/// domains. In the Tanh domain the result is clamped into [-1, 1]. Elsewhere, including the bounds of the
+/// BoundedContinuous domain (which belong to the network), the noisy values are left for the first update to map
+/// back into the domain.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Gaussian {
+    pub standard_deviation: f64,
+}
+
+impl NoiseChannel for Gaussian {
+    fn name(self: &Self) -> String {
+        format!("gaussian (σ = {})", self.standard_deviation)
+    }
+
+    fn corrupt(
+        self: &Self,
+        state: &DVector<f64>,
+        domain: NetworkDomain,
+        rng: &mut dyn RngCore,
+    ) -> CorruptedCue {
+        let normal = Normal::new(0.0, self.standard_deviation)
+            .expect("Gaussian noise standard deviation must be finite and non-negative!");
+        let mut corrupted_state = state.map(|value| value + normal.sample(rng));
+        if domain == NetworkDomain::Tanh {
+            corrupted_state.apply(|value| *value = value.clamp(-1.0, 1.0));
+        }
+        fully_known(corrupted_state)
+    }
+}
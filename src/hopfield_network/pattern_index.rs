@@ -0,0 +1,187 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::HopfieldNetwork;
+
+/// The parameters of an approximate nearest-memory index, see set_pattern_index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatternIndexParameters {
+    /// The number of independent hash tables. More tables find more of the true nearest patterns.
+    pub tables: usize,
+    /// The number of hyperplanes hashed per table, at most 64. More bits give smaller, more selective buckets.
+    pub bits_per_table: usize,
+    /// The seed of the random hyperplanes.
+    pub seed: u64,
+}
+
+impl Default for PatternIndexParameters {
+    fn default() -> Self {
+        Self {
+            tables: 8,
+            bits_per_table: 12,
+            seed: 0,
+        }
+    }
+}
+
+/// An approximate nearest-memory index over stored patterns, using random hyperplane locality sensitive hashing
+/// (SimHash). Each table hashes a pattern to the signs of its projections onto random hyperplanes, so patterns with
+/// a small angle between them usually share a bucket in at least one table. Queries look in their own bucket and
+/// every bucket one bit away in each table.
+///
+/// The index only selects candidates: overlaps are then computed exactly for the candidates alone, which is much
+/// cheaper than every stored pattern once tens of thousands are stored. Patterns far from every query may be missed,
+/// but those are not the nearest memories anyway. The angle orders patterns by normalized overlap, which is the
+/// same order as overlap when every pattern has the same norm (e.g. Bipolar patterns).
+#[derive(Debug, Clone)]
+pub struct PatternIndex {
+    parameters: PatternIndexParameters,
+    /// The hyperplane normals of every table, stacked as rows: table t uses rows t * bits_per_table onwards.
+    hyperplanes: DMatrix<f64>,
+    buckets: Vec<HashMap<u64, Vec<usize>>>,
+}
+
+impl PatternIndex {
+    /// Create an empty index for patterns of a given dimension.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The dimension of the patterns.
+    /// * `parameters`: The parameters of the index.
+    pub fn new_pattern_index(dimension: usize, parameters: PatternIndexParameters) -> Self {
+        assert!(
+            parameters.tables > 0,
+            "A pattern index must have at least one table!"
+        );
+        assert!(
+            (1..=64).contains(&parameters.bits_per_table),
+            "A pattern index must hash between 1 and 64 bits per table!"
+        );
+
+        let mut rng = StdRng::seed_from_u64(parameters.seed);
+        let hyperplanes = DMatrix::<f64>::from_fn(
+            parameters.tables * parameters.bits_per_table,
+            dimension,
+            |_, _| rng.sample(rand_distr::StandardNormal),
+        );
+        Self {
+            parameters,
+            hyperplanes,
+            buckets: vec![HashMap::new(); parameters.tables],
+        }
+    }
+
+    /// Create an index over every column of a pattern matrix. Each pattern is indexed by its column.
+    pub fn from_patterns(patterns: &DMatrix<f64>, parameters: PatternIndexParameters) -> Self {
+        let mut index = Self::new_pattern_index(patterns.nrows(), parameters);
+        for (pattern_index, pattern) in patterns.column_iter().enumerate() {
+            index.insert(pattern_index, &pattern.into_owned());
+        }
+        index
+    }
+
+    /// Hash a vector in every table.
+    fn hashes(self: &Self, vector: &DVector<f64>) -> Vec<u64> {
+        let projections = &self.hyperplanes * vector;
+        projections
+            .as_slice()
+            .chunks(self.parameters.bits_per_table)
+            .map(|table_projections| {
+                table_projections
+                    .iter()
+                    .enumerate()
+                    .fold(0u64, |hash, (bit, projection)| {
+                        if *projection > 0.0 {
+                            hash | (1 << bit)
+                        } else {
+                            hash
+                        }
+                    })
+            })
+            .collect()
+    }
+
+    /// Add a pattern to the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern_index`: The index the pattern is stored at, returned by candidates.
+    /// * `pattern`: The pattern.
+    pub fn insert(self: &mut Self, pattern_index: usize, pattern: &DVector<f64>) {
+        for (table, hash) in self.hashes(pattern).into_iter().enumerate() {
+            self.buckets[table]
+                .entry(hash)
+                .or_default()
+                .push(pattern_index);
+        }
+    }
+
+    /// Get the candidate nearest patterns of a query: every pattern in the bucket of the query, or a bucket one bit
+    /// away, in any table.
+    ///
+    /// # Returns
+    ///
+    /// The indices of the candidate patterns, sorted and without duplicates.
+    pub fn candidates(self: &Self, query: &DVector<f64>) -> Vec<usize> {
+        let mut candidates = Vec::new();
+        for (table, hash) in self.hashes(query).into_iter().enumerate() {
+            let probes = std::iter::once(hash)
+                .chain((0..self.parameters.bits_per_table).map(|bit| hash ^ (1 << bit)));
+            for probe in probes {
+                if let Some(bucket) = self.buckets[table].get(&probe) {
+                    candidates.extend_from_slice(bucket);
+                }
+            }
+        }
+        candidates.sort_unstable();
+        candidates.dedup();
+        candidates
+    }
+}
+
+impl HopfieldNetwork {
+    /// Set (or unset) an approximate nearest-memory index over the stored patterns of this network
+    /// (see PatternIndex), used by nearest_memories, maximum_overlap, and retrieve_dense_approximate
+    /// to select candidate patterns before computing overlaps exactly.
+    ///
+    /// The index is kept up to date as patterns are learned and forgotten. It is only worth the memory once many
+    /// thousands of patterns are stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameters`: The parameters of the index, or None to remove the index.
+    pub fn set_pattern_index(self: &mut Self, parameters: Option<PatternIndexParameters>) {
+        self.pattern_index = parameters
+            .map(|parameters| PatternIndex::from_patterns(&self.stored_patterns, parameters));
+    }
+
+    /// Rebuild the pattern index from the stored patterns, if there is one, e.g. after stored patterns are removed.
+    pub(super) fn rebuild_pattern_index(self: &mut Self) {
+        if let Some(pattern_index) = &self.pattern_index {
+            self.pattern_index = Some(PatternIndex::from_patterns(
+                &self.stored_patterns,
+                pattern_index.parameters,
+            ));
+        }
+    }
+
+    /// Get the indices of the stored patterns to compute overlaps with a state against: the candidates of the
+    /// pattern index if there is one, or None to use every stored pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to find candidates for.
+    /// * `minimum_candidates`: If the index finds fewer candidates than this, None is returned instead.
+    pub(super) fn candidate_patterns(
+        self: &Self,
+        state: &DVector<f64>,
+        minimum_candidates: usize,
+    ) -> Option<Vec<usize>> {
+        self.pattern_index
+            .as_ref()
+            .map(|pattern_index| pattern_index.candidates(state))
+            .filter(|candidates| candidates.len() >= minimum_candidates)
+    }
+}
@@ -0,0 +1,242 @@
+use nalgebra::{Complex, ComplexField, DMatrix, DVector};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::TAU;
+
+/// The largest change of a continuous phasor unit that still counts as stable during relaxation.
+const CONTINUOUS_STABILITY_TOLERANCE: f64 = 1e-9;
+
+/// Get the phasor of unit magnitude with the given phase, e^{iθ}.
+pub fn phasor(phase: f64) -> Complex<f64> {
+    Complex::new(phase.cos(), phase.sin())
+}
+
+/// The phases a phasor unit may take.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum PhaseResolution {
+    /// Any phase: the activation normalizes the local field to unit magnitude, z = h / |h|.
+    Continuous,
+    /// One of K evenly spaced phases, the K-th roots of unity: the activation rounds the phase of the local field to
+    /// the nearest multiple of 2π / K. K = 2 is the Bipolar domain.
+    Quantized { states: usize },
+}
+
+impl PhaseResolution {
+    /// Map a local field to a phasor of unit magnitude, quantizing the phase if required.
+    ///
+    /// # Arguments
+    ///
+    /// * `field`: The local field of the unit.
+    /// * `current`: The current value of the unit, kept if the field is zero.
+    pub fn activation(self: &Self, field: Complex<f64>, current: Complex<f64>) -> Complex<f64> {
+        if field.modulus() == 0.0 {
+            return current;
+        }
+        match self {
+            PhaseResolution::Continuous => field / field.modulus(),
+            PhaseResolution::Quantized { states } => {
+                let sector = TAU / *states as f64;
+                phasor((field.argument() / sector).round() * sector)
+            }
+        }
+    }
+}
+
+/// A complex-valued Hopfield network of phasor units (Noest; Jankowski, Lozowski and Zurada), each a unit-magnitude
+/// complex number z_i = e^{iθ_i}, coupled by a Hermitian weight matrix W (W_ji = conj(W_ij)) with a zero diagonal.
+///
+/// The energy E = -Re(zᴴWz) is real for Hermitian weights, and each asynchronous update z_i = f(h_i) of the
+/// phase-quantizing activation f (see PhaseResolution) to the local field h = Wz never increases it, so relaxation
+/// reaches a stable state. Phasor networks store patterns of phases, e.g. for multi-valued images or oscillator
+/// synchronization.
+///
+/// This is a sibling of HopfieldNetwork rather than a NetworkDomain, as the weights and states are complex.
+#[derive(Debug, Clone)]
+pub struct PhasorHopfieldNetwork {
+    matrix: DMatrix<Complex<f64>>,
+    resolution: PhaseResolution,
+    maximum_relaxation_iterations: usize,
+    stored_patterns: usize,
+    rng: StdRng,
+}
+
+impl PhasorHopfieldNetwork {
+    /// Create a new phasor network with zero weights.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The number of units.
+    /// * `resolution`: The phases the units may take.
+    /// * `maximum_relaxation_iterations`: The maximum number of update sweeps of a relaxation.
+    /// * `seed`: The seed of the random update order and random states.
+    pub fn new_phasor_hopfield_network(
+        dimension: usize,
+        resolution: PhaseResolution,
+        maximum_relaxation_iterations: usize,
+        seed: u64,
+    ) -> Self {
+        assert!(
+            dimension > 0,
+            "Phasor network dimension must be a positive integer!"
+        );
+        if let PhaseResolution::Quantized { states } = resolution {
+            assert!(
+                states >= 2,
+                "Quantized phasor units must have at least two states!"
+            );
+        }
+
+        Self {
+            matrix: DMatrix::zeros(dimension, dimension),
+            resolution,
+            maximum_relaxation_iterations,
+            stored_patterns: 0,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Get the number of units of this network.
+    pub fn get_dimension(self: &Self) -> usize {
+        self.matrix.nrows()
+    }
+
+    /// Get the phase resolution of the units.
+    pub fn get_resolution(self: &Self) -> PhaseResolution {
+        self.resolution
+    }
+
+    /// Get the Hermitian weight matrix.
+    pub fn get_matrix(self: &Self) -> &DMatrix<Complex<f64>> {
+        &self.matrix
+    }
+
+    /// Get the number of patterns learned so far.
+    pub fn get_stored_pattern_count(self: &Self) -> usize {
+        self.stored_patterns
+    }
+
+    /// Set the weight matrix directly. The matrix is made Hermitian, W = (W + Wᴴ) / 2, and its diagonal set to zero.
+    pub fn set_matrix(self: &mut Self, matrix: DMatrix<Complex<f64>>) {
+        assert!(
+            matrix.is_square() && matrix.nrows() == self.get_dimension(),
+            "Phasor weight matrix must be square with the dimension of the network!"
+        );
+        self.matrix = (&matrix + matrix.adjoint()).unscale(2.0);
+        self.matrix.fill_diagonal(Complex::new(0.0, 0.0));
+    }
+
+    /// Build a phasor state from phases, z_i = e^{iθ_i}. The phases are not quantized.
+    pub fn state_from_phases(phases: &[f64]) -> DVector<Complex<f64>> {
+        DVector::from_iterator(phases.len(), phases.iter().map(|phase| phasor(*phase)))
+    }
+
+    /// Get the phase of each unit of a state, in (-π, π].
+    pub fn phases(state: &DVector<Complex<f64>>) -> Vec<f64> {
+        state.iter().map(|unit| unit.argument()).collect()
+    }
+
+    /// Generate a random state, each unit with a uniformly random phase allowed by the resolution.
+    pub fn random_state(self: &mut Self) -> DVector<Complex<f64>> {
+        let dimension = self.get_dimension();
+        let phases: Vec<f64> = match self.resolution {
+            PhaseResolution::Continuous => (0..dimension)
+                .map(|_| self.rng.gen_range(0.0..TAU))
+                .collect(),
+            PhaseResolution::Quantized { states } => (0..dimension)
+                .map(|_| self.rng.gen_range(0..states) as f64 * TAU / states as f64)
+                .collect(),
+        };
+        Self::state_from_phases(&phases)
+    }
+
+    /// Store a collection of phasor patterns with the complex Hebbian rule, W += ξξᴴ / N with a zero diagonal, which
+    /// keeps the weights Hermitian.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Every unit should have unit magnitude.
+    pub fn learn_states(self: &mut Self, patterns: &[DVector<Complex<f64>>]) {
+        let dimension = self.get_dimension();
+        assert!(
+            patterns.iter().all(|pattern| pattern.len() == dimension),
+            "Every pattern must have the same dimension as the network!"
+        );
+
+        let scale = Complex::new(1.0 / dimension as f64, 0.0);
+        for pattern in patterns {
+            self.matrix
+                .gerc(scale, pattern, pattern, Complex::new(1.0, 0.0));
+        }
+        self.matrix.fill_diagonal(Complex::new(0.0, 0.0));
+        self.stored_patterns += patterns.len();
+    }
+
+    /// Get the energy of a state, E = -Re(zᴴWz).
+    pub fn state_energy(self: &Self, state: &DVector<Complex<f64>>) -> f64 {
+        -state.dotc(&(&self.matrix * state)).re
+    }
+
+    /// Get the overlap of a state with a pattern, |ξᴴz| / N: 1 if the state equals the pattern up to a global phase
+    /// (which leaves the energy unchanged), and near 0 for unrelated states.
+    pub fn pattern_overlap(
+        self: &Self,
+        state: &DVector<Complex<f64>>,
+        pattern: &DVector<Complex<f64>>,
+    ) -> f64 {
+        pattern.dotc(state).modulus() / self.get_dimension() as f64
+    }
+
+    /// Count the units of a state that would change if updated.
+    pub fn count_unstable_units(self: &Self, state: &DVector<Complex<f64>>) -> usize {
+        let fields = &self.matrix * state;
+        fields
+            .iter()
+            .zip(state.iter())
+            .filter(|(field, unit)| {
+                (self.resolution.activation(**field, **unit) - **unit).modulus()
+                    > CONTINUOUS_STABILITY_TOLERANCE
+            })
+            .count()
+    }
+
+    /// Relax a state by sweeping over the units in a random order, setting each unit to the activation of its local
+    /// field, until a sweep changes no unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of sweeps performed.
+    pub fn relax_state(
+        self: &mut Self,
+        mut state: DVector<Complex<f64>>,
+    ) -> (DVector<Complex<f64>>, usize) {
+        assert_eq!(
+            state.len(),
+            self.get_dimension(),
+            "State must have the same dimension as the network!"
+        );
+
+        let mut unit_indices: Vec<usize> = (0..self.get_dimension()).collect();
+        let mut sweeps = 0;
+        while sweeps < self.maximum_relaxation_iterations {
+            sweeps += 1;
+            unit_indices.shuffle(&mut self.rng);
+            let mut changed_units = 0;
+            for unit in unit_indices.iter() {
+                let field = self.matrix.row(*unit).transpose().dot(&state);
+                let next = self.resolution.activation(field, state[*unit]);
+                if (next - state[*unit]).modulus() > CONTINUOUS_STABILITY_TOLERANCE {
+                    changed_units += 1;
+                }
+                state[*unit] = next;
+            }
+            if changed_units == 0 {
+                break;
+            }
+        }
+        (state, sweeps)
+    }
+}
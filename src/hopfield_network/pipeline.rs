@@ -0,0 +1,60 @@
+use super::{state_generator::StateGenerator, HopfieldNetwork};
+use nalgebra::DVector;
+use std::time::{Duration, Instant};
+
+/// The queue capacity per thread used by relaxation_pipeline if the network has no in flight limits set.
+const DEFAULT_QUEUE_CAPACITY_PER_THREAD: usize = 4;
+
+/// The throughput of a run of relaxation_pipeline.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PipelineReport {
+    pub states_relaxed: usize,
+    pub elapsed: Duration,
+    pub states_per_second: f64,
+}
+
+/// Run an end-to-end generate → relax → sink pipeline.
+///
+/// States are generated on their own thread and passed to the relaxation threads over bounded channels,
+/// so generation never runs far ahead of relaxation and memory use stays flat however many states are run.
+/// If the network has in flight limits set in its builder these are used, otherwise each queue holds
+/// DEFAULT_QUEUE_CAPACITY_PER_THREAD states per thread.
+///
+/// # Arguments
+///
+/// * `network`: The network to relax states in.
+/// * `state_generator`: The generator to create states from.
+/// * `num_states`: The number of states to generate and relax.
+/// * `threads`: The number of relaxation threads to spawn. If None, the default thread count of the network is used.
+/// * `sink`: Called with the index and relaxed value of each state, in order of completion.
+///
+/// # Returns
+///
+/// A PipelineReport describing the throughput of the pipeline.
+pub fn relaxation_pipeline(
+    network: &mut HopfieldNetwork,
+    state_generator: &mut StateGenerator,
+    num_states: usize,
+    threads: Option<usize>,
+    sink: impl FnMut(usize, DVector<f64>),
+) -> PipelineReport {
+    let threads = threads.unwrap_or_else(|| network.default_thread_count());
+    let queue_capacity = network
+        .in_flight_queue_capacity(threads)
+        .unwrap_or(DEFAULT_QUEUE_CAPACITY_PER_THREAD * threads);
+
+    let now = Instant::now();
+    let states_relaxed = network.concurrent_relax_state_stream_with_capacity(
+        (0..num_states).map(|_| state_generator.next_state()),
+        threads,
+        Some(queue_capacity),
+        sink,
+    );
+    let elapsed = now.elapsed();
+
+    PipelineReport {
+        states_relaxed,
+        elapsed,
+        states_per_second: states_relaxed as f64 / elapsed.as_secs_f64(),
+    }
+}
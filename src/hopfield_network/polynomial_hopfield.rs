@@ -0,0 +1,302 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::NetworkDomain;
+
+/// The interaction function F of a polynomial Hopfield network, applied to the overlap of a state with each pattern.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InteractionFunction {
+    /// F(x) = x^n. Order 2 is the classic Hopfield network (with a zero diagonal).
+    Polynomial { order: u32 },
+    /// F(x) = x^n for x > 0, and 0 otherwise. Patterns anti-aligned with the state do not contribute.
+    RectifiedPolynomial { order: u32 },
+}
+
+impl InteractionFunction {
+    /// Get the interaction order n.
+    pub fn order(self: &Self) -> u32 {
+        match *self {
+            Self::Polynomial { order } | Self::RectifiedPolynomial { order } => order,
+        }
+    }
+
+    /// Apply the interaction function to an overlap.
+    pub fn apply(self: &Self, overlap: f64) -> f64 {
+        match *self {
+            Self::Polynomial { order } => overlap.powi(order as i32),
+            Self::RectifiedPolynomial { order } => {
+                if overlap > 0.0 {
+                    overlap.powi(order as i32)
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+/// A dense associative memory with interactions of order n (Krotov and Hopfield), with the energy
+///
+/// E(σ) = -Σ_μ F(ξ_μ · σ),
+///
+/// where ξ_μ are the P stored patterns and F is the interaction function, e.g. F(x) = x^n. Each unit is updated to
+/// whichever of its values gives the lowest energy, so the energy never increases. The capacity grows as N^(n-1),
+/// against N for the classic (n = 2) network.
+///
+/// An interaction of order n couples every n units, so the weights cannot be held in a single matrix: the energy is
+/// computed from the stored patterns directly, tracking the overlap of the state with each pattern.
+///
+/// Only the discrete domains are supported. Binary patterns and states are mapped to bipolar values (2ξ - 1) before
+/// overlaps are taken, as in learn_states of HopfieldNetwork. Overlaps reach N, so N^n must stay well within the
+/// range of f64 (below about 10³⁰⁰).
+#[derive(Debug, Clone)]
+pub struct PolynomialHopfieldNetwork {
+    dimension: usize,
+    domain: NetworkDomain,
+    interaction_function: InteractionFunction,
+    /// The stored patterns, one per row, as bipolar values in the Binary domain.
+    patterns: DMatrix<f64>,
+    rng: StdRng,
+}
+
+impl PolynomialHopfieldNetwork {
+    /// Create a new polynomial Hopfield network with no stored patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The dimension of the stored patterns.
+    /// * `domain`: The domain of the stored patterns. Must be Binary, Bipolar, or Ternary.
+    /// * `interaction_function`: The interaction function F. The order must be at least 2.
+    /// * `seed`: The seed of the random unit update order.
+    pub fn new_polynomial_hopfield_network(
+        dimension: usize,
+        domain: NetworkDomain,
+        interaction_function: InteractionFunction,
+        seed: u64,
+    ) -> Self {
+        assert!(
+            dimension > 0,
+            "Polynomial Hopfield network dimension must be a positive integer!"
+        );
+        assert!(
+            matches!(
+                domain,
+                NetworkDomain::Binary | NetworkDomain::Bipolar | NetworkDomain::Ternary
+            ),
+            "Polynomial Hopfield network domain must be Binary, Bipolar, or Ternary!"
+        );
+        assert!(
+            interaction_function.order() >= 2,
+            "Polynomial Hopfield network interaction order must be at least 2!"
+        );
+
+        Self {
+            dimension,
+            domain,
+            interaction_function,
+            patterns: DMatrix::<f64>::zeros(0, dimension),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Get the dimension of this network.
+    pub fn get_dimension(self: &Self) -> usize {
+        self.dimension
+    }
+
+    /// Get the domain of this network.
+    pub fn get_domain(self: &Self) -> NetworkDomain {
+        self.domain
+    }
+
+    /// Get the interaction function of this network.
+    pub fn get_interaction_function(self: &Self) -> InteractionFunction {
+        self.interaction_function
+    }
+
+    /// Get the stored patterns, one per row. Binary patterns are held as bipolar values.
+    pub fn get_patterns(self: &Self) -> &DMatrix<f64> {
+        &self.patterns
+    }
+
+    /// Get the number of stored patterns.
+    pub fn pattern_count(self: &Self) -> usize {
+        self.patterns.nrows()
+    }
+
+    /// Get the possible values of a unit in the domain of this network.
+    fn unit_values(self: &Self) -> &'static [f64] {
+        match self.domain {
+            NetworkDomain::Binary => &[0.0, 1.0],
+            NetworkDomain::Bipolar => &[-1.0, 1.0],
+            _ => &[-1.0, 0.0, 1.0],
+        }
+    }
+
+    /// Map a unit value to the value used in overlaps: 2v - 1 in the Binary domain, and v otherwise.
+    fn signed_value(self: &Self, value: f64) -> f64 {
+        match self.domain {
+            NetworkDomain::Binary => 2.0 * value - 1.0,
+            _ => value,
+        }
+    }
+
+    /// Store a collection of patterns, appending each as a row of the pattern matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store. Each must have the same dimension as the network.
+    pub fn learn_states(self: &mut Self, patterns: &[DVector<f64>]) {
+        assert!(
+            patterns
+                .iter()
+                .all(|pattern| pattern.len() == self.dimension),
+            "Every pattern must have the same dimension as the network!"
+        );
+
+        let first_row = self.patterns.nrows();
+        self.patterns = self
+            .patterns
+            .clone()
+            .insert_rows(first_row, patterns.len(), 0.0);
+        for (offset, pattern) in patterns.iter().enumerate() {
+            let signed_pattern = pattern.map(|value| self.signed_value(value));
+            self.patterns
+                .set_row(first_row + offset, &signed_pattern.transpose());
+        }
+    }
+
+    /// Get the overlap ξ_μ · σ of a state with every stored pattern.
+    ///
+    /// # Returns
+    ///
+    /// The overlap with each stored pattern, in the order they were stored.
+    pub fn overlaps(self: &Self, state: &DVector<f64>) -> DVector<f64> {
+        &self.patterns * state.map(|value| self.signed_value(value))
+    }
+
+    /// Get the energy of a state, E(σ) = -Σ_μ F(ξ_μ · σ).
+    pub fn state_energy(self: &Self, state: &DVector<f64>) -> f64 {
+        -self
+            .overlaps(state)
+            .iter()
+            .map(|overlap| self.interaction_function.apply(*overlap))
+            .sum::<f64>()
+    }
+
+    /// Get the energy a state would have if one unit took a given value, from the overlaps of the state.
+    fn energy_with_unit_value(
+        self: &Self,
+        overlaps: &DVector<f64>,
+        unit_index: usize,
+        signed_delta: f64,
+    ) -> f64 {
+        -overlaps
+            .iter()
+            .zip(self.patterns.column(unit_index).iter())
+            .map(|(overlap, pattern_value)| {
+                self.interaction_function
+                    .apply(overlap + pattern_value * signed_delta)
+            })
+            .sum::<f64>()
+    }
+
+    /// Update a single unit to whichever of its values gives the lowest energy, keeping the current value on ties.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to update, in place.
+    /// * `overlaps`: The overlaps of the state with every stored pattern (see overlaps), updated in place.
+    /// * `unit_index`: The unit to update.
+    ///
+    /// # Returns
+    ///
+    /// True if the unit changed.
+    pub fn update_unit(
+        self: &Self,
+        state: &mut DVector<f64>,
+        overlaps: &mut DVector<f64>,
+        unit_index: usize,
+    ) -> bool {
+        let current_signed_value = self.signed_value(state[unit_index]);
+        let mut best_value = state[unit_index];
+        let mut best_energy = self.energy_with_unit_value(overlaps, unit_index, 0.0);
+        for value in self.unit_values() {
+            let signed_delta = self.signed_value(*value) - current_signed_value;
+            let energy = self.energy_with_unit_value(overlaps, unit_index, signed_delta);
+            if energy < best_energy {
+                best_energy = energy;
+                best_value = *value;
+            }
+        }
+
+        if best_value == state[unit_index] {
+            return false;
+        }
+        let signed_delta = self.signed_value(best_value) - current_signed_value;
+        overlaps.axpy(signed_delta, &self.patterns.column(unit_index), 1.0);
+        state[unit_index] = best_value;
+        true
+    }
+
+    /// Check if a state is stable: no single unit change lowers the energy.
+    pub fn is_stable(self: &Self, state: &DVector<f64>) -> bool {
+        let mut state = state.clone();
+        let mut overlaps = self.overlaps(&state);
+        (0..self.dimension)
+            .all(|unit_index| !self.update_unit(&mut state, &mut overlaps, unit_index))
+    }
+
+    /// Count the stored patterns that are stable states of this network. Past capacity, stored patterns stop being
+    /// stable, so this measures how the capacity scales with the interaction order.
+    pub fn stable_pattern_count(self: &Self) -> usize {
+        self.patterns
+            .row_iter()
+            .map(|row| {
+                // Map bipolar rows back to Binary values
+                row.transpose().map(|value| match self.domain {
+                    NetworkDomain::Binary => (value + 1.0) / 2.0,
+                    _ => value,
+                })
+            })
+            .filter(|pattern| self.is_stable(pattern))
+            .count()
+    }
+
+    /// Relax a state by sweeping over every unit in a random order, updating each unit in turn (see update_unit),
+    /// until a sweep changes no unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax.
+    /// * `maximum_sweeps`: The maximum number of sweeps.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of sweeps performed. If maximum_sweeps sweeps were performed,
+    /// the state may not be stable (see is_stable).
+    pub fn relax_state(
+        self: &mut Self,
+        mut state: DVector<f64>,
+        maximum_sweeps: usize,
+    ) -> (DVector<f64>, usize) {
+        let mut overlaps = self.overlaps(&state);
+        let mut unit_indices: Vec<usize> = (0..self.dimension).collect();
+        let mut sweeps = 0;
+        while sweeps < maximum_sweeps {
+            sweeps += 1;
+            unit_indices.shuffle(&mut self.rng);
+            let mut changed_units = 0;
+            for unit_index in &unit_indices {
+                if self.update_unit(&mut state, &mut overlaps, *unit_index) {
+                    changed_units += 1;
+                }
+            }
+            if changed_units == 0 {
+                break;
+            }
+        }
+        (state, sweeps)
+    }
+}
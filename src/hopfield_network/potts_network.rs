@@ -0,0 +1,242 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+
+/// A network of Potts units, each taking one of q discrete labels (Kanter's Potts Hopfield model).
+///
+/// Couplings are held in an Nq × Nq matrix J of q × q blocks: entry (i q + k, j q + l) is J_ij^kl, the coupling
+/// between unit i with label k and unit j with label l. With the one-hot embedding s of a state (s_{iq+k} = 1 when
+/// unit i has label k) the energy is E = -sᵀJs, as in the other networks of this crate, and the field of label k of
+/// unit i is h_i^k = (Js)_{iq+k}. Each unit is updated to the label with the largest field. The diagonal blocks J_ii
+/// are always zero, so every update lowers the energy and relaxation reaches a stable state.
+///
+/// Units with q = 2 behave as Bipolar units. Larger q suits clustering and graph coloring, where units choose one
+/// of several classes or colors.
+#[derive(Debug, Clone)]
+pub struct PottsNetwork {
+    dimension: usize,
+    labels: usize,
+    matrix: DMatrix<f64>,
+    maximum_relaxation_iterations: usize,
+    rng: StdRng,
+}
+
+impl PottsNetwork {
+    /// Create a new Potts network with zero couplings.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The number of units N.
+    /// * `labels`: The number of labels q of each unit, at least 2.
+    /// * `maximum_relaxation_iterations`: The maximum number of update sweeps of a relaxation.
+    /// * `seed`: The seed of the random unit update order.
+    pub fn new_potts_network(
+        dimension: usize,
+        labels: usize,
+        maximum_relaxation_iterations: usize,
+        seed: u64,
+    ) -> Self {
+        assert!(
+            dimension > 0,
+            "Potts network dimension must be a positive integer!"
+        );
+        assert!(labels >= 2, "Potts units must have at least two labels!");
+
+        Self {
+            dimension,
+            labels,
+            matrix: DMatrix::<f64>::zeros(dimension * labels, dimension * labels),
+            maximum_relaxation_iterations,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Get the number of units of this network.
+    pub fn get_dimension(self: &Self) -> usize {
+        self.dimension
+    }
+
+    /// Get the number of labels of each unit.
+    pub fn get_labels(self: &Self) -> usize {
+        self.labels
+    }
+
+    /// Get the coupling matrix, see PottsNetwork for the layout.
+    pub fn get_matrix(self: &Self) -> &DMatrix<f64> {
+        &self.matrix
+    }
+
+    /// Set the coupling matrix directly, e.g. for a clustering or optimization problem. The diagonal blocks are
+    /// set to zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `matrix`: The Nq × Nq coupling matrix, see PottsNetwork for the layout.
+    pub fn set_matrix(self: &mut Self, matrix: DMatrix<f64>) {
+        assert!(
+            matrix.nrows() == self.dimension * self.labels && matrix.is_square(),
+            "Potts coupling matrix must be square with one row per unit label!"
+        );
+        self.matrix = matrix;
+        self.clean_matrix();
+    }
+
+    /// Set every diagonal block J_ii to zero, removing the coupling of each unit to itself.
+    fn clean_matrix(self: &mut Self) {
+        let labels = self.labels;
+        for unit in 0..self.dimension {
+            self.matrix
+                .view_mut((unit * labels, unit * labels), (labels, labels))
+                .fill(0.0);
+        }
+    }
+
+    fn check_state(self: &Self, state: &[usize]) {
+        assert!(
+            state.len() == self.dimension && state.iter().all(|label| *label < self.labels),
+            "Potts states must have one label less than q per unit!"
+        );
+    }
+
+    /// Get the one-hot embedding of a state, with a 1 at i q + σ_i for each unit i.
+    pub fn one_hot(self: &Self, state: &[usize]) -> DVector<f64> {
+        self.check_state(state);
+        let mut embedding = DVector::<f64>::zeros(self.dimension * self.labels);
+        for (unit, label) in state.iter().enumerate() {
+            embedding[unit * self.labels + label] = 1.0;
+        }
+        embedding
+    }
+
+    /// Get the centered embedding of a pattern, q s - 1, which sums to zero over the labels of each unit.
+    fn centered_embedding(self: &Self, pattern: &[usize]) -> DVector<f64> {
+        self.one_hot(pattern)
+            .map(|value| self.labels as f64 * value - 1.0)
+    }
+
+    /// Store a collection of patterns with the Potts Hebbian rule,
+    /// J_ij^kl += (q δ(ξ_i, k) - 1)(q δ(ξ_j, l) - 1) / N for each pattern ξ.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The patterns to store, each one label per unit.
+    pub fn learn_states(self: &mut Self, patterns: &[Vec<usize>]) {
+        let scale = 1.0 / self.dimension as f64;
+        for pattern in patterns {
+            let embedding = self.centered_embedding(pattern);
+            self.matrix.ger(scale, &embedding, &embedding, 1.0);
+        }
+        self.clean_matrix();
+    }
+
+    /// Add antiferromagnetic couplings between the units of each edge, J_ij^kk -= strength for every label k, so
+    /// neighboring units are pushed to different labels. With q colors, the stable states of a network with only
+    /// these couplings are proper colorings of the graph where one exists, or colorings with few conflicts.
+    ///
+    /// # Arguments
+    ///
+    /// * `edges`: The edges, as pairs of unit indices. Each edge couples both directions.
+    /// * `strength`: The strength of each coupling. Must be strictly positive.
+    pub fn add_antiferromagnetic_edges(self: &mut Self, edges: &[(usize, usize)], strength: f64) {
+        assert!(
+            strength > 0.0,
+            "Antiferromagnetic coupling strength must be strictly positive!"
+        );
+        assert!(
+            edges
+                .iter()
+                .all(|(i, j)| *i < self.dimension && *j < self.dimension),
+            "Every edge must join units less than the network dimension!"
+        );
+
+        for (i, j) in edges {
+            for label in 0..self.labels {
+                self.matrix[(i * self.labels + label, j * self.labels + label)] -= strength;
+                self.matrix[(j * self.labels + label, i * self.labels + label)] -= strength;
+            }
+        }
+        self.clean_matrix();
+    }
+
+    /// Get the energy of a state, E = -sᵀJs for the one-hot embedding s.
+    pub fn state_energy(self: &Self, state: &[usize]) -> f64 {
+        let embedding = self.one_hot(state);
+        -embedding.dot(&(&self.matrix * &embedding))
+    }
+
+    /// Get the field of every label of every unit, h_i^k = (Js)_{iq+k}, as an N × q matrix.
+    pub fn label_fields(self: &Self, state: &[usize]) -> DMatrix<f64> {
+        let fields = &self.matrix * self.one_hot(state);
+        DMatrix::from_fn(self.dimension, self.labels, |unit, label| {
+            fields[unit * self.labels + label]
+        })
+    }
+
+    /// Get the label of a unit with the largest field, keeping the current label on ties.
+    fn best_label(self: &Self, fields: &DVector<f64>, unit: usize, current_label: usize) -> usize {
+        let unit_fields = fields.rows(unit * self.labels, self.labels);
+        let mut best_label = current_label;
+        for label in 0..self.labels {
+            if unit_fields[label] > unit_fields[best_label] {
+                best_label = label;
+            }
+        }
+        best_label
+    }
+
+    /// Count the units of a state that are not on the label with the largest field.
+    pub fn count_unstable_units(self: &Self, state: &[usize]) -> usize {
+        let fields = &self.matrix * self.one_hot(state);
+        (0..self.dimension)
+            .filter(|unit| self.best_label(&fields, *unit, state[*unit]) != state[*unit])
+            .count()
+    }
+
+    /// Get the overlap of a state with a pattern, m = Σ_i (q δ(σ_i, ξ_i) - 1) / (N (q - 1)):
+    /// 1 for the pattern itself, and 0 on average for unrelated states.
+    pub fn pattern_overlap(self: &Self, state: &[usize], pattern: &[usize]) -> f64 {
+        self.check_state(state);
+        self.check_state(pattern);
+        let matches = state
+            .iter()
+            .zip(pattern.iter())
+            .filter(|(label, pattern_label)| label == pattern_label)
+            .count() as f64;
+        (self.labels as f64 * matches - self.dimension as f64)
+            / (self.dimension * (self.labels - 1)) as f64
+    }
+
+    /// Relax a state by sweeping over the units in a random order, moving each unit to the label with the largest
+    /// field, until a sweep changes no unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax, one label per unit. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the relaxed state and the number of sweeps performed.
+    pub fn relax_state(self: &mut Self, mut state: Vec<usize>) -> (Vec<usize>, usize) {
+        let mut fields = &self.matrix * self.one_hot(&state);
+        let mut unit_indices: Vec<usize> = (0..self.dimension).collect();
+        let mut sweeps = 0;
+        while sweeps < self.maximum_relaxation_iterations {
+            sweeps += 1;
+            unit_indices.shuffle(&mut self.rng);
+            let mut changed_units = 0;
+            for unit in unit_indices.iter() {
+                let current_label = state[*unit];
+                let best_label = self.best_label(&fields, *unit, current_label);
+                if best_label != current_label {
+                    fields += self.matrix.column(unit * self.labels + best_label);
+                    fields -= self.matrix.column(unit * self.labels + current_label);
+                    state[*unit] = best_label;
+                    changed_units += 1;
+                }
+            }
+            if changed_units == 0 {
+                break;
+            }
+        }
+        (state, sweeps)
+    }
+}
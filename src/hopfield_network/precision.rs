@@ -0,0 +1,423 @@
+use super::HopfieldNetwork;
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The precision used to accumulate the products of weights and unit values when calculating local fields from
+/// reduced precision weights, see reduced_precision_local_fields.
+///
+/// The network itself always stores and accumulates its weights in f64. Reduced precision storage is simulated from
+/// QuantizedWeights, to check what storing the weights in f32 or f16 would cost before deploying them elsewhere.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AccumulationPrecision {
+    /// Accumulate in the storage precision. Fastest, but errors grow with the network dimension.
+    Storage,
+    /// Accumulate in f64 regardless of the storage precision.
+    Double,
+}
+
+/// Round a value to the nearest half precision value, see f64_to_f16_bits.
+fn round_to_half(value: f64) -> f64 {
+    f16_bits_to_f64(f64_to_f16_bits(value))
+}
+
+/// Calculate local fields from weights stored in a reduced precision, with a chosen accumulation precision.
+///
+/// Accumulating half precision weights in the storage precision rounds the state, every product, and every partial
+/// sum to half precision, as f16 hardware would.
+///
+/// # Arguments
+///
+/// * `weights`: The reduced precision weights, see quantize_weights.
+/// * `precision`: The precision the weights are stored in, Single or Half.
+/// * `state`: The state to calculate the local fields of.
+/// * `accumulation_precision`: The precision to accumulate the sums in.
+///
+/// # Returns
+///
+/// The local field of each unit, widened to `f64`.
+pub fn reduced_precision_local_fields(
+    weights: &QuantizedWeights,
+    precision: WeightPrecision,
+    state: &DVector<f64>,
+    accumulation_precision: AccumulationPrecision,
+) -> DVector<f64> {
+    match (precision, accumulation_precision) {
+        (WeightPrecision::Single, AccumulationPrecision::Storage) => {
+            (&weights.single * state.map(|value| value as f32)).map(|value| value as f64)
+        }
+        (WeightPrecision::Single, AccumulationPrecision::Double) => DVector::<f64>::from_iterator(
+            weights.single.nrows(),
+            weights.single.row_iter().map(|row| {
+                row.iter()
+                    .zip(state.iter())
+                    .map(|(weight, value)| *weight as f64 * value)
+                    .sum()
+            }),
+        ),
+        (WeightPrecision::Half, AccumulationPrecision::Storage) => {
+            let state = state.map(round_to_half);
+            DVector::<f64>::from_iterator(
+                weights.half_bits.nrows(),
+                weights.half_bits.row_iter().map(|row| {
+                    row.iter()
+                        .zip(state.iter())
+                        .fold(0.0, |sum, (bits, value)| {
+                            round_to_half(sum + round_to_half(f16_bits_to_f64(*bits) * value))
+                        })
+                }),
+            )
+        }
+        (WeightPrecision::Half, AccumulationPrecision::Double) => DVector::<f64>::from_iterator(
+            weights.half_bits.nrows(),
+            weights.half_bits.row_iter().map(|row| {
+                row.iter()
+                    .zip(state.iter())
+                    .map(|(bits, value)| f16_bits_to_f64(*bits) * value)
+                    .sum()
+            }),
+        ),
+        _ => panic!("Accumulation precision only applies to Single and Half precision weights!"),
+    }
+}
+
+/// The divergence between reduced precision and full precision calculations, see verify_reduced_precision.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PrecisionReport {
+    pub states_checked: usize,
+    /// The largest absolute difference in the local field of any unit.
+    pub max_field_divergence: f64,
+    /// The largest absolute difference in the energy of any state.
+    pub max_energy_divergence: f64,
+    /// The number of units, over all states, that the activation function maps differently.
+    pub activation_mismatches: usize,
+}
+
+/// Compare f32 or f16 weights against the f64 reference of a network on a sample of states.
+///
+/// Use this to check if the accuracy lost by reduced precision storage matters for a given network before using it.
+///
+/// # Arguments
+///
+/// * `network`: The network to take the reference weights from.
+/// * `states`: The sample of states to compare on.
+/// * `precision`: The precision to store the weights in, Single or Half.
+/// * `accumulation_precision`: The precision to accumulate the reduced precision sums in.
+///
+/// # Returns
+///
+/// A PrecisionReport holding the worst divergences found.
+pub fn verify_reduced_precision(
+    network: &HopfieldNetwork,
+    states: &[DVector<f64>],
+    precision: WeightPrecision,
+    accumulation_precision: AccumulationPrecision,
+) -> PrecisionReport {
+    let weights = quantize_weights(network);
+    let activation_fn = network.get_domain().activation_fn();
+
+    let mut report = PrecisionReport {
+        states_checked: states.len(),
+        max_field_divergence: 0.0,
+        max_energy_divergence: 0.0,
+        activation_mismatches: 0,
+    };
+    for state in states {
+        let reference_fields = network.local_fields(state);
+        let fields =
+            reduced_precision_local_fields(&weights, precision, state, accumulation_precision);
+
+        report.max_field_divergence = report
+            .max_field_divergence
+            .max((&fields - &reference_fields).amax());
+        report.max_energy_divergence = report
+            .max_energy_divergence
+            .max((fields.dot(state) - reference_fields.dot(state)).abs());
+        report.activation_mismatches += activation_fn(fields, &network.activation_parameters)
+            .iter()
+            .zip(activation_fn(reference_fields, &network.activation_parameters).iter())
+            .filter(|(value, reference_value)| value != reference_value)
+            .count();
+    }
+
+    report
+}
+
+/// Convert a value to the nearest IEEE 754 half precision (binary16) value, rounding ties to even. Values beyond the
+/// half precision range become infinite.
+///
+/// # Arguments
+///
+/// * `value`: The value to convert.
+///
+/// # Returns
+///
+/// The bits of the half precision value as a `u16`.
+pub fn f64_to_f16_bits(value: f64) -> u16 {
+    let sign = if value.is_sign_negative() { 0x8000 } else { 0 };
+    let magnitude = value.abs();
+    if magnitude.is_nan() {
+        return sign | 0x7e00;
+    }
+    // Below the smallest normal value (2^-14) values are subnormal, multiples of 2^-24
+    if magnitude < 2f64.powi(-14) {
+        // A subnormal that rounds up to 2^-14 gives 0x0400, which is exactly the smallest normal value
+        return sign | (magnitude * 2f64.powi(24)).round_ties_even() as u16;
+    }
+
+    let mut exponent = ((magnitude.to_bits() >> 52) & 0x7ff) as i32 - 1023;
+    let mut mantissa = ((magnitude / 2f64.powi(exponent) - 1.0) * 1024.0).round_ties_even() as u16;
+    if mantissa == 1024 {
+        exponent += 1;
+        mantissa = 0;
+    }
+    if exponent > 15 {
+        return sign | 0x7c00;
+    }
+    sign | (((exponent + 15) as u16) << 10) | mantissa
+}
+
+/// Convert IEEE 754 half precision (binary16) bits to the value they represent, see f64_to_f16_bits.
+///
+/// # Arguments
+///
+/// * `bits`: The bits of the half precision value.
+///
+/// # Returns
+///
+/// The value as an `f64`, which represents every half precision value exactly.
+pub fn f16_bits_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = ((bits >> 10) & 0x1f) as i32;
+    let mantissa = (bits & 0x3ff) as f64;
+    sign * match exponent {
+        0 => mantissa * 2f64.powi(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (1.0 + mantissa / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+/// The precision the weights of a network are stored in, see quantization_audit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WeightPrecision {
+    /// The f64 weights of the network, the reference.
+    Double,
+    /// f32 weights.
+    Single,
+    /// IEEE 754 half precision (f16) weights.
+    Half,
+    /// Symmetric 8 bit integer weights: each weight is stored as round(w / s) in [-127, 127] for a single scale s
+    /// with the largest weight mapped to ±127.
+    Int8,
+}
+
+impl WeightPrecision {
+    /// Get the number of bytes each weight takes in this precision.
+    pub fn bytes_per_weight(self: &Self) -> usize {
+        match self {
+            Self::Double => 8,
+            Self::Single => 4,
+            Self::Half => 2,
+            Self::Int8 => 1,
+        }
+    }
+}
+
+/// The weights of a network converted to every reduced precision, see quantize_weights.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizedWeights {
+    pub single: DMatrix<f32>,
+    /// The half precision weights, as their bits (see f16_bits_to_f64).
+    pub half_bits: DMatrix<u16>,
+    pub int8: DMatrix<i8>,
+    /// The scale of the 8 bit weights: each weight is its integer times this.
+    pub int8_scale: f64,
+}
+
+impl QuantizedWeights {
+    /// Get the weights of a precision widened back to f64, i.e. the weights a network stored in that precision
+    /// effectively uses.
+    ///
+    /// # Arguments
+    ///
+    /// * `precision`: The precision to get the weights of. Double is not stored, so must be taken from the network.
+    pub fn dequantized(self: &Self, precision: WeightPrecision) -> DMatrix<f64> {
+        match precision {
+            WeightPrecision::Double => {
+                panic!("Double precision weights are not quantized, take them from the network!")
+            }
+            WeightPrecision::Single => self.single.map(|weight| weight as f64),
+            WeightPrecision::Half => self.half_bits.map(f16_bits_to_f64),
+            WeightPrecision::Int8 => self.int8.map(|weight| weight as f64 * self.int8_scale),
+        }
+    }
+}
+
+/// Convert the weights of a network to f32, f16, and int8.
+///
+/// # Arguments
+///
+/// * `network`: The network to convert the weights of.
+///
+/// # Returns
+///
+/// The QuantizedWeights of the network.
+pub fn quantize_weights(network: &HopfieldNetwork) -> QuantizedWeights {
+    let matrix = network.get_matrix();
+    let largest_weight = matrix.amax();
+    let int8_scale = if largest_weight > 0.0 {
+        largest_weight / 127.0
+    } else {
+        1.0
+    };
+    QuantizedWeights {
+        single: matrix.map(|weight| weight as f32),
+        half_bits: matrix.map(f64_to_f16_bits),
+        int8: matrix.map(|weight| (weight / int8_scale).round().clamp(-127.0, 127.0) as i8),
+        int8_scale,
+    }
+}
+
+/// The recall accuracy of a network with its weights in one precision, see quantization_audit.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PrecisionRecall {
+    pub precision: WeightPrecision,
+    pub bytes_per_weight: usize,
+    /// The largest absolute difference of any weight from the f64 weight.
+    pub max_weight_error: f64,
+    /// The mean absolute difference of the weights from the f64 weights.
+    pub mean_weight_error: f64,
+    /// The fraction of probe cues relaxed exactly onto their targets.
+    pub recall_accuracy: f64,
+    /// The fraction of probe cues relaxed onto the same state as with the f64 weights.
+    pub agreement_with_double: f64,
+    /// The number of probe targets that are not stable states with these weights.
+    pub unstable_targets: usize,
+}
+
+/// The result of quantization_audit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuantizationAudit {
+    /// The reduced precision weights of the network, ready for deployment.
+    pub weights: QuantizedWeights,
+    pub probes: usize,
+    /// The recall of every precision, from f64 down to int8.
+    pub precisions: Vec<PrecisionRecall>,
+}
+
+impl QuantizationAudit {
+    /// Write the recall of every precision as CSV, with one row per precision.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The writer to write the CSV to.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(
+            writer,
+            "precision,bytes_per_weight,max_weight_error,mean_weight_error,recall_accuracy,agreement_with_double,unstable_targets"
+        )?;
+        for precision in &self.precisions {
+            writeln!(
+                writer,
+                "{:?},{},{},{},{},{},{}",
+                precision.precision,
+                precision.bytes_per_weight,
+                precision.max_weight_error,
+                precision.mean_weight_error,
+                precision.recall_accuracy,
+                precision.agreement_with_double,
+                precision.unstable_targets
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Convert a trained network to f32, f16, and int8 weights and compare the recall of every precision against the f64
+/// weights on a probe set, to judge the precision and robustness trade-offs before deploying on constrained hardware.
+///
+/// Reduced precision is simulated: the network relaxes the probes with each set of weights rounded to the precision
+/// and widened back to f64 (see QuantizedWeights::dequantized), so only the rounding of the weights is measured, not
+/// that of the accumulation (see verify_reduced_precision). Every precision relaxes the probes with the same update
+/// orders. The weights, random number generator, and weight storage of the network are restored afterwards.
+///
+/// # Arguments
+///
+/// * `network`: The trained network to audit.
+/// * `probes`: The probe set, as pairs of a cue and the target it should be recalled as.
+/// * `seed`: The seed of the update orders the probes are relaxed with.
+///
+/// # Returns
+///
+/// A QuantizationAudit with the quantized weights and the recall of every precision.
+pub fn quantization_audit(
+    network: &mut HopfieldNetwork,
+    probes: &[(DVector<f64>, DVector<f64>)],
+    seed: u64,
+) -> QuantizationAudit {
+    let weights = quantize_weights(network);
+    let double_matrix = network.matrix.clone();
+    let hebbian_weights = network.hebbian_weights;
+    let network_rng = network.rng.clone();
+
+    // The dense weights must be used, as the factorized Hebbian form would ignore the swapped matrix
+    network.hebbian_weights = false;
+    let mut double_states: Vec<DVector<f64>> = Vec::new();
+    let mut precisions = Vec::new();
+    for precision in [
+        WeightPrecision::Double,
+        WeightPrecision::Single,
+        WeightPrecision::Half,
+        WeightPrecision::Int8,
+    ] {
+        network.matrix = match precision {
+            WeightPrecision::Double => double_matrix.clone(),
+            _ => weights.dequantized(precision),
+        };
+        network.rng = StdRng::seed_from_u64(seed);
+
+        let relaxed_states: Vec<DVector<f64>> = probes
+            .iter()
+            .map(|(cue, _)| network.relax_state_iterations(cue.clone()).0)
+            .collect();
+        if precision == WeightPrecision::Double {
+            double_states = relaxed_states.clone();
+        }
+
+        let weight_errors = (&network.matrix - &double_matrix).abs();
+        let recalled = probes
+            .iter()
+            .zip(&relaxed_states)
+            .filter(|((_, target), state)| *state == target)
+            .count();
+        let agreeing = double_states
+            .iter()
+            .zip(&relaxed_states)
+            .filter(|(double_state, state)| double_state == state)
+            .count();
+        precisions.push(PrecisionRecall {
+            precision,
+            bytes_per_weight: precision.bytes_per_weight(),
+            max_weight_error: weight_errors.max(),
+            mean_weight_error: weight_errors.mean(),
+            recall_accuracy: recalled as f64 / probes.len() as f64,
+            agreement_with_double: agreeing as f64 / probes.len() as f64,
+            unstable_targets: probes
+                .iter()
+                .filter(|(_, target)| network.count_unstable_units(target) > 0)
+                .count(),
+        });
+    }
+
+    network.matrix = double_matrix;
+    network.hebbian_weights = hebbian_weights;
+    network.rng = network_rng;
+
+    QuantizationAudit {
+        weights,
+        probes: probes.len(),
+        precisions,
+    }
+}
@@ -0,0 +1,330 @@
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+
+use super::{HopfieldNetwork, NetworkDomain};
+
+/// Which statistics a preprocessing step uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StandardizationAxis {
+    /// Skip the step.
+    None,
+    /// Use the statistics of each pattern (or cue) over its own units.
+    PerPattern,
+    /// Use the statistics of each unit over the patterns the preprocessor was fitted to.
+    PerUnit,
+}
+
+/// The preprocessing of real-valued data into network states, see PatternPreprocessor.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PreprocessingConfig {
+    /// How the mean is removed from the data.
+    pub mean_removal: StandardizationAxis,
+    /// How the data is divided by its standard deviation, after mean removal.
+    pub variance_normalization: StandardizationAxis,
+    /// The threshold mapping standardized values to the values of a discrete domain: values above it are mapped to
+    /// the high value (1), and the others to the low value (0 for Binary, -1 for Bipolar). Ternary values within ±the
+    /// threshold are mapped to 0. Must be set for Binary, Bipolar, and Ternary networks, and None for the continuous
+    /// domains, which are given the standardized values.
+    pub binarization_threshold: Option<f64>,
+}
+
+/// The statistics of one pattern used by per-pattern preprocessing, needed to invert its transform.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PatternStatistics {
+    /// The mean removed from the pattern, 0 unless mean removal is per pattern.
+    pub mean: f64,
+    /// The standard deviation the pattern was divided by, 1 unless variance normalization is per pattern.
+    pub standard_deviation: f64,
+}
+
+/// A preprocessing pipeline fitted to a set of real-valued patterns, mapping data into states of a network and
+/// mapping recalled states back into the space of the data.
+///
+/// The transform removes the mean, normalizes the variance, and then binarizes the values, as configured. The inverse
+/// transform undoes these in reverse order. Binarization loses information, so each unit value is mapped back to a
+/// reconstruction level: the mean standardized value of that unit over the fitted patterns that were mapped to it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PatternPreprocessor {
+    config: PreprocessingConfig,
+    domain: NetworkDomain,
+    /// The mean of each unit removed by per-unit mean removal, or zeros.
+    unit_means: DVector<f64>,
+    /// The standard deviation of each unit divided out by per-unit variance normalization, or ones.
+    unit_standard_deviations: DVector<f64>,
+    /// The reconstruction level of each unit (row) and discrete value (column, in the order of discrete_values).
+    reconstruction_levels: DMatrix<f64>,
+}
+
+/// Get the standard deviation of some values, or 1 if they are constant, so dividing by it is always safe.
+fn safe_standard_deviation(values: impl Iterator<Item = f64> + Clone) -> f64 {
+    let count = values.clone().count() as f64;
+    let mean = values.clone().sum::<f64>() / count;
+    let variance = values.map(|value| (value - mean).powi(2)).sum::<f64>() / count;
+    if variance > 0.0 {
+        variance.sqrt()
+    } else {
+        1.0
+    }
+}
+
+impl PatternPreprocessor {
+    /// Fit a preprocessor to a set of real-valued patterns.
+    ///
+    /// # Arguments
+    ///
+    /// * `config`: The preprocessing to apply.
+    /// * `domain`: The domain of the network the states are for.
+    /// * `patterns`: The patterns to fit the per-unit statistics and reconstruction levels to.
+    pub fn fit(
+        config: PreprocessingConfig,
+        domain: NetworkDomain,
+        patterns: &[DVector<f64>],
+    ) -> Self {
+        assert!(
+            !patterns.is_empty(),
+            "Preprocessing must be fitted to at least one pattern!"
+        );
+        let dimension = patterns[0].len();
+        assert!(
+            patterns.iter().all(|pattern| pattern.len() == dimension),
+            "Every pattern must have the same dimension!"
+        );
+        match domain {
+            NetworkDomain::Binary | NetworkDomain::Bipolar => assert!(
+                config.binarization_threshold.is_some(),
+                "Preprocessing for a Binary or Bipolar network needs a binarization threshold!"
+            ),
+            NetworkDomain::Ternary => assert!(
+                config
+                    .binarization_threshold
+                    .is_some_and(|threshold| threshold >= 0.0),
+                "Preprocessing for a Ternary network needs a non-negative binarization threshold!"
+            ),
+            _ => assert!(
+                config.binarization_threshold.is_none(),
+                "Preprocessing for a continuous network must not binarize!"
+            ),
+        }
+
+        let mut preprocessor = Self {
+            config,
+            domain,
+            unit_means: DVector::zeros(dimension),
+            unit_standard_deviations: DVector::from_element(dimension, 1.0),
+            reconstruction_levels: DMatrix::zeros(0, 0),
+        };
+        let pattern_matrix = DMatrix::from_columns(patterns);
+        if config.mean_removal == StandardizationAxis::PerUnit {
+            preprocessor.unit_means = pattern_matrix.column_mean();
+        }
+        if config.variance_normalization == StandardizationAxis::PerUnit {
+            let centered: Vec<DVector<f64>> = patterns
+                .iter()
+                .map(|pattern| preprocessor.remove_mean(pattern).0)
+                .collect();
+            preprocessor.unit_standard_deviations = DVector::from_fn(dimension, |unit, _| {
+                safe_standard_deviation(centered.iter().map(|pattern| pattern[unit]))
+            });
+        }
+
+        let discrete_values = preprocessor.discrete_values();
+        let standardized: Vec<DVector<f64>> = patterns
+            .iter()
+            .map(|pattern| preprocessor.standardize(pattern).0)
+            .collect();
+        preprocessor.reconstruction_levels =
+            DMatrix::from_fn(dimension, discrete_values.len(), |unit, value_index| {
+                let values: Vec<f64> = standardized
+                    .iter()
+                    .map(|pattern| pattern[unit])
+                    .filter(|value| preprocessor.binarize(*value) == discrete_values[value_index])
+                    .collect();
+                // A value no fitted pattern was mapped to is reconstructed as itself
+                if values.is_empty() {
+                    discrete_values[value_index]
+                } else {
+                    values.iter().sum::<f64>() / values.len() as f64
+                }
+            });
+        preprocessor
+    }
+
+    /// Get the config of this preprocessor.
+    pub fn get_config(self: &Self) -> PreprocessingConfig {
+        self.config
+    }
+
+    /// Get the mean of each unit removed by per-unit mean removal, zeros otherwise.
+    pub fn get_unit_means(self: &Self) -> &DVector<f64> {
+        &self.unit_means
+    }
+
+    /// Get the standard deviation of each unit divided out by per-unit variance normalization, ones otherwise.
+    pub fn get_unit_standard_deviations(self: &Self) -> &DVector<f64> {
+        &self.unit_standard_deviations
+    }
+
+    /// Get the discrete unit values of the domain, in the order of the reconstruction levels. Empty for continuous
+    /// domains.
+    fn discrete_values(self: &Self) -> &'static [f64] {
+        match self.domain {
+            NetworkDomain::Binary => &[0.0, 1.0],
+            NetworkDomain::Bipolar => &[-1.0, 1.0],
+            NetworkDomain::Ternary => &[-1.0, 0.0, 1.0],
+            _ => &[],
+        }
+    }
+
+    /// Remove the mean of a pattern, returning the centered pattern and the per-pattern mean removed.
+    fn remove_mean(self: &Self, pattern: &DVector<f64>) -> (DVector<f64>, f64) {
+        match self.config.mean_removal {
+            StandardizationAxis::None => (pattern.clone(), 0.0),
+            StandardizationAxis::PerPattern => {
+                let mean = pattern.mean();
+                (pattern.add_scalar(-mean), mean)
+            }
+            StandardizationAxis::PerUnit => (pattern - &self.unit_means, 0.0),
+        }
+    }
+
+    /// Remove the mean and normalize the variance of a pattern, before binarization.
+    fn standardize(self: &Self, pattern: &DVector<f64>) -> (DVector<f64>, PatternStatistics) {
+        let (centered, mean) = self.remove_mean(pattern);
+        let (standardized, standard_deviation) = match self.config.variance_normalization {
+            StandardizationAxis::None => (centered, 1.0),
+            StandardizationAxis::PerPattern => {
+                let standard_deviation = safe_standard_deviation(centered.iter().copied());
+                (centered / standard_deviation, standard_deviation)
+            }
+            StandardizationAxis::PerUnit => {
+                (centered.component_div(&self.unit_standard_deviations), 1.0)
+            }
+        };
+        (
+            standardized,
+            PatternStatistics {
+                mean,
+                standard_deviation,
+            },
+        )
+    }
+
+    /// Map a standardized value to a unit value of the domain.
+    fn binarize(self: &Self, value: f64) -> f64 {
+        let Some(threshold) = self.config.binarization_threshold else {
+            return value;
+        };
+        match self.domain {
+            NetworkDomain::Binary if value > threshold => 1.0,
+            NetworkDomain::Binary => 0.0,
+            NetworkDomain::Ternary if value > threshold => 1.0,
+            NetworkDomain::Ternary if value < -threshold => -1.0,
+            NetworkDomain::Ternary => 0.0,
+            _ if value > threshold => 1.0,
+            _ => -1.0,
+        }
+    }
+
+    /// Map a real-valued pattern into a state of the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `pattern`: The pattern to transform, with the dimension the preprocessor was fitted to.
+    ///
+    /// # Returns
+    ///
+    /// The state, and the statistics of the pattern needed to invert the transform.
+    pub fn transform(self: &Self, pattern: &DVector<f64>) -> (DVector<f64>, PatternStatistics) {
+        assert_eq!(
+            pattern.len(),
+            self.unit_means.len(),
+            "Pattern must have the dimension the preprocessor was fitted to!"
+        );
+        let (standardized, statistics) = self.standardize(pattern);
+        (standardized.map(|value| self.binarize(value)), statistics)
+    }
+
+    /// Map a state of the network back into the space of the data, e.g. to compare a recalled state with the data.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to map, e.g. a relaxed state.
+    /// * `statistics`: The statistics returned by transform for the cue the state was recalled from.
+    ///
+    /// # Returns
+    ///
+    /// The reconstructed pattern.
+    pub fn inverse_transform(
+        self: &Self,
+        state: &DVector<f64>,
+        statistics: &PatternStatistics,
+    ) -> DVector<f64> {
+        let discrete_values = self.discrete_values();
+        let standardized = if discrete_values.is_empty() {
+            state.clone()
+        } else {
+            DVector::from_fn(state.len(), |unit, _| {
+                let value_index = discrete_values
+                    .iter()
+                    .position(|value| *value == state[unit])
+                    .expect("State values must be values of the domain of the preprocessor!");
+                self.reconstruction_levels[(unit, value_index)]
+            })
+        };
+        let centered = standardized.component_mul(&self.unit_standard_deviations)
+            * statistics.standard_deviation;
+        (centered + &self.unit_means).add_scalar(statistics.mean)
+    }
+}
+
+impl HopfieldNetwork {
+    /// Fit a preprocessor to real-valued patterns and store the preprocessed patterns with learn_states.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The real-valued patterns to store. Each must have the same dimension as the network.
+    /// * `config`: The preprocessing to apply.
+    ///
+    /// # Returns
+    ///
+    /// The fitted PatternPreprocessor, to recall with in relax_state_preprocessed.
+    pub fn learn_states_preprocessed(
+        self: &mut Self,
+        patterns: &[DVector<f64>],
+        config: PreprocessingConfig,
+    ) -> PatternPreprocessor {
+        self.check_pattern_dimensions(patterns);
+        let preprocessor = PatternPreprocessor::fit(config, self.domain, patterns);
+        let states: Vec<DVector<f64>> = patterns
+            .iter()
+            .map(|pattern| preprocessor.transform(pattern).0)
+            .collect();
+        self.learn_states(&states);
+        preprocessor
+    }
+
+    /// Relax a real-valued cue: preprocess it, relax the state, and map the relaxed state back into the space of the
+    /// data with the inverse transform.
+    ///
+    /// # Arguments
+    ///
+    /// * `cue`: The real-valued cue.
+    /// * `preprocessor`: The preprocessor the patterns were stored with, see learn_states_preprocessed.
+    ///
+    /// # Returns
+    ///
+    /// The reconstructed pattern recalled from the cue.
+    pub fn relax_state_preprocessed(
+        self: &mut Self,
+        cue: &DVector<f64>,
+        preprocessor: &PatternPreprocessor,
+    ) -> DVector<f64> {
+        assert!(
+            preprocessor.domain == self.domain,
+            "Preprocessor must be fitted for the domain of the network!"
+        );
+        let (state, statistics) = preprocessor.transform(cue);
+        let relaxed_state = self.relax_state(state).state;
+        preprocessor.inverse_transform(&relaxed_state, &statistics)
+    }
+}
@@ -0,0 +1,758 @@
+//! Slow, obviously correct reference implementations of the hot paths of a network: local fields, energies,
+//! Hebbian learning, and asynchronous update sweeps. Everything here is written as plain loops over single
+//! weights, straight from the definitions, so it can be trusted to check the optimized implementations
+//! (factorized fields, incremental fields, matrix products) against.
+//!
+//! The reference is used in two ways:
+//! - Golden fixtures (see GoldenFixture) record reference results for small networks, so any rewrite of the hot
+//!   paths can be checked with verify_golden_fixtures.
+//! - A network with a verification mode set (see set_verification_mode) cross-checks a sample of every batch
+//!   relaxation and every Hebbian learning call against the reference, and panics on a mismatch.
+
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path};
+
+use super::{
+    activation_function::ActivationParameters,
+    state_generator::StateGeneratorBuilder,
+    update_algorithm::{self, FieldStorage, UpdateAlgorithm, UpdateDynamics},
+    update_rule::ActivationRule,
+    weight_mask::WeightMask,
+    HopfieldNetwork, HopfieldNetworkBuilder, NetworkDomain,
+};
+
+/// Calculate the local field of every unit, h_i = Σ_j W_ij s_j.
+pub fn reference_local_fields(matrix: &DMatrix<f64>, state: &DVector<f64>) -> DVector<f64> {
+    let dimension = state.len();
+    let mut fields = DVector::<f64>::zeros(dimension);
+    for row in 0..dimension {
+        let mut field = 0.0;
+        for column in 0..dimension {
+            field += matrix[(row, column)] * state[column];
+        }
+        fields[row] = field;
+    }
+    fields
+}
+
+/// Calculate the energy of a state, E = -Σ_i Σ_j W_ij s_i s_j.
+pub fn reference_state_energy(matrix: &DMatrix<f64>, state: &DVector<f64>) -> f64 {
+    let dimension = state.len();
+    let mut energy = 0.0;
+    for row in 0..dimension {
+        for column in 0..dimension {
+            energy -= matrix[(row, column)] * state[row] * state[column];
+        }
+    }
+    energy
+}
+
+/// Calculate a single Hebbian weight of a collection of patterns, W_ij = Σ_μ ξ_i ξ_j / N,
+/// where Binary patterns are mapped to bipolar values 2ξ - 1.
+///
+/// # Arguments
+///
+/// * `patterns`: The stored patterns.
+/// * `domain`: The domain of the patterns.
+/// * `row`: The unit i receiving input through the weight.
+/// * `column`: The unit j sending input through the weight.
+/// * `zero_diagonal`: If true, every self coupling W_ii is 0.
+pub fn reference_hebbian_weight(
+    patterns: &[DVector<f64>],
+    domain: NetworkDomain,
+    row: usize,
+    column: usize,
+    zero_diagonal: bool,
+) -> f64 {
+    if zero_diagonal && row == column {
+        return 0.0;
+    }
+
+    let learning_value = |value: f64| match domain {
+        NetworkDomain::Binary => 2.0 * value - 1.0,
+        _ => value,
+    };
+    let mut weight = 0.0;
+    for pattern in patterns {
+        weight += learning_value(pattern[row]) * learning_value(pattern[column]);
+    }
+    weight / pattern_dimension(patterns) as f64
+}
+
+/// Calculate the Hebbian weight matrix of a collection of patterns, see reference_hebbian_weight.
+pub fn reference_hebbian_matrix(
+    patterns: &[DVector<f64>],
+    domain: NetworkDomain,
+    zero_diagonal: bool,
+) -> DMatrix<f64> {
+    let dimension = pattern_dimension(patterns);
+    DMatrix::<f64>::from_fn(dimension, dimension, |row, column| {
+        reference_hebbian_weight(patterns, domain, row, column, zero_diagonal)
+    })
+}
+
+fn pattern_dimension(patterns: &[DVector<f64>]) -> usize {
+    patterns
+        .first()
+        .expect("The reference Hebbian rule needs at least one pattern!")
+        .len()
+}
+
+/// Get the weights a mask leaves in place: a copy of the matrix with every masked coupling set to 0.
+pub fn reference_masked_matrix(matrix: &DMatrix<f64>, mask: &WeightMask) -> DMatrix<f64> {
+    let mut masked_matrix = matrix.clone();
+    match mask {
+        WeightMask::WithinModules { module_of_unit } => {
+            for row in 0..matrix.nrows() {
+                for column in 0..matrix.ncols() {
+                    if module_of_unit[row] != module_of_unit[column] {
+                        masked_matrix[(row, column)] = 0.0;
+                    }
+                }
+            }
+        }
+        WeightMask::RemovedCouplings(couplings) => {
+            for (row, column) in couplings {
+                masked_matrix[(*row, *column)] = 0.0;
+            }
+        }
+    }
+    masked_matrix
+}
+
+/// Activate a single local field with the activation function of a domain.
+fn reference_activation(
+    domain: NetworkDomain,
+    activation_parameters: &ActivationParameters,
+    field: f64,
+) -> f64 {
+    (domain.activation_fn())(DVector::from_element(1, field), activation_parameters)[0]
+}
+
+/// Update each unit of a state once, in the given order, each from its local field at the time of its update.
+///
+/// # Arguments
+///
+/// * `matrix`: The weight matrix.
+/// * `domain`: The domain of the network.
+/// * `activation_parameters`: The parameters of the activation function.
+/// * `input`: External input added to the local fields, if any.
+/// * `order`: The units to update, in update order.
+/// * `state`: The state to update.
+///
+/// # Returns
+///
+/// The updated state.
+pub fn reference_update_sweep(
+    matrix: &DMatrix<f64>,
+    domain: NetworkDomain,
+    activation_parameters: &ActivationParameters,
+    input: Option<&DVector<f64>>,
+    order: &[usize],
+    state: &DVector<f64>,
+) -> DVector<f64> {
+    let mut state = state.clone();
+    for unit_index in order {
+        let mut field = reference_local_fields(matrix, &state)[*unit_index];
+        if let Some(input) = input {
+            field += input[*unit_index];
+        }
+        state[*unit_index] = reference_activation(domain, activation_parameters, field);
+    }
+    state
+}
+
+/// Check that one state could be reached from another by an update sweep in the given order.
+///
+/// The sweep is replayed unit by unit, taking each unit value from the swept state. A unit value is accepted if
+/// it is the activation of any field within the tolerance of the reference field, so results that differ only
+/// by floating point rounding (e.g. a field of exactly 0 computed as 1e-17) are accepted.
+///
+/// # Arguments
+///
+/// * `matrix`: The weight matrix.
+/// * `domain`: The domain of the network.
+/// * `activation_parameters`: The parameters of the activation function.
+/// * `input`: External input added to the local fields, if any.
+/// * `order`: The units that were updated, in update order.
+/// * `state`: The state before the sweep.
+/// * `swept_state`: The state after the sweep.
+/// * `tolerance`: The relative tolerance of fields and continuous unit values.
+///
+/// # Returns
+///
+/// Ok if the swept state is valid, or an Err describing the first invalid unit.
+#[allow(clippy::too_many_arguments)]
+pub fn check_update_sweep(
+    matrix: &DMatrix<f64>,
+    domain: NetworkDomain,
+    activation_parameters: &ActivationParameters,
+    input: Option<&DVector<f64>>,
+    order: &[usize],
+    state: &DVector<f64>,
+    swept_state: &DVector<f64>,
+    tolerance: f64,
+) -> Result<(), String> {
+    let mut state = state.clone();
+    for unit_index in order {
+        let mut field = reference_local_fields(matrix, &state)[*unit_index];
+        if let Some(input) = input {
+            field += input[*unit_index];
+        }
+        let field_tolerance = tolerance * (1.0 + field.abs());
+        // Activations are non-decreasing, so these bound the activation of every field within the tolerance
+        let lowest_value =
+            reference_activation(domain, activation_parameters, field - field_tolerance);
+        let highest_value =
+            reference_activation(domain, activation_parameters, field + field_tolerance);
+        let value = swept_state[*unit_index];
+        if value < lowest_value - tolerance || value > highest_value + tolerance {
+            return Err(format!(
+                "unit {} was updated to {} but its reference field {} activates to {}",
+                unit_index,
+                value,
+                field,
+                reference_activation(domain, activation_parameters, field)
+            ));
+        }
+        state[*unit_index] = value;
+    }
+
+    if state != *swept_state {
+        return Err("units outside the update order were changed".to_string());
+    }
+    Ok(())
+}
+
+/// Check two values agree to within a relative tolerance.
+fn values_agree(value: f64, reference_value: f64, tolerance: f64) -> bool {
+    (value - reference_value).abs() <= tolerance * (1.0 + reference_value.abs())
+}
+
+/// Check two vectors agree to within a relative tolerance, returning the first index that does not.
+fn first_disagreement(
+    vector: &DVector<f64>,
+    reference_vector: &DVector<f64>,
+    tolerance: f64,
+) -> Option<usize> {
+    (0..reference_vector.len())
+        .find(|index| !values_agree(vector[*index], reference_vector[*index], tolerance))
+}
+
+/// How a network cross-checks batch operations against the reference implementation,
+/// see set_verification_mode.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct VerificationMode {
+    /// The number of states checked per batch relaxation, and weight rows checked per Hebbian learning call.
+    pub sample_size: usize,
+    /// The relative tolerance of fields, energies, weights, and continuous unit values.
+    pub tolerance: f64,
+}
+
+impl HopfieldNetwork {
+    /// Set (or unset) the verification mode of this network.
+    ///
+    /// While set, concurrent_relax_state_collection checks an evenly spaced sample of each batch against the
+    /// reference implementation: one update sweep of each sampled cue, and the local fields and energy of each
+    /// sampled result. Learning that leaves the weights Hebbian checks a sample of weight rows against the
+    /// reference Hebbian rule. A mismatch panics with a description of the failed check.
+    ///
+    /// The reference is slow (every local field is recomputed from single weights), so keep the sample small.
+    /// Verification does not use the network random number generator, so results are unchanged by it.
+    ///
+    /// # Arguments
+    ///
+    /// * `verification_mode`: The verification to perform, or None to disable verification.
+    pub fn set_verification_mode(self: &mut Self, verification_mode: Option<VerificationMode>) {
+        self.verification_mode = verification_mode;
+    }
+
+    /// Get the weights local fields are calculated with, including the weight mask if one is applied.
+    fn reference_matrix(self: &Self) -> DMatrix<f64> {
+        match &self.weight_mask {
+            Some(mask) => reference_masked_matrix(&self.matrix, mask),
+            None => self.matrix.clone(),
+        }
+    }
+
+    /// Pick the indices of a batch checked by the verification mode, evenly spaced through the batch.
+    pub(super) fn verification_sample_indices(self: &Self, batch_size: usize) -> Vec<usize> {
+        let Some(verification_mode) = self.verification_mode else {
+            return Vec::new();
+        };
+        let sample_size = verification_mode.sample_size.min(batch_size);
+        (0..sample_size)
+            .map(|sample_index| sample_index * batch_size / sample_size)
+            .collect()
+    }
+
+    /// Update each unit of a state once in the given order, with the optimized update of this network. The reference
+    /// replays deterministic asynchronous sweeps, so units are always updated that way here, whatever the update
+    /// dynamics, temperature, and update rule.
+    fn update_state_in_order(
+        self: &Self,
+        mut state: DVector<f64>,
+        order: &[usize],
+        input: Option<&DVector<f64>>,
+    ) -> DVector<f64> {
+        let activation_rule = ActivationRule {
+            activation_fn: self.domain.scalar_activation_fn(),
+            activation_parameters: self.activation_parameters,
+        };
+        update_algorithm::sweep_units(
+            self.local_field_operator(),
+            &activation_rule,
+            self.update_algorithm.single_state(self.dimension),
+            UpdateDynamics::Asynchronous,
+            order,
+            &mut state,
+            input,
+            None,
+            // The activation rule draws no random numbers
+            &mut StdRng::seed_from_u64(0),
+        );
+        state
+    }
+
+    /// Cross-check a sample of a batch relaxation against the reference, panicking on a mismatch.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample`: The index in the batch and the cue of each sampled state.
+    /// * `relaxed_states`: The relaxed states of the entire batch, in batch order.
+    pub(super) fn verify_relaxation_sample(
+        self: &Self,
+        sample: &[(usize, DVector<f64>)],
+        relaxed_states: &[DVector<f64>],
+    ) {
+        let Some(verification_mode) = self.verification_mode else {
+            return;
+        };
+        let tolerance = verification_mode.tolerance;
+        let matrix = self.reference_matrix();
+        let input = self
+            .external_input
+            .as_ref()
+            .and_then(|external_input| external_input.at(0));
+
+        for (batch_index, cue) in sample {
+            let mut order = self.get_unit_indices();
+            order.shuffle(&mut StdRng::seed_from_u64(*batch_index as u64));
+            let swept_cue = self.update_state_in_order(cue.clone(), &order, input.as_ref());
+            if let Err(error) = check_update_sweep(
+                &matrix,
+                self.domain,
+                &self.activation_parameters,
+                input.as_ref(),
+                &order,
+                cue,
+                &swept_cue,
+                tolerance,
+            ) {
+                panic!(
+                    "Verification failed! Update sweep of state {} of the batch: {}",
+                    batch_index, error
+                );
+            }
+
+            let relaxed_state = &relaxed_states[*batch_index];
+            let reference_fields = reference_local_fields(&matrix, relaxed_state);
+            if let Some(unit_index) = first_disagreement(
+                &self.local_fields(relaxed_state),
+                &reference_fields,
+                tolerance,
+            ) {
+                panic!(
+                    "Verification failed! Local field of unit {} of relaxed state {} of the batch is {} but the reference field is {}",
+                    unit_index,
+                    batch_index,
+                    self.local_fields(relaxed_state)[unit_index],
+                    reference_fields[unit_index]
+                );
+            }
+            let energy = self.state_energy(relaxed_state);
+            let reference_energy = reference_state_energy(&matrix, relaxed_state);
+            if !values_agree(energy, reference_energy, tolerance) {
+                panic!(
+                    "Verification failed! Energy of relaxed state {} of the batch is {} but the reference energy is {}",
+                    batch_index, energy, reference_energy
+                );
+            }
+        }
+    }
+
+    /// Cross-check a sample of weight rows against the reference Hebbian rule, if the weights are still Hebbian,
+    /// panicking on a mismatch.
+    pub(super) fn verify_hebbian_weights(self: &Self) {
+        let Some(verification_mode) = self.verification_mode else {
+            return;
+        };
+        if !self.hebbian_weights || self.stored_patterns.ncols() == 0 {
+            return;
+        }
+
+        let patterns: Vec<DVector<f64>> = self
+            .stored_patterns
+            .column_iter()
+            .map(|pattern| pattern.into_owned())
+            .collect();
+        for row in self.verification_sample_indices(self.dimension) {
+            for column in 0..self.dimension {
+                let reference_weight = reference_hebbian_weight(
+                    &patterns,
+                    self.domain,
+                    row,
+                    column,
+                    self.force_zero_diagonal && !self.uncleaned_diagonal,
+                );
+                if !values_agree(
+                    self.matrix[(row, column)],
+                    reference_weight,
+                    verification_mode.tolerance,
+                ) {
+                    panic!(
+                        "Verification failed! Weight ({}, {}) is {} but the reference Hebbian weight is {}",
+                        row,
+                        column,
+                        self.matrix[(row, column)],
+                        reference_weight
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// A small network and its reference results, serialized to check future rewrites of the hot paths against.
+///
+/// Fixtures are created once from the reference implementation with generate, and checked into fixtures/reference.
+/// The network is built with the default builder parameters (symmetric, zero diagonal) and the stored activation
+/// parameters, and learns its patterns with the Hebbian rule.
+///
+/// To regenerate the fixtures, e.g. after adding a field, run generate_golden_fixtures on fixtures/reference, which
+/// calls generate for every fixture with the patterns and states it was first created with, then check the diff.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoldenFixture {
+    pub domain: NetworkDomain,
+    pub activation_parameters: ActivationParameters,
+    pub patterns: Vec<Vec<f64>>,
+    /// The Hebbian weight matrix of the patterns, row by row.
+    pub matrix: Vec<Vec<f64>>,
+    pub states: Vec<Vec<f64>>,
+    /// The local fields of each state.
+    pub local_fields: Vec<Vec<f64>>,
+    /// The energy of each state.
+    pub energies: Vec<f64>,
+    /// The unit update order of the sweeps.
+    pub update_order: Vec<usize>,
+    /// Each state after one update sweep in the update order.
+    pub swept_states: Vec<Vec<f64>>,
+}
+
+impl GoldenFixture {
+    /// Create a fixture from the reference implementation.
+    ///
+    /// # Arguments
+    ///
+    /// * `domain`: The domain of the network.
+    /// * `activation_parameters`: The parameters of the activation function.
+    /// * `patterns`: The patterns to learn. There must be at least one.
+    /// * `states`: The states to calculate fields, energies, and update sweeps of.
+    /// * `order_seed`: The seed of the random update order.
+    pub fn generate(
+        domain: NetworkDomain,
+        activation_parameters: ActivationParameters,
+        patterns: &[DVector<f64>],
+        states: &[DVector<f64>],
+        order_seed: u64,
+    ) -> Self {
+        let matrix = reference_hebbian_matrix(patterns, domain, true);
+        let mut update_order: Vec<usize> = (0..matrix.nrows()).collect();
+        update_order.shuffle(&mut StdRng::seed_from_u64(order_seed));
+
+        Self {
+            domain,
+            activation_parameters,
+            patterns: patterns
+                .iter()
+                .map(|pattern| pattern.as_slice().to_vec())
+                .collect(),
+            matrix: matrix
+                .row_iter()
+                .map(|row| row.iter().copied().collect())
+                .collect(),
+            states: states
+                .iter()
+                .map(|state| state.as_slice().to_vec())
+                .collect(),
+            local_fields: states
+                .iter()
+                .map(|state| reference_local_fields(&matrix, state).as_slice().to_vec())
+                .collect(),
+            energies: states
+                .iter()
+                .map(|state| reference_state_energy(&matrix, state))
+                .collect(),
+            swept_states: states
+                .iter()
+                .map(|state| {
+                    reference_update_sweep(
+                        &matrix,
+                        domain,
+                        &activation_parameters,
+                        None,
+                        &update_order,
+                        state,
+                    )
+                    .as_slice()
+                    .to_vec()
+                })
+                .collect(),
+            update_order,
+        }
+    }
+
+    /// Load a fixture from a JSON file.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        serde_json::from_str(&contents).map_err(|error| error.to_string())
+    }
+
+    /// Save this fixture to a JSON file.
+    pub fn save(self: &Self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string_pretty(self).map_err(|error| error.to_string())?;
+        fs::write(path, contents).map_err(|error| error.to_string())
+    }
+
+    /// Check both the reference and the optimized implementation reproduce this fixture.
+    ///
+    /// The optimized implementation is checked with every per-state update algorithm and field storage.
+    /// Update sweeps are checked with check_update_sweep, so sweeps that differ from the fixture only by floating
+    /// point rounding are accepted.
+    ///
+    /// # Arguments
+    ///
+    /// * `tolerance`: The relative tolerance of fields, energies, weights, and continuous unit values.
+    ///
+    /// # Returns
+    ///
+    /// Ok, or an Err describing the first mismatch.
+    pub fn verify(self: &Self, tolerance: f64) -> Result<(), String> {
+        let to_vectors = |values: &[Vec<f64>]| -> Vec<DVector<f64>> {
+            values
+                .iter()
+                .map(|value| DVector::from_column_slice(value))
+                .collect()
+        };
+        let patterns = to_vectors(&self.patterns);
+        let states = to_vectors(&self.states);
+        let dimension = self.matrix.len();
+        let matrix =
+            DMatrix::<f64>::from_fn(dimension, dimension, |row, column| self.matrix[row][column]);
+
+        let check = |name: &str, value: f64, fixture_value: f64| {
+            if values_agree(value, fixture_value, tolerance) {
+                Ok(())
+            } else {
+                Err(format!(
+                    "{} is {} but the fixture has {}",
+                    name, value, fixture_value
+                ))
+            }
+        };
+        let weight_indices =
+            || (0..dimension).flat_map(|row| (0..dimension).map(move |column| (row, column)));
+
+        // The reference itself must still produce the fixture
+        let reference_matrix = reference_hebbian_matrix(&patterns, self.domain, true);
+        for (row, column) in weight_indices() {
+            check(
+                &format!("reference weight ({}, {})", row, column),
+                reference_matrix[(row, column)],
+                self.matrix[row][column],
+            )?;
+        }
+        for (state_index, state) in states.iter().enumerate() {
+            let fields = reference_local_fields(&matrix, state);
+            for unit_index in 0..dimension {
+                check(
+                    &format!("reference field {} of state {}", unit_index, state_index),
+                    fields[unit_index],
+                    self.local_fields[state_index][unit_index],
+                )?;
+            }
+            check(
+                &format!("reference energy of state {}", state_index),
+                reference_state_energy(&matrix, state),
+                self.energies[state_index],
+            )?;
+            let swept_state = reference_update_sweep(
+                &matrix,
+                self.domain,
+                &self.activation_parameters,
+                None,
+                &self.update_order,
+                state,
+            );
+            for unit_index in 0..dimension {
+                check(
+                    &format!(
+                        "reference unit {} of swept state {}",
+                        unit_index, state_index
+                    ),
+                    swept_state[unit_index],
+                    self.swept_states[state_index][unit_index],
+                )?;
+            }
+        }
+
+        for update_algorithm in [
+            UpdateAlgorithm::FullField,
+            UpdateAlgorithm::IncrementalField,
+        ] {
+            for field_storage in [FieldStorage::Dense, FieldStorage::Factorized] {
+                let mut network = HopfieldNetworkBuilder::new_hopfield_network_builder()
+                    .set_network_dimension(dimension)
+                    .set_network_domain(self.domain)
+                    .set_update_algorithm(update_algorithm)
+                    .set_field_storage(field_storage)
+                    .build();
+                network.activation_parameters = self.activation_parameters;
+                network.learn_states(&patterns);
+                let context = format!("{:?} with {:?} storage", update_algorithm, field_storage);
+
+                for (row, column) in weight_indices() {
+                    check(
+                        &format!("{}: weight ({}, {})", context, row, column),
+                        network.matrix[(row, column)],
+                        self.matrix[row][column],
+                    )?;
+                }
+                for (state_index, state) in states.iter().enumerate() {
+                    let fields = network.local_fields(state);
+                    for unit_index in 0..dimension {
+                        check(
+                            &format!("{}: field {} of state {}", context, unit_index, state_index),
+                            fields[unit_index],
+                            self.local_fields[state_index][unit_index],
+                        )?;
+                    }
+                    check(
+                        &format!("{}: energy of state {}", context, state_index),
+                        network.state_energy(state),
+                        self.energies[state_index],
+                    )?;
+
+                    let swept_state =
+                        network.update_state_in_order(state.clone(), &self.update_order, None);
+                    check_update_sweep(
+                        &matrix,
+                        self.domain,
+                        &self.activation_parameters,
+                        None,
+                        &self.update_order,
+                        state,
+                        &swept_state,
+                        tolerance,
+                    )
+                    .map_err(|error| {
+                        format!(
+                            "{}: update sweep of state {}: {}",
+                            context, state_index, error
+                        )
+                    })?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Verify every golden fixture (every .json file) in a directory, see GoldenFixture::verify.
+///
+/// # Arguments
+///
+/// * `directory`: The fixture directory, usually fixtures/reference.
+/// * `tolerance`: The relative tolerance of fields, energies, weights, and continuous unit values.
+///
+/// # Returns
+///
+/// The number of fixtures verified, or an Err naming the first fixture that failed and why.
+pub fn verify_golden_fixtures(directory: &Path, tolerance: f64) -> Result<usize, String> {
+    let mut fixture_paths: Vec<_> = fs::read_dir(directory)
+        .map_err(|error| error.to_string())?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    fixture_paths.sort();
+
+    for path in &fixture_paths {
+        GoldenFixture::load(path)
+            .and_then(|fixture| fixture.verify(tolerance))
+            .map_err(|error| format!("{}: {}", path.display(), error))?;
+    }
+    Ok(fixture_paths.len())
+}
+
+/// The fixtures in fixtures/reference: the file name, domain, and seed of each. The seed seeds both the state generator
+/// of the patterns and states and the update order.
+const GOLDEN_FIXTURES: [(&str, NetworkDomain, u64); 5] = [
+    ("binary", NetworkDomain::Binary, 11),
+    ("bipolar", NetworkDomain::Bipolar, 12),
+    ("ternary", NetworkDomain::Ternary, 13),
+    ("bounded_continuous", NetworkDomain::BoundedContinuous, 14),
+    ("tanh", NetworkDomain::Tanh, 15),
+];
+
+/// Regenerate the golden fixtures from the reference implementation and save them to a directory, see GoldenFixture.
+///
+/// Every fixture is an 8 unit network of one domain with the default activation parameters, learning 2 patterns and
+/// checked on 3 states, all drawn from a state generator seeded with the seed of the fixture.
+///
+/// # Arguments
+///
+/// * `directory`: The fixture directory, usually fixtures/reference. Existing fixtures are overwritten.
+///
+/// # Returns
+///
+/// The number of fixtures saved, or an Err naming the first fixture that could not be saved and why.
+pub fn generate_golden_fixtures(directory: &Path) -> Result<usize, String> {
+    for (name, domain, seed) in GOLDEN_FIXTURES {
+        let mut state_generator = StateGeneratorBuilder::new_state_generator_builder()
+            .set_domain(domain)
+            .set_dimension(8)
+            .set_generator_seed(seed)
+            .build();
+        let patterns = state_generator.create_state_collection(2);
+        let states = state_generator.create_state_collection(3);
+        let path = directory.join(format!("{name}.json"));
+        GoldenFixture::generate(
+            domain,
+            ActivationParameters::default(),
+            &patterns,
+            &states,
+            seed,
+        )
+        .save(&path)
+        .map_err(|error| format!("{}: {}", path.display(), error))?;
+    }
+    Ok(GOLDEN_FIXTURES.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn golden_fixtures_verify() {
+        assert_eq!(
+            verify_golden_fixtures(Path::new("fixtures/reference"), 1e-9),
+            Ok(5)
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use std::ops::ControlFlow;
+
+use super::{network_event::RelaxationCallbackRegistry, HopfieldNetwork, RelaxationOverrides};
+
+/// What relax_state records after every update sweep, see HopfieldNetwork::set_trajectory_recording.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TrajectoryRecording {
+    /// Record nothing, the default.
+    Disabled,
+    /// Record the energy of the state.
+    Energy,
+    /// Record the energy of the state and the number of unstable units.
+    EnergyAndUnstableUnits,
+}
+
+impl TrajectoryRecording {
+    /// Get an empty trajectory to record into, or None if recording is disabled.
+    fn empty_trajectory(self: &Self) -> Option<RelaxationTrajectory> {
+        match self {
+            Self::Disabled => None,
+            Self::Energy => Some(RelaxationTrajectory {
+                energies: Vec::new(),
+                unstable_units: None,
+            }),
+            Self::EnergyAndUnstableUnits => Some(RelaxationTrajectory {
+                energies: Vec::new(),
+                unstable_units: Some(Vec::new()),
+            }),
+        }
+    }
+}
+
+/// The energy (and optionally the number of unstable units) of a state after every update sweep of its relaxation,
+/// for plotting convergence.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelaxationTrajectory {
+    /// The energy of the state after each sweep, see state_energy.
+    pub energies: Vec<f64>,
+    /// The number of unstable units after each sweep, if recorded. These are counted against the same fields the
+    /// units were updated with, including any external input.
+    pub unstable_units: Option<Vec<usize>>,
+}
+
+/// The result of relaxing a single state, see HopfieldNetwork::relax_state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelaxationResult {
+    /// The relaxed state. This may not be a fixed point if the relaxation did not converge.
+    pub state: DVector<f64>,
+    /// Whether the state finished relaxation with fewer unstable units than the maximum. If not, relaxation
+    /// was stopped by the maximum number of iterations.
+    pub converged: bool,
+    /// The number of update iterations performed, 0 if the attractor was taken from the attractor cache.
+    pub iterations: usize,
+    /// The energy of the relaxed state, see state_energy.
+    pub energy: f64,
+    /// The number of units of the relaxed state that are unstable.
+    pub unstable_units: usize,
+    /// The trajectory of the relaxation, if recording is enabled (see set_trajectory_recording). A cached attractor
+    /// has an empty trajectory.
+    pub trajectory: Option<RelaxationTrajectory>,
+    /// Whether a relaxation callback stopped the relaxation early, see add_relaxation_callback.
+    pub aborted: bool,
+}
+
+impl RelaxationResult {
+    /// Consume this result, keeping only the relaxed state.
+    pub fn into_state(self: Self) -> DVector<f64> {
+        self.state
+    }
+}
+
+impl HopfieldNetwork {
+    /// Set what relax_state records after every update sweep, exposed as the trajectory of the RelaxationResult.
+    /// Recording the energy costs an energy calculation per sweep.
+    ///
+    /// # Arguments
+    ///
+    /// * `trajectory_recording`: What to record.
+    pub fn set_trajectory_recording(self: &mut Self, trajectory_recording: TrajectoryRecording) {
+        self.trajectory_recording = trajectory_recording;
+    }
+
+    /// Get what relax_state records after every update sweep.
+    pub fn get_trajectory_recording(self: &Self) -> TrajectoryRecording {
+        self.trajectory_recording
+    }
+
+    /// Relax a state as relax_state does without the attractor cache, recording the trajectory if enabled and calling
+    /// the relaxation callbacks.
+    pub(super) fn relax_state_recorded(
+        self: &mut Self,
+        state: DVector<f64>,
+        overrides: &RelaxationOverrides,
+    ) -> RelaxationResult {
+        let mut trajectory = self.trajectory_recording.empty_trajectory();
+        let mut relaxation_callbacks = std::mem::replace(
+            &mut self.relaxation_callbacks,
+            RelaxationCallbackRegistry::new_relaxation_callback_registry(),
+        );
+        let mut iteration = 0;
+        let mut aborted = false;
+        let (state, iterations, unstable_units) =
+            self.relax_state_observed(state, overrides, |network, state, unstable_units| {
+                iteration += 1;
+                if trajectory.is_none() && relaxation_callbacks.is_empty() {
+                    return ControlFlow::Continue(());
+                }
+
+                let energy = network.state_energy(state);
+                if let Some(trajectory) = &mut trajectory {
+                    trajectory.energies.push(energy);
+                    if let Some(trajectory_unstable_units) = &mut trajectory.unstable_units {
+                        trajectory_unstable_units.push(unstable_units as usize);
+                    }
+                }
+                let control_flow = relaxation_callbacks.call(iteration, state, energy);
+                aborted = control_flow.is_break();
+                control_flow
+            });
+        self.relaxation_callbacks = relaxation_callbacks;
+
+        let mut result =
+            self.relaxation_result(state, iterations, unstable_units as usize, trajectory);
+        result.aborted = aborted;
+        result
+    }
+
+    /// Collect the result of a cached attractor, which needed no relaxation. Its stability is checked again.
+    pub(super) fn cached_relaxation_result(
+        self: &Self,
+        attractor: DVector<f64>,
+    ) -> RelaxationResult {
+        let unstable_units = self.count_unstable_units(&attractor);
+        let trajectory = self.trajectory_recording.empty_trajectory();
+        self.relaxation_result(attractor, 0, unstable_units, trajectory)
+    }
+
+    /// Collect the result of a relaxation, calculating the energy of the relaxed state.
+    fn relaxation_result(
+        self: &Self,
+        state: DVector<f64>,
+        iterations: usize,
+        unstable_units: usize,
+        trajectory: Option<RelaxationTrajectory>,
+    ) -> RelaxationResult {
+        RelaxationResult {
+            converged: super::has_converged(
+                unstable_units as i32,
+                self.maximum_relaxation_unstable_units,
+            ),
+            iterations,
+            energy: self.state_energy(&state),
+            unstable_units,
+            trajectory,
+            aborted: false,
+            state,
+        }
+    }
+}
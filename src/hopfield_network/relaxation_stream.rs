@@ -0,0 +1,452 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+use std::{
+    io::{self, Read, Write},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    ops::ControlFlow,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{HopfieldNetwork, RelaxationOverrides};
+
+/// The GUID appended to the client key of a WebSocket handshake, from RFC 6455.
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The largest handshake request accepted from a client, in bytes.
+const MAXIMUM_HANDSHAKE_BYTES: usize = 8192;
+
+/// How long the accept thread sleeps between polls for new clients.
+const ACCEPT_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// How long a send to a client may block before the client is dropped, so a stalled browser cannot stall relaxation.
+const CLIENT_WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The opcodes of the WebSocket frames the publisher handles, from RFC 6455.
+const TEXT_OPCODE: u8 = 0x1;
+const CLOSE_OPCODE: u8 = 0x8;
+const PING_OPCODE: u8 = 0x9;
+const PONG_OPCODE: u8 = 0xA;
+
+/// A snapshot of a relaxation, sent to every client as a JSON text message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RelaxationFrame {
+    /// The number of update sweeps before this snapshot, 0 for the initial state.
+    pub sweep: usize,
+    pub state: Vec<f64>,
+    /// The energy of the state, see state_energy.
+    pub energy: f64,
+    /// Whether this is the last frame of the relaxation.
+    pub finished: bool,
+}
+
+/// A small WebSocket server that streams relaxation frames to browsers, e.g. to animate recall in a teaching demo.
+///
+/// Clients connect to ws://address/ with any path. Frames are sent to every connected client as JSON text messages
+/// (see RelaxationFrame). Before each frame the messages clients have sent are read: pings are answered, a close
+/// is acknowledged and the client dropped, and anything else is ignored. Clients that disconnect, or that do not
+/// accept a frame within a second, are dropped. Only what streaming needs of RFC 6455 is implemented: the opening
+/// handshake, unfragmented text messages, and the close, ping and pong control frames.
+#[derive(Debug)]
+pub struct RelaxationPublisher {
+    clients: Arc<Mutex<Vec<WebSocketClient>>>,
+    running: Arc<AtomicBool>,
+    frame_interval: Duration,
+}
+
+impl RelaxationPublisher {
+    /// Start a publisher listening on an address, accepting clients on a background thread until it is dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `address`: The address to listen on, e.g. "127.0.0.1:9001".
+    /// * `frame_interval`: The time to wait after sending each frame, so relaxations are slow enough to watch.
+    pub fn bind(address: impl ToSocketAddrs, frame_interval: Duration) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let accept_clients = Arc::clone(&clients);
+        let accept_running = Arc::clone(&running);
+        thread::spawn(move || {
+            while accept_running.load(Ordering::Relaxed) {
+                match listener.accept() {
+                    Ok((stream, _)) => {
+                        // A failed handshake only loses that client
+                        if let Ok(stream) = accept_websocket(stream) {
+                            accept_clients.lock().unwrap().push(stream);
+                        }
+                    }
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_POLL_INTERVAL)
+                    }
+                    Err(_) => break,
+                }
+            }
+        });
+
+        Ok(Self {
+            clients,
+            running,
+            frame_interval,
+        })
+    }
+
+    /// Get the number of connected clients.
+    pub fn client_count(self: &Self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+
+    /// Wait until at least a number of clients are connected, e.g. so a demo starts once the browser is open.
+    ///
+    /// # Returns
+    ///
+    /// True if enough clients connected before the timeout.
+    pub fn wait_for_clients(self: &Self, clients: usize, timeout: Duration) -> bool {
+        let start = Instant::now();
+        while self.client_count() < clients {
+            if start.elapsed() >= timeout {
+                return false;
+            }
+            thread::sleep(ACCEPT_POLL_INTERVAL);
+        }
+        true
+    }
+
+    /// Send a frame to every connected client, dropping clients that closed or can no longer be sent to, and then
+    /// wait for the frame interval.
+    ///
+    /// The clients are sent to without holding the lock on them, so a slow client does not block new clients
+    /// connecting.
+    pub fn publish(self: &Self, frame: &RelaxationFrame) {
+        let message = serde_json::to_string(frame).unwrap();
+        let encoded = encode_frame(TEXT_OPCODE, message.as_bytes());
+
+        let mut clients = std::mem::take(&mut *self.clients.lock().unwrap());
+        clients.retain_mut(|client| {
+            matches!(client.handle_incoming(), Ok(true))
+                && client.stream.write_all(&encoded).is_ok()
+        });
+        // Clients accepted while sending were added to the emptied list
+        self.clients.lock().unwrap().extend(clients);
+        thread::sleep(self.frame_interval);
+    }
+}
+
+/// A client of a RelaxationPublisher that has completed the opening handshake.
+#[derive(Debug)]
+struct WebSocketClient {
+    stream: TcpStream,
+    /// Bytes received from the client that do not yet form a whole frame.
+    received: Vec<u8>,
+}
+
+impl WebSocketClient {
+    /// Read every message the client has sent so far without blocking, answering pings and acknowledging a close.
+    ///
+    /// # Returns
+    ///
+    /// Whether the client is still open.
+    fn handle_incoming(self: &mut Self) -> io::Result<bool> {
+        self.stream.set_nonblocking(true)?;
+        let mut buffer = [0; 1024];
+        let read_result = loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => break Ok(false),
+                Ok(read) => self.received.extend_from_slice(&buffer[..read]),
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break Ok(true),
+                Err(error) => break Err(error),
+            }
+        };
+        self.stream.set_nonblocking(false)?;
+        if !read_result? {
+            return Ok(false);
+        }
+
+        while let Some((opcode, payload, length)) = decode_client_frame(&self.received) {
+            self.received.drain(..length);
+            match opcode {
+                CLOSE_OPCODE => {
+                    // Echo the status code back, as the closing handshake asks
+                    let _ = self.stream.write_all(&encode_frame(
+                        CLOSE_OPCODE,
+                        &payload[..payload.len().min(2)],
+                    ));
+                    return Ok(false);
+                }
+                PING_OPCODE => self
+                    .stream
+                    .write_all(&encode_frame(PONG_OPCODE, &payload))?,
+                _ => {}
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Encode an unfragmented, unmasked frame, as sent by a server.
+fn encode_frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(payload.len() + 10);
+    encoded.push(0x80 | opcode); // A final frame
+    match payload.len() {
+        length if length < 126 => encoded.push(length as u8),
+        length if length <= u16::MAX as usize => {
+            encoded.push(126);
+            encoded.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            encoded.push(127);
+            encoded.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    encoded.extend_from_slice(payload);
+    encoded
+}
+
+/// Decode the first frame of the bytes received from a client, unmasking its payload.
+///
+/// # Returns
+///
+/// The opcode and payload of the frame, and the number of bytes it took up, or None if the bytes do not yet hold a
+/// whole frame.
+fn decode_client_frame(received: &[u8]) -> Option<(u8, Vec<u8>, usize)> {
+    let opcode = received.first()? & 0x0F;
+    let second_byte = *received.get(1)?;
+    let masked = second_byte & 0x80 != 0;
+    let (payload_length, mut offset) = match second_byte & 0x7F {
+        126 => (
+            u16::from_be_bytes(received.get(2..4)?.try_into().ok()?) as usize,
+            4,
+        ),
+        127 => (
+            u64::from_be_bytes(received.get(2..10)?.try_into().ok()?) as usize,
+            10,
+        ),
+        length => (length as usize, 2),
+    };
+    let mask = if masked {
+        let mask: [u8; 4] = received.get(offset..offset + 4)?.try_into().ok()?;
+        offset += 4;
+        mask
+    } else {
+        [0; 4]
+    };
+    let payload = received
+        .get(offset..offset.checked_add(payload_length)?)?
+        .iter()
+        .enumerate()
+        .map(|(index, byte)| byte ^ mask[index % 4])
+        .collect();
+    Some((opcode, payload, offset + payload_length))
+}
+
+impl Drop for RelaxationPublisher {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Read the opening handshake of a WebSocket client and accept it.
+fn accept_websocket(mut stream: TcpStream) -> io::Result<WebSocketClient> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut request = Vec::new();
+    let mut buffer = [0; 1024];
+    while !request.ends_with(b"\r\n\r\n") {
+        let read = stream.read(&mut buffer)?;
+        if read == 0 || request.len() + read > MAXIMUM_HANDSHAKE_BYTES {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Incomplete WebSocket handshake!",
+            ));
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let key = request
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("sec-websocket-key"))
+        .map(|(_, value)| value.trim().to_string())
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Missing Sec-WebSocket-Key header!",
+            )
+        })?;
+    let accept = websocket_accept_key(&key);
+    write!(
+        stream,
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {accept}\r\n\r\n"
+    )?;
+    stream.set_read_timeout(None)?;
+    stream.set_write_timeout(Some(CLIENT_WRITE_TIMEOUT))?;
+    Ok(WebSocketClient {
+        stream,
+        received: Vec::new(),
+    })
+}
+
+/// Get the Sec-WebSocket-Accept value that answers the Sec-WebSocket-Key of a client.
+fn websocket_accept_key(key: &str) -> String {
+    base64_encode(&sha1(format!("{key}{WEBSOCKET_GUID}").as_bytes()))
+}
+
+/// Get the SHA-1 digest of a message, as needed by the WebSocket handshake.
+fn sha1(message: &[u8]) -> [u8; 20] {
+    let mut hash: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend_from_slice(&(message.len() as u64 * 8).to_be_bytes());
+
+    for block in padded.chunks(64) {
+        let mut words = [0u32; 80];
+        for (index, word) in block.chunks(4).enumerate() {
+            words[index] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for index in 16..80 {
+            words[index] =
+                (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16])
+                    .rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = hash;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (value, added) in hash.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(added);
+        }
+    }
+
+    let mut digest = [0; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(hash) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+/// Encode bytes as padded standard base64.
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (index, byte)| {
+            group | (*byte as u32) << (16 - 8 * index)
+        });
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * index) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+impl HopfieldNetwork {
+    /// Relax a state while streaming a frame of the initial state and of the state after every update sweep to the
+    /// clients of a publisher. The last frame is marked finished.
+    ///
+    /// This always relaxes the state, bypassing the attractor cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax. Consumes the state.
+    /// * `publisher`: The publisher to stream the frames with.
+    ///
+    /// # Returns
+    ///
+    /// The relaxed state.
+    pub fn relax_state_streamed(
+        self: &mut Self,
+        state: DVector<f64>,
+        publisher: &RelaxationPublisher,
+    ) -> DVector<f64> {
+        publisher.publish(&RelaxationFrame {
+            sweep: 0,
+            state: state.iter().copied().collect(),
+            energy: self.state_energy(&state),
+            finished: false,
+        });
+        let mut sweep = 0;
+        let overrides = RelaxationOverrides::default();
+        let (state, _, _) = self.relax_state_observed(state, &overrides, |network, state, _| {
+            sweep += 1;
+            publisher.publish(&RelaxationFrame {
+                sweep,
+                state: state.iter().copied().collect(),
+                energy: network.state_energy(state),
+                finished: false,
+            });
+            ControlFlow::Continue(())
+        });
+        publisher.publish(&RelaxationFrame {
+            sweep,
+            state: state.iter().copied().collect(),
+            energy: self.state_energy(&state),
+            finished: true,
+        });
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accept_key_matches_rfc_6455_example() {
+        assert_eq!(
+            websocket_accept_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn decodes_rfc_6455_masked_frame() {
+        let received = [
+            0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58, 0x89,
+        ];
+        assert_eq!(
+            decode_client_frame(&received),
+            Some((TEXT_OPCODE, b"Hello".to_vec(), 11))
+        );
+        assert_eq!(decode_client_frame(&received[..10]), None);
+    }
+
+    #[test]
+    fn encodes_rfc_6455_unmasked_frame() {
+        assert_eq!(
+            encode_frame(TEXT_OPCODE, b"Hello"),
+            [0x81, 0x05, 0x48, 0x65, 0x6c, 0x6c, 0x6f]
+        );
+    }
+}
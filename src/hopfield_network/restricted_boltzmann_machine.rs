@@ -0,0 +1,340 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    activation_function::{sigmoid_activation_function, ActivationParameters},
+    NetworkDomain,
+};
+
+/// The standard deviation of the initial weights of a restricted Boltzmann machine.
+const INITIAL_WEIGHT_DEVIATION: f64 = 0.01;
+
+/// The parameters of contrastive divergence training, see RestrictedBoltzmannMachine::train_contrastive_divergence.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ContrastiveDivergenceParameters {
+    /// The number of Gibbs steps k of the negative phase. CD-1 is usually enough to learn useful memories.
+    pub gibbs_steps: usize,
+    /// The learning rate of the weights and biases.
+    pub learning_rate: f64,
+    /// The number of passes over the training patterns.
+    pub epochs: usize,
+    /// The number of patterns averaged over per update.
+    pub batch_size: usize,
+}
+
+impl Default for ContrastiveDivergenceParameters {
+    fn default() -> Self {
+        Self {
+            gibbs_steps: 1,
+            learning_rate: 0.1,
+            epochs: 100,
+            batch_size: 10,
+        }
+    }
+}
+
+/// A restricted Boltzmann machine: a layer of visible units coupled to a layer of hidden units by a rectangular weight
+/// matrix, with no couplings within a layer. The energy of a joint state is
+///
+/// E(v, h) = -vᵀWh - aᵀv - bᵀh,
+///
+/// where a and b are the visible and hidden biases. Given one layer the units of the other are independent, so each
+/// layer is sampled at once. Unlike a Hopfield network, whose weights are prescribed by a learning rule, the weights
+/// are learned by contrastive divergence so the distribution of the visible units matches the training patterns.
+///
+/// Units are either Binary, active with probability sigmoid(field), or Bipolar, +1 with probability
+/// sigmoid(2 * field). With small biases the energy of Bipolar units barely changes when every unit is inverted, so
+/// like a Hopfield network a Bipolar machine also learns the inverse of each pattern.
+#[derive(Debug, Clone)]
+pub struct RestrictedBoltzmannMachine {
+    domain: NetworkDomain,
+    /// The weights, one row per visible unit and one column per hidden unit.
+    weights: DMatrix<f64>,
+    visible_bias: DVector<f64>,
+    hidden_bias: DVector<f64>,
+    activation_parameters: ActivationParameters,
+    rng: StdRng,
+}
+
+impl RestrictedBoltzmannMachine {
+    /// Create a new restricted Boltzmann machine with small random weights and zero biases.
+    ///
+    /// # Arguments
+    ///
+    /// * `visible_units`: The number of visible units, the dimension of the patterns.
+    /// * `hidden_units`: The number of hidden units.
+    /// * `domain`: The domain of every unit. Must be Binary or Bipolar.
+    /// * `seed`: The seed of the initial weights and of every sample.
+    pub fn new_restricted_boltzmann_machine(
+        visible_units: usize,
+        hidden_units: usize,
+        domain: NetworkDomain,
+        seed: u64,
+    ) -> Self {
+        assert!(
+            visible_units > 0 && hidden_units > 0,
+            "Restricted Boltzmann machine layers must have at least one unit!"
+        );
+        assert!(
+            matches!(domain, NetworkDomain::Binary | NetworkDomain::Bipolar),
+            "Restricted Boltzmann machine domain must be Binary or Bipolar!"
+        );
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let weights = DMatrix::<f64>::from_fn(visible_units, hidden_units, |_, _| {
+            INITIAL_WEIGHT_DEVIATION * rng.sample::<f64, _>(rand_distr::StandardNormal)
+        });
+        // The sigmoid gain, as Bipolar units are +1 with probability sigmoid(2 * field)
+        let activation_parameters = ActivationParameters {
+            gain: match domain {
+                NetworkDomain::Binary => 1.0,
+                _ => 2.0,
+            },
+            ..ActivationParameters::default()
+        };
+        Self {
+            domain,
+            weights,
+            visible_bias: DVector::zeros(visible_units),
+            hidden_bias: DVector::zeros(hidden_units),
+            activation_parameters,
+            rng,
+        }
+    }
+
+    /// Get the number of visible units.
+    pub fn get_visible_units(self: &Self) -> usize {
+        self.weights.nrows()
+    }
+
+    /// Get the number of hidden units.
+    pub fn get_hidden_units(self: &Self) -> usize {
+        self.weights.ncols()
+    }
+
+    /// Get the domain of the units.
+    pub fn get_domain(self: &Self) -> NetworkDomain {
+        self.domain
+    }
+
+    /// Get the weights, one row per visible unit and one column per hidden unit.
+    pub fn get_weights(self: &Self) -> &DMatrix<f64> {
+        &self.weights
+    }
+
+    /// Get the visible biases.
+    pub fn get_visible_bias(self: &Self) -> &DVector<f64> {
+        &self.visible_bias
+    }
+
+    /// Get the hidden biases.
+    pub fn get_hidden_bias(self: &Self) -> &DVector<f64> {
+        &self.hidden_bias
+    }
+
+    /// Get the energy of a joint state, E(v, h) = -vᵀWh - aᵀv - bᵀh.
+    pub fn energy(self: &Self, visible: &DVector<f64>, hidden: &DVector<f64>) -> f64 {
+        -(visible.dot(&(&self.weights * hidden))
+            + self.visible_bias.dot(visible)
+            + self.hidden_bias.dot(hidden))
+    }
+
+    /// Get the free energy of a visible state, the energy with the hidden units summed out:
+    /// F(v) = -aᵀv - Σ_j log Σ_h exp(h x_j), where x = Wᵀv + b. Patterns the machine has learned have low free energy.
+    pub fn free_energy(self: &Self, visible: &DVector<f64>) -> f64 {
+        let hidden_fields = self.hidden_fields(visible);
+        let hidden_term: f64 = hidden_fields
+            .iter()
+            .map(|field| match self.domain {
+                // log(1 + e^x), computed without overflow for large x
+                NetworkDomain::Binary => field.max(0.0) + (-field.abs()).exp().ln_1p(),
+                // log(e^x + e^-x)
+                _ => field.abs() + (-2.0 * field.abs()).exp().ln_1p(),
+            })
+            .sum();
+        -self.visible_bias.dot(visible) - hidden_term
+    }
+
+    /// Get the fields of the hidden units given the visible units, Wᵀv + b.
+    fn hidden_fields(self: &Self, visible: &DVector<f64>) -> DVector<f64> {
+        self.weights.tr_mul(visible) + &self.hidden_bias
+    }
+
+    /// Get the fields of the visible units given the hidden units, Wh + a.
+    fn visible_fields(self: &Self, hidden: &DVector<f64>) -> DVector<f64> {
+        &self.weights * hidden + &self.visible_bias
+    }
+
+    /// Map fields to the probability that each unit is active (1 or +1).
+    fn active_probabilities(self: &Self, fields: DVector<f64>) -> DVector<f64> {
+        sigmoid_activation_function(fields, &self.activation_parameters)
+    }
+
+    /// Map active probabilities to the expected value of each unit.
+    fn expected_values(self: &Self, probabilities: DVector<f64>) -> DVector<f64> {
+        match self.domain {
+            NetworkDomain::Binary => probabilities,
+            _ => probabilities.map(|probability| 2.0 * probability - 1.0),
+        }
+    }
+
+    /// Sample unit values from the probability that each unit is active: the activation function of the domain maps
+    /// the probability less uniform noise to the active value when it is positive.
+    fn sample_units(self: &mut Self, probabilities: &DVector<f64>) -> DVector<f64> {
+        let noise = DVector::<f64>::from_fn(probabilities.len(), |_, _| self.rng.gen::<f64>());
+        (self.domain.activation_fn())(probabilities - noise, &ActivationParameters::default())
+    }
+
+    /// Get the expected value of every hidden unit given the visible units.
+    pub fn hidden_means(self: &Self, visible: &DVector<f64>) -> DVector<f64> {
+        self.expected_values(self.active_probabilities(self.hidden_fields(visible)))
+    }
+
+    /// Get the expected value of every visible unit given the hidden units.
+    pub fn visible_means(self: &Self, hidden: &DVector<f64>) -> DVector<f64> {
+        self.expected_values(self.active_probabilities(self.visible_fields(hidden)))
+    }
+
+    /// Sample the hidden units given the visible units.
+    pub fn sample_hidden(self: &mut Self, visible: &DVector<f64>) -> DVector<f64> {
+        let probabilities = self.active_probabilities(self.hidden_fields(visible));
+        self.sample_units(&probabilities)
+    }
+
+    /// Sample the visible units given the hidden units.
+    pub fn sample_visible(self: &mut Self, hidden: &DVector<f64>) -> DVector<f64> {
+        let probabilities = self.active_probabilities(self.visible_fields(hidden));
+        self.sample_units(&probabilities)
+    }
+
+    /// Take one step of block Gibbs sampling: sample the hidden units, then the visible units.
+    ///
+    /// # Returns
+    ///
+    /// The new visible state.
+    pub fn gibbs_step(self: &mut Self, visible: &DVector<f64>) -> DVector<f64> {
+        let hidden = self.sample_hidden(visible);
+        self.sample_visible(&hidden)
+    }
+
+    /// Reconstruct a visible state through the hidden layer without sampling, visible_means(hidden_means(v)).
+    /// The reconstruction of a learned pattern is close to the pattern.
+    pub fn reconstruct(self: &Self, visible: &DVector<f64>) -> DVector<f64> {
+        self.visible_means(&self.hidden_means(visible))
+    }
+
+    /// Draw visible states from the distribution of this machine by block Gibbs sampling from an initial state.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_visible`: The visible state to start the chain from.
+    /// * `burn_in_steps`: The number of Gibbs steps to discard before the first sample.
+    /// * `sample_count`: The number of visible states to draw.
+    /// * `steps_between_samples`: The number of Gibbs steps between samples, at least 1.
+    ///
+    /// # Returns
+    ///
+    /// The sampled visible states, in the order they were drawn.
+    pub fn sample(
+        self: &mut Self,
+        initial_visible: DVector<f64>,
+        burn_in_steps: usize,
+        sample_count: usize,
+        steps_between_samples: usize,
+    ) -> Vec<DVector<f64>> {
+        assert!(
+            steps_between_samples > 0,
+            "There must be at least one Gibbs step between samples!"
+        );
+
+        let mut visible = initial_visible;
+        for _ in 0..burn_in_steps {
+            visible = self.gibbs_step(&visible);
+        }
+        let mut samples = Vec::with_capacity(sample_count);
+        for _ in 0..sample_count {
+            for _ in 0..steps_between_samples {
+                visible = self.gibbs_step(&visible);
+            }
+            samples.push(visible.clone());
+        }
+        samples
+    }
+
+    /// Train this machine on a collection of patterns with k-step contrastive divergence (CD-k).
+    ///
+    /// For each batch, the positive phase takes the hidden means of the patterns, and the negative phase runs k steps
+    /// of block Gibbs sampling from the patterns. The weights move by the difference of the visible-hidden
+    /// correlations of the two phases, ΔW = η (⟨v hᵀ⟩_data - ⟨v hᵀ⟩_k), and the biases likewise.
+    ///
+    /// # Arguments
+    ///
+    /// * `patterns`: The training patterns. Each must have one value per visible unit.
+    /// * `parameters`: The parameters of training.
+    ///
+    /// # Returns
+    ///
+    /// The mean squared reconstruction error (see reconstruct) of the patterns after each epoch.
+    pub fn train_contrastive_divergence(
+        self: &mut Self,
+        patterns: &[DVector<f64>],
+        parameters: &ContrastiveDivergenceParameters,
+    ) -> Vec<f64> {
+        assert!(
+            patterns
+                .iter()
+                .all(|pattern| pattern.len() == self.get_visible_units()),
+            "Every pattern must have one value per visible unit!"
+        );
+        assert!(
+            parameters.gibbs_steps > 0,
+            "Contrastive divergence needs at least one Gibbs step!"
+        );
+        assert!(
+            parameters.batch_size > 0,
+            "Contrastive divergence batch size must be at least one!"
+        );
+
+        let mut pattern_order: Vec<usize> = (0..patterns.len()).collect();
+        let mut reconstruction_errors = Vec::with_capacity(parameters.epochs);
+        for _ in 0..parameters.epochs {
+            pattern_order.shuffle(&mut self.rng);
+            for batch in pattern_order.chunks(parameters.batch_size) {
+                let mut weight_gradient =
+                    DMatrix::<f64>::zeros(self.get_visible_units(), self.get_hidden_units());
+                let mut visible_gradient = DVector::<f64>::zeros(self.get_visible_units());
+                let mut hidden_gradient = DVector::<f64>::zeros(self.get_hidden_units());
+
+                for pattern_index in batch {
+                    let positive_visible = &patterns[*pattern_index];
+                    let positive_hidden = self.hidden_means(positive_visible);
+
+                    let mut negative_visible = positive_visible.clone();
+                    for _ in 0..parameters.gibbs_steps {
+                        negative_visible = self.gibbs_step(&negative_visible);
+                    }
+                    let negative_hidden = self.hidden_means(&negative_visible);
+
+                    weight_gradient.ger(1.0, positive_visible, &positive_hidden, 1.0);
+                    weight_gradient.ger(-1.0, &negative_visible, &negative_hidden, 1.0);
+                    visible_gradient += positive_visible - &negative_visible;
+                    hidden_gradient += positive_hidden - negative_hidden;
+                }
+
+                let scale = parameters.learning_rate / batch.len() as f64;
+                self.weights += weight_gradient * scale;
+                self.visible_bias += visible_gradient * scale;
+                self.hidden_bias += hidden_gradient * scale;
+            }
+
+            let total_error: f64 = patterns
+                .iter()
+                .map(|pattern| (self.reconstruct(pattern) - pattern).norm_squared())
+                .sum();
+            reconstruction_errors
+                .push(total_error / (patterns.len() * self.get_visible_units()).max(1) as f64);
+        }
+        reconstruction_errors
+    }
+}
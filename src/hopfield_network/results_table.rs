@@ -0,0 +1,327 @@
+use super::HopfieldNetwork;
+use nalgebra::DVector;
+#[cfg(feature = "parquet")]
+use parquet::{
+    data_type::{DataType, DoubleType, Int64Type},
+    file::{
+        properties::WriterProperties,
+        writer::{SerializedFileWriter, SerializedRowGroupWriter},
+    },
+    schema::parser::parse_message_type,
+};
+#[cfg(feature = "parquet")]
+use std::sync::Arc;
+use std::{collections::BTreeMap, io};
+
+/// The Parquet schema of a ResultsTable, one column per field of ResultsRow. Seeds and the other unsigned columns are
+/// stored as INT64 annotated as unsigned, so they read back unchanged.
+#[cfg(feature = "parquet")]
+const PARQUET_SCHEMA: &str = "
+message results_row {
+    REQUIRED INT64 id (INTEGER(64, false));
+    REQUIRED INT64 seed (INTEGER(64, false));
+    REQUIRED INT64 iterations (INTEGER(64, false));
+    REQUIRED DOUBLE energy;
+    OPTIONAL INT64 best_match (INTEGER(64, false));
+    REQUIRED DOUBLE overlap;
+}
+";
+
+/// A single row of a ResultsTable, describing one relaxed state.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResultsRow {
+    pub id: usize,
+    pub seed: u64,
+    pub iterations: usize,
+    pub energy: f64,
+    /// The index of the stored pattern with the highest overlap, if any patterns are stored.
+    pub best_match: Option<usize>,
+    /// The overlap with the best matching stored pattern, or 0 if no patterns are stored.
+    pub overlap: f64,
+}
+
+/// A summary of a group of rows in a ResultsTable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResultsGroupSummary {
+    pub count: usize,
+    pub mean_iterations: f64,
+    pub mean_energy: f64,
+    pub mean_overlap: f64,
+}
+
+/// A table of relaxation results, with one row per relaxed state.
+///
+/// This gives column access and grouped summaries so aggregation code does not need to
+/// start from a Vec of structs every time. Tables can be written as CSV, or as Parquet with the parquet feature.
+#[derive(Debug, Clone)]
+pub struct ResultsTable {
+    rows: Vec<ResultsRow>,
+}
+
+impl ResultsTable {
+    /// Create a new, empty ResultsTable.
+    pub fn new_results_table() -> Self {
+        Self { rows: Vec::new() }
+    }
+
+    /// Add a row to the table.
+    pub fn push_row(self: &mut Self, row: ResultsRow) {
+        self.rows.push(row);
+    }
+
+    /// Add a row to the table describing a relaxed state, calculating the energy and best match from the network.
+    ///
+    /// # Arguments
+    ///
+    /// * `network`: The network the state was relaxed in.
+    /// * `id`: The identifier of the state, e.g. its index in a batch.
+    /// * `seed`: The seed used to generate the state.
+    /// * `iterations`: The number of update iterations used in relaxation.
+    /// * `state`: The relaxed state.
+    pub fn record(
+        self: &mut Self,
+        network: &HopfieldNetwork,
+        id: usize,
+        seed: u64,
+        iterations: usize,
+        state: &DVector<f64>,
+    ) {
+        let (best_match, overlap) = match network.nearest_memories(state, 1).first() {
+            Some((pattern_index, _, overlap)) => (Some(*pattern_index), *overlap),
+            None => (None, 0.0),
+        };
+
+        self.push_row(ResultsRow {
+            id,
+            seed,
+            iterations,
+            energy: network.state_energy(state),
+            best_match,
+            overlap,
+        });
+    }
+
+    /// Get the number of rows in the table.
+    pub fn len(self: &Self) -> usize {
+        self.rows.len()
+    }
+
+    /// Check if the table has no rows.
+    pub fn is_empty(self: &Self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Get all rows of the table, in the order they were added.
+    pub fn rows(self: &Self) -> &[ResultsRow] {
+        &self.rows
+    }
+
+    /// Get the id column.
+    pub fn ids(self: &Self) -> Vec<usize> {
+        self.rows.iter().map(|row| row.id).collect()
+    }
+
+    /// Get the seed column.
+    pub fn seeds(self: &Self) -> Vec<u64> {
+        self.rows.iter().map(|row| row.seed).collect()
+    }
+
+    /// Get the iterations column.
+    pub fn iterations(self: &Self) -> Vec<usize> {
+        self.rows.iter().map(|row| row.iterations).collect()
+    }
+
+    /// Get the energy column.
+    pub fn energies(self: &Self) -> Vec<f64> {
+        self.rows.iter().map(|row| row.energy).collect()
+    }
+
+    /// Get the best match column.
+    pub fn best_matches(self: &Self) -> Vec<Option<usize>> {
+        self.rows.iter().map(|row| row.best_match).collect()
+    }
+
+    /// Get the overlap column.
+    pub fn overlaps(self: &Self) -> Vec<f64> {
+        self.rows.iter().map(|row| row.overlap).collect()
+    }
+
+    /// Summarize the rows of the table grouped by some key.
+    ///
+    /// # Arguments
+    ///
+    /// * `key_fn`: A function mapping each row to the key of its group.
+    ///
+    /// # Returns
+    ///
+    /// A map from each key to the summary of its group, ordered by key.
+    pub fn group_by<K: Ord>(
+        self: &Self,
+        key_fn: impl Fn(&ResultsRow) -> K,
+    ) -> BTreeMap<K, ResultsGroupSummary> {
+        let mut groups: BTreeMap<K, Vec<&ResultsRow>> = BTreeMap::new();
+        for row in &self.rows {
+            groups.entry(key_fn(row)).or_default().push(row);
+        }
+
+        groups
+            .into_iter()
+            .map(|(key, rows)| {
+                let count = rows.len() as f64;
+                let summary = ResultsGroupSummary {
+                    count: rows.len(),
+                    mean_iterations: rows.iter().map(|row| row.iterations as f64).sum::<f64>()
+                        / count,
+                    mean_energy: rows.iter().map(|row| row.energy).sum::<f64>() / count,
+                    mean_overlap: rows.iter().map(|row| row.overlap).sum::<f64>() / count,
+                };
+                (key, summary)
+            })
+            .collect()
+    }
+
+    /// Summarize the rows of the table grouped by the stored pattern they best match.
+    pub fn group_by_best_match(self: &Self) -> BTreeMap<Option<usize>, ResultsGroupSummary> {
+        self.group_by(|row| row.best_match)
+    }
+
+    /// Write the table as CSV, with a header row. Rows with no best match leave that column empty.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The destination of the CSV, e.g. a File.
+    pub fn write_csv(self: &Self, mut writer: impl io::Write) -> io::Result<()> {
+        writeln!(writer, "id,seed,iterations,energy,best_match,overlap")?;
+        for row in &self.rows {
+            let best_match = row
+                .best_match
+                .map(|pattern_index| pattern_index.to_string())
+                .unwrap_or_default();
+            writeln!(
+                writer,
+                "{},{},{},{},{},{}",
+                row.id, row.seed, row.iterations, row.energy, best_match, row.overlap
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the table as a Parquet file, with a single row group and the columns of write_csv. Rows with no best
+    /// match have a null best_match.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer`: The destination of the Parquet file, e.g. a File.
+    #[cfg(feature = "parquet")]
+    pub fn write_parquet(
+        self: &Self,
+        writer: impl io::Write + Send,
+    ) -> parquet::errors::Result<()> {
+        let schema = Arc::new(parse_message_type(PARQUET_SCHEMA)?);
+        let properties = Arc::new(WriterProperties::builder().build());
+        let mut file_writer = SerializedFileWriter::new(writer, schema, properties)?;
+
+        // Unsigned columns keep their bits in the signed physical type, see PARQUET_SCHEMA
+        let ids: Vec<i64> = self.rows.iter().map(|row| row.id as i64).collect();
+        let seeds: Vec<i64> = self.rows.iter().map(|row| row.seed as i64).collect();
+        let iterations: Vec<i64> = self.rows.iter().map(|row| row.iterations as i64).collect();
+        let best_matches: Vec<i64> = self
+            .rows
+            .iter()
+            .filter_map(|row| row.best_match)
+            .map(|pattern_index| pattern_index as i64)
+            .collect();
+        let best_match_levels: Vec<i16> = self
+            .rows
+            .iter()
+            .map(|row| row.best_match.is_some() as i16)
+            .collect();
+
+        let mut row_group_writer = file_writer.next_row_group()?;
+        write_parquet_column::<Int64Type>(&mut row_group_writer, &ids, None)?;
+        write_parquet_column::<Int64Type>(&mut row_group_writer, &seeds, None)?;
+        write_parquet_column::<Int64Type>(&mut row_group_writer, &iterations, None)?;
+        write_parquet_column::<DoubleType>(&mut row_group_writer, &self.energies(), None)?;
+        write_parquet_column::<Int64Type>(
+            &mut row_group_writer,
+            &best_matches,
+            Some(&best_match_levels),
+        )?;
+        write_parquet_column::<DoubleType>(&mut row_group_writer, &self.overlaps(), None)?;
+        row_group_writer.close()?;
+
+        file_writer.close()?;
+        Ok(())
+    }
+}
+
+/// Write the next column of a Parquet row group, see ResultsTable::write_parquet.
+///
+/// # Arguments
+///
+/// * `row_group_writer`: The row group to write the column to. Columns are written in the order of PARQUET_SCHEMA.
+/// * `values`: The non-null values of the column.
+/// * `definition_levels`: For an optional column, 1 for each row with a value and 0 for each null row.
+#[cfg(feature = "parquet")]
+fn write_parquet_column<T: DataType>(
+    row_group_writer: &mut SerializedRowGroupWriter<'_, impl io::Write + Send>,
+    values: &[T::T],
+    definition_levels: Option<&[i16]>,
+) -> parquet::errors::Result<()> {
+    let mut column_writer = row_group_writer
+        .next_column()?
+        .expect("ResultsTable::write_parquet wrote more columns than PARQUET_SCHEMA has!");
+    column_writer
+        .typed::<T>()
+        .write_batch(values, definition_levels, None)?;
+    column_writer.close()
+}
+
+#[cfg(all(test, feature = "parquet"))]
+mod tests {
+    use super::*;
+    use parquet::{
+        file::reader::{FileReader, SerializedFileReader},
+        record::RowAccessor,
+    };
+    use std::fs::File;
+
+    #[test]
+    fn parquet_tables_read_back_unchanged() {
+        let mut table = ResultsTable::new_results_table();
+        for (id, best_match) in [Some(2), None, Some(0)].into_iter().enumerate() {
+            table.push_row(ResultsRow {
+                id,
+                seed: u64::MAX - id as u64,
+                iterations: 10 * id,
+                energy: -1.5 * id as f64,
+                best_match,
+                overlap: 0.25 * id as f64,
+            });
+        }
+
+        let path = std::env::temp_dir().join("hopfield_results_table_round_trip.parquet");
+        table.write_parquet(File::create(&path).unwrap()).unwrap();
+        let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+        let rows: Vec<ResultsRow> = reader
+            .get_row_iter(None)
+            .unwrap()
+            .map(|row| {
+                let row = row.unwrap();
+                ResultsRow {
+                    id: row.get_ulong(0).unwrap() as usize,
+                    seed: row.get_ulong(1).unwrap(),
+                    iterations: row.get_ulong(2).unwrap() as usize,
+                    energy: row.get_double(3).unwrap(),
+                    best_match: row
+                        .get_ulong(4)
+                        .ok()
+                        .map(|pattern_index| pattern_index as usize),
+                    overlap: row.get_double(5).unwrap(),
+                }
+            })
+            .collect();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(rows, table.rows());
+    }
+}
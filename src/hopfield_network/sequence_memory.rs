@@ -0,0 +1,259 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+
+use super::{
+    learning_rule::HebbianRule, update_algorithm::UpdateDynamics, update_rule::UpdateRule,
+    HopfieldNetwork, RelaxationDynamics,
+};
+
+/// The trajectory of a sequence replay, see replay_sequence.
+#[derive(Debug, Clone)]
+pub struct SequenceReplay {
+    /// The state after each synchronous step, starting with the initial state.
+    pub states: Vec<DVector<f64>>,
+    /// For each state, the index of the stored pattern it is closest to, or None if no pattern passed the overlap
+    /// threshold.
+    pub recalled_patterns: Vec<Option<usize>>,
+}
+
+impl SequenceReplay {
+    /// Get the sequence of patterns recalled, skipping states near no pattern and merging consecutive steps at the
+    /// same pattern.
+    pub fn pattern_sequence(self: &Self) -> Vec<usize> {
+        let mut sequence: Vec<usize> = Vec::new();
+        for pattern_index in self.recalled_patterns.iter().flatten() {
+            if sequence.last() != Some(pattern_index) {
+                sequence.push(*pattern_index);
+            }
+        }
+        sequence
+    }
+}
+
+impl HopfieldNetwork {
+    /// Store an ordered sequence of patterns, so that synchronous updates replay it (Sompolinsky and Kanter).
+    ///
+    /// Each pattern is stored with the Hebbian rule (see learn_states), and each transition ξ^μ → ξ^μ+1 adds the
+    /// asymmetric term λ ξ^μ+1 (ξ^μ)ᵀ / N to the weights, where λ is the transition strength. Binary patterns use
+    /// their bipolar mapping, as in learning. At a pattern the local field is then roughly ξ^μ + λ ξ^μ+1, so with
+    /// λ > 1 a synchronous step moves the state to the next pattern, while with λ < 1 every pattern stays a fixed
+    /// point and the transitions only bias the dynamics.
+    ///
+    /// The transition terms are tracked separately as the sequence weights, which clean_matrix leaves asymmetric even
+    /// while force_symmetric is set. The matrix is cleaned as set by the clean policy (see set_clean_policy), then
+    /// clipped, with any clipped amount taken from the sequence weights. Forgetting a pattern does not remove its
+    /// transitions; see forget_sequences. The attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence`: The patterns of the sequence, in order. Must contain at least two patterns.
+    /// * `transition_strength`: The strength λ of each transition relative to the patterns.
+    /// * `cyclic`: Whether the last pattern transitions back to the first.
+    pub fn learn_sequence(
+        self: &mut Self,
+        sequence: &[DVector<f64>],
+        transition_strength: f64,
+        cyclic: bool,
+    ) {
+        assert!(
+            sequence.len() >= 2,
+            "A sequence must contain at least two patterns!"
+        );
+        self.learn_states_with(sequence, &HebbianRule);
+
+        let learning_vectors: Vec<DVector<f64>> = sequence
+            .iter()
+            .map(|pattern| self.learning_vector(pattern))
+            .collect();
+        let transition_count = if cyclic {
+            sequence.len()
+        } else {
+            sequence.len() - 1
+        };
+        let scale = transition_strength / self.dimension as f64;
+        let mut transition_weights = DMatrix::<f64>::zeros(self.dimension, self.dimension);
+        for transition in 0..transition_count {
+            let next = (transition + 1) % sequence.len();
+            transition_weights.ger(
+                scale,
+                &learning_vectors[next],
+                &learning_vectors[transition],
+                1.0,
+            );
+        }
+        if let Some(connectivity) = &self.connectivity {
+            transition_weights.component_mul_assign(connectivity);
+        }
+
+        let weights_before = self.weight_delta_snapshot();
+        self.matrix += &transition_weights;
+        self.sequence_weights = Some(match self.sequence_weights.take() {
+            Some(sequence_weights) => sequence_weights + transition_weights,
+            None => transition_weights,
+        });
+        self.clean_matrix_after_learning();
+        self.clip_weights();
+
+        // The transitions are not outer products of the stored patterns with themselves
+        self.hebbian_weights = false;
+        self.emit_weight_delta(weights_before, 0);
+        self.clear_attractor_cache();
+    }
+
+    /// Get the asymmetric sequence weights learned by learn_sequence, or None if no sequence has been learned.
+    /// These are included in the weight matrix.
+    pub fn get_sequence_weights(self: &Self) -> Option<&DMatrix<f64>> {
+        self.sequence_weights.as_ref()
+    }
+
+    /// Remove every sequence transition from the weights, keeping the stored patterns. The attractor cache is cleared.
+    ///
+    /// Weights clipped while the sequence weights were tracked are charged to the transitions (see clip_weights), so
+    /// the weights of the patterns alone are restored and then cleaned and clipped like any other weight change.
+    pub fn forget_sequences(self: &mut Self) {
+        let Some(sequence_weights) = self.sequence_weights.take() else {
+            return;
+        };
+
+        let weights_before = self.weight_delta_snapshot();
+        self.matrix -= sequence_weights;
+        self.clean_matrix_after_learning();
+        self.clip_weights();
+        self.emit_weight_delta(weights_before, 0);
+        self.clear_attractor_cache();
+    }
+
+    /// Update every unit of a state at once from the local fields of the state, rather than one unit at a time.
+    ///
+    /// Synchronous updates are what let asymmetric sequence weights step from one pattern to the next. Units are
+    /// updated with the update rule of the network (see get_update_rule), so a positive temperature gives stochastic
+    /// steps. Any external input for the given step is added to the local fields. A single step starts with no fatigue;
+    /// see replay_sequence for fatigue across steps.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to update.
+    /// * `step`: The step number, used only for the external input.
+    ///
+    /// # Returns
+    ///
+    /// The updated state.
+    pub fn synchronous_step(self: &mut Self, state: &DVector<f64>, step: usize) -> DVector<f64> {
+        let update_rule = self.get_update_rule();
+        let mut rng = StdRng::seed_from_u64(self.rng.next_u64());
+        let mut unit_indices = self.get_unit_indices();
+        let mut state = state.clone();
+        let mut adaptation = None;
+        self.synchronous_dynamics(update_rule.as_ref()).advance(
+            step,
+            &mut unit_indices,
+            &mut rng,
+            &mut state,
+            &mut adaptation,
+        );
+        state
+    }
+
+    /// Get the relaxation dynamics of this network with synchronous updates, whatever its update dynamics.
+    pub(super) fn synchronous_dynamics<'a>(
+        self: &'a Self,
+        update_rule: &'a dyn UpdateRule,
+    ) -> RelaxationDynamics<'a> {
+        RelaxationDynamics {
+            update_dynamics: UpdateDynamics::Synchronous,
+            ..self.relaxation_dynamics(update_rule)
+        }
+    }
+
+    /// Replay a learned sequence by stepping a state synchronously for a fixed number of steps, see learn_sequence.
+    ///
+    /// Each step is a synchronous_step, with the external input of that step. Fatigue, configured on the builder,
+    /// builds up across the steps of the replay, so units that have been active for a while are suppressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The initial state, e.g. a noisy cue of the first pattern of a sequence. Consumes the state.
+    /// * `steps`: The number of synchronous steps to take. Replay does not stop early.
+    /// * `minimum_overlap`: The overlap a stored pattern must reach for a state to count as recalling it.
+    ///
+    /// # Returns
+    ///
+    /// A SequenceReplay with the trajectory and the pattern recalled at each step.
+    pub fn replay_sequence(
+        self: &mut Self,
+        state: DVector<f64>,
+        steps: usize,
+        minimum_overlap: f64,
+    ) -> SequenceReplay {
+        let update_rule = self.get_update_rule();
+        let mut rng = StdRng::seed_from_u64(self.rng.next_u64());
+        let mut unit_indices = self.get_unit_indices();
+        let relaxation_dynamics = self.synchronous_dynamics(update_rule.as_ref());
+        let mut adaptation =
+            (!self.fatigue.is_disabled()).then(|| DVector::<f64>::zeros(state.len()));
+
+        let mut states = vec![state];
+        for step in 0..steps {
+            let mut next_state = states.last().unwrap().clone();
+            relaxation_dynamics.advance(
+                step,
+                &mut unit_indices,
+                &mut rng,
+                &mut next_state,
+                &mut adaptation,
+            );
+            states.push(next_state);
+        }
+
+        let recalled_patterns = self
+            .overlaps_matrix(&states)
+            .column_iter()
+            .map(|overlaps| {
+                overlaps
+                    .iter()
+                    .copied()
+                    .enumerate()
+                    .max_by(|a, b| a.1.total_cmp(&b.1))
+                    .filter(|(_, overlap)| *overlap >= minimum_overlap)
+                    .map(|(pattern_index, _)| pattern_index)
+            })
+            .collect();
+        SequenceReplay {
+            states,
+            recalled_patterns,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hopfield_network::{HopfieldNetworkBuilder, NetworkDomain};
+
+    #[test]
+    fn forgetting_clipped_sequences_restores_the_patterns() {
+        let sequence: Vec<DVector<f64>> = [
+            [1.0, 1.0, 1.0, 1.0, -1.0, -1.0, -1.0, -1.0],
+            [1.0, -1.0, 1.0, -1.0, 1.0, -1.0, 1.0, -1.0],
+            [1.0, 1.0, -1.0, -1.0, 1.0, 1.0, -1.0, -1.0],
+        ]
+        .iter()
+        .map(|pattern| DVector::from_row_slice(pattern))
+        .collect();
+        let network_builder = HopfieldNetworkBuilder::new_hopfield_network_builder()
+            .set_network_dimension(8)
+            .set_network_domain(NetworkDomain::Bipolar)
+            .set_weight_clip(Some(0.2));
+
+        let mut sequence_network = network_builder.clone().build();
+        sequence_network.learn_sequence(&sequence, 1.0, true);
+        assert!(sequence_network
+            .get_sequence_weights()
+            .is_some_and(|sequence_weights| sequence_weights.iter().any(|weight| *weight != 0.0)));
+        sequence_network.forget_sequences();
+
+        let mut pattern_network = network_builder.build();
+        pattern_network.learn_states_with(&sequence, &HebbianRule);
+        assert!((sequence_network.get_matrix() - pattern_network.get_matrix()).amax() < 1e-12);
+    }
+}
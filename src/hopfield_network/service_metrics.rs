@@ -0,0 +1,229 @@
+use super::network_event::NetworkEvent;
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// The upper bounds of the recall latency histogram buckets, in seconds.
+const LATENCY_BUCKETS: [f64; 10] = [1e-5, 5e-5, 1e-4, 5e-4, 1e-3, 5e-3, 1e-2, 5e-2, 0.1, 1.0];
+
+/// The upper bounds of the relaxation iterations histogram buckets.
+const ITERATION_BUCKETS: [f64; 10] = [1.0, 2.0, 3.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0];
+
+/// A Prometheus histogram with fixed buckets.
+#[derive(Debug, Clone)]
+struct Histogram {
+    bounds: &'static [f64],
+    /// The number of observations in each bucket (not cumulative).
+    counts: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+impl Histogram {
+    fn new_histogram(bounds: &'static [f64]) -> Self {
+        Self {
+            bounds,
+            counts: vec![0; bounds.len()],
+            sum: 0.0,
+            count: 0,
+        }
+    }
+
+    fn observe(self: &mut Self, value: f64) {
+        if let Some(bucket) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.counts[bucket] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// Write this histogram in the Prometheus text exposition format.
+    fn render(self: &Self, output: &mut String, name: &str, help: &str) {
+        let _ = writeln!(output, "# HELP {name} {help}");
+        let _ = writeln!(output, "# TYPE {name} histogram");
+        let mut cumulative_count = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative_count += count;
+            let _ = writeln!(output, "{name}_bucket{{le=\"{bound}\"}} {cumulative_count}");
+        }
+        let _ = writeln!(output, "{name}_bucket{{le=\"+Inf\"}} {}", self.count);
+        let _ = writeln!(output, "{name}_sum {}", self.sum);
+        let _ = writeln!(output, "{name}_count {}", self.count);
+    }
+}
+
+#[derive(Debug)]
+struct ServiceMetricsState {
+    recall_latency: Histogram,
+    relaxation_iterations: Histogram,
+    recalls_converged: u64,
+    queue_depth: usize,
+}
+
+/// Metrics for monitoring a network deployed as an associative memory service: recall latency, relaxation
+/// iterations, convergence rate, and request queue depth. Rendered in the Prometheus text exposition format,
+/// and served by serve_prometheus_metrics.
+///
+/// Share between threads with an Arc. Recalls are recorded either directly with record_recall or automatically
+/// by registering event_hook on the network, which records every serial relaxation.
+#[derive(Debug)]
+pub struct ServiceMetrics {
+    state: Mutex<ServiceMetricsState>,
+}
+
+impl ServiceMetrics {
+    /// Create a new set of metrics with nothing recorded.
+    pub fn new_service_metrics() -> Self {
+        Self {
+            state: Mutex::new(ServiceMetricsState {
+                recall_latency: Histogram::new_histogram(&LATENCY_BUCKETS),
+                relaxation_iterations: Histogram::new_histogram(&ITERATION_BUCKETS),
+                recalls_converged: 0,
+                queue_depth: 0,
+            }),
+        }
+    }
+
+    /// Record a single recall (relaxation) served.
+    ///
+    /// # Arguments
+    ///
+    /// * `latency`: The time taken to relax the state.
+    /// * `iterations`: The number of update iterations used.
+    /// * `converged`: Whether the state reached a stable state within the iteration limit.
+    pub fn record_recall(self: &Self, latency: Duration, iterations: usize, converged: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.recall_latency.observe(latency.as_secs_f64());
+        state.relaxation_iterations.observe(iterations as f64);
+        if converged {
+            state.recalls_converged += 1;
+        }
+    }
+
+    /// Set the number of requests currently waiting to be served.
+    pub fn set_queue_depth(self: &Self, queue_depth: usize) {
+        self.state.lock().unwrap().queue_depth = queue_depth;
+    }
+
+    /// Create a hook recording every serial relaxation of a network, to register with add_event_hook.
+    ///
+    /// Latency is measured from the RelaxationStarted event to the RelaxationFinished event.
+    /// Concurrent relaxations do not emit these events, so record those with record_recall instead.
+    pub fn event_hook(self: &Arc<Self>) -> impl FnMut(&NetworkEvent) + Send + 'static {
+        let metrics = Arc::clone(self);
+        let mut relaxation_start = None;
+        move |event| match event {
+            NetworkEvent::RelaxationStarted { .. } => relaxation_start = Some(Instant::now()),
+            NetworkEvent::RelaxationFinished {
+                iterations,
+                converged,
+                ..
+            } => {
+                if let Some(start) = relaxation_start.take() {
+                    metrics.record_recall(start.elapsed(), *iterations, *converged);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Render every metric in the Prometheus text exposition format.
+    pub fn render_prometheus(self: &Self) -> String {
+        let state = self.state.lock().unwrap();
+        let recalls = state.recall_latency.count;
+        let convergence_rate = if recalls == 0 {
+            1.0
+        } else {
+            state.recalls_converged as f64 / recalls as f64
+        };
+
+        let mut output = String::new();
+        state.recall_latency.render(
+            &mut output,
+            "hopfield_recall_latency_seconds",
+            "Time taken to relax a recall cue.",
+        );
+        state.relaxation_iterations.render(
+            &mut output,
+            "hopfield_relaxation_iterations",
+            "Update iterations used to relax a recall cue.",
+        );
+        let _ = writeln!(
+            output,
+            "# HELP hopfield_recalls_converged_total Recalls that reached a stable state.\n\
+             # TYPE hopfield_recalls_converged_total counter\n\
+             hopfield_recalls_converged_total {}",
+            state.recalls_converged
+        );
+        let _ = writeln!(
+            output,
+            "# HELP hopfield_convergence_rate Fraction of recalls that reached a stable state.\n\
+             # TYPE hopfield_convergence_rate gauge\n\
+             hopfield_convergence_rate {convergence_rate}"
+        );
+        let _ = writeln!(
+            output,
+            "# HELP hopfield_queue_depth Requests waiting to be served.\n\
+             # TYPE hopfield_queue_depth gauge\n\
+             hopfield_queue_depth {}",
+            state.queue_depth
+        );
+        output
+    }
+}
+
+/// How long a scrape connection may wait on a read or write before it is dropped.
+const SCRAPE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Serve metrics to Prometheus over HTTP, answering GET /metrics with the rendered metrics and anything else
+/// with 404. Connections are handled one at a time, which is plenty for a scraper. A connection that stalls for
+/// longer than a few seconds is dropped, so a client that never sends its request cannot block other scrapes.
+///
+/// This blocks forever, so run it on its own thread.
+///
+/// # Arguments
+///
+/// * `listener`: The listener to accept scrape connections from, e.g. bound to 0.0.0.0:9100.
+/// * `metrics`: The metrics to serve.
+///
+/// # Returns
+///
+/// An error if accepting a connection fails. Errors on individual connections are ignored.
+pub fn serve_prometheus_metrics(
+    listener: TcpListener,
+    metrics: Arc<ServiceMetrics>,
+) -> io::Result<()> {
+    loop {
+        let (stream, _) = listener.accept()?;
+        if stream.set_read_timeout(Some(SCRAPE_TIMEOUT)).is_err()
+            || stream.set_write_timeout(Some(SCRAPE_TIMEOUT)).is_err()
+        {
+            continue;
+        }
+        let mut reader = BufReader::new(&stream);
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).is_err() {
+            continue;
+        }
+        // Read the rest of the request head, so the connection is not reset with unread data
+        let mut header_line = String::new();
+        while matches!(reader.read_line(&mut header_line), Ok(length) if length > 2) {
+            header_line.clear();
+        }
+
+        let response = if request_line.starts_with("GET /metrics ") {
+            let body = metrics.render_prometheus();
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+        } else {
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+        };
+        let _ = (&stream).write_all(response.as_bytes());
+    }
+}
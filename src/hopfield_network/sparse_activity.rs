@@ -0,0 +1,106 @@
+use nalgebra::DVector;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    relaxation_result::RelaxationResult, HopfieldNetwork, NetworkDomain, RelaxationOverrides,
+};
+
+/// How the overall activity of a Binary network is held near a target during relaxation, so sparse memories are
+/// not lost to the all-off or all-on states the plain dynamics fall into.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ActivityControl {
+    /// Subtract a global inhibition term from every local field, h_i - γ(a_i - f), where a_i is the fraction of
+    /// the other units that are active, f the target coding level and γ the strength. This is ordinary dynamics with
+    /// every weight between distinct units lowered by γ/(N-1) and a constant input of γf.
+    GlobalInhibition { coding_level: f64, strength: f64 },
+    /// Activate exactly the k units with the largest local fields and silence the rest, updating every unit at once.
+    KWinnersTakeAll { active_units: usize },
+}
+
+impl HopfieldNetwork {
+    /// Relax a Binary state while holding its activity near a target, see ActivityControl.
+    ///
+    /// Relaxation runs as relax_state does, with the external input, fatigue, weight mask and summation order of the
+    /// network, stopping once fewer units are unstable than the maximum. Under global inhibition units are updated
+    /// with the update rule of the network (see get_update_rule) and its update dynamics, so a positive temperature
+    /// gives stochastic updates. Under k-winners-take-all the winners are chosen together from the local fields, so
+    /// the update rule, temperature and update dynamics do not apply, and a unit is unstable if it is not among the
+    /// winners of the fields it was updated with.
+    ///
+    /// The attractor cache is bypassed.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to relax. Consumes the state.
+    /// * `activity_control`: How the activity is controlled.
+    ///
+    /// # Returns
+    ///
+    /// A RelaxationResult of the relaxed state, with its stability checked under the activity control.
+    pub fn relax_state_with_activity_control(
+        self: &mut Self,
+        state: DVector<f64>,
+        activity_control: ActivityControl,
+    ) -> RelaxationResult {
+        assert!(
+            self.domain == NetworkDomain::Binary,
+            "Activity control is only defined in the Binary domain!"
+        );
+        match activity_control {
+            ActivityControl::GlobalInhibition {
+                coding_level,
+                strength,
+            } => assert!(
+                (0.0..=1.0).contains(&coding_level) && strength >= 0.0,
+                "Global inhibition needs a coding level in [0, 1] and a non-negative strength!"
+            ),
+            ActivityControl::KWinnersTakeAll { active_units } => assert!(
+                active_units <= self.dimension,
+                "k-winners-take-all cannot activate more units than the network dimension!"
+            ),
+        }
+
+        let overrides = RelaxationOverrides {
+            activity_control: Some(activity_control),
+            ..Default::default()
+        };
+        self.relax_state_recorded(state, &overrides)
+    }
+}
+
+/// Set the given units of a Binary state to the k-winners-take-all of their local fields: the k given units with the
+/// largest fields are activated and the rest silenced, ties broken by unit index. Units that are not given keep
+/// their values.
+///
+/// # Arguments
+///
+/// * `fields`: The local field of every unit.
+/// * `unit_indices`: The units to update, in any order.
+/// * `active_units`: The number of units k to activate.
+/// * `state`: The state to update in place.
+pub(super) fn set_k_winners(
+    fields: &DVector<f64>,
+    unit_indices: &[usize],
+    active_units: usize,
+    state: &mut DVector<f64>,
+) {
+    let mut ranked_units = unit_indices.to_vec();
+    ranked_units.sort_unstable();
+    ranked_units.sort_by(|a, b| fields[*b].total_cmp(&fields[*a]));
+    for (rank, unit_index) in ranked_units.into_iter().enumerate() {
+        state[unit_index] = if rank < active_units { 1.0 } else { 0.0 };
+    }
+}
+
+/// Get the Binary state with exactly k active units: those with the largest local fields, ties broken by unit index.
+///
+/// # Arguments
+///
+/// * `fields`: The local field of every unit.
+/// * `active_units`: The number of units k to activate.
+pub fn k_winners_take_all(fields: &DVector<f64>, active_units: usize) -> DVector<f64> {
+    let unit_indices: Vec<usize> = (0..fields.len()).collect();
+    let mut state = DVector::zeros(fields.len());
+    set_k_winners(fields, &unit_indices, active_units, &mut state);
+    state
+}
@@ -0,0 +1,150 @@
+use nalgebra::DVector;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use super::HopfieldNetwork;
+
+/// How the unspecified units of a sparse cue are initialized before completion, see sparse_cue_state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CueFill {
+    /// A random value of the network domain, drawn uniformly from [-1, 1] and mapped by the activation function
+    /// (as a StateGenerator does).
+    Random,
+    /// Zero, which sits on the threshold of the Bipolar activation, so the known units alone decide the first update.
+    Zero,
+    /// The mean value of the unit over the stored patterns, or zero if no patterns are stored.
+    Mean,
+}
+
+/// A cue that specifies the values of only some units, as (index, value) pairs, for completing patterns where only a
+/// small fraction of the units are observed. The remaining units are filled in by a CueFill policy and then relaxed
+/// while the specified units are held fixed (see complete_sparse_cue).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SparseCue {
+    dimension: usize,
+    /// The specified units and their values, sorted by unit index.
+    entries: Vec<(usize, f64)>,
+}
+
+impl SparseCue {
+    /// Create a new sparse cue.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The dimension of the full state.
+    /// * `entries`: The (index, value) pairs of the specified units. Every index must be less than the dimension.
+    ///   If an index appears more than once, the last value is used.
+    pub fn new_sparse_cue(dimension: usize, entries: Vec<(usize, f64)>) -> Self {
+        assert!(
+            entries.iter().all(|(unit, _)| *unit < dimension),
+            "Every specified unit of a sparse cue must be less than the dimension!"
+        );
+
+        let mut entries = entries;
+        // A stable sort keeps repeated indices in order, so reversing first keeps the last value of each
+        entries.reverse();
+        entries.sort_by_key(|(unit, _)| *unit);
+        entries.dedup_by_key(|(unit, _)| *unit);
+        Self { dimension, entries }
+    }
+
+    /// Create a sparse cue from the units of a full state at the given indices.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The full state to take values from.
+    /// * `known_units`: The indices of the units to specify.
+    pub fn from_state(state: &DVector<f64>, known_units: &[usize]) -> Self {
+        Self::new_sparse_cue(
+            state.len(),
+            known_units
+                .iter()
+                .map(|unit| (*unit, state[*unit]))
+                .collect(),
+        )
+    }
+
+    /// Get the dimension of the full state.
+    pub fn get_dimension(self: &Self) -> usize {
+        self.dimension
+    }
+
+    /// Get the (index, value) pairs of the specified units, sorted by index.
+    pub fn get_entries(self: &Self) -> &[(usize, f64)] {
+        &self.entries
+    }
+
+    /// Get the indices of the specified units, sorted.
+    pub fn known_units(self: &Self) -> Vec<usize> {
+        self.entries.iter().map(|(unit, _)| *unit).collect()
+    }
+
+    /// Get the indices of the units that are not specified, sorted.
+    pub fn unknown_units(self: &Self) -> Vec<usize> {
+        let mut known_units = self.entries.iter().map(|(unit, _)| *unit).peekable();
+        (0..self.dimension)
+            .filter(|unit| {
+                if known_units.peek() == Some(unit) {
+                    known_units.next();
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect()
+    }
+}
+
+impl HopfieldNetwork {
+    /// Build the full initial state of a sparse cue, filling the unspecified units by a policy.
+    ///
+    /// # Arguments
+    ///
+    /// * `cue`: The sparse cue. Must have the same dimension as the network.
+    /// * `fill`: How to initialize the unspecified units.
+    ///
+    /// # Returns
+    ///
+    /// The full state, with the specified units set to their values.
+    pub fn sparse_cue_state(self: &mut Self, cue: &SparseCue, fill: CueFill) -> DVector<f64> {
+        assert_eq!(
+            cue.get_dimension(),
+            self.dimension,
+            "Sparse cue must have the same dimension as the network!"
+        );
+
+        let mut state = match fill {
+            CueFill::Random => (self.activation_fn)(
+                DVector::<f64>::from_fn(self.dimension, |_, _| self.rng.gen_range(-1.0..1.0)),
+                &self.activation_parameters,
+            ),
+            CueFill::Zero => DVector::<f64>::zeros(self.dimension),
+            CueFill::Mean if self.stored_patterns.ncols() > 0 => self.stored_patterns.column_mean(),
+            CueFill::Mean => DVector::<f64>::zeros(self.dimension),
+        };
+        for (unit, value) in cue.get_entries() {
+            state[*unit] = *value;
+        }
+        state
+    }
+
+    /// Complete a sparse cue: fill the unspecified units by a policy (see sparse_cue_state), then relax only the
+    /// unspecified units while the specified units are held fixed (see relax_state_subset).
+    ///
+    /// # Arguments
+    ///
+    /// * `cue`: The sparse cue. Must have the same dimension as the network.
+    /// * `fill`: How to initialize the unspecified units.
+    ///
+    /// # Returns
+    ///
+    /// A tuple of the completed state and the number of update iterations performed.
+    pub fn complete_sparse_cue(
+        self: &mut Self,
+        cue: &SparseCue,
+        fill: CueFill,
+    ) -> (DVector<f64>, usize) {
+        let state = self.sparse_cue_state(cue, fill);
+        self.relax_state_subset(state, &cue.unknown_units())
+    }
+}
@@ -0,0 +1,125 @@
+use nalgebra::DVector;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::{experiment::mean_and_std, HopfieldNetwork, NetworkDomain};
+
+/// The energies per spin of states quenched to zero temperature, see quench_energies_per_spin.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpinGlassQuenchReport {
+    /// The energy per spin of each relaxed state, in the order the random states were drawn.
+    pub energies_per_spin: Vec<f64>,
+    /// The magnetization of each relaxed state.
+    pub magnetizations: Vec<f64>,
+    pub mean_energy_per_spin: f64,
+    pub std_energy_per_spin: f64,
+    /// The lowest energy per spin found, an upper bound on the ground state energy per spin.
+    pub lowest_energy_per_spin: f64,
+}
+
+impl HopfieldNetwork {
+    /// Get the spin glass energy of a state, H = -½sᵀWs - hᵀs, with the external field h taken as the external input
+    /// at sweep 0 (see ExternalInput::constant for a static field) and no field if there is no external input.
+    ///
+    /// This is the Hamiltonian of the Sherrington-Kirkpatrick model for the couplings of
+    /// RandomWeightScaling::SherringtonKirkpatrick, counting each pair of units once. Note state_energy counts
+    /// each pair twice and ignores the external input, so H = state_energy / 2 without a field.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to calculate the energy of.
+    pub fn spin_glass_energy(self: &Self, state: &DVector<f64>) -> f64 {
+        let field_term = self
+            .external_input
+            .as_ref()
+            .and_then(|external_input| external_input.at(0))
+            .map_or(0.0, |field| field.dot(state));
+        -0.5 * state.dot(&self.local_fields(state)) - field_term
+    }
+
+    /// Get the spin glass energy per spin of a state, H / N, see spin_glass_energy. For the Sherrington-Kirkpatrick
+    /// model without a field the ground state energy per spin approaches -0.7633 as N grows.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to calculate the energy of.
+    pub fn energy_per_spin(self: &Self, state: &DVector<f64>) -> f64 {
+        self.spin_glass_energy(state) / self.dimension as f64
+    }
+
+    /// Get the magnetization of a state, the mean unit value Σ_i s_i / N.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to calculate the magnetization of.
+    pub fn magnetization(self: &Self, state: &DVector<f64>) -> f64 {
+        state.mean()
+    }
+
+    /// Quench uniformly random Bipolar states to zero temperature by relaxing them, and report the energy per spin of
+    /// the relaxed states. Relaxation includes the external input, so a static external field is respected.
+    ///
+    /// The relaxed states are local minima, so for the Sherrington-Kirkpatrick model the mean energy per spin is
+    /// well above the ground state, near -0.71 for large N without a field.
+    ///
+    /// # Arguments
+    ///
+    /// * `samples`: The number of random states to quench.
+    /// * `seed`: The seed of the random states.
+    /// * `threads`: The number of threads to relax with. If None, the thread count is chosen automatically.
+    ///
+    /// # Returns
+    ///
+    /// A SpinGlassQuenchReport of the relaxed states.
+    pub fn quench_energies_per_spin(
+        self: &mut Self,
+        samples: usize,
+        seed: u64,
+        threads: Option<usize>,
+    ) -> SpinGlassQuenchReport {
+        assert!(
+            self.domain == NetworkDomain::Bipolar,
+            "Spin glass quenches require a network with the Bipolar domain!"
+        );
+        assert!(samples > 0, "Spin glass quenches need at least one sample!");
+
+        let mut rng = StdRng::seed_from_u64(seed);
+        let states: Vec<DVector<f64>> = (0..samples)
+            .map(|_| {
+                DVector::from_fn(
+                    self.dimension,
+                    |_, _| {
+                        if rng.gen_bool(0.5) {
+                            1.0
+                        } else {
+                            -1.0
+                        }
+                    },
+                )
+            })
+            .collect();
+        let relaxed_states = self.concurrent_relax_state_collection(states, threads);
+
+        let energies_per_spin: Vec<f64> = relaxed_states
+            .iter()
+            .map(|state| self.energy_per_spin(state))
+            .collect();
+        let magnetizations = relaxed_states
+            .iter()
+            .map(|state| self.magnetization(state))
+            .collect();
+        let (mean_energy_per_spin, std_energy_per_spin) = mean_and_std(&energies_per_spin);
+        let lowest_energy_per_spin = energies_per_spin
+            .iter()
+            .copied()
+            .fold(f64::INFINITY, f64::min);
+
+        SpinGlassQuenchReport {
+            energies_per_spin,
+            magnetizations,
+            mean_energy_per_spin,
+            std_energy_per_spin,
+            lowest_energy_per_spin,
+        }
+    }
+}
@@ -2,17 +2,49 @@ pub mod state_generator_builder;
 
 pub use state_generator_builder::StateGeneratorBuilder;
 
-use super::super::{activation_function::ActivationFunction, NetworkDomain};
+use super::super::{
+    activation_function::{ActivationFunction, ActivationParameters},
+    experiment::derive_seed,
+    NetworkDomain,
+};
 use nalgebra::DVector;
 use rand::{rngs::StdRng, Rng};
 use rand_distr::Uniform;
+use serde::{Deserialize, Serialize};
+
+/// Everything needed to regenerate the exact states of a StateGenerator: the builder it was built from, with the
+/// seed filled in, and where the generator came from if it was split from a parent (see StateGenerator::split).
+///
+/// Store this alongside results (e.g. as JSON) so the pattern sets of a sweep can be regenerated from it alone.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateGeneratorManifest {
+    /// The builder of the generator, with the generator seed set to the seed actually used.
+    pub builder: StateGeneratorBuilder,
+    /// The seed of the parent generator and the index of this child, if this generator was split from a parent.
+    /// The seed of the builder is then derive_seed(parent_seed, split_index).
+    pub split_origin: Option<(u64, usize)>,
+}
+
+impl StateGeneratorManifest {
+    /// Build a fresh generator from this manifest, which generates exactly the same states as the original
+    /// generator did from when it was created.
+    pub fn regenerate(self: &Self) -> StateGenerator {
+        let mut generator = self.builder.build();
+        generator.split_origin = self.split_origin;
+        generator
+    }
+}
 
 #[derive(Debug)]
 pub struct StateGenerator {
     rng: StdRng,
     rng_distribution: Uniform<f64>,
     rng_seed: u64,
+    /// The builder this generator was built from, with the seed filled in, for manifest and split.
+    builder: StateGeneratorBuilder,
+    split_origin: Option<(u64, usize)>,
     activation_function: ActivationFunction,
+    activation_parameters: ActivationParameters,
     dimension: usize,
     domain: NetworkDomain,
 }
@@ -48,7 +80,7 @@ impl StateGenerator {
             (0..self.dimension).map(|_| self.rng.sample(self.rng_distribution)),
         );
 
-        (self.activation_function)(vector)
+        (self.activation_function)(vector, &self.activation_parameters)
     }
 
     /// Create a number of new states - returning this as a vector of DVectors
@@ -59,4 +91,83 @@ impl StateGenerator {
     pub fn create_state_collection(self: &mut Self, num_states: usize) -> Vec<DVector<f64>> {
         (0..num_states).map(|_| self.next_state()).collect()
     }
+
+    /// Get the manifest of this generator, from which its states can be regenerated exactly.
+    pub fn manifest(self: &Self) -> StateGeneratorManifest {
+        StateGeneratorManifest {
+            builder: self.builder.clone(),
+            split_origin: self.split_origin,
+        }
+    }
+
+    /// Split this generator into independent child generators, e.g. one per thread.
+    ///
+    /// Child i has the same distribution, domain and dimension as this generator, and the seed
+    /// derive_seed(seed, i) of this generator's seed. The children depend only on the seed of this generator,
+    /// not on how many states it has generated, so splitting again gives the same children. Each child records
+    /// its origin in its manifest.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_children`: The number of child generators to create.
+    ///
+    /// # Returns
+    ///
+    /// The child generators, in order of index.
+    pub fn split(self: &Self, num_children: usize) -> Vec<StateGenerator> {
+        (0..num_children)
+            .map(|child_index| {
+                let mut child = self
+                    .builder
+                    .clone()
+                    .set_generator_seed(derive_seed(self.rng_seed, child_index as u64))
+                    .build();
+                child.split_origin = Some((self.rng_seed, child_index));
+                child
+            })
+            .collect()
+    }
+
+    /// Create a number of new states across several threads, with one child generator per thread (see split).
+    ///
+    /// Thread i generates a contiguous block of the states from child i, so the states depend only on the seed of
+    /// this generator and the number of threads, and not on thread scheduling. They can be regenerated from the
+    /// manifests of split(threads). Like split, this does not advance this generator.
+    ///
+    /// # Arguments
+    ///
+    /// * `num_states`: The number of states to create.
+    /// * `threads`: The number of threads, and so child generators, to use. Must be strictly positive.
+    ///
+    /// # Returns
+    ///
+    /// A collection of states, the block of child 0 first.
+    pub fn create_state_collection_parallel(
+        self: &Self,
+        num_states: usize,
+        threads: usize,
+    ) -> Vec<DVector<f64>> {
+        assert!(threads > 0, "Thread count must be strictly positive!");
+        let states_per_thread = num_states.div_ceil(threads);
+
+        let mut state_collection = Vec::with_capacity(num_states);
+        crossbeam::scope(|scope| {
+            let handles: Vec<_> = self
+                .split(threads)
+                .into_iter()
+                .enumerate()
+                .map(|(child_index, mut child)| {
+                    let num_child_states = num_states
+                        .saturating_sub(child_index * states_per_thread)
+                        .min(states_per_thread);
+                    scope.spawn(move |_| child.create_state_collection(num_child_states))
+                })
+                .collect();
+            for handle in handles {
+                state_collection.extend(handle.join().unwrap());
+            }
+        })
+        .unwrap();
+        state_collection
+    }
 }
@@ -1,19 +1,21 @@
-use super::{NetworkDomain, StateGenerator};
+use super::{ActivationParameters, NetworkDomain, StateGenerator};
 use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 use rand_distr::Uniform;
+use serde::{Deserialize, Serialize};
 
 /// Define a builder for a new state generator.
 ///
 /// The builder takes parameters to define the behavior of the state generator once built
 ///
 /// See the associated methods for more details on what each parameter affects.
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StateGeneratorBuilder {
     random_lower_bound: f64,
     random_upper_bound: f64,
     generator_seed: u64,
     dimension: usize,
     domain: NetworkDomain,
+    activation_parameters: ActivationParameters,
 }
 
 #[allow(dead_code)]
@@ -25,6 +27,7 @@ impl StateGeneratorBuilder {
             generator_seed: 0,
             dimension: 0,
             domain: NetworkDomain::Unspecified,
+            activation_parameters: ActivationParameters::default(),
         }
     }
 
@@ -69,6 +72,48 @@ impl StateGeneratorBuilder {
         self
     }
 
+    /// Set the threshold of the binary activation used to map generated values into the Binary domain.
+    /// Values above the threshold become 1, so with the default uniform distribution on [-1, 1] a fraction
+    /// (1 - threshold) / 2 of units are 1: a threshold of 1 - 2f gives sparse states with coding level f.
+    ///
+    /// Defaults to 0.0.
+    pub fn set_binary_threshold(mut self: Self, binary_threshold: f64) -> Self {
+        self.activation_parameters.binary_threshold = binary_threshold;
+        self
+    }
+
+    /// Set the threshold of the ternary activation used to map generated values into the Ternary domain.
+    /// Values within ±threshold become 0, so with the default uniform distribution on [-1, 1] a fraction
+    /// threshold of units are 0, giving sparse ternary states.
+    ///
+    /// Defaults to 0.5. Must be non-negative, and strictly less than the largest absolute bound.
+    pub fn set_ternary_threshold(mut self: Self, ternary_threshold: f64) -> Self {
+        self.activation_parameters.ternary_threshold = ternary_threshold;
+        self
+    }
+
+    /// Set the bounds of the clipping activation used to map generated values into the BoundedContinuous domain.
+    ///
+    /// Defaults to [-1, 1]. The lower bound must be strictly less than the upper bound.
+    pub fn set_continuous_bounds(mut self: Self, lower_bound: f64, upper_bound: f64) -> Self {
+        self.activation_parameters.continuous_lower_bound = lower_bound;
+        self.activation_parameters.continuous_upper_bound = upper_bound;
+        self
+    }
+
+    /// Serialize the parameters of this builder to a JSON config, so the exact construction of a generator
+    /// can be stored alongside results and repeated later with from_config.
+    ///
+    /// Note that if the generator seed is left at 0 the rebuilt generator will use a different random seed.
+    pub fn to_config(self: &Self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Create a builder from a JSON config created by to_config, or return an error if the config is not valid.
+    pub fn from_config(config: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(config)
+    }
+
     /// Checks if the builder will create a valid generator. Ensures that all parameters are in a valid range.
     fn check_valid(self: &Self) {
         assert!(self.random_lower_bound < self.random_upper_bound,
@@ -79,6 +124,12 @@ impl StateGeneratorBuilder {
 
         assert!(self.domain != NetworkDomain::Unspecified,
             "StateGeneratorBuilder encountered an error during build! Domain must be a valid network domain!");
+
+        assert!(self.activation_parameters.ternary_threshold >= 0.0,
+            "StateGeneratorBuilder encountered an error during build! Ternary threshold must be non-negative!");
+
+        assert!(self.activation_parameters.continuous_lower_bound < self.activation_parameters.continuous_upper_bound,
+            "StateGeneratorBuilder encountered an error during build! Continuous lower bound must be strictly less than the upper bound!");
     }
 
     /// Build a state generator from the parameters given. Note that this function is NON CONSUMING!
@@ -104,9 +155,23 @@ impl StateGeneratorBuilder {
             rng,
             rng_distribution,
             rng_seed,
+            builder: self.clone().set_generator_seed(rng_seed),
+            split_origin: None,
             activation_function: self.domain.activation_fn(),
+            activation_parameters: self.activation_parameters,
             dimension: self.dimension,
-            domain: self.domain.clone(),
+            domain: self.domain,
         }
     }
+
+    /// Build a state generator from the parameters given, but with a different dimension.
+    /// Like build(), this function is NON CONSUMING, so one builder can act as a template for generators
+    /// of many dimensions with the same distribution, domain, and seed settings.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The dimension of the states to be generated. Must be strictly positive.
+    pub fn build_with_dimension(self: &Self, dimension: usize) -> StateGenerator {
+        self.clone().set_dimension(dimension).build()
+    }
 }
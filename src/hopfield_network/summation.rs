@@ -0,0 +1,310 @@
+use nalgebra::{DMatrix, DVector};
+use serde::{Deserialize, Serialize};
+
+use super::{weight_mask::WeightMask, HopfieldNetwork};
+
+/// The number of terms summed sequentially at the leaves of a pairwise summation.
+const PAIRWISE_BLOCK_SIZE: usize = 8;
+
+/// The order in which the terms of local fields and energies are summed.
+///
+/// Floating point addition is not associative, so the same sum computed in a different order can differ in the
+/// last bits. The native order is whatever the linear algebra backend chooses, which may change with the platform,
+/// the backend, or its version. Small differences near a unit threshold can then flip a unit and send a relaxation
+/// to a different attractor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum SummationOrder {
+    /// Let the linear algebra backend choose the order. Fastest.
+    #[default]
+    Native,
+    /// Sum the terms of every local field and energy by pairwise summation in a fixed order, using only scalar
+    /// additions and multiplications. Results are bit-identical on every IEEE 754 platform, and the rounding error
+    /// grows only with the logarithm of the dimension. Several times slower than Native.
+    Pairwise,
+    /// Sum the terms of every local field and energy sequentially with compensated (Kahan-Neumaier) summation,
+    /// which tracks the rounding error of every addition. The rounding error does not grow with the dimension, so
+    /// energies and the unstable unit check stay accurate above ~10⁵ units. Like Pairwise, results are
+    /// bit-identical on every IEEE 754 platform. Slower than Pairwise.
+    Compensated,
+}
+
+/// Sum values by pairwise summation: split the values in half, sum each half recursively, and add the two sums.
+/// Blocks of at most 8 values are summed sequentially from the first value.
+///
+/// The order of additions depends only on the number of values, so the result is the same on every platform.
+///
+/// # Arguments
+///
+/// * `values`: The values to sum.
+///
+/// # Returns
+///
+/// The sum of the values, 0.0 if there are none.
+pub fn pairwise_sum(values: &[f64]) -> f64 {
+    if values.len() <= PAIRWISE_BLOCK_SIZE {
+        let mut sum = 0.0;
+        for value in values {
+            sum += value;
+        }
+        return sum;
+    }
+    let (first_half, second_half) = values.split_at(values.len() / 2);
+    pairwise_sum(first_half) + pairwise_sum(second_half)
+}
+
+/// Sum values sequentially with compensated (Kahan-Neumaier) summation: the rounding error of every addition is
+/// accumulated separately and added back at the end, so the result is accurate to a few units in the last place
+/// regardless of the number of values.
+///
+/// # Arguments
+///
+/// * `values`: The values to sum.
+///
+/// # Returns
+///
+/// The sum of the values, 0.0 if there are none.
+pub fn compensated_sum(values: &[f64]) -> f64 {
+    let mut sum = 0.0;
+    let mut compensation = 0.0;
+    for value in values {
+        let total = sum + value;
+        // Recover the low order bits lost from whichever of the two terms is smaller
+        if f64::abs(sum) >= f64::abs(*value) {
+            compensation += (sum - total) + value;
+        } else {
+            compensation += (value - total) + sum;
+        }
+        sum = total;
+    }
+    sum + compensation
+}
+
+/// Sum values in a given order. The Native order sums sequentially.
+pub fn sum_in_order(values: &[f64], summation_order: SummationOrder) -> f64 {
+    match summation_order {
+        SummationOrder::Native => values.iter().sum(),
+        SummationOrder::Pairwise => pairwise_sum(values),
+        SummationOrder::Compensated => compensated_sum(values),
+    }
+}
+
+/// Calculate the local fields of a state by summing the terms of each row of the weight matrix in a given order
+/// (see sum_in_order).
+///
+/// # Arguments
+///
+/// * `matrix`: The weight matrix.
+/// * `state`: The state to calculate the local fields of.
+/// * `mask`: A connectivity mask to apply, if any. Masked couplings contribute exactly 0.0.
+/// * `summation_order`: The order to sum the terms of each local field in.
+///
+/// # Returns
+///
+/// A DVector of `f64` holding the local field of each unit.
+pub fn fixed_order_local_fields(
+    matrix: &DMatrix<f64>,
+    state: &DVector<f64>,
+    mask: Option<&WeightMask>,
+    summation_order: SummationOrder,
+) -> DVector<f64> {
+    let mut products = vec![0.0; state.len()];
+    DVector::<f64>::from_fn(matrix.nrows(), |row, _| {
+        for (column, product) in products.iter_mut().enumerate() {
+            *product = matrix[(row, column)] * state[column];
+        }
+        if let Some(mask) = mask {
+            mask.zero_masked_products(row, &mut products);
+        }
+        sum_in_order(&products, summation_order)
+    })
+}
+
+/// Calculate the local field of a single unit by summing the terms of its row of the weight matrix in a given order,
+/// as fixed_order_local_fields does for every unit.
+///
+/// # Arguments
+///
+/// * `matrix`: The weight matrix.
+/// * `state`: The state to calculate the local field of.
+/// * `mask`: A connectivity mask to apply, if any. Masked couplings contribute exactly 0.0.
+/// * `summation_order`: The order to sum the terms of the local field in.
+/// * `row`: The unit to calculate the local field of.
+///
+/// # Returns
+///
+/// The local field of the unit.
+pub fn fixed_order_local_field(
+    matrix: &DMatrix<f64>,
+    state: &DVector<f64>,
+    mask: Option<&WeightMask>,
+    summation_order: SummationOrder,
+    row: usize,
+) -> f64 {
+    let mut products: Vec<f64> = (0..state.len())
+        .map(|column| matrix[(row, column)] * state[column])
+        .collect();
+    if let Some(mask) = mask {
+        mask.zero_masked_products(row, &mut products);
+    }
+    sum_in_order(&products, summation_order)
+}
+
+/// Calculate the local fields of a state with a given summation order.
+///
+/// # Arguments
+///
+/// * `matrix`: The weight matrix.
+/// * `state`: The state to calculate the local fields of.
+/// * `summation_order`: The order to sum the terms of each local field in.
+///
+/// # Returns
+///
+/// A DVector of `f64` holding the local field of each unit.
+pub fn local_fields_in_order(
+    matrix: &DMatrix<f64>,
+    state: &DVector<f64>,
+    summation_order: SummationOrder,
+) -> DVector<f64> {
+    match summation_order {
+        SummationOrder::Native => matrix * state,
+        summation_order => fixed_order_local_fields(matrix, state, None, summation_order),
+    }
+}
+
+impl HopfieldNetwork {
+    /// Get the summation order of local fields and energies in this network, see SummationOrder.
+    pub fn get_summation_order(self: &Self) -> SummationOrder {
+        self.summation_order
+    }
+
+    /// Set the summation order of local fields and energies in this network, see SummationOrder.
+    ///
+    /// Pairwise and compensated summation make relaxation, energies, and delta rule training bit-identical across
+    /// platforms and linear algebra backends, so published experiments can be reproduced exactly. Hebbian weights of discrete
+    /// patterns are sums of small integers, which are exact in any order, so they are already reproducible. Weights
+    /// learned from continuous patterns are computed in the native order.
+    ///
+    /// The attractor cache is cleared, as cached attractors may have been found with a different order.
+    ///
+    /// # Arguments
+    ///
+    /// * `summation_order`: The new summation order.
+    pub fn set_summation_order(self: &mut Self, summation_order: SummationOrder) {
+        self.summation_order = summation_order;
+        self.clear_attractor_cache();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hopfield_network::{
+        reference::{reference_local_fields, reference_state_energy},
+        state_generator::StateGeneratorBuilder,
+        HopfieldNetworkBuilder, NetworkDomain,
+    };
+
+    /// Build a Tanh network of a dimension with continuous weights, so the terms of every field are inexact, and
+    /// return it with some states to calculate fields of.
+    fn continuous_network(
+        dimension: usize,
+        summation_order: SummationOrder,
+    ) -> (HopfieldNetwork, Vec<DVector<f64>>) {
+        let mut state_generator = StateGeneratorBuilder::new_state_generator_builder()
+            .set_domain(NetworkDomain::Tanh)
+            .set_dimension(dimension)
+            .set_generator_seed(5)
+            .build();
+        let mut network = HopfieldNetworkBuilder::new_hopfield_network_builder()
+            .set_network_dimension(dimension)
+            .set_network_domain(NetworkDomain::Tanh)
+            .set_summation_order(summation_order)
+            .build();
+        network.learn_states(&state_generator.create_state_collection(10));
+        (network, state_generator.create_state_collection(5))
+    }
+
+    /// Sum the products W_ij s_j of every row, taken straight from the reference definition, in a given order.
+    fn reference_products_in_order(
+        matrix: &DMatrix<f64>,
+        state: &DVector<f64>,
+        summation_order: SummationOrder,
+    ) -> DVector<f64> {
+        DVector::<f64>::from_fn(matrix.nrows(), |row, _| {
+            let products: Vec<f64> = (0..state.len())
+                .map(|column| matrix[(row, column)] * state[column])
+                .collect();
+            sum_in_order(&products, summation_order)
+        })
+    }
+
+    #[test]
+    fn fixed_order_fields_are_bit_identical_to_reference_products() {
+        for summation_order in [SummationOrder::Pairwise, SummationOrder::Compensated] {
+            let (network, states) = continuous_network(200, summation_order);
+            for state in &states {
+                assert_eq!(
+                    network.local_fields(state),
+                    reference_products_in_order(network.get_matrix(), state, summation_order),
+                    "{summation_order:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn single_unit_fields_are_bit_identical_to_fixed_order_fields() {
+        for summation_order in [SummationOrder::Pairwise, SummationOrder::Compensated] {
+            let (network, states) = continuous_network(200, summation_order);
+            for state in &states {
+                let fields = network.local_fields(state);
+                for unit in 0..state.len() {
+                    assert_eq!(
+                        fixed_order_local_field(
+                            network.get_matrix(),
+                            state,
+                            None,
+                            summation_order,
+                            unit
+                        ),
+                        fields[unit],
+                        "{summation_order:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn small_pairwise_fields_are_bit_identical_to_reference() {
+        // Up to the block size pairwise summation adds the terms sequentially, as the reference does
+        let (network, states) = continuous_network(PAIRWISE_BLOCK_SIZE, SummationOrder::Pairwise);
+        for state in &states {
+            assert_eq!(
+                network.local_fields(state),
+                reference_local_fields(network.get_matrix(), state)
+            );
+        }
+    }
+
+    #[test]
+    fn fixed_order_fields_and_energies_match_reference() {
+        for summation_order in [SummationOrder::Pairwise, SummationOrder::Compensated] {
+            let (network, states) = continuous_network(200, summation_order);
+            for state in &states {
+                let reference_fields = reference_local_fields(network.get_matrix(), state);
+                assert!(
+                    (network.local_fields(state) - &reference_fields).amax()
+                        <= 1e-12 * reference_fields.amax(),
+                    "{summation_order:?}"
+                );
+                let reference_energy = reference_state_energy(network.get_matrix(), state);
+                assert!(
+                    (network.state_energy(state) - reference_energy).abs()
+                        <= 1e-12 * reference_energy.abs(),
+                    "{summation_order:?}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use nalgebra::DMatrix;
+use rand::{rngs::StdRng, seq::IteratorRandom, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use super::HopfieldNetwork;
+
+/// The connectivity of a network: which pairs of units are coupled. Connections are undirected, so a missing
+/// connection removes both W_ij and W_ji. Self couplings are governed by force_zero_diagonal instead.
+///
+/// Set on the builder with set_topology. Learning never creates weights between unconnected units (see
+/// clean_matrix), so every update and energy of the network respects the topology.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Topology {
+    /// Every unit is connected to every other unit, the classic Hopfield network.
+    FullyConnected,
+    /// Each pair of units is connected independently with probability `connection_probability` (Erdős–Rényi).
+    ErdosRenyi {
+        connection_probability: f64,
+        seed: u64,
+    },
+    /// A ring of units each connected to its `neighbours` nearest units (half on either side), with each connection
+    /// then rewired to a uniformly random unit with probability `rewiring_probability` (Watts–Strogatz).
+    /// Small rewiring probabilities give a small-world network. `neighbours` must be even.
+    WattsStrogatz {
+        neighbours: usize,
+        rewiring_probability: f64,
+        seed: u64,
+    },
+    /// A `width` by `height` grid, with unit `row * width + column` connected to the units above, below, left
+    /// and right of it. If `periodic`, the grid wraps around at its edges (a torus).
+    Lattice {
+        width: usize,
+        height: usize,
+        periodic: bool,
+    },
+}
+
+impl Topology {
+    /// Check this topology can be built for a network of the given dimension.
+    pub fn is_valid_for(self: &Self, dimension: usize) -> bool {
+        match *self {
+            Topology::FullyConnected => true,
+            Topology::ErdosRenyi {
+                connection_probability,
+                ..
+            } => (0.0..=1.0).contains(&connection_probability),
+            Topology::WattsStrogatz {
+                neighbours,
+                rewiring_probability,
+                ..
+            } => {
+                neighbours % 2 == 0
+                    && neighbours < dimension
+                    && (0.0..=1.0).contains(&rewiring_probability)
+            }
+            Topology::Lattice { width, height, .. } => width * height == dimension,
+        }
+    }
+
+    /// Build the connectivity matrix of this topology: 1 where two units are connected and 0 elsewhere.
+    /// The matrix is symmetric, and its diagonal is 1 so that the topology leaves self couplings alone.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The number of units.
+    ///
+    /// # Returns
+    ///
+    /// The connectivity matrix, as a `DMatrix<f64>` to be multiplied elementwise with the weights.
+    pub fn connectivity_matrix(self: &Self, dimension: usize) -> DMatrix<f64> {
+        assert!(
+            self.is_valid_for(dimension),
+            "Topology parameters are not valid for a network of dimension {dimension}!"
+        );
+
+        let mut connectivity = DMatrix::<f64>::identity(dimension, dimension);
+        let mut connect = |first: usize, second: usize| {
+            connectivity[(first, second)] = 1.0;
+            connectivity[(second, first)] = 1.0;
+        };
+
+        match *self {
+            Topology::FullyConnected => connectivity.fill(1.0),
+            Topology::ErdosRenyi {
+                connection_probability,
+                seed,
+            } => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                for first in 0..dimension {
+                    for second in first + 1..dimension {
+                        if rng.gen_bool(connection_probability) {
+                            connect(first, second);
+                        }
+                    }
+                }
+            }
+            Topology::WattsStrogatz {
+                neighbours,
+                rewiring_probability,
+                seed,
+            } => {
+                for unit in 0..dimension {
+                    for offset in 1..=neighbours / 2 {
+                        connect(unit, (unit + offset) % dimension);
+                    }
+                }
+
+                let mut rng = StdRng::seed_from_u64(seed);
+                for unit in 0..dimension {
+                    for offset in 1..=neighbours / 2 {
+                        if !rng.gen_bool(rewiring_probability) {
+                            continue;
+                        }
+                        // Rewire to a unit this unit is not yet connected to, if there is one
+                        let Some(target) = (0..dimension)
+                            .filter(|target| connectivity[(unit, *target)] == 0.0)
+                            .choose(&mut rng)
+                        else {
+                            continue;
+                        };
+                        connectivity[(unit, (unit + offset) % dimension)] = 0.0;
+                        connectivity[((unit + offset) % dimension, unit)] = 0.0;
+                        connectivity[(unit, target)] = 1.0;
+                        connectivity[(target, unit)] = 1.0;
+                    }
+                }
+            }
+            Topology::Lattice {
+                width,
+                height,
+                periodic,
+            } => {
+                for row in 0..height {
+                    for column in 0..width {
+                        let unit = row * width + column;
+                        if column + 1 < width {
+                            connect(unit, unit + 1);
+                        } else if periodic && width > 1 {
+                            connect(unit, row * width);
+                        }
+                        if row + 1 < height {
+                            connect(unit, unit + width);
+                        } else if periodic && height > 1 {
+                            connect(unit, column);
+                        }
+                    }
+                }
+            }
+        }
+        connectivity
+    }
+}
+
+impl HopfieldNetwork {
+    /// Get the topology this network was built on.
+    pub fn get_topology(self: &Self) -> Topology {
+        self.topology
+    }
+
+    /// Get the connectivity matrix of this network (see Topology::connectivity_matrix),
+    /// or None if the network is fully connected.
+    pub fn get_connectivity(self: &Self) -> Option<&DMatrix<f64>> {
+        self.connectivity.as_ref()
+    }
+
+    /// Get the number of other units each unit is connected to.
+    pub fn unit_degrees(self: &Self) -> Vec<usize> {
+        match &self.connectivity {
+            Some(connectivity) => connectivity
+                .row_iter()
+                .map(|row| row.iter().filter(|connected| **connected != 0.0).count() - 1)
+                .collect(),
+            None => vec![self.dimension - 1; self.dimension],
+        }
+    }
+
+    /// Zero every weight between units that are not connected in the topology of this network.
+    ///
+    /// The weights are no longer Hebbian if any connection is missing, as the factorized local fields do not
+    /// account for the topology.
+    pub(super) fn apply_connectivity(self: &mut Self) {
+        if let Some(connectivity) = &self.connectivity {
+            self.matrix.component_mul_assign(connectivity);
+            self.hebbian_weights = false;
+        }
+    }
+}
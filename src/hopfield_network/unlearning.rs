@@ -0,0 +1,86 @@
+use nalgebra::DVector;
+
+use super::{
+    network_domain::FIXED_POINT_STABILITY_TOLERANCE, state_generator::StateGenerator,
+    HopfieldNetwork,
+};
+
+/// The parameters of Hopfield-Feinstein-Palmer unlearning.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnlearningParameters {
+    /// The unlearning rate ε, the strength of each anti-Hebbian update relative to learning one pattern.
+    /// Small values (e.g. 0.01) are needed so that the stored patterns themselves are not unlearned.
+    pub unlearning_rate: f64,
+    /// The number of dream states to relax and unlearn.
+    pub dream_states: usize,
+}
+
+/// The outcome of an unlearning run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UnlearningReport {
+    /// The number of dream states relaxed and unlearned.
+    pub dream_states: usize,
+    /// The number of dream states that relaxed to an attractor other than a stored pattern or its inverse.
+    /// This should fall as unlearning removes spurious minima.
+    pub spurious_attractors: usize,
+}
+
+impl HopfieldNetwork {
+    /// "Dream" to remove spurious minima by Hopfield-Feinstein-Palmer unlearning.
+    ///
+    /// Each dream state is generated, relaxed, and the attractor it reaches is unlearned with a small anti-Hebbian
+    /// update, W -= ε ssᵀ / N. Spurious minima have larger basins than the stored patterns past low loading,
+    /// so they are found (and weakened) most often. Binary attractors are mapped to bipolar values first,
+    /// as in learn_states. The matrix is cleaned after each update as set by the clean policy (see set_clean_policy).
+    ///
+    /// The stored patterns are not changed. Unlearning changes the weights, so the attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `state_generator`: The generator to create dream states from, which should match the network domain.
+    /// * `parameters`: The unlearning rate and number of dream states.
+    ///
+    /// # Returns
+    ///
+    /// An UnlearningReport counting the dream states that reached spurious attractors.
+    pub fn unlearn(
+        self: &mut Self,
+        state_generator: &mut StateGenerator,
+        parameters: UnlearningParameters,
+    ) -> UnlearningReport {
+        let weights_before = self.weight_delta_snapshot();
+        let scale = parameters.unlearning_rate / self.dimension as f64;
+        let mut spurious_attractors = 0;
+
+        for _ in 0..parameters.dream_states {
+            let (attractor, _) = self.relax_state_iterations(state_generator.next_state());
+            let attractor = self.learning_vector(&attractor);
+            if !self.is_stored_learning_vector(&attractor) {
+                spurious_attractors += 1;
+            }
+
+            self.matrix.ger(-scale, &attractor, &attractor, 1.0);
+            self.clean_matrix_after_learning();
+        }
+
+        // The weights are no longer the outer products of the stored patterns alone
+        self.hebbian_weights = false;
+        self.emit_weight_delta(weights_before, 0);
+        self.clear_attractor_cache();
+        UnlearningReport {
+            dream_states: parameters.dream_states,
+            spurious_attractors,
+        }
+    }
+
+    /// Check if a learning vector is exactly that of a stored pattern, or its inverse.
+    fn is_stored_learning_vector(self: &Self, learning_vector: &DVector<f64>) -> bool {
+        let norm = learning_vector.norm_squared();
+        self.stored_patterns.column_iter().any(|pattern| {
+            let pattern = self.learning_vector(&pattern.into_owned());
+            let overlap = pattern.dot(learning_vector).abs();
+            (overlap - norm).abs() <= FIXED_POINT_STABILITY_TOLERANCE
+                && (pattern.norm_squared() - norm).abs() <= FIXED_POINT_STABILITY_TOLERANCE
+        })
+    }
+}
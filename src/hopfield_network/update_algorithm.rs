@@ -0,0 +1,294 @@
+use nalgebra::{DMatrix, DVector};
+use rand::{rngs::StdRng, seq::SliceRandom, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+use super::{
+    activation_function::ActivationParameters, local_field::LocalFieldOperator,
+    summation::SummationOrder, update_rule::UpdateRule, HopfieldNetwork, NetworkDomain,
+    RelaxationDynamics,
+};
+
+/// Below this dimension recalculating every local field per unit update is as fast as tracking the fields,
+/// so UpdateAlgorithm::Auto uses FullField.
+const INCREMENTAL_FIELD_MINIMUM_DIMENSION: usize = 32;
+
+/// How local fields are recalculated while units are updated one at a time during relaxation. Synchronous dynamics
+/// (see UpdateDynamics) calculate the fields once per sweep whatever the algorithm.
+///
+/// Every algorithm runs the same asynchronous dynamics, but the fields are summed in a different order,
+/// so results may differ where a local field is within floating point rounding of a threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateAlgorithm {
+    /// Choose by the network dimension: FullField for small networks and IncrementalField otherwise.
+    Auto,
+    /// Recalculate every local field (a full matrix-vector product) before each unit update.
+    FullField,
+    /// Calculate the local fields once per sweep, then update them by the weight column of each unit that changes.
+    IncrementalField,
+    /// Relax each thread's states of a concurrent collection together, updating a unit in every state at once
+    /// with a single row-matrix product. States in a batch share their update order.
+    /// Only used by concurrent_relax_state_collection with deterministic asynchronous dynamics, the built in update
+    /// rule, and without external input, fatigue, a weight mask, or a fixed summation order; otherwise
+    /// IncrementalField is used instead.
+    BatchedRows,
+}
+
+/// Whether the units of a state are updated one at a time or all at once during relaxation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum UpdateDynamics {
+    /// Update units one at a time in a random order, each from the latest state. With symmetric weights and a
+    /// non-negative diagonal every update lowers or keeps the energy, so relaxation always reaches a fixed point.
+    Asynchronous,
+    /// Update every unit simultaneously from the previous state (the Little model). Relaxation may end in a 2-cycle
+    /// instead of a fixed point, which is reported as not converged once the maximum number of iterations is reached.
+    Synchronous,
+}
+
+/// How the weights are stored for calculating local fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FieldStorage {
+    /// Use the factorized form while the weights are Hebbian and fewer than half as many patterns are stored as
+    /// there are units, and the dense matrix otherwise.
+    Auto,
+    /// Always use the dense matrix.
+    Dense,
+    /// Use the factorized form whenever the weights are Hebbian, however many patterns are stored.
+    Factorized,
+}
+
+impl UpdateAlgorithm {
+    /// Get the algorithm to relax a single state with: Auto and BatchedRows are resolved to a per-state algorithm.
+    pub(super) fn single_state(self: Self, dimension: usize) -> UpdateAlgorithm {
+        match self {
+            Self::FullField => Self::FullField,
+            Self::IncrementalField | Self::BatchedRows => Self::IncrementalField,
+            Self::Auto if dimension < INCREMENTAL_FIELD_MINIMUM_DIMENSION => Self::FullField,
+            Self::Auto => Self::IncrementalField,
+        }
+    }
+}
+
+impl HopfieldNetwork {
+    /// Get the update algorithm used to relax states, before Auto is resolved.
+    pub fn get_update_algorithm(self: &Self) -> UpdateAlgorithm {
+        self.update_algorithm
+    }
+
+    /// Get the dynamics units are updated with during relaxation.
+    pub fn get_update_dynamics(self: &Self) -> UpdateDynamics {
+        self.update_dynamics
+    }
+
+    /// Update every unit of a given state once, simultaneously from the given state, whatever the update
+    /// dynamics of this network. This is a synchronous_step without external input or fatigue.
+    ///
+    /// # Arguments
+    ///
+    /// * `state`: The state to update. Consumes the state.
+    ///
+    /// # Returns
+    ///
+    /// The newly updated state.
+    pub fn update_state_synchronous(self: &mut Self, mut state: DVector<f64>) -> DVector<f64> {
+        let update_rule = self.get_update_rule();
+        let mut rng = StdRng::seed_from_u64(self.rng.next_u64());
+        let mut unit_indices = self.get_unit_indices();
+        let relaxation_dynamics = RelaxationDynamics {
+            external_input: None,
+            ..self.synchronous_dynamics(update_rule.as_ref())
+        };
+        relaxation_dynamics.advance(0, &mut unit_indices, &mut rng, &mut state, &mut None);
+        state
+    }
+
+    /// Get the algorithm concurrent_relax_state_collection relaxes states with.
+    pub(super) fn collection_update_algorithm(self: &Self) -> UpdateAlgorithm {
+        let batchable = self.update_dynamics == UpdateDynamics::Asynchronous
+            && self.temperature == 0.0
+            && self.update_rule.is_none()
+            && self.external_input.is_none()
+            && self.fatigue.is_disabled()
+            && self.weight_mask.is_none()
+            && self.summation_order == SummationOrder::Native;
+        match self.update_algorithm {
+            UpdateAlgorithm::BatchedRows if batchable => UpdateAlgorithm::BatchedRows,
+            update_algorithm => update_algorithm.single_state(self.dimension),
+        }
+    }
+
+    /// Benchmark concurrent relaxation of a small sample of states with every update algorithm and use the fastest
+    /// from now on. Like auto_tune_threads, the sample should be representative of the full batch.
+    ///
+    /// The attractor cache is bypassed while benchmarking.
+    ///
+    /// # Arguments
+    ///
+    /// * `sample`: The states to benchmark with. These are cloned, not consumed.
+    ///
+    /// # Returns
+    ///
+    /// The fastest update algorithm, which is now the update algorithm of this network.
+    pub fn auto_tune_update_algorithm(self: &mut Self, sample: &[DVector<f64>]) -> UpdateAlgorithm {
+        let attractor_cache = self.attractor_cache.take();
+
+        let mut best_algorithm = UpdateAlgorithm::FullField;
+        let mut best_time = Duration::MAX;
+        for update_algorithm in [
+            UpdateAlgorithm::FullField,
+            UpdateAlgorithm::IncrementalField,
+            UpdateAlgorithm::BatchedRows,
+        ] {
+            self.update_algorithm = update_algorithm;
+            let now = Instant::now();
+            self.concurrent_relax_state_collection(sample.to_vec(), None);
+            let elapsed = now.elapsed();
+            if elapsed < best_time {
+                best_time = elapsed;
+                best_algorithm = update_algorithm;
+            }
+        }
+
+        self.attractor_cache = attractor_cache;
+        self.update_algorithm = best_algorithm;
+        best_algorithm
+    }
+}
+
+/// Update every given unit of a state once, in order, with an update rule and a per-state update algorithm. With
+/// synchronous dynamics every unit is updated from the fields of the state before the sweep, so the order and
+/// algorithm do not matter.
+///
+/// # Arguments
+///
+/// * `local_field_operator`: The operator to calculate local fields with.
+/// * `update_rule`: The rule giving the next value of each unit.
+/// * `update_algorithm`: FullField or IncrementalField, see UpdateAlgorithm::single_state.
+/// * `update_dynamics`: Whether the units are updated one at a time or all at once.
+/// * `unit_indices`: The units to update, in update order.
+/// * `state`: The state to update in place.
+/// * `input`: External input added to the local fields, if any.
+/// * `adaptation`: Unit adaptation subtracted from the local fields, if any.
+/// * `rng`: The random number generator passed to the update rule.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn sweep_units(
+    local_field_operator: LocalFieldOperator,
+    update_rule: &dyn UpdateRule,
+    update_algorithm: UpdateAlgorithm,
+    update_dynamics: UpdateDynamics,
+    unit_indices: &[usize],
+    state: &mut DVector<f64>,
+    input: Option<&DVector<f64>>,
+    adaptation: Option<&DVector<f64>>,
+    rng: &mut StdRng,
+) {
+    let total_fields = |state: &DVector<f64>| {
+        let mut fields = local_field_operator.local_fields(state);
+        if let Some(input) = input {
+            fields += input;
+        }
+        if let Some(adaptation) = adaptation {
+            fields -= adaptation;
+        }
+        fields
+    };
+
+    if update_dynamics == UpdateDynamics::Synchronous {
+        let fields = total_fields(state);
+        let previous_state = state.clone();
+        for unit_index in unit_indices {
+            state[*unit_index] = update_rule.next_value(
+                *unit_index,
+                fields[*unit_index],
+                previous_state[*unit_index],
+                rng,
+            );
+        }
+        return;
+    }
+
+    match update_algorithm {
+        UpdateAlgorithm::IncrementalField => {
+            let mut fields = total_fields(state);
+            for unit_index in unit_indices {
+                let next_value = update_rule.next_value(
+                    *unit_index,
+                    fields[*unit_index],
+                    state[*unit_index],
+                    rng,
+                );
+                let delta = next_value - state[*unit_index];
+                if delta != 0.0 {
+                    local_field_operator.add_unit_change(&mut fields, *unit_index, delta);
+                    state[*unit_index] = next_value;
+                }
+            }
+        }
+        _ => {
+            for unit_index in unit_indices {
+                let field = total_fields(state)[*unit_index];
+                state[*unit_index] =
+                    update_rule.next_value(*unit_index, field, state[*unit_index], rng);
+            }
+        }
+    }
+}
+
+/// Relax a batch of states together with the BatchedRows algorithm, for use in the concurrent relaxation threads.
+/// Every state in the batch is updated in the same order, and relaxation stops once every state is stable.
+///
+/// Returns each relaxed state, and whether it converged: finished with fewer unstable units than the maximum.
+#[allow(clippy::too_many_arguments)]
+pub(super) fn relax_state_batch_with_rng(
+    matrix: &DMatrix<f64>,
+    domain: NetworkDomain,
+    activation_parameters: ActivationParameters,
+    unit_indices: &mut [usize],
+    maximum_relaxation_iterations: i32,
+    maximum_relaxation_unstable_units: i32,
+    rng: &mut StdRng,
+    states: &[DVector<f64>],
+) -> Vec<(DVector<f64>, bool)> {
+    if states.is_empty() {
+        return Vec::new();
+    }
+
+    let activation_fn = domain.activation_fn();
+    let mut batch = DMatrix::from_columns(states);
+    let mut unstable_units = vec![0; states.len()];
+    for _ in 0..maximum_relaxation_iterations {
+        unit_indices.shuffle(rng);
+        for unit_index in unit_indices.iter() {
+            let row_fields = (matrix.row(*unit_index) * &batch).transpose();
+            let next_values = activation_fn(row_fields, &activation_parameters);
+            batch.set_row(*unit_index, &next_values.transpose());
+        }
+
+        let fields = matrix * &batch;
+        for (state_index, unstable_units) in unstable_units.iter_mut().enumerate() {
+            *unstable_units = super::count_unstable_updated_units(
+                domain,
+                &fields.column(state_index).into_owned(),
+                &batch.column(state_index).into_owned(),
+                &activation_parameters,
+                unit_indices,
+            );
+        }
+        if unstable_units.iter().all(|unstable_units| {
+            super::has_converged(*unstable_units, maximum_relaxation_unstable_units)
+        }) {
+            break;
+        }
+    }
+
+    batch
+        .column_iter()
+        .zip(unstable_units)
+        .map(|(state, unstable_units)| {
+            (
+                state.into_owned(),
+                super::has_converged(unstable_units, maximum_relaxation_unstable_units),
+            )
+        })
+        .collect()
+}
@@ -0,0 +1,85 @@
+use rand::rngs::StdRng;
+use std::{fmt, sync::Arc};
+
+use super::{
+    activation_function::{ActivationParameters, ScalarActivationFunction},
+    glauber::GlauberRule,
+    HopfieldNetwork,
+};
+
+/// A rule for the next value of a unit during relaxation, used with HopfieldNetwork::set_update_rule.
+///
+/// Implement this to relax states with custom dynamics (e.g. a different activation, stochastic updates, or a leaky
+/// continuous update) without touching the network internals. The network takes care of calculating the local fields,
+/// including any external input and fatigue, of the update order and update dynamics, and of stopping relaxation.
+/// Relaxation still stops once no unit is unstable against the deterministic activation of the network domain.
+///
+/// Rules are shared between the concurrent relaxation threads, so must be Send and Sync.
+pub trait UpdateRule: fmt::Debug + Send + Sync {
+    /// Get the next value of a unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `unit_index`: The index of the unit being updated.
+    /// * `field`: The local field of the unit, with any external input added and adaptation subtracted.
+    /// * `value`: The current value of the unit.
+    /// * `rng`: The random number generator of this relaxation, for stochastic rules.
+    fn next_value(&self, unit_index: usize, field: f64, value: f64, rng: &mut StdRng) -> f64;
+}
+
+/// The deterministic update of a HopfieldNetwork: each unit is set to the activation of its local field.
+#[derive(Debug, Clone, Copy)]
+pub struct ActivationRule {
+    pub activation_fn: ScalarActivationFunction,
+    pub activation_parameters: ActivationParameters,
+}
+
+impl UpdateRule for ActivationRule {
+    fn next_value(&self, _unit_index: usize, field: f64, _value: f64, _rng: &mut StdRng) -> f64 {
+        (self.activation_fn)(field, &self.activation_parameters)
+    }
+}
+
+impl HopfieldNetwork {
+    /// Set a custom rule to update units with during relaxation, serial or concurrent, in place of the activation of
+    /// the network domain (or Glauber dynamics at a positive temperature). The attractor cache is cleared, as cached
+    /// attractors were found with the previous rule; a stochastic rule should not be used with the attractor cache.
+    ///
+    /// # Arguments
+    ///
+    /// * `update_rule`: The rule to update units with, or None to use the built in rule again.
+    pub fn set_update_rule(self: &mut Self, update_rule: Option<Arc<dyn UpdateRule>>) {
+        self.update_rule = update_rule;
+        self.clear_attractor_cache();
+    }
+
+    /// Get the rule units are updated with: the custom rule if one is set, otherwise Glauber dynamics at a positive
+    /// temperature and the activation of the network domain at zero temperature.
+    pub fn get_update_rule(self: &Self) -> Arc<dyn UpdateRule> {
+        self.update_rule_with(self.activation_parameters)
+    }
+
+    /// Get the rule units are updated with, as get_update_rule, with the built in rules using given activation
+    /// parameters rather than those of the network. A custom rule is returned as is.
+    ///
+    /// # Arguments
+    ///
+    /// * `activation_parameters`: The activation parameters of the built in rules.
+    pub(super) fn update_rule_with(
+        self: &Self,
+        activation_parameters: ActivationParameters,
+    ) -> Arc<dyn UpdateRule> {
+        match &self.update_rule {
+            Some(update_rule) => Arc::clone(update_rule),
+            None if self.temperature > 0.0 => Arc::new(GlauberRule {
+                domain: self.domain,
+                temperature: self.temperature,
+                activation_parameters,
+            }),
+            None => Arc::new(ActivationRule {
+                activation_fn: self.domain.scalar_activation_fn(),
+                activation_parameters,
+            }),
+        }
+    }
+}
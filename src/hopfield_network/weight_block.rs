@@ -0,0 +1,171 @@
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+use std::{fmt, ops::Range};
+
+use super::HopfieldNetwork;
+
+/// A rectangular block of a weight matrix, so large networks can be trained in pieces
+/// (e.g. across processes) and reassembled. Serializable for transport between processes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WeightBlock {
+    /// The first row of the block in the full matrix.
+    pub row_start: usize,
+    /// The first column of the block in the full matrix.
+    pub column_start: usize,
+    pub rows: usize,
+    pub columns: usize,
+    /// The weights of the block in row-major order.
+    pub values: Vec<f64>,
+}
+
+impl WeightBlock {
+    /// Get the weight at a position in the full matrix, which must lie inside this block.
+    fn weight(self: &Self, row: usize, column: usize) -> f64 {
+        self.values[(row - self.row_start) * self.columns + (column - self.column_start)]
+    }
+}
+
+/// The errors that can occur when importing weight blocks.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WeightBlockError {
+    /// The block at this index does not fit inside the weight matrix, or its values do not match its shape.
+    InvalidBlock { block_index: usize },
+    /// Two blocks overlap but disagree on the weight at (row, column) by more than the tolerance.
+    InconsistentOverlap {
+        row: usize,
+        column: usize,
+        first_value: f64,
+        second_value: f64,
+    },
+}
+
+impl fmt::Display for WeightBlockError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidBlock { block_index } => write!(
+                f,
+                "weight block {} does not fit the weight matrix",
+                block_index
+            ),
+            Self::InconsistentOverlap {
+                row,
+                column,
+                first_value,
+                second_value,
+            } => write!(
+                f,
+                "overlapping weight blocks disagree at ({}, {}): {} != {}",
+                row, column, first_value, second_value
+            ),
+        }
+    }
+}
+
+impl std::error::Error for WeightBlockError {}
+
+impl HopfieldNetwork {
+    /// Export a rectangular block of the weight matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `rows`: The range of rows to export. Must lie inside the matrix.
+    /// * `columns`: The range of columns to export. Must lie inside the matrix.
+    ///
+    /// # Returns
+    ///
+    /// The WeightBlock holding those weights.
+    pub fn export_weight_block(
+        self: &Self,
+        rows: Range<usize>,
+        columns: Range<usize>,
+    ) -> WeightBlock {
+        assert!(
+            rows.end <= self.dimension && columns.end <= self.dimension,
+            "Weight block ranges must lie inside the weight matrix!"
+        );
+
+        let values = rows
+            .clone()
+            .flat_map(|row| columns.clone().map(move |column| (row, column)))
+            .map(|(row, column)| self.matrix[(row, column)])
+            .collect();
+        WeightBlock {
+            row_start: rows.start,
+            column_start: columns.start,
+            rows: rows.len(),
+            columns: columns.len(),
+            values,
+        }
+    }
+
+    /// Import a collection of weight blocks into the weight matrix, overwriting the weights they cover.
+    ///
+    /// Every block is checked before any weights are written: blocks must fit inside the matrix, and where blocks
+    /// overlap they must agree to within the tolerance. If any check fails the matrix is left unchanged.
+    ///
+    /// The imported weights are taken as given (the matrix is not cleaned), except that weights between units that are
+    /// not connected in the topology of the network stay zero. The attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `blocks`: The blocks to import.
+    /// * `tolerance`: The largest difference allowed between overlapping blocks.
+    ///
+    /// # Returns
+    ///
+    /// An error describing the first problem found, if any.
+    pub fn import_weight_blocks(
+        self: &mut Self,
+        blocks: &[WeightBlock],
+        tolerance: f64,
+    ) -> Result<(), WeightBlockError> {
+        // The index of the block that first covered each weight
+        let mut coverage =
+            DMatrix::<Option<usize>>::from_element(self.dimension, self.dimension, None);
+
+        for (block_index, block) in blocks.iter().enumerate() {
+            if block.row_start + block.rows > self.dimension
+                || block.column_start + block.columns > self.dimension
+                || block.values.len() != block.rows * block.columns
+            {
+                return Err(WeightBlockError::InvalidBlock { block_index });
+            }
+
+            for row in block.row_start..block.row_start + block.rows {
+                for column in block.column_start..block.column_start + block.columns {
+                    match coverage[(row, column)] {
+                        Some(first_block_index) => {
+                            let first_value = blocks[first_block_index].weight(row, column);
+                            let second_value = block.weight(row, column);
+                            if (first_value - second_value).abs() > tolerance {
+                                return Err(WeightBlockError::InconsistentOverlap {
+                                    row,
+                                    column,
+                                    first_value,
+                                    second_value,
+                                });
+                            }
+                        }
+                        None => coverage[(row, column)] = Some(block_index),
+                    }
+                }
+            }
+        }
+
+        let weights_before = self.weight_delta_snapshot();
+        for row in 0..self.dimension {
+            for column in 0..self.dimension {
+                if let Some(block_index) = coverage[(row, column)] {
+                    self.matrix[(row, column)] = blocks[block_index].weight(row, column);
+                }
+            }
+        }
+        self.apply_connectivity();
+
+        // The imported weights need not be the outer products of the stored patterns
+        self.hebbian_weights = false;
+        self.emit_weight_delta(weights_before, 0);
+        self.clear_attractor_cache();
+        Ok(())
+    }
+}
@@ -0,0 +1,204 @@
+use nalgebra::DMatrix;
+use serde::{Deserialize, Serialize};
+
+use super::{network_event::NetworkEvent, HopfieldNetwork};
+
+/// How weight deltas are exported after each learning event.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WeightDeltaFormat {
+    /// Export every weight change, including zeros, as a full matrix.
+    Dense,
+    /// Export only the weights that changed by more than the tolerance.
+    Sparse { tolerance: f64 },
+}
+
+/// A single changed weight of a sparse weight delta.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeightChange {
+    pub row: usize,
+    pub column: usize,
+    pub value: f64,
+}
+
+/// The change ΔW a learning event made to the weight matrix.
+///
+/// Serializable so that external tools can audit learning, and can be applied to or reverted from
+/// a weight matrix to replay or undo a single learning step.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum WeightDelta {
+    /// The full delta matrix, with values in row-major order.
+    Dense { dimension: usize, values: Vec<f64> },
+    /// Only the changed weights.
+    Sparse {
+        dimension: usize,
+        changes: Vec<WeightChange>,
+    },
+}
+
+impl WeightDelta {
+    /// Find the delta between two weight matrices of the same shape.
+    ///
+    /// # Arguments
+    ///
+    /// * `before`: The weights before the change.
+    /// * `after`: The weights after the change.
+    /// * `format`: The format to give the delta in.
+    ///
+    /// # Returns
+    ///
+    /// The delta `after - before` in the given format.
+    pub fn between(before: &DMatrix<f64>, after: &DMatrix<f64>, format: WeightDeltaFormat) -> Self {
+        assert!(
+            before.shape() == after.shape(),
+            "Weight matrices must have the same shape to find the delta between them!"
+        );
+        let dimension = after.nrows();
+        let delta = after - before;
+        match format {
+            WeightDeltaFormat::Dense => WeightDelta::Dense {
+                dimension,
+                values: delta.transpose().iter().copied().collect(),
+            },
+            WeightDeltaFormat::Sparse { tolerance } => {
+                let mut changes = Vec::new();
+                for row in 0..dimension {
+                    for column in 0..delta.ncols() {
+                        let value = delta[(row, column)];
+                        if value.abs() > tolerance {
+                            changes.push(WeightChange { row, column, value });
+                        }
+                    }
+                }
+                WeightDelta::Sparse { dimension, changes }
+            }
+        }
+    }
+
+    /// Get the dimension of the network this delta applies to.
+    pub fn dimension(self: &Self) -> usize {
+        match self {
+            WeightDelta::Dense { dimension, .. } | WeightDelta::Sparse { dimension, .. } => {
+                *dimension
+            }
+        }
+    }
+
+    /// Get this delta as a full matrix.
+    pub fn to_matrix(self: &Self) -> DMatrix<f64> {
+        match self {
+            WeightDelta::Dense { dimension, values } => {
+                DMatrix::from_row_slice(*dimension, *dimension, values)
+            }
+            WeightDelta::Sparse { dimension, changes } => {
+                let mut matrix = DMatrix::zeros(*dimension, *dimension);
+                for change in changes {
+                    matrix[(change.row, change.column)] += change.value;
+                }
+                matrix
+            }
+        }
+    }
+
+    /// Add this delta, scaled, to a weight matrix.
+    ///
+    /// # Arguments
+    ///
+    /// * `matrix`: The weight matrix to change.
+    /// * `scale`: The factor to scale the delta by, e.g. 1 to replay the change or -1 to undo it.
+    pub fn add_to(self: &Self, matrix: &mut DMatrix<f64>, scale: f64) {
+        assert!(
+            matrix.nrows() == self.dimension() && matrix.ncols() == self.dimension(),
+            "Weight delta must have the same dimension as the weight matrix!"
+        );
+        match self {
+            WeightDelta::Dense { dimension, values } => {
+                for row in 0..*dimension {
+                    for column in 0..*dimension {
+                        matrix[(row, column)] += scale * values[row * dimension + column];
+                    }
+                }
+            }
+            WeightDelta::Sparse { changes, .. } => {
+                for change in changes {
+                    matrix[(change.row, change.column)] += scale * change.value;
+                }
+            }
+        }
+    }
+}
+
+impl HopfieldNetwork {
+    /// Set the format weight deltas are exported in after each learning event, or None to stop exporting them.
+    ///
+    /// While set, every change to the weights by learning or weight import emits a WeightsChanged event
+    /// carrying the delta, which event hooks can record. Finding the delta copies the weight matrix,
+    /// so this is off by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `format`: The format to export deltas in.
+    pub fn set_weight_delta_export(self: &mut Self, format: Option<WeightDeltaFormat>) {
+        self.weight_delta_format = format;
+    }
+
+    /// Replay a weight delta on the weight matrix, e.g. to replicate a learning step from another network.
+    ///
+    /// Only the weights are changed: the stored patterns are not. Weights between units that are not connected in
+    /// the topology of the network stay zero. The attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta`: The delta to apply.
+    pub fn apply_weight_delta(self: &mut Self, delta: &WeightDelta) {
+        self.change_weights_by_delta(delta, 1.0);
+    }
+
+    /// Undo a weight delta on the weight matrix, e.g. to revert a single learning step.
+    ///
+    /// Only the weights are changed: the stored patterns are not. The attractor cache is cleared.
+    ///
+    /// # Arguments
+    ///
+    /// * `delta`: The delta to undo.
+    pub fn revert_weight_delta(self: &mut Self, delta: &WeightDelta) {
+        self.change_weights_by_delta(delta, -1.0);
+    }
+
+    fn change_weights_by_delta(self: &mut Self, delta: &WeightDelta, scale: f64) {
+        let before = self.weight_delta_snapshot();
+        delta.add_to(&mut self.matrix, scale);
+        self.apply_connectivity();
+
+        // The changed weights need not be the outer products of the stored patterns
+        self.hebbian_weights = false;
+        self.emit_weight_delta(before, 0);
+        self.clear_attractor_cache();
+    }
+
+    /// Copy the weights before a change, if weight deltas are being exported.
+    pub(super) fn weight_delta_snapshot(self: &Self) -> Option<DMatrix<f64>> {
+        self.weight_delta_format.map(|_| self.matrix.clone())
+    }
+
+    /// Emit a WeightsChanged event for the change from a snapshot to the current weights.
+    /// Does nothing if there is no snapshot, i.e. weight deltas are not being exported.
+    ///
+    /// # Arguments
+    ///
+    /// * `before`: The snapshot taken by weight_delta_snapshot before the change.
+    /// * `pattern_count`: The number of patterns learned in the change, which are the next to be stored.
+    pub(super) fn emit_weight_delta(
+        self: &mut Self,
+        before: Option<DMatrix<f64>>,
+        pattern_count: usize,
+    ) {
+        if let (Some(before), Some(format)) = (before, self.weight_delta_format) {
+            let delta = WeightDelta::between(&before, &self.matrix, format);
+            self.event_hooks.emit(&NetworkEvent::WeightsChanged {
+                first_pattern_index: self.stored_patterns.ncols(),
+                pattern_count,
+                delta: &delta,
+            });
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use nalgebra::DMatrix;
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use super::NetworkDomain;
+
+/// How random initial weights are scaled, see set_random_weight_scaling on the builder.
+///
+/// Local fields sum N weighted inputs, so weights of fixed size give fields that grow as √N and saturate every
+/// activation from the first update. Scaling the weights with the dimension keeps the fields of order one.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RandomWeightScaling {
+    /// Standard Gaussian weights wrapped into (-1, 1), ignoring the dimension and domain.
+    Unscaled,
+    /// Gaussian weights with variance 1/N.
+    InverseDimension,
+    /// Gaussian weights with variance 1/(N E[s²]), where E[s²] is the mean square unit value of a uniformly
+    /// random state of the domain, so the local fields of such states have unit variance.
+    DomainCoupled,
+    /// Gaussian weights with a fixed standard deviation.
+    StandardDeviation(f64),
+    /// The couplings of the Sherrington-Kirkpatrick spin glass: symmetric Gaussian weights J_ij = J_ji with mean
+    /// J₀/N and standard deviation J/√N, and a zero diagonal. Unlike the other scalings the matrix is symmetric as
+    /// drawn, so each coupling has the full variance rather than half of it after symmetrization.
+    SherringtonKirkpatrick {
+        coupling_mean: f64,
+        coupling_standard_deviation: f64,
+    },
+}
+
+impl RandomWeightScaling {
+    /// Get the standard deviation of the Gaussian weights for a network, or None if the weights are Unscaled.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The dimension of the network.
+    /// * `domain`: The domain of the network.
+    pub fn standard_deviation(self: &Self, dimension: usize, domain: NetworkDomain) -> Option<f64> {
+        match *self {
+            Self::Unscaled => None,
+            Self::InverseDimension => Some((1.0 / dimension as f64).sqrt()),
+            Self::DomainCoupled => {
+                Some((1.0 / (dimension as f64 * domain.mean_square_unit_value())).sqrt())
+            }
+            Self::StandardDeviation(standard_deviation) => Some(standard_deviation),
+            Self::SherringtonKirkpatrick {
+                coupling_standard_deviation,
+                ..
+            } => Some(coupling_standard_deviation / (dimension as f64).sqrt()),
+        }
+    }
+
+    /// Create a random weight matrix scaled by this rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `dimension`: The dimension of the network.
+    /// * `domain`: The domain of the network.
+    /// * `rng`: The random number generator to sample weights from.
+    ///
+    /// # Returns
+    ///
+    /// A new `dimension` by `dimension` matrix of random weights.
+    pub fn random_matrix(
+        self: &Self,
+        dimension: usize,
+        domain: NetworkDomain,
+        rng: &mut StdRng,
+    ) -> DMatrix<f64> {
+        let standard_deviation = self.standard_deviation(dimension, domain);
+        if let Self::SherringtonKirkpatrick { coupling_mean, .. } = *self {
+            let mean = coupling_mean / dimension as f64;
+            let standard_deviation = standard_deviation.unwrap_or(0.0);
+            let mut matrix = DMatrix::<f64>::zeros(dimension, dimension);
+            for row in 0..dimension {
+                for column in row + 1..dimension {
+                    let sample =
+                        rng.sample::<f64, rand_distr::StandardNormal>(rand_distr::StandardNormal);
+                    matrix[(row, column)] = mean + standard_deviation * sample;
+                    matrix[(column, row)] = matrix[(row, column)];
+                }
+            }
+            return matrix;
+        }
+        DMatrix::<f64>::from_iterator(
+            dimension,
+            dimension,
+            (0..dimension * dimension).map(|_| {
+                let sample =
+                    rng.sample::<f64, rand_distr::StandardNormal>(rand_distr::StandardNormal);
+                match standard_deviation {
+                    Some(standard_deviation) => standard_deviation * sample,
+                    None => sample % 1.,
+                }
+            }),
+        )
+    }
+}
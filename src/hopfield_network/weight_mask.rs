@@ -0,0 +1,152 @@
+use nalgebra::{DMatrix, DVector};
+
+use super::HopfieldNetwork;
+
+/// A temporary connectivity mask, removing some couplings from the local field computation
+/// without changing (or copying) the weight matrix.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WeightMask {
+    /// Keep only couplings between units of the same module, disabling every inter-module coupling.
+    /// Holds the module label of each unit.
+    WithinModules { module_of_unit: Vec<usize> },
+    /// Remove specific couplings. Each (row, column) pair removes the weight W_ij, the input to unit i from unit j.
+    /// Remove (j, i) as well to cut a connection in both directions.
+    RemovedCouplings(Vec<(usize, usize)>),
+}
+
+impl WeightMask {
+    /// Check this mask can be applied to a network of the given dimension.
+    fn is_valid_for(self: &Self, dimension: usize) -> bool {
+        match self {
+            Self::WithinModules { module_of_unit } => module_of_unit.len() == dimension,
+            Self::RemovedCouplings(couplings) => couplings
+                .iter()
+                .all(|(row, column)| *row < dimension && *column < dimension),
+        }
+    }
+
+    /// Calculate the local fields of a state under a weight matrix with this mask applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `matrix`: The unmasked weight matrix.
+    /// * `state`: The state to calculate the local fields of.
+    ///
+    /// # Returns
+    ///
+    /// A DVector of `f64` holding the masked local field of each unit.
+    pub fn masked_local_fields(
+        self: &Self,
+        matrix: &DMatrix<f64>,
+        state: &DVector<f64>,
+    ) -> DVector<f64> {
+        match self {
+            Self::WithinModules { module_of_unit } => {
+                let mut fields = DVector::<f64>::zeros(state.len());
+                for (column, weights) in matrix.column_iter().enumerate() {
+                    for (row, weight) in weights.iter().enumerate() {
+                        if module_of_unit[row] == module_of_unit[column] {
+                            fields[row] += weight * state[column];
+                        }
+                    }
+                }
+                fields
+            }
+            Self::RemovedCouplings(couplings) => {
+                let mut fields = matrix * state;
+                for (row, column) in couplings {
+                    fields[*row] -= matrix[(*row, *column)] * state[*column];
+                }
+                fields
+            }
+        }
+    }
+
+    /// Zero the products W_ij s_j of the couplings this mask removes from one row of the weight matrix,
+    /// see summation::fixed_order_local_fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `row`: The row of the weight matrix, i.e. the unit receiving the inputs.
+    /// * `products`: The product of each weight in the row with the value of its input unit, zeroed in place.
+    pub fn zero_masked_products(self: &Self, row: usize, products: &mut [f64]) {
+        match self {
+            Self::WithinModules { module_of_unit } => {
+                for (column, product) in products.iter_mut().enumerate() {
+                    if module_of_unit[row] != module_of_unit[column] {
+                        *product = 0.0;
+                    }
+                }
+            }
+            Self::RemovedCouplings(couplings) => {
+                for (removed_row, column) in couplings {
+                    if *removed_row == row {
+                        products[*column] = 0.0;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Update masked local fields after a single unit changes, see LocalFieldOperator::add_unit_change.
+    pub fn add_masked_unit_change(
+        self: &Self,
+        matrix: &DMatrix<f64>,
+        fields: &mut DVector<f64>,
+        unit_index: usize,
+        delta: f64,
+    ) {
+        match self {
+            Self::WithinModules { module_of_unit } => {
+                for (row, weight) in matrix.column(unit_index).iter().enumerate() {
+                    if module_of_unit[row] == module_of_unit[unit_index] {
+                        fields[row] += weight * delta;
+                    }
+                }
+            }
+            Self::RemovedCouplings(couplings) => {
+                fields.axpy(delta, &matrix.column(unit_index), 1.0);
+                for (row, column) in couplings {
+                    if *column == unit_index {
+                        fields[*row] -= matrix[(*row, *column)] * delta;
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl HopfieldNetwork {
+    /// Run a function on this network with a connectivity mask applied, e.g. to relax states with the couplings
+    /// between modules disabled. The mask is removed (and any previous mask restored) when the function returns.
+    ///
+    /// The weight matrix is neither changed nor copied: every local field (and so every relaxation, serial or
+    /// concurrent, and every energy) is computed as if the masked weights were zero. The attractor cache is
+    /// bypassed while the mask is applied, as cached attractors were found without it.
+    ///
+    /// # Arguments
+    ///
+    /// * `mask`: The mask to apply. Must match the network dimension.
+    /// * `scope`: The function to run with the mask applied.
+    ///
+    /// # Returns
+    ///
+    /// The result of the scoped function.
+    pub fn with_weight_mask<R>(
+        self: &mut Self,
+        mask: WeightMask,
+        scope: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        assert!(
+            mask.is_valid_for(self.dimension),
+            "Weight mask must match the network dimension!"
+        );
+
+        let previous_mask = self.weight_mask.replace(mask);
+        let attractor_cache = self.attractor_cache.take();
+        let result = scope(self);
+        self.attractor_cache = attractor_cache;
+        self.weight_mask = previous_mask;
+        result
+    }
+}
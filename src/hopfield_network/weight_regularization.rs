@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+
+use super::HopfieldNetwork;
+
+/// Regularization applied to the weights while learning, see set_weight_decay and set_weight_clip on the builder.
+///
+/// Long online training runs keep adding to the weights, so without regularization their magnitudes grow without
+/// bound (and a few large weights can dominate every local field).
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WeightRegularization {
+    /// The L2 weight decay λ: the existing weights are scaled by (1 - λ) before each pattern is learned.
+    pub decay: f64,
+    /// If set, every weight is clipped into [-clip, clip] after learning.
+    pub clip: Option<f64>,
+}
+
+impl WeightRegularization {
+    /// Check if this regularization ever changes the weights.
+    pub fn is_disabled(self: &Self) -> bool {
+        self.decay == 0.0 && self.clip.is_none()
+    }
+}
+
+impl HopfieldNetwork {
+    /// Get the weight regularization applied while learning.
+    pub fn get_weight_regularization(self: &Self) -> WeightRegularization {
+        self.weight_regularization
+    }
+
+    /// Decay the weights before learning some patterns, scaling them by (1 - λ) once per pattern.
+    /// Any sequence weights decay with the rest of the matrix.
+    ///
+    /// The weights are no longer Hebbian if any decay is applied.
+    pub(super) fn decay_weights(self: &mut Self, pattern_count: usize) {
+        let decay = self.weight_regularization.decay;
+        if decay == 0.0 || pattern_count == 0 {
+            return;
+        }
+
+        let factor = (1.0 - decay).powi(pattern_count as i32);
+        self.matrix *= factor;
+        if let Some(sequence_weights) = &mut self.sequence_weights {
+            *sequence_weights *= factor;
+        }
+        self.hebbian_weights = false;
+    }
+
+    /// Clip every weight into [-clip, clip], if clipping is enabled.
+    ///
+    /// Any amount clipped from the weights is also taken from the sequence weights, so the matrix less the sequence
+    /// weights is unchanged by clipping and forget_sequences removes exactly what the transitions contributed.
+    ///
+    /// The weights are no longer Hebbian if any weight was clipped.
+    pub(super) fn clip_weights(self: &mut Self) {
+        let Some(clip) = self.weight_regularization.clip else {
+            return;
+        };
+
+        let clipped_matrix = self.matrix.map(|weight| weight.clamp(-clip, clip));
+        if clipped_matrix == self.matrix {
+            return;
+        }
+
+        if let Some(sequence_weights) = &mut self.sequence_weights {
+            *sequence_weights += &clipped_matrix - &self.matrix;
+        }
+        self.matrix = clipped_matrix;
+        self.hebbian_weights = false;
+    }
+}
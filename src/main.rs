@@ -1,3 +1,5 @@
+// The network is written with explicit self types (self: &Self) throughout
+#[allow(clippy::needless_arbitrary_self_type)]
 mod hopfield_network;
 
 use std::time::Instant;
@@ -21,6 +23,7 @@ fn main() {
             .set_domain(DOMAIN);
 
     let mut state_generator = state_generator_builder.build();
+    network.learn_states(&state_generator.create_state_collection(10));
 
     let now = Instant::now();
     let states = state_generator.create_state_collection(10000);
@@ -31,6 +34,15 @@ fn main() {
 
     let now = Instant::now();
     let states = state_generator.create_state_collection(10000);
-    network.concurrent_relax_state_collection(states, 8);
+    network.concurrent_relax_state_collection(states, Some(8));
     println!("{}", now.elapsed().as_nanos());
+
+    let report = pipeline::relaxation_pipeline(
+        &mut network,
+        &mut state_generator,
+        10000,
+        Some(8),
+        |_, _| {},
+    );
+    println!("{}", report.elapsed.as_nanos());
 }